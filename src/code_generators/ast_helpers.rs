@@ -87,19 +87,19 @@ pub fn build_property_call(
 }
 
 pub fn build_args(
+    rng: &mut impl Rng,
     sig: &JsMethodSignature,
     value_pool: &[String],
 ) -> Vec<Expr> {
     sig.types()
     .iter()
-    .map(|ty| build_arg_expr(*ty, value_pool))
+    .map(|ty| build_arg_expr(rng, *ty, value_pool))
     .collect()
 }
 
-fn build_arg_expr(ty: JsObjectType, value_pool: &[String]) -> Expr {
-    let mut rng = rand::rngs::ThreadRng::default();
+fn build_arg_expr(rng: &mut impl Rng, ty: JsObjectType, value_pool: &[String]) -> Expr {
     if !value_pool.is_empty() && rng.random_bool(0.35) {
-        if let Some(existing) = value_pool.choose(&mut rng) {
+        if let Some(existing) = value_pool.choose(rng) {
             return Expr::Ident(Ident {
                 span: DUMMY_SP,
                 sym: Atom::from(existing.as_str()),
@@ -114,7 +114,7 @@ fn build_arg_expr(ty: JsObjectType, value_pool: &[String]) -> Expr {
             // Bias toward interesting edge numbers.
             let specials = [0.0f64, -0.0, -1.0, 1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 0xffff_ffffu32 as f64];
             let v = if rng.random_bool(0.15) {
-                *specials.choose(&mut rng).unwrap_or(&0.0)
+                *specials.choose(rng).unwrap_or(&0.0)
             } else {
                 rng.random_range(-16i32..=128i32) as f64
             };
@@ -133,7 +133,7 @@ fn build_arg_expr(ty: JsObjectType, value_pool: &[String]) -> Expr {
         }
         JsObjectType::JsString => {
             let choices = ["foo", "bar", "baz", "mcsky", "こんにちは"];
-            let s = choices.choose(&mut rng).copied().unwrap_or("s");
+            let s = choices.choose(rng).copied().unwrap_or("s");
             Expr::Lit(Lit::Str(Str {
                 span: DUMMY_SP,
                 value: Atom::from(s).into(),
@@ -151,7 +151,7 @@ fn build_arg_expr(ty: JsObjectType, value_pool: &[String]) -> Expr {
         JsObjectType::Function => {
             // Prefer reusing a value from scope/value pool when asked for a function.
             if !value_pool.is_empty() && rng.random_bool(0.5) {
-                if let Some(existing) = value_pool.choose(&mut rng) {
+                if let Some(existing) = value_pool.choose(rng) {
                     return Expr::Ident(Ident {
                         span: DUMMY_SP,
                         sym: Atom::from(existing.as_str()),
@@ -189,7 +189,7 @@ fn build_arg_expr(ty: JsObjectType, value_pool: &[String]) -> Expr {
                 3 => JsObjectType::Function,
                 _ => JsObjectType::Array,
             };
-            build_arg_expr(concrete, value_pool)
+            build_arg_expr(rng, concrete, value_pool)
         }
         _ => {
             // Fallback to null
@@ -199,8 +199,7 @@ fn build_arg_expr(ty: JsObjectType, value_pool: &[String]) -> Expr {
 }
 
 /// Build a random literal expression
-pub fn build_random_literal(ty: JsObjectType) -> Expr {
-    let mut rng = rand::rngs::ThreadRng::default();
+pub fn build_random_literal(rng: &mut impl Rng, ty: JsObjectType) -> Expr {
     match ty {
         // Boolean is easiest so start with it lol
         JsObjectType::Boolean => {
@@ -235,7 +234,7 @@ pub fn build_random_literal(ty: JsObjectType) -> Expr {
             // Bias toward interesting edge numbers.
             let specials = [0.0f64, -0.0, -1.0, 1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 0xffff_ffffu32 as f64];
             let v = if rng.random_bool(0.15) {
-                *specials.choose(&mut rng).unwrap_or(&0.0)
+                *specials.choose(rng).unwrap_or(&0.0)
             } else {
                 rng.random_range(-16i32..=128i32) as f64
             };
@@ -247,7 +246,7 @@ pub fn build_random_literal(ty: JsObjectType) -> Expr {
         }
         JsObjectType::JsString => {
             let choices = ["foo", "bar", "baz", "mcsky", "こんにちは"];
-            let s = choices.choose(&mut rng).copied().unwrap_or("s");
+            let s = choices.choose(rng).copied().unwrap_or("s");
             Expr::Lit(Lit::Str(Str {
                 span: DUMMY_SP,
                 value: Atom::from(s).into(),
@@ -261,9 +260,9 @@ pub fn build_random_literal(ty: JsObjectType) -> Expr {
             let num_props = rng.random_range(1..=3);
             let mut props = Vec::new();
             for _ in 0..num_props {
-                let key = prop_keys.choose(&mut rng).copied().unwrap_or("key");
+                let key = prop_keys.choose(rng).copied().unwrap_or("key");
                 prop_keys.retain(|&k| k != key);
-                let value_expr = build_random_literal(JsObjectType::Number);
+                let value_expr = build_random_literal(rng, JsObjectType::Number);
                 let prop = PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
                     key: PropName::Ident(IdentName::new(Atom::from(key), DUMMY_SP)),
                     value: Box::new(value_expr),
@@ -283,11 +282,132 @@ pub fn build_random_literal(ty: JsObjectType) -> Expr {
     }
 }
 
+/// Build a random literal of a random primitive type
+pub fn build_random_primitive_literal(rng: &mut impl Rng) -> Expr {
+    let ty = JsObjectType::random_primitive_type(rng);
+    build_random_literal(rng, ty)
+}
+
 pub fn build_ident_expr_from_str(name: &str) -> Expr {
+    build_ident(name).into()
+}
+
+pub fn build_ident(name: &str) -> Ident {
     Ident {
         span: DUMMY_SP,
         sym: Atom::from(name),
         optional: false,
         ctxt: SyntaxContext::empty(),
-    }.into()
-}
\ No newline at end of file
+    }
+}
+
+pub fn build_num(value: f64) -> Expr {
+    Expr::Lit(Lit::Num(Number {
+        span: DUMMY_SP,
+        value,
+        raw: None,
+    }))
+}
+
+/// A number literal, negated with a unary minus if negative (`-0` included)
+pub fn build_signed_num(value: f64) -> Expr {
+    let literal = build_num(value.abs());
+    if value.is_sign_negative() {
+        Expr::Unary(UnaryExpr {
+            span: DUMMY_SP,
+            op: UnaryOp::Minus,
+            arg: Box::new(literal),
+        })
+    } else {
+        literal
+    }
+}
+
+pub fn build_block(stmts: Vec<Stmt>) -> BlockStmt {
+    BlockStmt {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        stmts,
+    }
+}
+
+pub fn build_expr_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
+
+pub fn build_return(arg: Expr) -> Stmt {
+    Stmt::Return(ReturnStmt {
+        span: DUMMY_SP,
+        arg: Some(Box::new(arg)),
+    })
+}
+
+pub fn build_bin(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+/// `<left> <op> <right>`, parenthesizing binary and unary operands since the printer
+/// doesn't and `-2 ** 2` doesn't parse
+pub fn build_paren_bin(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    let wrap = |expr: Expr| match expr {
+        Expr::Unary(_) | Expr::Bin(_) => Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+        }),
+        expr => expr,
+    };
+    build_bin(op, wrap(left), wrap(right))
+}
+
+pub fn build_member_expr(obj: Expr, prop: MemberProp) -> MemberExpr {
+    MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(obj),
+        prop,
+    }
+}
+
+/// `<obj>.<prop>`
+pub fn build_member(obj: Expr, prop: &str) -> Expr {
+    Expr::Member(build_member_expr(obj, MemberProp::Ident(IdentName::new(Atom::from(prop), DUMMY_SP))))
+}
+
+pub fn build_assign(op: AssignOp, left: impl Into<AssignTarget>, right: Expr) -> Expr {
+    Expr::Assign(AssignExpr {
+        span: DUMMY_SP,
+        op,
+        left: left.into(),
+        right: Box::new(right),
+    })
+}
+pub fn build_str_lit(value: &str) -> Str {
+    Str {
+        span: DUMMY_SP,
+        value: Atom::from(value).into(),
+        raw: None,
+    }
+}
+
+/// Returns the (lossy) UTF-8 value of a string literal
+pub fn str_lit_value(lit: &Str) -> String {
+    lit.value.to_string_lossy().into_owned()
+}
+
+/// True if `name` can be used after a dot, e.g. `obj.name`.
+/// Only plain ASCII identifier names are accepted.
+pub fn is_valid_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
@@ -0,0 +1,263 @@
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::swc_ecma_ast::*;
+
+use crate::code_generators::ast_helpers::{
+    build_args, build_assign, build_bin, build_ctor_expr, build_expr_stmt, build_ident, build_ident_expr_from_str,
+    build_num, build_property_call, build_random_literal, build_var_decl,
+};
+use crate::mutators::js_objects::js_objects::{get_global_object, get_global_object_names};
+use crate::mutators::js_objects::js_types::JsObjectType;
+
+/// Seeds synthesized by `--bootstrap` when there is no initial corpus
+pub const DEFAULT_BOOTSTRAP_SEEDS: usize = 32;
+
+/// Shapes of the synthesized seeds, cycled through so every kind is covered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeedKind {
+    Array,    // let v0 = [1, 2]; v0.push(3); let v1 = v0.indexOf(2);
+    Object,   // let v0 = { a: 1 }; v0.b = "x"; let v1 = Object.keys(v0);
+    Function, // function f0(a0, a1) { return a0 + a1; } let v0 = f0(1, 2);
+    Loop,     // let v0 = 0; for (let v1 = 0; v1 < 10; v1++) { v0 = v0 + v1; }
+    Builtin,  // let v0 = new Map(); let v1 = v0.set(1, 2);
+}
+
+const SEED_KINDS: &[SeedKind] = &[
+    SeedKind::Array,
+    SeedKind::Object,
+    SeedKind::Function,
+    SeedKind::Loop,
+    SeedKind::Builtin,
+];
+
+fn let_stmt(name: &str, init: Expr) -> Stmt {
+    Stmt::Decl(Decl::Var(Box::new(build_var_decl(name, init))))
+}
+
+fn random_primitive(rng: &mut rand::rngs::ThreadRng) -> Expr {
+    let ty = *[JsObjectType::Number, JsObjectType::JsString, JsObjectType::Boolean]
+        .choose(rng)
+        .expect("there are primitive types");
+    build_random_literal(rng, ty)
+}
+
+/// `<obj>.<method>(...)` with a random instance method of the catalog object `object`
+fn build_instance_call(rng: &mut rand::rngs::ThreadRng, object: &str, obj: &str, value_pool: &[String]) -> Option<Expr> {
+    let global = get_global_object(object)?;
+    let method = *global.instance_methods().choose(rng)?;
+    let args = match method.signatures().choose(rng) {
+        Some(signature) => build_args(rng, signature, value_pool),
+        None => Vec::new(),
+    };
+    Some(build_property_call(obj, method.sym(), args))
+}
+
+fn array_seed(rng: &mut rand::rngs::ThreadRng) -> Vec<Stmt> {
+    let elems = (0..rng.random_range(2..=5))
+        .map(|_| {
+            Some(ExprOrSpread {
+                spread: None,
+                expr: Box::new(build_random_literal(rng, JsObjectType::Number)),
+            })
+        })
+        .collect();
+    let mut stmts = vec![
+        let_stmt("v0", Expr::Array(ArrayLit { span: DUMMY_SP, elems })),
+        build_expr_stmt(build_property_call("v0", "push", vec![random_primitive(rng)])),
+    ];
+    if let Some(call) = build_instance_call(rng, "Array", "v0", &["v0".to_string()]) {
+        stmts.push(let_stmt("v1", call));
+    }
+    stmts
+}
+
+fn object_seed(rng: &mut rand::rngs::ThreadRng) -> Vec<Stmt> {
+    let props = ["a", "b", "c"][..rng.random_range(1..=3)]
+        .iter()
+        .map(|key| {
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName::new(Atom::from(*key), DUMMY_SP)),
+                value: Box::new(random_primitive(rng)),
+            })))
+        })
+        .collect();
+    let assign_prop = Expr::Assign(AssignExpr {
+        span: DUMMY_SP,
+        op: AssignOp::Assign,
+        left: AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(build_ident_expr_from_str("v0")),
+            prop: MemberProp::Ident(IdentName::new(Atom::from("d"), DUMMY_SP)),
+        })),
+        right: Box::new(random_primitive(rng)),
+    });
+    let method = *["keys", "values", "entries", "getOwnPropertyNames"]
+        .choose(rng)
+        .expect("there are key listing methods");
+    vec![
+        let_stmt("v0", Expr::Object(ObjectLit { span: DUMMY_SP, props })),
+        build_expr_stmt(assign_prop),
+        let_stmt("v1", build_property_call("Object", method, vec![build_ident_expr_from_str("v0")])),
+    ]
+}
+
+fn function_seed(rng: &mut rand::rngs::ThreadRng) -> Vec<Stmt> {
+    let op = *[op!(bin, "+"), op!(bin, "-"), op!("*"), op!("<"), op!("===")]
+        .choose(rng)
+        .expect("there are operators");
+    let params = ["a0", "a1"]
+        .iter()
+        .map(|name| Param {
+            span: DUMMY_SP,
+            decorators: Vec::new(),
+            pat: Pat::Ident(BindingIdent {
+                id: build_ident(name),
+                type_ann: None,
+            }),
+        })
+        .collect();
+    let body = vec![Stmt::Return(ReturnStmt {
+        span: DUMMY_SP,
+        arg: Some(Box::new(build_bin(op, build_ident_expr_from_str("a0"), build_ident_expr_from_str("a1")))),
+    })];
+    let decl = Stmt::Decl(Decl::Fn(FnDecl {
+        ident: build_ident("f0"),
+        declare: false,
+        function: Box::new(Function {
+            params,
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts: body,
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    }));
+    let call = |rng: &mut rand::rngs::ThreadRng| {
+        Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            callee: Callee::Expr(Box::new(build_ident_expr_from_str("f0"))),
+            args: vec![random_primitive(rng), random_primitive(rng)]
+                .into_iter()
+                .map(|arg| ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(arg),
+                })
+                .collect(),
+            type_args: None,
+        })
+    };
+    vec![decl, let_stmt("v0", call(rng)), let_stmt("v1", call(rng))]
+}
+
+fn loop_seed(rng: &mut rand::rngs::ThreadRng) -> Vec<Stmt> {
+    let bound = rng.random_range(2..=100) as f64;
+    let body = build_expr_stmt(build_assign(
+        AssignOp::Assign,
+        build_ident("v0"),
+        build_bin(op!(bin, "+"), build_ident_expr_from_str("v0"), build_ident_expr_from_str("v1")),
+    ));
+    vec![
+        let_stmt("v0", build_num(0.0)),
+        Stmt::For(ForStmt {
+            span: DUMMY_SP,
+            init: Some(VarDeclOrExpr::VarDecl(Box::new(build_var_decl("v1", build_num(0.0))))),
+            test: Some(Box::new(build_bin(op!("<"), build_ident_expr_from_str("v1"), build_num(bound)))),
+            update: Some(Box::new(Expr::Update(UpdateExpr {
+                span: DUMMY_SP,
+                op: op!("++"),
+                prefix: false,
+                arg: Box::new(build_ident_expr_from_str("v1")),
+            }))),
+            body: Box::new(Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts: vec![body],
+            })),
+        }),
+    ]
+}
+
+/// A catalog object built with one of its constructors, or used through a static method
+/// if it has none, then called a method on
+fn builtin_seed(rng: &mut rand::rngs::ThreadRng) -> Vec<Stmt> {
+    let names = get_global_object_names();
+    let Some(global) = names.choose(rng).and_then(|name| get_global_object(name)) else {
+        return array_seed(rng);
+    };
+    let value_pool = vec!["v0".to_string()];
+    let ctor_signatures = global.get_constructor_signatures();
+    if let Some(signature) = ctor_signatures.choose(rng) {
+        let args = build_args(rng, signature, &[]);
+        let mut stmts = vec![let_stmt("v0", build_ctor_expr(global.sym(), args))];
+        if let Some(call) = build_instance_call(rng, global.sym(), "v0", &value_pool) {
+            stmts.push(let_stmt("v1", call));
+        }
+        return stmts;
+    }
+    match global.static_methods().choose(rng) {
+        Some(method) => {
+            let args = match method.signatures().choose(rng) {
+                Some(signature) => build_args(rng, signature, &[]),
+                None => Vec::new(),
+            };
+            vec![let_stmt("v0", build_property_call(global.sym(), method.sym(), args))]
+        }
+        None => array_seed(rng),
+    }
+}
+
+/// `count` small valid programs covering arrays, objects, functions, loops and the
+/// built-ins of the catalog, to start fuzzing without an initial corpus
+pub fn bootstrap_seeds(count: usize) -> Vec<Script> {
+    let mut rng = rand::rng();
+    (0..count)
+        .map(|i| {
+            let body = match SEED_KINDS[i % SEED_KINDS.len()] {
+                SeedKind::Array => array_seed(&mut rng),
+                SeedKind::Object => object_seed(&mut rng),
+                SeedKind::Function => function_seed(&mut rng),
+                SeedKind::Loop => loop_seed(&mut rng),
+                SeedKind::Builtin => builtin_seed(&mut rng),
+            };
+            Script {
+                span: DUMMY_SP,
+                body,
+                shebang: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+
+    #[test]
+    fn bootstrap_seeds_parse() {
+        let seeds = bootstrap_seeds(20);
+        assert_eq!(seeds.len(), 20);
+        let sources: Vec<String> = seeds
+            .into_iter()
+            .map(|seed| String::from_utf8(generate_js(seed).expect("code generation failed")).unwrap())
+            .collect();
+        for source in &sources {
+            let reparsed = parse_js(source.clone()).expect("bootstrap seed does not parse");
+            assert!(!reparsed.body.is_empty(), "empty seed");
+        }
+        assert!(sources.iter().any(|source| source.contains("for (")));
+        assert!(sources.iter().any(|source| source.contains("function f0(")));
+        assert!(sources.iter().any(|source| source.contains("Object.")));
+        assert!(sources.iter().any(|source| source.contains(".push(")));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bootstrap;
 pub mod code_generator;
 pub mod ast_helpers;
 pub mod il;
\ No newline at end of file
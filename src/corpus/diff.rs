@@ -0,0 +1,182 @@
+use std::collections::HashSet;
+
+use crate::corpus::manager::{CorpusEntry, CorpusMetadata};
+
+/// Totals over the entries of one corpus
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorpusAggregate {
+    pub entries: usize,
+    pub size_bytes: usize,
+    pub total_reward: f64,
+    pub mean_reward: f64,
+    /// Distinct edges contributed by the entries
+    pub edges: usize,
+}
+
+/// What one corpus has that the other doesn't. Entries are matched by fingerprint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorpusDiff {
+    pub a: CorpusAggregate,
+    pub b: CorpusAggregate,
+    /// Ids of the entries of `a` whose fingerprint isn't in `b`
+    pub only_in_a: Vec<u64>,
+    pub only_in_b: Vec<u64>,
+    pub shared_entries: usize,
+    pub edges_only_in_a: usize,
+    pub edges_only_in_b: usize,
+    pub shared_edges: usize,
+}
+
+/// An edge with the engine it belongs to, see `EntryEdges`
+type Edge<'a> = (Option<&'a str>, u32);
+
+fn aggregate(entries: &[CorpusEntry], edges: &HashSet<Edge>) -> CorpusAggregate {
+    let total_reward: f64 = entries.iter().map(|entry| entry.total_reward).sum();
+    CorpusAggregate {
+        entries: entries.len(),
+        size_bytes: entries.iter().map(|entry| entry.size_bytes).sum(),
+        total_reward,
+        mean_reward: if entries.is_empty() { 0.0 } else { total_reward / entries.len() as f64 },
+        edges: edges.len(),
+    }
+}
+
+fn edge_union(entries: &[CorpusEntry]) -> HashSet<Edge<'_>> {
+    entries.iter().flat_map(|entry| entry.edges.iter()).collect()
+}
+
+/// Ids of the entries of `entries` with a fingerprint not in `other`
+fn unique_ids(entries: &[CorpusEntry], other: &[CorpusEntry]) -> Vec<u64> {
+    let other: HashSet<u64> = other.iter().map(|entry| entry.fingerprint).collect();
+    entries
+        .iter()
+        .filter(|entry| !other.contains(&entry.fingerprint))
+        .map(|entry| entry.id)
+        .collect()
+}
+
+pub(crate) fn diff_corpus(a: &CorpusMetadata, b: &CorpusMetadata) -> CorpusDiff {
+    let edges_a = edge_union(&a.entries);
+    let edges_b = edge_union(&b.entries);
+    let only_in_a = unique_ids(&a.entries, &b.entries);
+    let only_in_b = unique_ids(&b.entries, &a.entries);
+    CorpusDiff {
+        a: aggregate(&a.entries, &edges_a),
+        b: aggregate(&b.entries, &edges_b),
+        shared_entries: a.entries.len() - only_in_a.len(),
+        only_in_a,
+        only_in_b,
+        edges_only_in_a: edges_a.difference(&edges_b).count(),
+        edges_only_in_b: edges_b.difference(&edges_a).count(),
+        shared_edges: edges_a.intersection(&edges_b).count(),
+    }
+}
+
+fn format_ids(ids: &[u64]) -> String {
+    const MAX_LISTED: usize = 20;
+    let mut listed: Vec<String> = ids.iter().take(MAX_LISTED).map(u64::to_string).collect();
+    if ids.len() > MAX_LISTED {
+        listed.push(format!("... ({} more)", ids.len() - MAX_LISTED));
+    }
+    listed.join(", ")
+}
+
+pub fn format_corpus_diff(diff: &CorpusDiff) -> String {
+    let mut out = String::new();
+    for (name, aggregate) in [("a", &diff.a), ("b", &diff.b)] {
+        out.push_str(&format!(
+            "[{}] entries: {}, size: {} bytes, reward: {:.2} (mean {:.4}), edges: {}\n",
+            name,
+            aggregate.entries,
+            aggregate.size_bytes,
+            aggregate.total_reward,
+            aggregate.mean_reward,
+            aggregate.edges
+        ));
+    }
+    out.push_str(&format!("shared entries: {}, shared edges: {}\n", diff.shared_entries, diff.shared_edges));
+    out.push_str(&format!(
+        "only in a: {} entries, {} edges [{}]\n",
+        diff.only_in_a.len(),
+        diff.edges_only_in_a,
+        format_ids(&diff.only_in_a)
+    ));
+    out.push_str(&format!(
+        "only in b: {} entries, {} edges [{}]\n",
+        diff.only_in_b.len(),
+        diff.edges_only_in_b,
+        format_ids(&diff.only_in_b)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn entry(id: u64, fingerprint: u64, edge_hits: Vec<u32>, total_reward: f64) -> CorpusEntry {
+        CorpusEntry {
+            id,
+            path: PathBuf::from(format!("seed_{}.js", id)),
+            fingerprint,
+            edges: edge_hits.into(),
+            size_bytes: 10,
+            total_reward,
+            last_reward: 0.0,
+            exec_time_ms: Duration::from_millis(1),
+            num_mutations: 0,
+            last_selected_ts: None,
+            mutations_since_new_coverage: 0,
+            tags: Vec::new(),
+            parent_id: None,
+            producer: None,
+        }
+    }
+
+    fn metadata(entries: Vec<CorpusEntry>) -> CorpusMetadata {
+        CorpusMetadata {
+            next_id: entries.len() as u64,
+            journal_seqs: Default::default(),
+            entries,
+        }
+    }
+
+    #[test]
+    fn diff_reports_unique_entries_and_edges() {
+        let a = metadata(vec![
+            entry(0, 100, vec![1, 2], 1.0),
+            entry(1, 101, vec![3], 3.0),
+        ]);
+        // same fingerprint as a's first entry under another id
+        let b = metadata(vec![
+            entry(5, 100, vec![1, 2], 2.0),
+            entry(6, 200, vec![2, 4, 5], 0.0),
+            entry(7, 201, vec![], 1.0),
+        ]);
+
+        let diff = diff_corpus(&a, &b);
+        assert_eq!(diff.only_in_a, vec![1]);
+        assert_eq!(diff.only_in_b, vec![6, 7]);
+        assert_eq!(diff.shared_entries, 1);
+        assert_eq!(diff.edges_only_in_a, 1);
+        assert_eq!(diff.edges_only_in_b, 2);
+        assert_eq!(diff.shared_edges, 2);
+        assert_eq!(
+            diff.a,
+            CorpusAggregate {
+                entries: 2,
+                size_bytes: 20,
+                total_reward: 4.0,
+                mean_reward: 2.0,
+                edges: 3,
+            }
+        );
+        assert_eq!(diff.b.entries, 3);
+        assert_eq!(diff.b.edges, 4);
+
+        let report = format_corpus_diff(&diff);
+        assert!(report.contains("only in b: 2 entries, 2 edges [6, 7]"), "unexpected report {}", report);
+    }
+}
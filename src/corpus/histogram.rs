@@ -0,0 +1,92 @@
+/// Upper bounds (exclusive) of the size buckets, the last bucket takes everything larger
+const BUCKET_BOUNDS: [usize; 6] = [256, 1024, 4 * 1024, 16 * 1024, 64 * 1024, 256 * 1024];
+
+/// Number of corpus programs per size range, to see whether the corpus drifts toward
+/// large programs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeHistogram {
+    counts: [u64; BUCKET_BOUNDS.len() + 1],
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes >= 1024 {
+        format!("{}K", bytes / 1024)
+    } else {
+        bytes.to_string()
+    }
+}
+
+impl SizeHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_sizes(sizes: impl IntoIterator<Item = usize>) -> Self {
+        let mut histogram = Self::new();
+        for size in sizes {
+            histogram.record(size);
+        }
+        histogram
+    }
+
+    fn bucket(size: usize) -> usize {
+        BUCKET_BOUNDS
+            .iter()
+            .position(|&bound| size < bound)
+            .unwrap_or(BUCKET_BOUNDS.len())
+    }
+
+    pub fn record(&mut self, size: usize) {
+        self.counts[Self::bucket(size)] += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Label and count of every bucket, smallest sizes first
+    pub fn buckets(&self) -> Vec<(String, u64)> {
+        let mut lower = 0;
+        let mut buckets = Vec::with_capacity(self.counts.len());
+        for (i, &count) in self.counts.iter().enumerate() {
+            let label = match BUCKET_BOUNDS.get(i) {
+                Some(&upper) => format!("{}-{}", format_size(lower), format_size(upper)),
+                None => format!("{}+", format_size(lower)),
+            };
+            buckets.push((label, count));
+            lower = BUCKET_BOUNDS.get(i).copied().unwrap_or(lower);
+        }
+        buckets
+    }
+
+    /// One line with the count and share of every bucket, e.g.
+    /// `0-256: 12 (40.0%), 256-1K: 18 (60.0%), ...`
+    pub fn format(&self) -> String {
+        let total = self.total();
+        self.buckets()
+            .into_iter()
+            .map(|(label, count)| {
+                let share = if total == 0 { 0.0 } else { count as f64 / total as f64 * 100.0 };
+                format!("{}: {} ({:.1}%)", label, count, share)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_land_in_their_buckets() {
+        let histogram = SizeHistogram::from_sizes([0, 255, 256, 1000, 5000, 5000, 300 * 1024]);
+        assert_eq!(histogram.total(), 7);
+        let counts: Vec<u64> = histogram.buckets().into_iter().map(|(_, count)| count).collect();
+        assert_eq!(counts, vec![2, 2, 0, 2, 0, 0, 1]);
+        let labels: Vec<String> = histogram.buckets().into_iter().map(|(label, _)| label).collect();
+        assert_eq!(labels.first().map(String::as_str), Some("0-256"));
+        assert_eq!(labels.last().map(String::as_str), Some("256K+"));
+        assert!(histogram.format().starts_with("0-256: 2 (28.6%), 256-1K: 2 (28.6%)"));
+    }
+}
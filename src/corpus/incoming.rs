@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use tokio::fs;
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinHandle;
+
+use crate::corpus::CorpusManager;
+use crate::mutators::minifier::Minifier;
+use crate::parsing::parser::{generate_js_with_decls, parse_js_with_decls, source_type};
+use crate::runner::pool::{FuzzPool, JobResults, Outcome};
+use crate::utils::reward::RewardConfig;
+
+/// How often `--watch-incoming` looks for new files
+pub const INCOMING_SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Processed files are moved here, under the watched directory
+const DONE_DIR: &str = "done";
+
+/// Scans a file that doesn't parse survives before it is given up on and moved to the
+/// done directory. Files still being written fail to parse until they are complete.
+const MAX_PARSE_ATTEMPTS: u32 = 5;
+
+/// A parsed program dropped in the watched directory, regenerated the same way as the
+/// initial corpus
+#[derive(Debug)]
+pub struct IncomingSeed {
+    pub path: PathBuf,
+    pub code: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct PendingFile {
+    len: u64,
+    modified: Option<SystemTime>,
+    failed_parses: u32,
+    /// Handed out by `scan`, waiting to be executed and moved
+    sent: bool,
+}
+
+/// Picks up the files other tools drop into a directory, e.g. another fuzzer's queue.
+/// A file is only read once its size and modification time are the same in two
+/// consecutive scans, so half-written files are usually skipped; the ones that are
+/// still caught midway fail to parse and are retried on later scans.
+pub struct IncomingWatcher {
+    dir: PathBuf,
+    seed_ext: String,
+    pending: HashMap<PathBuf, PendingFile>,
+}
+
+impl IncomingWatcher {
+    pub fn new(dir: PathBuf, seed_ext: &str) -> Self {
+        Self {
+            dir,
+            seed_ext: seed_ext.to_string(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Programs of the files that are ready, every file is returned once
+    pub async fn scan(&mut self) -> Result<Vec<IncomingSeed>> {
+        let mut seeds = Vec::new();
+        let mut present = Vec::new();
+        let mut dir = fs::read_dir(&self.dir)
+        .await
+        .with_context(|| format!("failed to read incoming directory {:?}", self.dir))?;
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata().await else {
+                continue;
+            };
+            if !metadata.is_file() || !crate::has_seed_ext(&path, &self.seed_ext) {
+                continue;
+            }
+            present.push(path.clone());
+            let (len, modified) = (metadata.len(), metadata.modified().ok());
+
+            let pending = self.pending.entry(path.clone()).or_insert(PendingFile {
+                len,
+                modified,
+                failed_parses: 0,
+                sent: false,
+            });
+            if pending.sent {
+                continue;
+            }
+            if pending.len != len || pending.modified != modified {
+                pending.len = len;
+                pending.modified = modified;
+                continue;
+            }
+            match prepare_program(&path).await {
+                Ok(code) => {
+                    pending.sent = true;
+                    seeds.push(IncomingSeed { path, code });
+                }
+                Err(err) => {
+                    pending.failed_parses += 1;
+                    if pending.failed_parses >= MAX_PARSE_ATTEMPTS {
+                        eprintln!("[incoming] giving up on {:?}: {:?}", path, err);
+                        self.pending.remove(&path);
+                        if let Err(err) = move_to_done(&self.dir, &path).await {
+                            eprintln!("{:?}", err);
+                        }
+                    }
+                }
+            }
+        }
+        // files moved away by someone else
+        self.pending.retain(|path, _| present.contains(path));
+        Ok(seeds)
+    }
+}
+
+/// Parse, minify and regenerate the program in `path`
+async fn prepare_program(path: &Path) -> Result<Vec<u8>> {
+    let source = fs::read(path)
+    .await
+    .with_context(|| format!("failed to read {:?}", path))?;
+    let source = String::from_utf8(source).with_context(|| format!("{:?} is not UTF-8", path))?;
+    let (script, module_decls) =
+        parse_js_with_decls(source, source_type()).with_context(|| format!("failed to parse {:?}", path))?;
+    let minified = Minifier.mutate(script)?;
+    generate_js_with_decls(minified, module_decls)
+}
+
+async fn move_to_done(dir: &Path, path: &Path) -> Result<()> {
+    let done_dir = dir.join(DONE_DIR);
+    fs::create_dir_all(&done_dir)
+    .await
+    .with_context(|| format!("failed to create {:?}", done_dir))?;
+    let file_name = path
+    .file_name()
+    .with_context(|| format!("{:?} has no file name", path))?;
+    fs::rename(path, done_dir.join(file_name))
+    .await
+    .with_context(|| format!("failed to move {:?} to {:?}", path, done_dir))
+}
+
+/// Scan `dir` every `interval` in the background, the returned channel yields the new
+/// programs
+pub fn spawn_incoming_watcher(dir: PathBuf, seed_ext: &str, interval: Duration) -> mpsc::Receiver<IncomingSeed> {
+    let (seed_tx, seed_rx) = mpsc::channel(64);
+    let mut watcher = IncomingWatcher::new(dir, seed_ext);
+    tokio::spawn(async move {
+        loop {
+            match watcher.scan().await {
+                Ok(seeds) => {
+                    for seed in seeds {
+                        if seed_tx.send(seed).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(err) => eprintln!("{:?}", err),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+    seed_rx
+}
+
+/// Run `seed` on every engine and add it to the corpus if it finds new coverage, then
+/// move its file to the done directory next to it
+pub async fn ingest_incoming_seed(
+    pool: &mut FuzzPool,
+    corpus_manager: Arc<Mutex<CorpusManager>>,
+    seed: IncomingSeed,
+    rewards: RewardConfig,
+) -> Result<JoinHandle<()>> {
+    let mut result_rx = pool.schedule_job(seed.code.clone()).await?;
+    Ok(tokio::spawn(async move {
+        // one result arrives per engine in the pool, the seed is added once for all
+        let (job_results, errors) = JobResults::collect(&mut result_rx).await;
+        for err in errors {
+            eprintln!("Worker execution error: {:?}", err);
+        }
+        let outcome = job_results.outcome();
+        let mut added = false;
+        if outcome == Outcome::NewCoverage {
+            let mut mgr = corpus_manager.lock().await;
+            match mgr
+            .add_entry(
+                &seed.code,
+                job_results.new_edges(),
+                crate::compute_reward(&outcome, &rewards),
+                job_results.exec_time_ms(),
+                false,
+            )
+            .await
+            {
+                Ok(entry) => added = entry.is_some(),
+                Err(err) => eprintln!("Failed to add {:?} to the corpus: {:?}", seed.path, err),
+            }
+        }
+        if added {
+            println!("[incoming] added {:?} to the corpus", seed.path);
+        }
+        let dir = seed.path.parent().unwrap_or(Path::new("."));
+        if let Err(err) = move_to_done(dir, &seed.path).await {
+            eprintln!("{:?}", err);
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::pool::JobResult;
+
+    #[tokio::test]
+    async fn incoming_files_are_ingested_and_moved() {
+        let root = std::env::temp_dir().join(format!("jellyfuzz_incoming_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let incoming = root.join("incoming");
+        std::fs::create_dir_all(&incoming).unwrap();
+        let corpus_manager = Arc::new(Mutex::new(CorpusManager::load(root.join("corpus")).await.unwrap()));
+        let mut pool = FuzzPool::mock("mock", |_| JobResult {
+            engine: "mock".to_string(),
+            status_code: 0,
+            signal: 0,
+            new_coverage: true,
+            edge_hits: vec![1, 2, 3],
+            is_crash: false,
+            is_timeout: false,
+            is_stack_overflow: false,
+            exec_time_ms: Duration::from_millis(1),
+            sanitizer: None,
+            cached: false,
+        });
+
+        // a file that is still being written doesn't parse yet
+        let path = incoming.join("seed.js");
+        std::fs::write(&path, "let v0 = [1, 2").unwrap();
+        let mut watcher = IncomingWatcher::new(incoming.clone(), "js");
+        assert!(watcher.scan().await.unwrap().is_empty());
+        assert!(watcher.scan().await.unwrap().is_empty());
+
+        std::fs::write(&path, "let v0 = [1, 2, 3];\nv0.push(4);").unwrap();
+        assert!(watcher.scan().await.unwrap().is_empty());
+        let mut seeds = watcher.scan().await.unwrap();
+        assert_eq!(seeds.len(), 1);
+        assert!(watcher.scan().await.unwrap().is_empty(), "a seed was handed out twice");
+
+        let handle = ingest_incoming_seed(
+            &mut pool,
+            Arc::clone(&corpus_manager),
+            seeds.pop().unwrap(),
+            RewardConfig::default(),
+        )
+        .await
+        .unwrap();
+        handle.await.unwrap();
+
+        assert_eq!(corpus_manager.lock().await.len(), 1);
+        assert!(!path.exists());
+        assert!(incoming.join(DONE_DIR).join("seed.js").exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
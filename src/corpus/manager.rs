@@ -1,6 +1,9 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::fs as async_fs;
 
@@ -11,8 +14,75 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 
-const METADATA_FILE: &str = "metadata.json";
+use crate::corpus::histogram::SizeHistogram;
+use crate::corpus::writer::{CorpusOp, CorpusWriter};
+use crate::runner::sanitizer::SanitizerReport;
+use crate::utils::rand_utils::random_weighted_choice;
+
+pub(crate) const METADATA_FILE: &str = "metadata.json";
+const JOURNAL_FILE: &str = "metadata.journal";
+const JOURNAL_EXTENSION: &str = "journal";
+/// The snapshot is rewritten and the journal truncated once it holds this many records
+const JOURNAL_COMPACT_RECORDS: usize = 4096;
 const MAX_MUTATIONS_PER_ENTRY: u64 = 128;
+/// Bounds of the number of mutations a seed gets without new coverage before it fades
+const MIN_MUTATION_BUDGET: u64 = 8;
+const MAX_MUTATION_BUDGET: u64 = 64;
+/// Programs above this size get half the budget, mutating them is slower and noisier
+const LARGE_SEED_BYTES: usize = 4096;
+/// Selection weight is divided by this for every budget spent without new coverage
+const BUDGET_DECAY: f64 = 10.0;
+/// Entries whose execution took longer are tagged "slow"
+const SLOW_ENTRY_MS: u128 = 100;
+const TIMEOUTS_DIR: &str = "timeouts";
+/// Seeds of the sharded layout go to `seeds/<prefix>/`
+const SHARDED_SEEDS_DIR: &str = "seeds";
+const STACK_OVERFLOWS_DIR: &str = "stackoverflow";
+pub const DEFAULT_MAX_TIMEOUTS: usize = 1000;
+const DONOR_CACHE_SIZE: usize = 64;
+/// Complementary donors are picked among this many random entries, so the pick stays
+/// cheap on big corpora and the same donor doesn't win every time
+const COMPLEMENTARY_CANDIDATES: usize = 32;
+
+/// Edges a program hit. Edge ids of different engines are unrelated, so the edges of
+/// a program executed on several engines are kept per engine.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryEdges {
+    /// Edges of a program executed on a single engine
+    pub edge_hits: Vec<u32>,
+    /// Edges of a program executed on several engines, by engine name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub engine_edges: BTreeMap<String, Vec<u32>>,
+}
+
+impl EntryEdges {
+    pub fn len(&self) -> usize {
+        self.edge_hits.len() + self.engine_edges.values().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every edge with the engine it belongs to, None for `edge_hits`
+    pub fn iter(&self) -> impl Iterator<Item = (Option<&str>, u32)> + '_ {
+        let single = self.edge_hits.iter().map(|&edge| (None, edge));
+        let per_engine = self
+            .engine_edges
+            .iter()
+            .flat_map(|(engine, edges)| edges.iter().map(move |&edge| (Some(engine.as_str()), edge)));
+        single.chain(per_engine)
+    }
+}
+
+impl From<Vec<u32>> for EntryEdges {
+    fn from(edge_hits: Vec<u32>) -> Self {
+        Self {
+            edge_hits,
+            engine_edges: BTreeMap::new(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorpusEntry {
@@ -20,19 +90,154 @@ pub struct CorpusEntry {
     /// Path relative to the corpus root directory.
     pub path: PathBuf,
     pub fingerprint: u64,
-    pub edge_hits: Vec<u32>,
+    #[serde(flatten)]
+    pub edges: EntryEdges,
     pub size_bytes: usize,
     pub total_reward: f64,
     pub last_reward: f64,
     pub exec_time_ms: Duration,
     pub num_mutations: u64,
     pub last_selected_ts: Option<u64>,
+    /// Times the seed was picked since one of its mutants last found new coverage
+    #[serde(default)]
+    pub mutations_since_new_coverage: u64,
+    /// Free-form labels for later analysis, e.g. "slow" or "regex-heavy"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Seed this entry was mutated from, None for ingested entries
+    #[serde(default)]
+    pub parent_id: Option<u64>,
+    /// Name of the mutator that produced this entry from its parent
+    #[serde(default)]
+    pub producer: Option<String>,
+}
+
+impl CorpusEntry {
+    /// Mutations the seed gets without new coverage before its selection weight decays.
+    /// Seeds that reached more edges get more, large ones less.
+    pub fn mutation_budget(&self) -> u64 {
+        let mut budget = MIN_MUTATION_BUDGET + self.edges.len() as u64 / 4;
+        if self.size_bytes > LARGE_SEED_BYTES {
+            budget /= 2;
+        }
+        budget.clamp(MIN_MUTATION_BUDGET, MAX_MUTATION_BUDGET)
+    }
+
+    /// Weight of the seed when picking one to mutate, 1.0 while within its budget
+    pub fn selection_weight(&self) -> f64 {
+        let budget = self.mutation_budget();
+        if self.mutations_since_new_coverage <= budget {
+            return 1.0;
+        }
+        let overruns = (self.mutations_since_new_coverage - budget).div_ceil(budget);
+        // capped so the weight never reaches 0 and the seed can still be picked
+        BUDGET_DECAY.powi(-(overruns.min(6) as i32))
+    }
+
+    /// Tags for the notable properties of the entry's execution and program
+    pub fn notable_tags(&self) -> Vec<&'static str> {
+        let mut tags = Vec::new();
+        if self.exec_time_ms.as_millis() > SLOW_ENTRY_MS {
+            tags.push("slow");
+        }
+        if self.size_bytes > LARGE_SEED_BYTES {
+            tags.push("large");
+        }
+        tags
+    }
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct CorpusMetadata {
+    pub next_id: u64,
+    /// Sequence number of the last record of each journal included in this snapshot, by
+    /// journal file name
+    #[serde(default)]
+    pub journal_seqs: BTreeMap<String, u64>,
+    pub entries: Vec<CorpusEntry>,
+}
+
+/// A change to the corpus metadata, appended to the journal instead of rewriting the
+/// whole snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum JournalRecord {
+    Add(CorpusEntry),
+    Result {
+        id: u64,
+        reward: f64,
+        exec_time_ms: Duration,
+        num_mutations: u64,
+        last_selected_ts: Option<u64>,
+        #[serde(default)]
+        mutations_since_new_coverage: u64,
+    },
+    Remove(u64),
+    Tag {
+        id: u64,
+        tag: String,
+    },
+}
+
+/// One line of the journal
 #[derive(Debug, Serialize, Deserialize)]
-struct CorpusMetadata {
-    next_id: u64,
-    entries: Vec<CorpusEntry>,
+struct JournalLine {
+    seq: u64,
+    record: JournalRecord,
+}
+
+impl CorpusMetadata {
+    fn apply(&mut self, record: JournalRecord) {
+        match record {
+            JournalRecord::Add(entry) => {
+                self.next_id = self.next_id.max(entry.id.saturating_add(1));
+                if !self.entries.iter().any(|known| known.id == entry.id) {
+                    self.entries.push(entry);
+                }
+            }
+            JournalRecord::Result {
+                id,
+                reward,
+                exec_time_ms,
+                num_mutations,
+                last_selected_ts,
+                mutations_since_new_coverage,
+            } => {
+                if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+                    entry.last_reward = reward;
+                    entry.total_reward += reward;
+                    entry.exec_time_ms = exec_time_ms;
+                    entry.num_mutations = num_mutations;
+                    entry.last_selected_ts = last_selected_ts;
+                    entry.mutations_since_new_coverage = mutations_since_new_coverage;
+                }
+            }
+            JournalRecord::Remove(id) => self.entries.retain(|entry| entry.id != id),
+            JournalRecord::Tag { id, tag } => {
+                if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+                    if !entry.tags.contains(&tag) {
+                        entry.tags.push(tag);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Where a new corpus entry comes from
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    pub parent_id: u64,
+    pub producer: String,
+}
+
+/// Sidecar of a saved crash, `crash_*.origin.json`, telling which seed and mutator it
+/// comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashOrigin {
+    pub engine: String,
+    pub parent_id: u64,
+    pub mutator: String,
+    pub timestamp: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -41,15 +246,156 @@ pub struct CorpusSelection {
     pub path: PathBuf,
 }
 
+/// What makes two corpus entries duplicates of each other
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DedupMode {
+    /// Same edge set, whatever the program. Keeps only the first program reaching it.
+    Coverage,
+    /// Same program bytes
+    Source,
+    /// Same program bytes and same edges
+    #[default]
+    Both,
+}
+
+/// Where new seed files are written. Entries keep their path in the metadata, so a
+/// corpus written with either layout, or a mix of both, loads the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CorpusLayout {
+    /// `seed_{id}.js` in the corpus root
+    #[default]
+    Flat,
+    /// `seeds/ab/seed_{id}.js`, spread over 256 directories by a hash of the file name,
+    /// for filesystems that get slow with 100k files in one directory
+    Sharded,
+}
+
+impl CorpusLayout {
+    /// Path of the seed `id` relative to the corpus root
+    pub fn seed_path(self, id: u64) -> PathBuf {
+        let file_name = format!("seed_{id}.js");
+        match self {
+            CorpusLayout::Flat => PathBuf::from(file_name),
+            CorpusLayout::Sharded => {
+                let hash = format!("{:x}", md5::compute(&file_name));
+                PathBuf::from(SHARDED_SEEDS_DIR).join(&hash[..2]).join(file_name)
+            }
+        }
+    }
+}
+
+/// Subset of seed ids owned by one of several fuzzer instances sharing a corpus directory.
+/// Shard `index/total` selects seeds with `id % total == index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub total: u64,
+}
+
+impl Shard {
+    pub fn owns(&self, id: u64) -> bool {
+        id % self.total == self.index
+    }
+
+    /// Smallest id at or after `id` owned by the shard
+    pub fn next_owned(&self, id: u64) -> u64 {
+        id + (self.index + self.total - id % self.total) % self.total
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (index, total) = s
+            .split_once('/')
+            .ok_or_else(|| format!("expected <index>/<total>, got {:?}", s))?;
+        let index: u64 = index
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid shard index {:?}", index))?;
+        let total: u64 = total
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid shard total {:?}", total))?;
+        if total == 0 || index >= total {
+            return Err(format!("shard index must be smaller than the total, got {}/{}", index, total));
+        }
+        Ok(Self { index, total })
+    }
+}
+
+/// Small LRU of parsed donor scripts, so splicing doesn't re-read and re-parse the same
+/// popular donors from disk every time
+struct DonorCache {
+    capacity: usize,
+    // least recently used first
+    entries: VecDeque<(u64, Script)>,
+}
+
+impl DonorCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    fn get(&mut self, id: u64) -> Option<Script> {
+        let pos = self.entries.iter().position(|(cached_id, _)| *cached_id == id)?;
+        let entry = self.entries.remove(pos)?;
+        let script = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(script)
+    }
+
+    fn insert(&mut self, id: u64, script: Script) {
+        self.remove(id);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((id, script));
+    }
+
+    fn remove(&mut self, id: u64) {
+        self.entries.retain(|(cached_id, _)| *cached_id != id);
+    }
+}
+
 pub struct CorpusManager {
     root: PathBuf,
     metadata_path: PathBuf,
+    journal_path: PathBuf,
     entries: Vec<CorpusEntry>,
     next_id: u64,
+    journal_seq: u64,
+    journal_len: usize,
+    shard: Option<Shard>,
+    // timeout repros are stored separately and capped so a pathological seed can't fill the disk
+    max_timeouts: usize,
+    stored_timeouts: usize,
+    timeouts_seen: u64,
+    timeout_fingerprints: HashSet<u64>,
+    save_stack_overflows: bool,
+    /// Also put the parent seed and mutator of crashes without a sanitizer report in
+    /// their file name
+    crash_origin_in_name: bool,
+    dedup_mode: DedupMode,
+    layout: CorpusLayout,
+    donor_cache: DonorCache,
+    /// Background task doing the disk writes, None to write them inline
+    writer: Option<CorpusWriter>,
 }
 
 impl CorpusManager {
     pub async fn load(root: PathBuf) -> Result<Self> {
+        Self::load_sharded(root, None).await
+    }
+    
+    /// Load the corpus as the instance running `shard`. Seed selection is restricted to the
+    /// ids the shard owns, and changes go to a journal of its own so instances sharing the
+    /// directory don't interleave their records or truncate each other's.
+    pub async fn load_sharded(root: PathBuf, shard: Option<Shard>) -> Result<Self> {
         if fs::metadata(&root).await.is_err() {
             fs::create_dir_all(&root)
             .await
@@ -57,31 +403,148 @@ impl CorpusManager {
         }
         
         let metadata_path = root.join(METADATA_FILE);
-        let (entries, next_id) = if fs::metadata(&metadata_path).await.is_ok() {
-            let blob = fs::read(&metadata_path)
-            .await
-            .with_context(|| format!("failed to read metadata {:?}", metadata_path))?;
-            if blob.is_empty() {
-                (Vec::new(), 0)
-            } else {
-                let meta: CorpusMetadata = serde_json::from_slice(&blob)
-                .with_context(|| "failed to deserialize corpus metadata".to_string())?;
-                let max_id = meta.entries.iter().map(|e| e.id).max().unwrap_or(0);
-                let next = meta.next_id.max(max_id.saturating_add(1));
-                (meta.entries, next)
-            }
-        } else {
-            (Vec::new(), 0)
+        let journal_name = journal_file_name(shard);
+        let journal_path = root.join(&journal_name);
+        let (meta, journal_lens) = read_metadata(&metadata_path).await?;
+        
+        let stored_timeouts = match std::fs::read_dir(root.join(TIMEOUTS_DIR)) {
+            Ok(dir) => dir.filter(|entry| entry.is_ok()).count(),
+            Err(_) => 0,
         };
         
         Ok(Self {
             root,
             metadata_path,
-            entries,
-            next_id,
+            journal_path,
+            entries: meta.entries,
+            next_id: meta.next_id,
+            journal_seq: meta.journal_seqs.get(&journal_name).copied().unwrap_or(0),
+            journal_len: journal_lens.get(&journal_name).copied().unwrap_or(0),
+            shard,
+            max_timeouts: DEFAULT_MAX_TIMEOUTS,
+            stored_timeouts,
+            timeouts_seen: 0,
+            timeout_fingerprints: HashSet::new(),
+            save_stack_overflows: false,
+            crash_origin_in_name: false,
+            dedup_mode: DedupMode::default(),
+            layout: CorpusLayout::default(),
+            donor_cache: DonorCache::new(DONOR_CACHE_SIZE),
+            writer: None,
         })
     }
     
+    /// Hand the disk writes over to a background task from now on, so adding, updating
+    /// and removing entries don't wait for the disk. Writes are applied in order; call
+    /// `close_writer` before exiting so the queued ones aren't lost.
+    pub fn spawn_writer(&mut self) {
+        if self.writer.is_none() {
+            self.writer = Some(CorpusWriter::spawn());
+        }
+    }
+    
+    /// Wait until every write queued so far reached the disk
+    pub async fn flush_writes(&self) -> Result<()> {
+        match &self.writer {
+            Some(writer) => writer.flush().await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Apply the queued writes and go back to writing inline
+    pub async fn close_writer(&mut self) -> Result<()> {
+        match self.writer.take() {
+            Some(writer) => writer.close().await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Queue `op` on the writer, or apply it right away without one
+    async fn persist_op(&self, op: CorpusOp) -> Result<()> {
+        match &self.writer {
+            Some(writer) => writer.send(op),
+            None => op.apply().await,
+        }
+    }
+    
+    /// Read a corpus file, including one whose write is still queued
+    pub async fn read_file(&self, path: &Path) -> Result<Vec<u8>> {
+        if let Some(contents) = self.writer.as_ref().and_then(|writer| writer.pending().get(path)) {
+            return Ok(contents.to_vec());
+        }
+        fs::read(path)
+        .await
+        .with_context(|| format!("failed to read corpus entry {:?}", path))
+    }
+    
+    /// Fingerprints are computed with the mode in effect when an entry is added, so entries
+    /// loaded from a corpus built with another mode won't be recognized as duplicates
+    pub fn set_dedup_mode(&mut self, dedup_mode: DedupMode) {
+        self.dedup_mode = dedup_mode;
+    }
+    
+    /// Layout of the seed files added from now on, existing entries stay where they are
+    pub fn set_layout(&mut self, layout: CorpusLayout) {
+        self.layout = layout;
+    }
+    
+    /// Stop writing timeout repros once `max_timeouts` of them are stored
+    pub fn set_max_timeouts(&mut self, max_timeouts: usize) {
+        self.max_timeouts = max_timeouts;
+    }
+    
+    /// Keep repros of programs overflowing the stack in the stackoverflow directory
+    pub fn set_save_stack_overflows(&mut self, save_stack_overflows: bool) {
+        self.save_stack_overflows = save_stack_overflows;
+    }
+    
+    pub fn set_crash_origin_in_name(&mut self, crash_origin_in_name: bool) {
+        self.crash_origin_in_name = crash_origin_in_name;
+    }
+    
+    /// Number of timeouts passed to `add_entry`, including the ones that weren't stored
+    pub fn timeouts_seen(&self) -> u64 {
+        self.timeouts_seen
+    }
+    
+    pub fn stored_timeouts(&self) -> usize {
+        self.stored_timeouts
+    }
+    
+    pub fn shard(&self) -> Option<Shard> {
+        self.shard
+    }
+    
+    /// Instances sharing a directory only hand out ids their shard owns, so they never
+    /// allocate the same one between two reloads
+    fn allocate_id(&mut self) -> u64 {
+        let id = match self.shard {
+            Some(shard) => shard.next_owned(self.next_id),
+            None => self.next_id,
+        };
+        self.next_id = id + 1;
+        id
+    }
+    
+    /// Merge entries other instances added to the metadata snapshot and journals since
+    /// they were last read. Entries are matched by id, which sharded instances only allocate
+    /// from the ids their shard owns. Returns the number of new entries.
+    pub async fn reload_metadata(&mut self) -> Result<usize> {
+        let (meta, _) = read_metadata(&self.metadata_path).await?;
+        
+        let mut added = 0;
+        for entry in meta.entries {
+            if self.entries.iter().any(|known| known.id == entry.id) {
+                continue;
+            }
+            self.next_id = self.next_id.max(entry.id.saturating_add(1));
+            self.entries.push(entry);
+            added += 1;
+        }
+        self.next_id = self.next_id.max(meta.next_id);
+        Ok(added)
+    }
+    
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
@@ -90,6 +553,11 @@ impl CorpusManager {
         self.entries.len()
     }
     
+    /// How the sizes of the corpus programs are distributed
+    pub fn size_histogram(&self) -> SizeHistogram {
+        SizeHistogram::from_sizes(self.entries.iter().map(|entry| entry.size_bytes))
+    }
+    
     #[allow(dead_code)]
     pub fn entries(&self) -> &[CorpusEntry] {
         &self.entries
@@ -101,14 +569,38 @@ impl CorpusManager {
         .any(|entry| entry.fingerprint == fingerprint)
     }
     
+    /// Pick a random seed, weighted by `CorpusEntry::selection_weight`. When sharded, only
+    /// seeds owned by the shard are picked unless the shard doesn't own any yet.
     pub fn pick_random(&mut self) -> Option<CorpusSelection> {
+        self.pick_random_with(&mut rand::rng())
+    }
+
+    /// `pick_random` drawing from `rng`, for the seeded per-iteration RNG of `--rng-seed`
+    pub fn pick_random_with(&mut self, rng: &mut impl Rng) -> Option<CorpusSelection> {
         if self.entries.is_empty() {
             return None;
         }
-        let mut rng = rand::rng();
-        let idx = rng.random_range(0..self.entries.len());
+        let mut candidates: Vec<usize> = match self.shard {
+            Some(shard) => self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| shard.owns(entry.id))
+                .map(|(idx, _)| idx)
+                .collect(),
+            None => Vec::new(),
+        };
+        if candidates.is_empty() {
+            candidates = (0..self.entries.len()).collect();
+        }
+        let weights: Vec<(usize, f64)> = candidates
+            .iter()
+            .map(|&idx| (idx, self.entries[idx].selection_weight()))
+            .collect();
+        let idx = random_weighted_choice(rng, &weights);
         let entry = &mut self.entries[idx];
         entry.num_mutations = entry.num_mutations.saturating_add(1);
+        entry.mutations_since_new_coverage = entry.mutations_since_new_coverage.saturating_add(1);
         entry.last_selected_ts = Some(current_timestamp());
         Some(CorpusSelection {
             id: entry.id,
@@ -118,13 +610,20 @@ impl CorpusManager {
     
     pub async fn record_result(&mut self, id: u64, reward: f64, exec_time_ms: Duration) -> Result<()> {
         let mut should_remove = false;
-        let mut updated = false;
+        let mut update = None;
         {
             if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
                 entry.last_reward = reward;
                 entry.total_reward += reward;
                 entry.exec_time_ms = exec_time_ms;
-                updated = true;
+                update = Some(JournalRecord::Result {
+                    id,
+                    reward,
+                    exec_time_ms,
+                    num_mutations: entry.num_mutations,
+                    last_selected_ts: entry.last_selected_ts,
+                    mutations_since_new_coverage: entry.mutations_since_new_coverage,
+                });
                 if entry.num_mutations > MAX_MUTATIONS_PER_ENTRY {
                     should_remove = true;
                 }
@@ -134,129 +633,378 @@ impl CorpusManager {
         if should_remove {
             self.remove_entry(id).await?;
             Ok(())
-        } else if updated {
-            self.persist().await?; // TODO: optimize by only calling this function after a number of updates
-            Ok(())
+        } else if let Some(update) = update {
+            self.append_journal(update).await
         } else {
             Ok(())
         }
     }
     
+    /// A mutant of seed `id` found new coverage, it gets a fresh mutation budget. The
+    /// reset is journaled with the next result of the seed.
+    pub fn record_new_coverage(&mut self, id: u64) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.mutations_since_new_coverage = 0;
+        }
+    }
+    
     pub async fn add_entry(
         &mut self,
         script_bytes: &[u8],
-        edge_hits: Vec<u32>,
+        edges: impl Into<EntryEdges>,
         reward: f64,
         exec_time_ms: Duration,
         is_timeout: bool,
     ) -> Result<Option<CorpusEntry>> {
-        let fingerprint = compute_fingerprint(script_bytes, &edge_hits);
-        if self.contains_fingerprint(fingerprint) {
+        self.add_entry_with_provenance(script_bytes, edges, reward, exec_time_ms, is_timeout, None)
+        .await
+    }
+    
+    /// `add_entry` for a program mutated from another entry, recording where it comes from
+    pub async fn add_entry_with_provenance(
+        &mut self,
+        script_bytes: &[u8],
+        edges: impl Into<EntryEdges>,
+        reward: f64,
+        exec_time_ms: Duration,
+        is_timeout: bool,
+        provenance: Option<Provenance>,
+    ) -> Result<Option<CorpusEntry>> {
+        let edges = edges.into();
+        // timeouts carry no edges, so they are always told apart by their source
+        let dedup_mode = if is_timeout { DedupMode::Both } else { self.dedup_mode };
+        let fingerprint = compute_fingerprint(dedup_mode, script_bytes, &edges);
+        if is_timeout {
+            self.timeouts_seen += 1;
+            if self.stored_timeouts >= self.max_timeouts
+                || !self.timeout_fingerprints.insert(fingerprint)
+            {
+                return Ok(None);
+            }
+        } else if self.contains_fingerprint(fingerprint) {
             return Ok(None);
         }
         
-        let id = self.next_id;
-        self.next_id += 1;
+        let id = self.allocate_id();
         
         let file_name = format!("seed_{id}.js");
-        let relative_path = PathBuf::from(&file_name);
+        let relative_path = self.layout.seed_path(id);
         let absolute_path = self.root.join(&relative_path);
         
         if is_timeout {
             println!("Storing timeout corpus entry {:?}", file_name);
+            self.stored_timeouts += 1;
             // For timeouts, we store the script in a separate directory
-            let timeout_dir = self.root.join("timeouts");
-            if fs::metadata(&timeout_dir).await.is_err() {
-                fs::create_dir_all(&timeout_dir).await.with_context(|| {
-                    format!("failed to create timeout directory {:?}", timeout_dir)
-                })?;
-            }
-            let timeout_path = timeout_dir.join(&file_name);
-            fs::write(&timeout_path, script_bytes)
-            .await
-            .with_context(|| {
-                format!("failed to write timeout corpus entry {:?}", timeout_path)
-            })?;
+            let timeout_path = self.root.join(TIMEOUTS_DIR).join(&file_name);
+            self.persist_op(CorpusOp::WriteFile {
+                path: timeout_path,
+                contents: Arc::from(script_bytes),
+            })
+            .await?;
             return Ok(None);
         }
         
-        fs::write(&absolute_path, script_bytes)
-        .await
-        .with_context(|| format!("failed to write corpus entry {:?}", absolute_path))?;
+        self.persist_op(CorpusOp::WriteFile {
+            path: absolute_path,
+            contents: Arc::from(script_bytes),
+        })
+        .await?;
         
         let entry = CorpusEntry {
             id,
             path: relative_path,
             fingerprint,
-            edge_hits,
+            edges,
             size_bytes: script_bytes.len(),
             total_reward: reward.max(0.0),
             last_reward: reward,
             exec_time_ms,
             num_mutations: 0,
             last_selected_ts: None,
+            mutations_since_new_coverage: 0,
+            tags: Vec::new(),
+            parent_id: provenance.as_ref().map(|provenance| provenance.parent_id),
+            producer: provenance.map(|provenance| provenance.producer),
         };
         self.entries.push(entry.clone());
-        self.persist().await?; // TODO: optimize by only calling this function after a number of additions
+        self.append_journal(JournalRecord::Add(entry.clone())).await?;
         Ok(Some(entry))
     }
     
+    /// The chain of entries `id` descends from, starting with `id` itself and ending with
+    /// an ingested root, or with the oldest ancestor still in the corpus
+    pub fn lineage(&self, id: u64) -> Vec<&CorpusEntry> {
+        let mut chain = Vec::new();
+        let mut next = Some(id);
+        while let Some(id) = next {
+            let Some(entry) = self.entries.iter().find(|entry| entry.id == id) else {
+                break;
+            };
+            chain.push(entry);
+            // ids only grow, so a parent id that doesn't would be a cycle
+            next = entry.parent_id.filter(|&parent_id| parent_id < id);
+        }
+        chain
+    }
+    
+    /// Label entry `id` with `tag`. Tagging an entry twice with the same tag is a no-op.
+    pub async fn tag_entry(&mut self, id: u64, tag: &str) -> Result<()> {
+        let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) else {
+            anyhow::bail!("no corpus entry with id {}", id);
+        };
+        if entry.tags.iter().any(|known| known == tag) {
+            return Ok(());
+        }
+        entry.tags.push(tag.to_string());
+        self.append_journal(JournalRecord::Tag { id, tag: tag.to_string() }).await
+    }
+    
     pub async fn remove_entry(&mut self, id: u64) -> Result<()> {
         if let Some(pos) = self.entries.iter().position(|entry| entry.id == id) {
             let entry = self.entries.remove(pos);
-            let absolute_path = self.root.join(&entry.path);
-            if fs::metadata(&absolute_path).await.is_ok() {
-                fs::remove_file(&absolute_path)
-                .await
-                .with_context(|| format!("failed to remove corpus entry {:?}", absolute_path))?;
-            }
-            self.persist().await?;
+            self.donor_cache.remove(id);
+            self.persist_op(CorpusOp::RemoveFile(self.root.join(&entry.path))).await?;
+            self.append_journal(JournalRecord::Remove(id)).await?;
         }
         Ok(())
     }
     
-    async fn persist(&self) -> Result<()> {
-        let data = CorpusMetadata {
-            next_id: self.next_id,
-            entries: self.entries.clone(),
+    /// Drop the entries whose file is gone, e.g. after part of the corpus directory was
+    /// deleted by hand. Returns the number of entries dropped.
+    pub async fn validate(&mut self) -> Result<usize> {
+        let mut missing = Vec::new();
+        for entry in &self.entries {
+            if fs::metadata(self.root.join(&entry.path)).await.is_err() {
+                missing.push(entry.id);
+            }
+        }
+        for &id in &missing {
+            self.remove_entry(id).await?;
+        }
+        Ok(missing.len())
+    }
+    
+    /// Drop the entries with the fingerprint of an older entry, e.g. left by merging two
+    /// corpora, so the same program isn't picked twice as often. Returns the number of
+    /// entries dropped.
+    pub async fn remove_duplicates(&mut self) -> Result<usize> {
+        let mut first_ids: HashMap<u64, u64> = HashMap::new();
+        let mut duplicates = Vec::new();
+        for entry in &self.entries {
+            let first_id = *first_ids.entry(entry.fingerprint).or_insert(entry.id);
+            if first_id != entry.id {
+                duplicates.push(entry.id);
+            }
+        }
+        for &id in &duplicates {
+            let Some(pos) = self.entries.iter().position(|entry| entry.id == id) else {
+                continue;
+            };
+            let entry = self.entries.remove(pos);
+            // two entries can point to the same file, the one kept still needs it
+            if !self.entries.iter().any(|kept| kept.path == entry.path) {
+                self.persist_op(CorpusOp::RemoveFile(self.root.join(&entry.path))).await?;
+            }
+            self.append_journal(JournalRecord::Remove(id)).await?;
+        }
+        Ok(duplicates.len())
+    }
+    
+    /// Append a record to the journal, compacting it once it grows past
+    /// `JOURNAL_COMPACT_RECORDS`
+    async fn append_journal(&mut self, record: JournalRecord) -> Result<()> {
+        self.journal_seq += 1;
+        let line = JournalLine {
+            seq: self.journal_seq,
+            record,
         };
-        let blob = serde_json::to_vec_pretty(&data)
+        let mut blob = serde_json::to_vec(&line)
+        .with_context(|| "failed to serialize journal record".to_string())?;
+        blob.push(b'\n');
+        self.persist_op(CorpusOp::AppendJournal {
+            journal_path: self.journal_path.clone(),
+            line: blob,
+        })
+        .await?;
+        self.journal_len += 1;
+        
+        if self.journal_len >= JOURNAL_COMPACT_RECORDS {
+            self.compact().await?;
+        }
+        Ok(())
+    }
+    
+    /// Rewrite the snapshot and truncate the journal. The snapshot records the last
+    /// journal sequence number it includes, so if the fuzzer dies before the truncation
+    /// the leftover records are skipped on the next load.
+    ///
+    /// Sharded instances share the snapshot, so one first merges the entries the others
+    /// added and keeps their journals replayed from where the snapshot on disk left them.
+    pub async fn compact(&mut self) -> Result<()> {
+        let mut journal_seqs = BTreeMap::new();
+        if self.shard.is_some() {
+            self.flush_writes().await?;
+            self.reload_metadata().await?;
+            journal_seqs = read_snapshot(&self.metadata_path).await?.journal_seqs;
+        }
+        let mut meta = self.metadata();
+        journal_seqs.append(&mut meta.journal_seqs);
+        meta.journal_seqs = journal_seqs;
+        let snapshot = serde_json::to_vec_pretty(&meta)
         .with_context(|| "failed to serialize corpus metadata".to_string())?;
-        let temp_path = self.metadata_path.with_extension("json.tmp");
-        fs::write(&temp_path, blob)
-        .await
-        .with_context(|| format!("failed to write temp metadata {:?}", temp_path))?;
-        fs::rename(&temp_path, &self.metadata_path)
-        .await
-        .with_context(|| "failed to atomically update metadata file".to_string())?;
+        self.persist_op(CorpusOp::Compact {
+            metadata_path: self.metadata_path.clone(),
+            journal_path: self.journal_path.clone(),
+            snapshot,
+        })
+        .await?;
+        self.journal_len = 0;
         Ok(())
     }
     
+    /// The current metadata, including every journaled change
+    pub(crate) fn metadata(&self) -> CorpusMetadata {
+        CorpusMetadata {
+            next_id: self.next_id,
+            journal_seqs: BTreeMap::from([(journal_file_name(self.shard), self.journal_seq)]),
+            entries: self.entries.clone(),
+        }
+    }
+    
     pub fn root(&self) -> &Path {
         &self.root
     }
     
-    pub async fn get_random_script(&self) -> Result<Option<Script>> {
+    /// Donors are weighted by the number of edges they contributed, so high-coverage
+    /// seeds are spliced in more often
+    fn pick_donor_index(&self, rng: &mut impl Rng) -> Option<usize> {
         if self.entries.is_empty() {
-            return Ok(None);
+            return None;
         }
-        let mut rng = rand::rng();
-        let idx = rng.random_range(0..self.entries.len());
+        let weights: Vec<(usize, f64)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (idx, 1.0 + entry.edges.len() as f64))
+            .collect();
+        Some(random_weighted_choice(rng, &weights))
+    }
+    
+    /// The donors with the most edges that aren't in `seed_edges` are the most likely to
+    /// add something to the seed. A random subset of the entries is weighted by the number
+    /// of those edges; when none of them has any, one is picked uniformly.
+    fn pick_complementary_index(&self, seed_edges: &EntryEdges, rng: &mut impl Rng) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let seed_edges: HashSet<(Option<&str>, u32)> = seed_edges.iter().collect();
+        let scanned = COMPLEMENTARY_CANDIDATES.min(self.entries.len());
+        let candidates = rand::seq::index::sample(rng, self.entries.len(), scanned);
+        let weights: Vec<(usize, f64)> = candidates
+            .iter()
+            .map(|idx| {
+                let disjoint = self.entries[idx].edges.iter().filter(|edge| !seed_edges.contains(edge)).count();
+                (idx, disjoint as f64)
+            })
+            .filter(|&(_, disjoint)| disjoint > 0.0)
+            .collect();
+        if weights.is_empty() {
+            return Some(candidates.index(rng.random_range(0..candidates.len())));
+        }
+        Some(random_weighted_choice(rng, &weights))
+    }
+    
+    /// Pick a donor script for splicing, preferring high-coverage entries. Recently used
+    /// donors are served from an in-memory cache. Returns the donor's id with the script.
+    pub async fn get_random_script(&mut self, rng: &mut impl Rng) -> Result<Option<(u64, Script)>> {
+        let Some(idx) = self.pick_donor_index(rng) else {
+            return Ok(None);
+        };
+        self.load_donor(idx).await.map(Some)
+    }
+    
+    /// Pick a donor script favoring those whose edges are the most disjoint from
+    /// `seed_edges`, so the splice is likely to reach new coverage
+    pub async fn get_complementary_script(
+        &mut self,
+        seed_edges: &EntryEdges,
+        rng: &mut impl Rng,
+    ) -> Result<Option<(u64, Script)>> {
+        let Some(idx) = self.pick_complementary_index(seed_edges, rng) else {
+            return Ok(None);
+        };
+        self.load_donor(idx).await.map(Some)
+    }
+    
+    /// The script of entry `id`, through the donor cache. Used to splice a logged donor
+    /// in again.
+    pub async fn get_script(&mut self, id: u64) -> Result<Option<Script>> {
+        let Some(idx) = self.entries.iter().position(|entry| entry.id == id) else {
+            return Ok(None);
+        };
+        let (_, script) = self.load_donor(idx).await?;
+        Ok(Some(script))
+    }
+    
+    async fn load_donor(&mut self, idx: usize) -> Result<(u64, Script)> {
         let entry = &self.entries[idx];
+        let id = entry.id;
+        if let Some(script) = self.donor_cache.get(id) {
+            return Ok((id, script));
+        }
         let absolute_path = self.root.join(&entry.path);
-        let script_bytes = fs::read(&absolute_path)
-        .await
-        .with_context(|| format!("failed to read corpus entry {:?}", absolute_path))?;
+        let script_bytes = self.read_file(&absolute_path).await?;
         let script = crate::parsing::parser::parse_js(String::from_utf8_lossy(&script_bytes).to_string())
         .with_context(|| format!("failed to parse corpus entry {:?}", absolute_path))?;
-        Ok(Some(script))
+        self.donor_cache.insert(id, script.clone());
+        Ok((id, script))
     }
     
-    pub async fn persist_crash(&self, contents: &[u8]) -> Result<()> {
-        let hash = format!("{:x}", md5::compute(contents));
+    /// Save a program that overflowed the stack as `stackoverflow/so_{md5}.js`, if enabled
+    pub async fn persist_stack_overflow(&self, contents: &[u8]) -> Result<()> {
+        if !self.save_stack_overflows {
+            return Ok(());
+        }
+        let dir = self.root.join(STACK_OVERFLOWS_DIR);
+        async_fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("failed to create stack overflow directory {:?}", dir))?;
+        let path = dir.join(format!("so_{:x}.js", md5::compute(contents)));
+        async_fs::write(&path, contents)
+            .await
+            .with_context(|| format!("failed to save stack overflow repro {:?}", path))?;
+        Ok(())
+    }
+    
+    /// Save a crashing input as `crashes/crash_{engine}_{md5}.js`. Crashes with a
+    /// sanitizer report are named after the report instead, keeping the first repro of
+    /// every sanitizer bug, and the report is saved next to it as json. The seed and
+    /// mutator the input comes from are saved next to it as `.origin.json`, and in the
+    /// name as `crash_{engine}_seed{id}_{mutator}_{md5}.js` if enabled.
+    pub async fn persist_crash(
+        &self,
+        contents: &[u8],
+        engine: &str,
+        sanitizer: Option<&SanitizerReport>,
+        provenance: Option<&Provenance>,
+    ) -> Result<()> {
+        let name = match (sanitizer, provenance) {
+            (Some(report), _) => format!("crash_{}_{}", engine, report.fingerprint()),
+            (None, Some(provenance)) if self.crash_origin_in_name => format!(
+                "crash_{}_seed{}_{}_{:x}",
+                engine,
+                provenance.parent_id,
+                provenance.producer,
+                md5::compute(contents)
+            ),
+            (None, _) => format!("crash_{}_{:x}", engine, md5::compute(contents)),
+        };
         let mut path = self.root().to_path_buf();
         path.push("crashes");
-        path.push(format!("crash_{}.js", hash));
+        path.push(format!("{}.js", name));
+        if sanitizer.is_some() && async_fs::metadata(&path).await.is_ok() {
+            return Ok(());
+        }
         if let Some(parent) = path.parent() {
             async_fs::create_dir_all(parent)
                 .await
@@ -265,14 +1013,142 @@ impl CorpusManager {
         async_fs::write(path.clone(), contents)
             .await
             .with_context(|| format!("failed to save crash repro {:?}", path))?;
+        if let Some(report) = sanitizer {
+            let report_path = path.with_extension("json");
+            let blob = serde_json::to_vec_pretty(report)
+                .with_context(|| "failed to serialize sanitizer report".to_string())?;
+            async_fs::write(&report_path, blob)
+                .await
+                .with_context(|| format!("failed to save sanitizer report {:?}", report_path))?;
+        }
+        if let Some(provenance) = provenance {
+            let origin = CrashOrigin {
+                engine: engine.to_string(),
+                parent_id: provenance.parent_id,
+                mutator: provenance.producer.clone(),
+                timestamp: current_timestamp(),
+            };
+            let origin_path = path.with_extension("origin.json");
+            let blob = serde_json::to_vec_pretty(&origin)
+                .with_context(|| "failed to serialize crash origin".to_string())?;
+            async_fs::write(&origin_path, blob)
+                .await
+                .with_context(|| format!("failed to save crash origin {:?}", origin_path))?;
+        }
         Ok(())
     }
 }
 
-fn compute_fingerprint(script_bytes: &[u8], edge_hits: &[u32]) -> u64 {
+/// Read the metadata of the corpus at `path`, either the corpus directory or its
+/// `metadata.json`. The journals next to it are replayed too.
+pub(crate) async fn load_metadata(path: &Path) -> Result<CorpusMetadata> {
+    let is_dir = fs::metadata(path).await.map(|meta| meta.is_dir()).unwrap_or(false);
+    let metadata_path = if is_dir { path.join(METADATA_FILE) } else { path.to_path_buf() };
+    if fs::metadata(&metadata_path).await.is_err() {
+        anyhow::bail!("no corpus metadata at {:?}", metadata_path);
+    }
+    let (meta, _) = read_metadata(&metadata_path).await?;
+    Ok(meta)
+}
+
+/// The journal an instance appends to, `metadata.journal` or `metadata.<index>.journal`
+/// when sharded
+fn journal_file_name(shard: Option<Shard>) -> String {
+    match shard {
+        Some(shard) => format!("metadata.{}.{}", shard.index, JOURNAL_EXTENSION),
+        None => JOURNAL_FILE.to_string(),
+    }
+}
+
+/// Read the snapshot, without replaying any journal
+async fn read_snapshot(metadata_path: &Path) -> Result<CorpusMetadata> {
+    let mut meta = CorpusMetadata::default();
+    if fs::metadata(metadata_path).await.is_ok() {
+        let blob = fs::read(metadata_path)
+        .await
+        .with_context(|| format!("failed to read metadata {:?}", metadata_path))?;
+        if !blob.is_empty() {
+            meta = serde_json::from_slice(&blob)
+            .with_context(|| "failed to deserialize corpus metadata".to_string())?;
+            let max_id = meta.entries.iter().map(|e| e.id).max().unwrap_or(0);
+            meta.next_id = meta.next_id.max(max_id.saturating_add(1));
+        }
+    }
+    Ok(meta)
+}
+
+/// Read the snapshot and replay every journal next to it over it, those of all shards
+/// included. Also returns the number of records in each journal, by file name.
+async fn read_metadata(metadata_path: &Path) -> Result<(CorpusMetadata, BTreeMap<String, usize>)> {
+    let mut meta = read_snapshot(metadata_path).await?;
+    let root = metadata_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    
+    let mut journal_names = Vec::new();
+    if let Ok(mut dir) = fs::read_dir(&root).await {
+        while let Some(entry) = dir
+        .next_entry()
+        .await
+        .with_context(|| format!("failed to list corpus directory {:?}", root))?
+        {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = Path::new(&name);
+            if name.starts_with("metadata.") && path.extension().is_some_and(|ext| ext == JOURNAL_EXTENSION) {
+                journal_names.push(name);
+            }
+        }
+    }
+    journal_names.sort();
+    
+    let mut journal_lens = BTreeMap::new();
+    for name in journal_names {
+        let journal_path = root.join(&name);
+        let blob = fs::read(&journal_path)
+        .await
+        .with_context(|| format!("failed to read journal {:?}", journal_path))?;
+        let mut journal_len = 0;
+        let mut journal_seq = meta.journal_seqs.get(&name).copied().unwrap_or(0);
+        for line in blob.split(|byte| *byte == b'\n').filter(|line| !line.is_empty()) {
+            // only the last record can be torn, if the fuzzer died while appending it
+            let Ok(line) = serde_json::from_slice::<JournalLine>(line) else {
+                break;
+            };
+            journal_len += 1;
+            // already included in the snapshot
+            if line.seq <= journal_seq {
+                continue;
+            }
+            journal_seq = line.seq;
+            meta.apply(line.record);
+        }
+        meta.journal_seqs.insert(name.clone(), journal_seq);
+        journal_lens.insert(name, journal_len);
+    }
+    Ok((meta, journal_lens))
+}
+
+fn compute_fingerprint(dedup_mode: DedupMode, script_bytes: &[u8], edges: &EntryEdges) -> u64 {
     let mut hasher = DefaultHasher::new();
-    script_bytes.hash(&mut hasher);
-    edge_hits.hash(&mut hasher);
+    match dedup_mode {
+        DedupMode::Coverage => {
+            let mut edge_hits = edges.edge_hits.clone();
+            edge_hits.sort_unstable();
+            edge_hits.hash(&mut hasher);
+        }
+        DedupMode::Source => script_bytes.hash(&mut hasher),
+        DedupMode::Both => {
+            script_bytes.hash(&mut hasher);
+            edges.edge_hits.hash(&mut hasher);
+        }
+    }
+    // adds nothing for single engine entries
+    if dedup_mode != DedupMode::Source {
+        for (engine, engine_edges) in &edges.engine_edges {
+            let mut engine_edges = engine_edges.clone();
+            engine_edges.sort_unstable();
+            engine.hash(&mut hasher);
+            engine_edges.hash(&mut hasher);
+        }
+    }
     hasher.finish()
 }
 
@@ -283,4 +1159,610 @@ fn current_timestamp() -> u64 {
     .unwrap_or(0)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_corpus_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("jellyfuzz_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn parses_shard_spec() {
+        assert_eq!("1/4".parse::<Shard>(), Ok(Shard { index: 1, total: 4 }));
+        assert!("4/4".parse::<Shard>().is_err());
+        assert!("0/0".parse::<Shard>().is_err());
+        assert!("1".parse::<Shard>().is_err());
+    }
+
+    /// Adds the same three candidates under `dedup_mode` and returns which were accepted
+    async fn dedup_decisions(dedup_mode: DedupMode) -> Vec<bool> {
+        let dir = temp_corpus_dir(&format!("dedup_{:?}", dedup_mode));
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.set_dedup_mode(dedup_mode);
+        let candidates: [(&[u8], Vec<u32>); 4] = [
+            (b"let v0 = 1;", vec![1, 2]),
+            // same coverage, different program
+            (b"let v0 = 2;", vec![2, 1]),
+            // same program, different coverage
+            (b"let v0 = 1;", vec![3]),
+            // exact duplicate
+            (b"let v0 = 1;", vec![1, 2]),
+        ];
+        let mut accepted = Vec::new();
+        for (script, edges) in candidates {
+            let entry = mgr
+            .add_entry(script, edges, 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+            accepted.push(entry.is_some());
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+        accepted
+    }
+
+    #[tokio::test]
+    async fn dedup_modes_decide_duplicates() {
+        assert_eq!(dedup_decisions(DedupMode::Coverage).await, vec![true, false, true, false]);
+        assert_eq!(dedup_decisions(DedupMode::Source).await, vec![true, true, false, false]);
+        assert_eq!(dedup_decisions(DedupMode::Both).await, vec![true, true, true, false]);
+    }
+
+    #[tokio::test]
+    async fn sharded_pick_only_returns_owned_ids() {
+        let dir = temp_corpus_dir("shard");
+        let mut mgr = CorpusManager::load_sharded(dir.clone(), Some(Shard { index: 0, total: 2 }))
+        .await
+        .expect("failed to load corpus");
+        for i in 0..10 {
+            let script = format!("let v0 = {};", i);
+            mgr.add_entry(script.as_bytes(), vec![i], 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+        }
+        for _ in 0..100 {
+            let selection = mgr.pick_random().expect("corpus is not empty");
+            assert_eq!(selection.id % 2, 0);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn unproductive_seed_weight_decays_past_budget() {
+        let dir = temp_corpus_dir("budget");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.add_entry(b"let v0 = 1;", vec![1], 1.0, Duration::from_millis(1), false)
+        .await
+        .expect("failed to add entry");
+        let budget = mgr.entries()[0].mutation_budget();
+
+        for _ in 0..budget {
+            mgr.pick_random().expect("corpus is not empty");
+        }
+        assert_eq!(mgr.entries()[0].selection_weight(), 1.0);
+
+        mgr.pick_random().expect("corpus is not empty");
+        let decayed = mgr.entries()[0].selection_weight();
+        assert!(decayed < 0.5, "weight {} did not decay", decayed);
+        for _ in 0..budget {
+            mgr.pick_random().expect("corpus is not empty");
+        }
+        assert!(mgr.entries()[0].selection_weight() < decayed);
+
+        mgr.record_new_coverage(0);
+        assert_eq!(mgr.entries()[0].selection_weight(), 1.0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn timeouts_stop_being_written_beyond_cap() {
+        let dir = temp_corpus_dir("timeouts");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.set_max_timeouts(2);
+        for script in ["while(1){}", "while(1){}", "for(;;){}", "do{}while(1)", "while(true){}"] {
+            mgr.add_entry(script.as_bytes(), Vec::new(), -1.0, Duration::from_millis(1), true)
+            .await
+            .expect("failed to add timeout");
+        }
+        let written = std::fs::read_dir(dir.join(TIMEOUTS_DIR))
+        .expect("timeouts directory missing")
+        .count();
+        assert_eq!(written, 2);
+        assert_eq!(mgr.stored_timeouts(), 2);
+        assert_eq!(mgr.timeouts_seen(), 5);
+        assert!(mgr.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn donor_selection_prefers_high_coverage() {
+        let dir = temp_corpus_dir("donors");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.add_entry(b"let v0 = 1;", vec![1], 1.0, Duration::from_millis(1), false)
+        .await
+        .expect("failed to add entry");
+        mgr.add_entry(b"let v0 = 2;", (0..99).collect::<Vec<u32>>(), 1.0, Duration::from_millis(1), false)
+        .await
+        .expect("failed to add entry");
+
+        let high_coverage_picks = (0..1000)
+        .filter(|_| mgr.pick_donor_index(&mut rand::rng()) == Some(1))
+        .count();
+        assert!(high_coverage_picks > 900, "picked high-coverage donor {} times", high_coverage_picks);
+
+        let donor = mgr.get_random_script(&mut rand::rng()).await.expect("failed to get donor");
+        assert!(donor.is_some());
+        let (id, script) = donor.unwrap();
+        assert_eq!(mgr.get_script(id).await.expect("failed to get donor"), Some(script));
+        assert!(mgr.get_script(99).await.expect("failed to look up donor").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// How often each entry is picked as the complementary donor of `seed_edges`
+    fn complementary_picks(mgr: &CorpusManager, seed_edges: &EntryEdges) -> Vec<usize> {
+        let mut picks = vec![0; mgr.len()];
+        let mut rng = crate::utils::rand_utils::unseeded_rng();
+        for _ in 0..2000 {
+            picks[mgr.pick_complementary_index(seed_edges, &mut rng).expect("corpus is not empty")] += 1;
+        }
+        picks
+    }
+
+    #[tokio::test]
+    async fn complementary_donor_has_the_most_disjoint_edges() {
+        let dir = temp_corpus_dir("complementary_donors");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        // the seed, a donor covering the same edges and more, and two with other edges
+        for (code, edges) in [
+            ("let v0 = 1;", (0..10).collect::<Vec<u32>>()),
+            ("let v0 = 2;", (0..14).collect()),
+            ("let v0 = 3;", (8..16).collect()),
+            ("let v0 = 4;", (20..23).collect()),
+        ] {
+            mgr.add_entry(code.as_bytes(), edges, 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+        }
+        assert_eq!(mgr.len(), 4);
 
+        let seed_edges = EntryEdges::from((0..10).collect::<Vec<u32>>());
+        let picks = complementary_picks(&mgr, &seed_edges);
+        // 6 new edges beat the 4 of the superset and the 3 of the smaller donor, the seed
+        // itself adds nothing
+        assert_eq!(picks[0], 0);
+        assert!(picks[2] > picks[1] && picks[1] > picks[3] && picks[3] > 0, "unexpected picks {:?}", picks);
+        let donor = mgr
+        .get_complementary_script(&seed_edges, &mut rand::rng())
+        .await
+        .expect("failed to get donor");
+        let (id, _) = donor.expect("no donor");
+        assert_ne!(id, mgr.entries()[0].id);
+
+        // without any new edges, every donor is as good as the others
+        let picks = complementary_picks(&mgr, &EntryEdges::from((0..30).collect::<Vec<u32>>()));
+        assert!(picks.iter().all(|&count| count > 0), "unexpected picks {:?}", picks);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn edges_of_different_engines_are_kept_apart() {
+        let dir = temp_corpus_dir("engine_edges");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        let engine_edges = |v8: Vec<u32>, jsc: Vec<u32>| EntryEdges {
+            edge_hits: Vec::new(),
+            engine_edges: BTreeMap::from([("jsc".to_string(), jsc), ("v8".to_string(), v8)]),
+        };
+        // the same edge ids in another engine are other edges
+        for (code, edges) in [
+            ("let v0 = 1;", engine_edges(vec![1, 2, 3], vec![])),
+            ("let v0 = 2;", engine_edges(vec![], vec![1, 2, 3])),
+            ("let v0 = 3;", engine_edges(vec![1, 2], vec![7])),
+        ] {
+            let added = mgr
+            .add_entry(code.as_bytes(), edges, 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+            assert!(added.is_some(), "{} was taken for a duplicate", code);
+        }
+        assert_eq!(mgr.entries()[2].edges.len(), 3);
+
+        let seed_edges = mgr.entries()[0].edges.clone();
+        let picks = complementary_picks(&mgr, &seed_edges);
+        assert_eq!(picks[0], 0);
+        assert!(picks[1] > picks[2], "unexpected picks {:?}", picks);
+
+        // the entries keep their engines across a reload
+        drop(mgr);
+        let mgr = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(mgr.entries()[1].edges, engine_edges(vec![], vec![1, 2, 3]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn donor_cache_evicts_least_recently_used() {
+        let script = crate::parsing::parser::parse_js("1;".to_string()).unwrap();
+        let mut cache = DonorCache::new(2);
+        cache.insert(1, script.clone());
+        cache.insert(2, script.clone());
+        assert!(cache.get(1).is_some());
+        cache.insert(3, script);
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(1).is_some());
+        assert!(cache.get(3).is_some());
+    }
+
+    /// Populates a corpus with adds, results and a removal
+    async fn journaled_corpus(dir: &Path) -> CorpusManager {
+        let mut mgr = CorpusManager::load(dir.to_path_buf()).await.expect("failed to load corpus");
+        for i in 0..3 {
+            let script = format!("let v0 = {};", i);
+            mgr.add_entry(script.as_bytes(), vec![i], 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+        }
+        mgr.pick_random().expect("corpus is not empty");
+        mgr.record_result(0, 2.5, Duration::from_millis(7)).await.expect("failed to record result");
+        mgr.record_result(2, -1.0, Duration::from_millis(3)).await.expect("failed to record result");
+        mgr.remove_entry(1).await.expect("failed to remove entry");
+        mgr
+    }
+
+    fn corpus_state(mgr: &CorpusManager) -> serde_json::Value {
+        serde_json::json!({
+            "next_id": mgr.next_id,
+            "entries": mgr.entries,
+        })
+    }
+
+    #[tokio::test]
+    async fn journal_replay_reconstructs_state() {
+        let dir = temp_corpus_dir("journal_replay");
+        let mgr = journaled_corpus(&dir).await;
+        assert!(!dir.join(METADATA_FILE).exists(), "snapshot written before compaction");
+
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(corpus_state(&reloaded), corpus_state(&mgr));
+        assert_eq!(reloaded.journal_len, 6);
+        assert_eq!(reloaded.len(), 2);
+
+        // a record torn by a crash is ignored
+        let mut journal = std::fs::OpenOptions::new().append(true).open(dir.join(JOURNAL_FILE)).unwrap();
+        std::io::Write::write_all(&mut journal, b"{\"seq\":7,\"rec").unwrap();
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(corpus_state(&reloaded), corpus_state(&mgr));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn writer_task_persists_ops_in_order() {
+        let dir = temp_corpus_dir("writer_task");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.spawn_writer();
+        for i in 0..3 {
+            let script = format!("let v0 = {};", i);
+            mgr.add_entry(script.as_bytes(), vec![i], 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+        }
+        // queued files can be read back before they are written
+        let seed = mgr.read_file(&dir.join("seed_2.js")).await.expect("failed to read queued seed");
+        assert_eq!(seed, b"let v0 = 2;");
+        mgr.record_result(0, 2.5, Duration::from_millis(7)).await.expect("failed to record result");
+        mgr.compact().await.expect("compaction failed");
+        // removed right after it was added, the remove must not overtake the write
+        mgr.remove_entry(1).await.expect("failed to remove entry");
+        mgr.tag_entry(2, "slow").await.expect("failed to tag entry");
+        mgr.close_writer().await.expect("failed to drain the writer");
+
+        assert!(dir.join("seed_0.js").exists());
+        assert!(!dir.join("seed_1.js").exists(), "removed seed written back");
+        assert_eq!(std::fs::read(dir.join("seed_2.js")).unwrap(), b"let v0 = 2;");
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(corpus_state(&reloaded), corpus_state(&mgr));
+        assert_eq!(reloaded.journal_len, 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn crash_origin_records_the_parent_seed() {
+        let dir = temp_corpus_dir("crash_origin");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        let provenance = Provenance {
+            parent_id: 42,
+            producer: "OperatorSwap".to_string(),
+        };
+        let contents = b"crash();";
+        mgr.persist_crash(contents, "v8", None, Some(&provenance))
+        .await
+        .expect("failed to persist crash");
+        let hash = format!("{:x}", md5::compute(contents));
+        let crashes = dir.join("crashes");
+        assert!(crashes.join(format!("crash_v8_{}.js", hash)).exists());
+        let blob = std::fs::read(crashes.join(format!("crash_v8_{}.origin.json", hash))).expect("no origin sidecar");
+        let origin: CrashOrigin = serde_json::from_slice(&blob).expect("invalid origin sidecar");
+        assert_eq!(origin.parent_id, 42);
+        assert_eq!(origin.mutator, "OperatorSwap");
+        assert_eq!(origin.engine, "v8");
+
+        mgr.set_crash_origin_in_name(true);
+        mgr.persist_crash(contents, "v8", None, Some(&provenance))
+        .await
+        .expect("failed to persist crash");
+        assert!(crashes.join(format!("crash_v8_seed42_OperatorSwap_{}.js", hash)).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn tags_round_trip_through_metadata() {
+        let dir = temp_corpus_dir("tags");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.add_entry(b"let v0 = 1;", vec![1], 1.0, Duration::from_millis(500), false)
+        .await
+        .expect("failed to add entry");
+        assert_eq!(mgr.entries()[0].notable_tags(), vec!["slow"]);
+        mgr.tag_entry(0, "slow").await.expect("failed to tag entry");
+        mgr.tag_entry(0, "regex-heavy").await.expect("failed to tag entry");
+        mgr.tag_entry(0, "slow").await.expect("failed to tag entry");
+        assert!(mgr.tag_entry(42, "slow").await.is_err());
+
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(reloaded.entries()[0].tags, vec!["slow", "regex-heavy"]);
+
+        mgr.compact().await.expect("compaction failed");
+        let metadata = load_metadata(&dir).await.expect("failed to load metadata");
+        assert_eq!(metadata.entries[0].tags, vec!["slow", "regex-heavy"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn lineage_follows_parents_to_the_root() {
+        let dir = temp_corpus_dir("lineage");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.add_entry(b"let v0 = 1;", vec![1], 1.0, Duration::from_millis(1), false)
+        .await
+        .expect("failed to add entry");
+        for (i, producer) in ["ArityMutator", "SpreadMutator"].iter().enumerate() {
+            let script = format!("let v0 = {};", i + 2);
+            let provenance = Provenance {
+                parent_id: i as u64,
+                producer: producer.to_string(),
+            };
+            mgr.add_entry_with_provenance(script.as_bytes(), vec![i as u32 + 2], 1.0, Duration::from_millis(1), false, Some(provenance))
+            .await
+            .expect("failed to add entry");
+        }
+
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        let chain: Vec<(u64, Option<&str>)> = reloaded
+        .lineage(2)
+        .iter()
+        .map(|entry| (entry.id, entry.producer.as_deref()))
+        .collect();
+        assert_eq!(chain, vec![(2, Some("SpreadMutator")), (1, Some("ArityMutator")), (0, None)]);
+        assert_eq!(reloaded.lineage(0).len(), 1);
+        assert!(reloaded.lineage(42).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn compaction_preserves_state() {
+        let dir = temp_corpus_dir("journal_compaction");
+        let mut mgr = journaled_corpus(&dir).await;
+        let journal = std::fs::read(dir.join(JOURNAL_FILE)).unwrap();
+        mgr.compact().await.expect("compaction failed");
+        assert!(std::fs::read(dir.join(JOURNAL_FILE)).unwrap().is_empty());
+
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(corpus_state(&reloaded), corpus_state(&mgr));
+
+        // records left over from a compaction interrupted before truncating are skipped
+        std::fs::write(dir.join(JOURNAL_FILE), journal).unwrap();
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(corpus_state(&reloaded), corpus_state(&mgr));
+
+        mgr.record_result(0, 1.0, Duration::from_millis(1)).await.expect("failed to record result");
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(corpus_state(&reloaded), corpus_state(&mgr));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    async fn add_entries(mgr: &mut CorpusManager, scripts: std::ops::Range<u32>) {
+        for i in scripts {
+            let script = format!("let v0 = {};", i);
+            mgr.add_entry(script.as_bytes(), vec![i], 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry")
+            .expect("entry was not added");
+        }
+    }
+
+    /// Contents of the files behind the entries, sorted. Panics on duplicate ids.
+    fn stored_scripts(mgr: &CorpusManager, dir: &Path) -> Vec<String> {
+        let ids: HashSet<u64> = mgr.entries().iter().map(|entry| entry.id).collect();
+        assert_eq!(ids.len(), mgr.len(), "duplicate ids");
+        let mut scripts: Vec<String> = mgr
+        .entries()
+        .iter()
+        .map(|entry| String::from_utf8(std::fs::read(dir.join(&entry.path)).unwrap()).unwrap())
+        .collect();
+        scripts.sort();
+        scripts
+    }
+
+    fn expected_scripts(scripts: std::ops::Range<u32>) -> Vec<String> {
+        let mut expected: Vec<String> = scripts.map(|i| format!("let v0 = {};", i)).collect();
+        expected.sort();
+        expected
+    }
+
+    #[tokio::test]
+    async fn shards_allocate_disjoint_ids_without_reloading() {
+        let dir = temp_corpus_dir("shard_ids");
+        let mut first = CorpusManager::load_sharded(dir.clone(), Some(Shard { index: 0, total: 2 }))
+        .await
+        .expect("failed to load corpus");
+        let mut second = CorpusManager::load_sharded(dir.clone(), Some(Shard { index: 1, total: 2 }))
+        .await
+        .expect("failed to load corpus");
+
+        add_entries(&mut first, 0..4).await;
+        add_entries(&mut second, 4..8).await;
+        assert!(first.entries().iter().all(|entry| entry.id % 2 == 0));
+        assert!(second.entries().iter().all(|entry| entry.id % 2 == 1));
+
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(stored_scripts(&reloaded, &dir), expected_scripts(0..8));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn shards_sharing_a_directory_keep_each_others_entries() {
+        let dir = temp_corpus_dir("shared_journal");
+        let mut first = CorpusManager::load_sharded(dir.clone(), Some(Shard { index: 0, total: 2 }))
+        .await
+        .expect("failed to load corpus");
+        let mut second = CorpusManager::load_sharded(dir.clone(), Some(Shard { index: 1, total: 2 }))
+        .await
+        .expect("failed to load corpus");
+
+        add_entries(&mut first, 0..3).await;
+        assert_eq!(second.reload_metadata().await.expect("failed to reload"), 3);
+        add_entries(&mut second, 3..6).await;
+        second.record_result(1, 2.0, Duration::from_millis(1)).await.expect("failed to record result");
+        // compacting one instance leaves the records of the other alone
+        first.compact().await.expect("compaction failed");
+        assert!(std::fs::read(dir.join("metadata.0.journal")).unwrap().is_empty());
+        assert!(!std::fs::read(dir.join("metadata.1.journal")).unwrap().is_empty());
+        assert!(!dir.join(JOURNAL_FILE).exists());
+
+        add_entries(&mut second, 6..7).await;
+        second.compact().await.expect("compaction failed");
+        assert_eq!(first.reload_metadata().await.expect("failed to reload"), 1);
+        add_entries(&mut first, 7..8).await;
+
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(stored_scripts(&reloaded, &dir), expected_scripts(0..8));
+
+        // a restarted instance carries on with the sequence of its own journal
+        let restarted = CorpusManager::load_sharded(dir.clone(), Some(Shard { index: 0, total: 2 }))
+        .await
+        .expect("failed to reload corpus");
+        assert_eq!((restarted.journal_seq, restarted.journal_len), (first.journal_seq, 1));
+        assert_eq!(restarted.len(), 8);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn entries_with_missing_files_are_dropped() {
+        let dir = temp_corpus_dir("validate");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        for i in 0..2 {
+            let script = format!("let v0 = {};", i);
+            mgr.add_entry(script.as_bytes(), vec![i], 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+        }
+        let gone = mgr.entries()[0].clone();
+        std::fs::remove_file(dir.join(&gone.path)).unwrap();
+
+        let mut reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(reloaded.validate().await.expect("validation failed"), 1);
+        assert_eq!(reloaded.len(), 1);
+        assert!(reloaded.entries().iter().all(|entry| entry.id != gone.id));
+
+        // the removal is persisted
+        let mut reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded.validate().await.expect("validation failed"), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn sharded_entries_resolve_after_reload() {
+        let dir = temp_corpus_dir("layout");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        mgr.add_entry(b"let v0 = 0;", vec![0], 1.0, Duration::from_millis(1), false)
+        .await
+        .expect("failed to add entry");
+        mgr.set_layout(CorpusLayout::Sharded);
+        for i in 1..4 {
+            let script = format!("let v0 = {};", i);
+            let entry = mgr
+            .add_entry(script.as_bytes(), vec![i], 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry")
+            .expect("entry was not added");
+            assert_eq!(entry.path, CorpusLayout::Sharded.seed_path(entry.id));
+            assert!(entry.path.starts_with(SHARDED_SEEDS_DIR));
+        }
+
+        // a corpus mixing both layouts loads without knowing either
+        let mut reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(reloaded.validate().await.expect("validation failed"), 0);
+        assert_eq!(reloaded.len(), 4);
+        assert_eq!(reloaded.entries()[0].path, PathBuf::from("seed_0.js"));
+        for entry in reloaded.entries() {
+            let expected = format!("let v0 = {};", entry.id);
+            assert_eq!(std::fs::read(dir.join(&entry.path)).unwrap(), expected.as_bytes());
+        }
+        for _ in 0..8 {
+            let selection = reloaded.pick_random().expect("corpus is not empty");
+            assert!(selection.path.exists(), "{:?} does not resolve", selection.path);
+            assert!(reloaded.get_random_script(&mut rand::rng()).await.expect("failed to read donor").is_some());
+        }
+
+        let removed = reloaded.entries()[3].clone();
+        reloaded.remove_entry(removed.id).await.expect("failed to remove entry");
+        assert!(!dir.join(&removed.path).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn duplicate_fingerprints_are_dropped() {
+        let dir = temp_corpus_dir("duplicates");
+        let mut mgr = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        for i in 0..3 {
+            let script = format!("let v0 = {};", i);
+            mgr.add_entry(script.as_bytes(), vec![i], 1.0, Duration::from_millis(1), false)
+            .await
+            .expect("failed to add entry");
+        }
+        // as if two corpora holding the same program were merged
+        mgr.entries[2].fingerprint = mgr.entries[0].fingerprint;
+        mgr.compact().await.expect("compaction failed");
+        let kept = mgr.entries()[0].clone();
+        let duplicate = mgr.entries()[2].clone();
+
+        // loading alone leaves the corpus as it is
+        let mut reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(reloaded.len(), 3);
+        assert_eq!(reloaded.remove_duplicates().await.expect("dedup failed"), 1);
+        assert_eq!(reloaded.len(), 2);
+        assert!(reloaded.entries().iter().any(|entry| entry.id == kept.id));
+        assert!(reloaded.entries().iter().all(|entry| entry.id != duplicate.id));
+        assert!(dir.join(&kept.path).exists());
+        assert!(!dir.join(&duplicate.path).exists());
+
+        // the removal is persisted
+        let reloaded = CorpusManager::load(dir.clone()).await.expect("failed to reload corpus");
+        assert_eq!(reloaded.len(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reload_picks_up_entries_from_other_instances() {
+        let dir = temp_corpus_dir("reload");
+        let mut first = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        let mut second = CorpusManager::load(dir.clone()).await.expect("failed to load corpus");
+        first.add_entry(b"let v0 = 1;", vec![1], 1.0, Duration::from_millis(1), false)
+        .await
+        .expect("failed to add entry");
+
+        assert_eq!(second.reload_metadata().await.expect("reload failed"), 1);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second.reload_metadata().await.expect("reload failed"), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
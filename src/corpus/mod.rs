@@ -1,3 +1,8 @@
+pub mod diff;
+pub mod histogram;
+pub mod incoming;
 pub mod manager;
+pub mod snapshot;
+pub mod writer;
 
 pub use manager::CorpusManager;
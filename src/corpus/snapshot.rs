@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::fs;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::corpus::CorpusManager;
+use crate::corpus::manager::{CorpusMetadata, METADATA_FILE};
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+pub const DEFAULT_SNAPSHOTS_KEPT: usize = 5;
+
+/// Write `metadata` to a new timestamped directory under `<root>/snapshots` and delete
+/// all but the newest `keep` snapshots. With `include_seeds` the seed files are copied
+/// next to it, so the snapshot is a corpus directory that can be resumed from.
+pub(crate) async fn write_snapshot(
+    root: &Path,
+    metadata: &CorpusMetadata,
+    include_seeds: bool,
+    keep: usize,
+) -> Result<PathBuf> {
+    let snapshots_dir = root.join(SNAPSHOTS_DIR);
+    // names sort chronologically
+    let name = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+    let mut dir = snapshots_dir.join(&name);
+    let mut suffix = 1;
+    while fs::metadata(&dir).await.is_ok() {
+        dir = snapshots_dir.join(format!("{}_{}", name, suffix));
+        suffix += 1;
+    }
+    fs::create_dir_all(&dir)
+    .await
+    .with_context(|| format!("failed to create snapshot directory {:?}", dir))?;
+
+    let blob = serde_json::to_vec_pretty(metadata)
+    .with_context(|| "failed to serialize corpus metadata".to_string())?;
+    fs::write(dir.join(METADATA_FILE), blob)
+    .await
+    .with_context(|| format!("failed to write snapshot {:?}", dir))?;
+    if include_seeds {
+        for entry in &metadata.entries {
+            // seeds of the sharded layout are in subdirectories
+            if let Some(parent) = entry.path.parent() {
+                fs::create_dir_all(dir.join(parent))
+                .await
+                .with_context(|| format!("failed to create snapshot directory for {:?}", entry.path))?;
+            }
+            fs::copy(root.join(&entry.path), dir.join(&entry.path))
+            .await
+            .with_context(|| format!("failed to copy seed {:?} to snapshot", entry.path))?;
+        }
+    }
+
+    prune_snapshots(&snapshots_dir, keep).await?;
+    Ok(dir)
+}
+
+/// Delete the oldest snapshots until at most `keep` are left
+async fn prune_snapshots(snapshots_dir: &Path, keep: usize) -> Result<()> {
+    let mut snapshots = Vec::new();
+    let mut dir = fs::read_dir(snapshots_dir)
+    .await
+    .with_context(|| format!("failed to read snapshot directory {:?}", snapshots_dir))?;
+    while let Some(entry) = dir.next_entry().await? {
+        if entry.file_type().await?.is_dir() {
+            snapshots.push(entry.path());
+        }
+    }
+    snapshots.sort();
+    let excess = snapshots.len().saturating_sub(keep);
+    for snapshot in &snapshots[..excess] {
+        fs::remove_dir_all(snapshot)
+        .await
+        .with_context(|| format!("failed to delete old snapshot {:?}", snapshot))?;
+    }
+    Ok(())
+}
+
+/// Snapshot the corpus every `interval` in the background
+pub fn spawn_snapshot_task(
+    corpus_manager: Arc<Mutex<CorpusManager>>,
+    interval: Duration,
+    include_seeds: bool,
+    keep: usize,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            // the lock is held while copying so no seed is removed midway
+            let mgr = corpus_manager.lock().await;
+            if let Err(err) = mgr.flush_writes().await {
+                eprintln!("Failed to flush corpus writes: {:?}", err);
+            }
+            match write_snapshot(mgr.root(), &mgr.metadata(), include_seeds, keep).await {
+                Ok(dir) => println!("[snapshot] corpus saved to {:?}", dir),
+                Err(err) => eprintln!("Failed to snapshot corpus: {:?}", err),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn snapshots_are_written_and_capped() {
+        let root = std::env::temp_dir().join(format!("jellyfuzz_snapshots_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let mut mgr = CorpusManager::load(root.clone()).await.unwrap();
+        mgr.add_entry(b"let v0 = 1;", vec![1, 2, 3], 1.0, Duration::from_millis(1), false)
+        .await
+        .unwrap()
+        .expect("entry was not added");
+
+        let mut written = Vec::new();
+        for _ in 0..4 {
+            written.push(write_snapshot(&root, &mgr.metadata(), true, 2).await.unwrap());
+        }
+        let mut kept: Vec<PathBuf> = std::fs::read_dir(root.join(SNAPSHOTS_DIR))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+        kept.sort();
+        assert_eq!(kept, written[2..]);
+
+        // a snapshot with seeds is a corpus of its own
+        let restored = CorpusManager::load(kept[1].clone()).await.unwrap();
+        assert_eq!(restored.len(), 1);
+        let seed = &restored.entries()[0].path;
+        assert_eq!(std::fs::read(kept[1].join(seed)).unwrap(), b"let v0 = 1;");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// A disk write of the corpus. The writer task applies them one at a time in the order
+/// they were sent, so e.g. removing a seed can't overtake writing it.
+#[derive(Debug)]
+pub(crate) enum CorpusOp {
+    /// Write a file, creating its directory if needed
+    WriteFile { path: PathBuf, contents: Arc<[u8]> },
+    /// Remove a file if it exists
+    RemoveFile(PathBuf),
+    /// Append a serialized line to the journal
+    AppendJournal { journal_path: PathBuf, line: Vec<u8> },
+    /// Atomically replace the metadata snapshot, then truncate the journal
+    Compact {
+        metadata_path: PathBuf,
+        journal_path: PathBuf,
+        snapshot: Vec<u8>,
+    },
+    /// Answered once every op sent before it is applied
+    Flush(oneshot::Sender<()>),
+}
+
+impl CorpusOp {
+    pub(crate) async fn apply(self) -> Result<()> {
+        match self {
+            CorpusOp::WriteFile { path, contents } => {
+                if let Some(parent) = path.parent() {
+                    if fs::metadata(parent).await.is_err() {
+                        fs::create_dir_all(parent)
+                        .await
+                        .with_context(|| format!("failed to create directory {:?}", parent))?;
+                    }
+                }
+                fs::write(&path, &contents)
+                .await
+                .with_context(|| format!("failed to write corpus file {:?}", path))?;
+            }
+            CorpusOp::RemoveFile(path) => {
+                if fs::metadata(&path).await.is_ok() {
+                    fs::remove_file(&path)
+                    .await
+                    .with_context(|| format!("failed to remove corpus entry {:?}", path))?;
+                }
+            }
+            CorpusOp::AppendJournal { journal_path, line } => {
+                let mut journal = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&journal_path)
+                .await
+                .with_context(|| format!("failed to open journal {:?}", journal_path))?;
+                journal.write_all(&line)
+                .await
+                .with_context(|| format!("failed to append to journal {:?}", journal_path))?;
+                journal.flush()
+                .await
+                .with_context(|| format!("failed to flush journal {:?}", journal_path))?;
+            }
+            CorpusOp::Compact {
+                metadata_path,
+                journal_path,
+                snapshot,
+            } => {
+                // instances sharing the snapshot each compact their own journal
+                let temp_path = journal_path.with_extension("json.tmp");
+                fs::write(&temp_path, snapshot)
+                .await
+                .with_context(|| format!("failed to write temp metadata {:?}", temp_path))?;
+                fs::rename(&temp_path, &metadata_path)
+                .await
+                .with_context(|| "failed to atomically update metadata file".to_string())?;
+                fs::write(&journal_path, b"")
+                .await
+                .with_context(|| format!("failed to truncate journal {:?}", journal_path))?;
+            }
+            CorpusOp::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Contents of the files sent to the writer and not written yet, so they can be read
+/// back before they reach the disk
+#[derive(Debug, Default)]
+pub(crate) struct PendingWrites {
+    files: Mutex<HashMap<PathBuf, Arc<[u8]>>>,
+}
+
+impl PendingWrites {
+    fn insert(&self, path: PathBuf, contents: Arc<[u8]>) {
+        self.files.lock().expect("pending writes lock poisoned").insert(path, contents);
+    }
+
+    fn remove(&self, path: &Path, contents: &Arc<[u8]>) {
+        let mut files = self.files.lock().expect("pending writes lock poisoned");
+        // a later write of the same path is still pending
+        if files.get(path).is_some_and(|pending| Arc::ptr_eq(pending, contents)) {
+            files.remove(path);
+        }
+    }
+
+    pub(crate) fn get(&self, path: &Path) -> Option<Arc<[u8]>> {
+        self.files.lock().expect("pending writes lock poisoned").get(path).cloned()
+    }
+}
+
+/// Handle on the task persisting the corpus in the background
+pub(crate) struct CorpusWriter {
+    tx: mpsc::UnboundedSender<CorpusOp>,
+    pending: Arc<PendingWrites>,
+    task: JoinHandle<()>,
+}
+
+impl CorpusWriter {
+    pub(crate) fn spawn() -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<CorpusOp>();
+        let pending = Arc::new(PendingWrites::default());
+        let task_pending = Arc::clone(&pending);
+        let task = tokio::spawn(async move {
+            while let Some(op) = rx.recv().await {
+                let written = match &op {
+                    CorpusOp::WriteFile { path, contents } => Some((path.clone(), Arc::clone(contents))),
+                    _ => None,
+                };
+                if let Err(err) = op.apply().await {
+                    eprintln!("Failed to persist corpus: {:?}", err);
+                }
+                if let Some((path, contents)) = written {
+                    task_pending.remove(&path, &contents);
+                }
+            }
+        });
+        Self { tx, pending, task }
+    }
+
+    /// Queue `op` and return right away
+    pub(crate) fn send(&self, op: CorpusOp) -> Result<()> {
+        if let CorpusOp::WriteFile { path, contents } = &op {
+            self.pending.insert(path.clone(), Arc::clone(contents));
+        }
+        self.tx
+        .send(op)
+        .map_err(|_| anyhow::anyhow!("corpus writer task is gone"))
+    }
+
+    pub(crate) fn pending(&self) -> &PendingWrites {
+        &self.pending
+    }
+
+    /// Wait until every op sent so far is applied
+    pub(crate) async fn flush(&self) -> Result<()> {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.send(CorpusOp::Flush(done_tx))?;
+        done_rx.await.context("corpus writer task is gone")
+    }
+
+    /// Apply the ops still queued and stop the task
+    pub(crate) async fn close(self) -> Result<()> {
+        drop(self.tx);
+        self.task.await.context("corpus writer task panicked")
+    }
+}
@@ -1,77 +1,129 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use tokio::sync::{Mutex, mpsc};
-use tokio::fs as async_fs;
 use tokio::task::JoinHandle;
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::{compute_reward, corpus};
 use crate::corpus::CorpusManager;
+use crate::corpus::manager::Provenance;
+use crate::mutators::validity::apply_undefined_ref_policy;
 use crate::mutators::{ManagedMutator, get_random_splicer, get_weighted_ast_mutator_choice};
-use crate::parsing::parser::{generate_js, parse_js};
-use crate::runner::pool::{FuzzPool, JobResult};
+use crate::parsing::parser::{generate_js_with_decls, parse_js, parse_js_with_decls, source_type};
+use crate::runner::pool::{FuzzPool, JobResult, JobResults, Outcome, ProgramTooLarge};
+use crate::utils::iteration_log::{IterationRecord, IterationStep, iteration_rng};
+use crate::utils::mutation_log::{MutationLog, MutationRecord};
+use crate::utils::reward::RewardConfig;
+use crate::utils::stage_times::{STAGE_TIMES, Stage};
+use crate::utils::state::{FuzzerSettings, UndefinedRefPolicy};
+use swc_ecma_visit::swc_ecma_ast::Script;
 
+/// Mutate a random corpus sample and schedule the mutants. Returns the number of jobs
+/// scheduled. `iteration` is the index of the call in the fuzz loop.
 pub async fn fuzz_sample(
     corpus_manager: Arc<Mutex<CorpusManager>>,
     mutators: &[Arc<ManagedMutator>],
     handles: &mut Vec<JoinHandle<()>>, 
-    pool: &mut FuzzPool
-) {
+    pool: &mut FuzzPool,
+    settings: &FuzzerSettings,
+    iteration: u64,
+) -> usize {
+    let mut scheduled = 0;
+    let mut rng = match settings.rng_seed {
+        Some(seed) => iteration_rng(seed, iteration),
+        None => StdRng::from_rng(&mut rand::rng()),
+    };
     // pick a random sample from the corpus
-    let (seed, id) = {
+    let read_start = Instant::now();
+    let (source, id, seed_edges) = {
         let mut mgr = corpus_manager.lock().await;
-        let sample = mgr.pick_random()
+        let sample = mgr.pick_random_with(&mut rng)
         .expect("should always be able to pick sample");
-        let source = async_fs::read(&sample.path).await
+        let source = mgr.read_file(&sample.path).await
         .expect("should be able to read corpus sample");
-        let source = String::from_utf8(source).unwrap_or(String::new());
-        (parse_js(source), sample.id)
+        // splices pick the donor that complements these
+        let seed_edges = mgr
+        .entries()
+        .iter()
+        .find(|entry| entry.id == sample.id)
+        .map(|entry| entry.edges.clone())
+        .unwrap_or_default();
+        (String::from_utf8(source).unwrap_or(String::new()), sample.id, seed_edges)
     };
+    STAGE_TIMES.record(Stage::SeedRead, read_start.elapsed());
+    let seed = STAGE_TIMES.time(Stage::Parse, || parse_js_with_decls(source, source_type()));
     if seed.is_err() {
-        return;
+        return scheduled;
     }
-    let mut seed = seed.unwrap();
-    let mut rng = rand::rng();
+    // the declarations of a module seed go around every mutant
+    let (mut seed, module_decls) = seed.unwrap();
+    // scheduled programs so far, for the iteration log
+    let mut steps = Vec::new();
     
     // execute mutation on the sample
     // TODO: make the number consecutive mutations an option rather than hardcoding it
     for _ in 0..10 {
         // with a random probability splice
-        let mutator = get_weighted_ast_mutator_choice(mutators);
-        let mutated_seed = mutator.mutate(seed.clone());
-        if mutated_seed.is_err() {
+        let Some((mutator, mut mutated_seed, step_seed, mut step_rng)) =
+            STAGE_TIMES.time(Stage::Mutate, || pick_and_mutate(mutators, &seed, &mut rng))
+        else {
+            continue;
+        };
+        if !check_undefined_refs(&mutator, &mut mutated_seed, settings.undefined_refs, &mut step_rng) {
             continue;
         }
-        let mutated_seed = mutated_seed.unwrap();
         
         // execute the mutation
-        let mutated_source = generate_js(mutated_seed.clone());
+        let mutated_source = STAGE_TIMES.time(Stage::Generate, || {
+            generate_js_with_decls(mutated_seed.clone(), module_decls.clone())
+        });
         if mutated_source.is_err() {
             continue;
         }
         let mutated_source = mutated_source.unwrap();
+        if settings.verify_roundtrip && !verify_roundtrip(&mutator, &mutated_source) {
+            continue;
+        }
         
         // schedule execution
-        let result_rx = match pool.schedule_job(mutated_source.clone()).await {
+        let schedule_start = Instant::now();
+        let result_rx = pool.schedule_job(mutated_source.clone()).await;
+        STAGE_TIMES.record(Stage::Schedule, schedule_start.elapsed());
+        let result_rx = match result_rx {
             Ok(rx) => rx,
             Err(err) => {
                 eprintln!("Failed to schedule job: {:?}", err);
                 continue;
             }
         };
-        handles.push(tokio::task::spawn(result_handler(result_rx, mutator, corpus_manager.clone(), id, mutated_source)));
+        scheduled += 1;
+        let mutator_name = mutator.name().to_string();
+        let handle = tokio::task::spawn(result_handler(result_rx, mutator, corpus_manager.clone(), id, mutated_source, settings.rewards, settings.mutation_log.clone()));
+        push_handle(handles, handle, settings).await;
+        steps.push(IterationStep {
+            mutator: mutator_name,
+            rng_seed: step_seed,
+            donor_id: None,
+        });
         seed = mutated_seed;
         
         // with a probability also splice
         if rng.random_bool(0.2) {
-            let splicer = get_random_splicer(mutators);
+            let splicer = get_random_splicer(mutators, &mut rng);
             if !splicer.is_none() {
                 let splicer = splicer.unwrap();
                 let donor = {
-                    let mgr = corpus_manager.lock().await;
-                    mgr.get_random_script().await
+                    let mut mgr = corpus_manager.lock().await;
+                    // without edges every donor is equally complementary
+                    if seed_edges.is_empty() {
+                        mgr.get_random_script(&mut rng).await
+                    } else {
+                        mgr.get_complementary_script(&seed_edges, &mut rng).await
+                    }
                 };
-                let donor = match donor {
-                    Ok(Some(script)) => script,
+                let (donor_id, donor) = match donor {
+                    Ok(Some(donor)) => donor,
                     Ok(None) => {
                         eprintln!("No donor script available for splicing");
                         continue;
@@ -81,23 +133,120 @@ pub async fn fuzz_sample(
                         continue;
                     }
                 };
-                let mutated_seed = splicer.splice(&seed, &donor).expect("splicing failed");
-                let mutated_source = generate_js(mutated_seed.clone());
+                let step_seed: u64 = rng.random();
+                let mut step_rng = StdRng::seed_from_u64(step_seed);
+                let mut mutated_seed = STAGE_TIMES
+                .time(Stage::Mutate, || splicer.splice(&seed, &donor, &mut step_rng))
+                .expect("splicing failed");
+                if !check_undefined_refs(&splicer, &mut mutated_seed, settings.undefined_refs, &mut step_rng) {
+                    continue;
+                }
+                let mutated_source = STAGE_TIMES.time(Stage::Generate, || {
+                    generate_js_with_decls(mutated_seed.clone(), module_decls.clone())
+                });
                 if mutated_source.is_err() {
                     continue;
                 }
                 let mutated_source = mutated_source.unwrap();
-                let result_rx = match pool.schedule_job(mutated_source.clone()).await {
+                if settings.verify_roundtrip && !verify_roundtrip(&splicer, &mutated_source) {
+                    continue;
+                }
+                let schedule_start = Instant::now();
+                let result_rx = pool.schedule_job(mutated_source.clone()).await;
+                STAGE_TIMES.record(Stage::Schedule, schedule_start.elapsed());
+                let result_rx = match result_rx {
                     Ok(rx) => rx,
                     Err(err) => {
                         eprintln!("Failed to schedule job: {:?}", err);
                         continue;
                     }
                 };
-                handles.push(tokio::task::spawn(result_handler(result_rx, splicer, corpus_manager.clone(), id, mutated_source)));
+                scheduled += 1;
+                let splicer_name = splicer.name().to_string();
+                let handle = tokio::task::spawn(result_handler(result_rx, splicer, corpus_manager.clone(), id, mutated_source, settings.rewards, settings.mutation_log.clone()));
+                push_handle(handles, handle, settings).await;
+                steps.push(IterationStep {
+                    mutator: splicer_name,
+                    rng_seed: step_seed,
+                    donor_id: Some(donor_id),
+                });
             }
         }
     }
+    if let Some(log) = &settings.iteration_log {
+        let record = IterationRecord {
+            iteration,
+            seed_id: id,
+            steps,
+        };
+        if let Err(err) = log.append(&record) {
+            eprintln!("{:?}", err);
+        }
+    }
+    scheduled
+}
+
+/// Mutations tried on a seed before giving up when they all leave it unchanged
+const MAX_NOOP_RETRIES: usize = 4;
+
+/// Mutate the seed with a random mutator, trying other mutators when one leaves the seed
+/// unchanged. Unchanged seeds are not worth an execution. Every attempt draws from its
+/// own RNG, seeded from `rng`; the seed and the RNG of the successful one are returned
+/// with the mutant, so the rest of the step keeps drawing from it.
+fn pick_and_mutate(
+    mutators: &[Arc<ManagedMutator>],
+    seed: &Script,
+    rng: &mut StdRng,
+) -> Option<(Arc<ManagedMutator>, Script, u64, StdRng)> {
+    for _ in 0..MAX_NOOP_RETRIES {
+        let mutator = get_weighted_ast_mutator_choice(mutators, rng);
+        let step_seed: u64 = rng.random();
+        let mut step_rng = StdRng::seed_from_u64(step_seed);
+        match mutator.mutate_if_changed(seed.clone(), &mut step_rng) {
+            Ok(Some(mutated)) => return Some((mutator, mutated, step_seed, step_rng)),
+            Ok(None) => continue,
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/// Check that generated code parses again. A mutator producing code that doesn't is
+/// buggy, so the failure is recorded as an invalid for it.
+fn verify_roundtrip(mutator: &ManagedMutator, code: &[u8]) -> bool {
+    let parses = match std::str::from_utf8(code) {
+        Ok(source) => parse_js(source.to_string()).is_ok(),
+        Err(_) => false,
+    };
+    if !parses {
+        mutator.record_invalid(false);
+    }
+    parses
+}
+
+/// Apply the undefined reference policy to a mutated AST. Returns false if the mutation
+/// should be dropped.
+fn check_undefined_refs(
+    mutator: &ManagedMutator,
+    ast: &mut Script,
+    policy: UndefinedRefPolicy,
+    rng: &mut StdRng,
+) -> bool {
+    let valid = apply_undefined_ref_policy(ast, policy, rng);
+    if !valid {
+        mutator.record_invalid(false);
+    }
+    valid
+}
+
+/// In deterministic mode results are handled in scheduling order, so wait for the
+/// handler right away instead of letting it race with later jobs
+async fn push_handle(handles: &mut Vec<JoinHandle<()>>, handle: JoinHandle<()>, settings: &FuzzerSettings) {
+    if settings.deterministic {
+        handle.await.expect("fuzz loop task failed");
+    } else {
+        handles.push(handle);
+    }
 }
 
 async fn result_handler(
@@ -105,46 +254,236 @@ async fn result_handler(
     mutator: Arc<ManagedMutator>,
     corpus_manager: Arc<Mutex<CorpusManager>>,
     id: u64,
-    mutated_source: Vec<u8>
+    mutated_source: Vec<u8>,
+    rewards: RewardConfig,
+    mutation_log: Option<Arc<MutationLog>>,
 ) {
-    let job_result = match result_rx.recv().await {
-        Some(Ok(res)) => res,
-        Some(Err(err)) => {
+    // one result arrives per engine in the pool, the job is judged on all of them
+    let (job_results, errors) = JobResults::collect(&mut result_rx).await;
+    let mut too_large = false;
+    for err in errors {
+        if err.downcast_ref::<ProgramTooLarge>().is_some() {
+            too_large = true;
+        } else {
             eprintln!("Worker execution error: {:?}", err);
-            return;
-        }
-        None => {
-            eprintln!("Worker dropped execution result");
-            return;
         }
-    };
+    }
+    // the program never ran, blame the mutator that bloated it
+    if too_large {
+        mutator.record_invalid(false);
+    }
+    if job_results.results.is_empty() {
+        return;
+    }
     
-    let reward = compute_reward(&job_result);
-    mutator.record_reward(reward);
-    if job_result.is_timeout || job_result.status_code != 0 {
-        mutator.record_invalid(job_result.is_timeout);
+    let outcome = job_results.outcome();
+    if let Some(log) = &mutation_log {
+        let record = MutationRecord::new(id, mutator.name(), &mutated_source, &outcome);
+        if let Err(err) = log.append(&record) {
+            eprintln!("{:?}", err);
+        }
     }
+    // a replay of the exec cache, the program and its crashes were accounted for when it
+    // first ran
+    if job_results.is_cached() {
+        mutator.record_noop();
+        return;
+    }
+    let reward = compute_reward(&outcome, &rewards);
+    mutator.record_reward(reward, rewards.decay);
+    if matches!(outcome, Outcome::Timeout | Outcome::StackOverflow | Outcome::RuntimeError) {
+        mutator.record_invalid(outcome == Outcome::Timeout);
+    }
+    let update_start = Instant::now();
     {
         let mut mgr = corpus_manager.lock().await;
-        let _ = mgr.record_result(id, reward, job_result.exec_time_ms)
+        if outcome == Outcome::NewCoverage {
+            mgr.record_new_coverage(id);
+        }
+        let _ = mgr.record_result(id, reward, job_results.exec_time_ms())
         .await;
         
-        if job_result.is_crash {
-            println!(
-                "Crash detected (exit {}, signal {}); reward {}",
-                job_result.status_code, job_result.signal, reward
-            );
-            let _ = mgr.persist_crash(&mutated_source);
+        let provenance = Provenance {
+            parent_id: id,
+            producer: mutator.name().to_string(),
+        };
+        match outcome {
+            Outcome::Crash { .. } => {
+                // every engine the program crashed gets its own repro
+                for job_result in job_results.crashes() {
+                    println!(
+                        "[{}] Crash detected (exit {}, signal {}); reward {}",
+                        job_result.engine, job_result.status_code, job_result.signal, reward
+                    );
+                    if let Some(report) = &job_result.sanitizer {
+                        println!(
+                            "[{}] {}: {} in {}",
+                            job_result.engine,
+                            report.sanitizer,
+                            report.error_type,
+                            report.top_frame.as_deref().unwrap_or("unknown frame")
+                        );
+                    }
+                    let sanitizer = job_result.sanitizer.as_ref();
+                    if let Err(err) = mgr.persist_crash(&mutated_source, &job_result.engine, sanitizer, Some(&provenance)).await {
+                        eprintln!("Failed to persist crash: {:?}", err);
+                    }
+                }
+            }
+            Outcome::NewCoverage => {
+                let added = mgr.add_entry_with_provenance(
+                    &mutated_source, 
+                    job_results.new_edges(), 
+                    reward, 
+                    job_results.exec_time_ms(), 
+                    false,
+                    Some(provenance),
+                ).await;
+                if let Ok(Some(entry)) = added {
+                    for tag in entry.notable_tags() {
+                        let _ = mgr.tag_entry(entry.id, tag).await;
+                    }
+                }
+            }
+            Outcome::Timeout => {
+                // capped and deduplicated by the corpus manager
+                let _ = mgr.add_entry(
+                    &mutated_source,
+                    Vec::new(),
+                    reward,
+                    job_results.exec_time_ms(),
+                    true
+                ).await;
+            }
+            Outcome::StackOverflow => {
+                if let Err(err) = mgr.persist_stack_overflow(&mutated_source).await {
+                    eprintln!("Failed to persist stack overflow: {:?}", err);
+                }
+            }
+            Outcome::NoCoverage | Outcome::RuntimeError => {}
         }
-        
-        if job_result.new_coverage && job_result.status_code == 0 && !job_result.is_timeout {
-            let _ = mgr.add_entry(
-                &mutated_source, 
-                job_result.edge_hits.clone(), 
-                reward, 
-                job_result.exec_time_ms, 
-                job_result.is_timeout
-            ).await;
+    }
+    STAGE_TIMES.record(Stage::CorpusUpdate, update_start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::manager::load_metadata;
+    use crate::mutators::{AstMutator, get_ast_mutators_with_dictionary};
+    use crate::utils::dictionary::Dictionary;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::path::Path;
+    use std::time::Duration;
+
+    struct NopMutator;
+
+    impl AstMutator for NopMutator {
+        fn mutate(&self, ast: Script, _rng: &mut StdRng) -> anyhow::Result<Script> {
+            Ok(ast)
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn broken_code_fails_roundtrip() {
+        let mutator = ManagedMutator::new("NopMutator", Box::new(NopMutator), false);
+        assert!(verify_roundtrip(&mutator, b"let v0 = 1 + 2;"));
+        assert_eq!(mutator.stats_snapshot().invalid_count, 0);
+
+        assert!(!verify_roundtrip(&mutator, b"let v0 = (1 + ;"));
+        assert!(!verify_roundtrip(&mutator, &[0xff, 0xfe]));
+        assert_eq!(mutator.stats_snapshot().invalid_count, 2);
+    }
+
+    #[tokio::test]
+    async fn cached_results_are_not_rewarded() {
+        let dir = std::env::temp_dir().join(format!("jellyfuzz_cached_result_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let corpus_manager = Arc::new(Mutex::new(CorpusManager::load(dir.clone()).await.unwrap()));
+        let mutator = Arc::new(ManagedMutator::new("NopMutator", Box::new(NopMutator), false));
+        let (result_tx, result_rx) = mpsc::channel(1);
+        result_tx
+            .send(Ok(JobResult {
+                is_timeout: true,
+                cached: true,
+                ..hashed_result(b"while (true) {}")
+            }))
+            .await
+            .unwrap();
+        drop(result_tx);
+        let source = b"while (true) {}".to_vec();
+        let rewards = RewardConfig::default();
+        result_handler(result_rx, mutator.clone(), corpus_manager, 0, source, rewards, None).await;
+
+        let stats = mutator.stats_snapshot();
+        assert_eq!((stats.invalid_count, stats.timeout_count, stats.noop_count), (0, 0, 1));
+        assert_eq!(stats.total_reward, 0.0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Stands in for an engine whose coverage only depends on the program
+    fn hashed_result(js_code: &[u8]) -> JobResult {
+        let mut hasher = DefaultHasher::new();
+        js_code.hash(&mut hasher);
+        let hash = hasher.finish();
+        JobResult {
+            engine: "mock".to_string(),
+            status_code: 0,
+            signal: 0,
+            new_coverage: hash % 3 == 0,
+            edge_hits: vec![(hash % 97) as u32, (hash >> 32) as u32 % 97],
+            is_crash: false,
+            is_timeout: false,
+            is_stack_overflow: false,
+            exec_time_ms: Duration::from_millis(1),
+            sanitizer: None,
+            cached: false,
+        }
+    }
+
+    /// Fuzz a fresh copy of a tiny corpus in `dir` the way the deterministic fuzz loop
+    /// does and return the number of entries in its metadata afterwards
+    async fn deterministic_run(dir: &Path, rng_seed: u64) -> usize {
+        let _ = std::fs::remove_dir_all(dir);
+        let mut mgr = CorpusManager::load(dir.to_path_buf()).await.unwrap();
+        let seeds = [
+            "let v0 = [1, 2, 3]; v0.push(4); print(v0.length);",
+            "let v0 = \"abc\"; let v1 = v0 + 1; print(v1);",
+            "function f0(a0) { return a0 * 2; } let v0 = 0; for (let v1 = 0; v1 < 5; v1++) { v0 += f0(v1); }",
+        ];
+        for (idx, source) in seeds.iter().enumerate() {
+            mgr.add_entry(source.as_bytes(), vec![idx as u32 + 100], 1.0, Duration::from_millis(1), false)
+            .await
+            .unwrap();
+        }
+        let corpus_manager = Arc::new(Mutex::new(mgr));
+        let mutators = get_ast_mutators_with_dictionary(Arc::new(Dictionary::default()));
+        let settings = FuzzerSettings {
+            deterministic: true,
+            rng_seed: Some(rng_seed),
+            ..Default::default()
+        };
+        let mut pool = FuzzPool::mock("mock", hashed_result);
+        let mut handles = Vec::new();
+        for iteration in 1..=40 {
+            fuzz_sample(corpus_manager.clone(), &mutators, &mut handles, &mut pool, &settings, iteration).await;
+        }
+        // every result was handled before the next job got scheduled
+        assert!(handles.is_empty());
+        // fold the journal into metadata.json
+        corpus_manager.lock().await.compact().await.unwrap();
+        let entries = load_metadata(dir).await.unwrap().entries.len();
+        std::fs::remove_dir_all(dir).unwrap();
+        entries
+    }
+
+    #[tokio::test]
+    async fn deterministic_runs_grow_the_corpus_alike() {
+        let dir = |run: &str| std::env::temp_dir().join(format!("jellyfuzz_deterministic_{}_{}", run, std::process::id()));
+        let first = deterministic_run(&dir("a"), 42).await;
+        let second = deterministic_run(&dir("b"), 42).await;
+        assert!(first > 3, "the corpus did not grow");
+        assert_eq!(first, second);
+    }
+}
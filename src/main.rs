@@ -10,22 +10,45 @@ mod code_generators;
 use anyhow::{Context, Result, bail};
 use clap::Parser;
 use rand::seq::IndexedRandom;
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use tokio::fs as async_fs;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinHandle;
 use tokio::time::{Instant, sleep};
 
+use crate::code_generators::bootstrap::{DEFAULT_BOOTSTRAP_SEEDS, bootstrap_seeds};
 use crate::corpus::CorpusManager;
+use crate::corpus::incoming::{self, INCOMING_SCAN_INTERVAL, IncomingSeed};
+use crate::corpus::manager::{CorpusLayout, DEFAULT_MAX_TIMEOUTS, DedupMode, EntryEdges, Shard};
+use crate::corpus::snapshot::{self, DEFAULT_SNAPSHOTS_KEPT};
 use crate::fuzzer::fuzz_sample;
 use crate::mutators::minifier::Minifier;
-use crate::mutators::{ManagedMutator, get_ast_mutators, get_mutator_by_name};
-use crate::parsing::parser::{generate_js, parse_js};
+use crate::mutators::{
+    ManagedMutator, get_ast_mutators, get_ast_mutators_with_dictionary, get_mutator_by_name,
+    get_natives_mutator,
+};
+use crate::parsing::parser::{self, SourceType, generate_js, parse_js};
 use crate::profiles::profile::JsEngineProfile;
-use crate::runner::pool::{FuzzPool, JobResult};
+use crate::runner::autoscale::{AutoScaler, LoadMetrics, ScaleDecision, WorkerBounds};
+use crate::runner::backend::ExecBackendKind;
+use crate::runner::pool::{EdgeTracker, FuzzPool, JobResults, Outcome};
+use crate::runner::process::EngineStdio;
+use crate::runner::replay;
+use crate::utils::dictionary::Dictionary;
+use crate::utils::iteration_log::{IterationLog, find_iteration};
+use crate::utils::mutation_log::MutationLog;
+use crate::utils::pause::{FUZZ_PAUSE, PauseCheck, spawn_pause_handler};
+use crate::utils::rand_utils::unseeded_rng;
+use crate::utils::reward::RewardConfig;
+use crate::utils::run_report::{RunReporter, write_run_report};
+use crate::utils::stage_times::STAGE_TIMES;
+use crate::utils::state::{DETERMINISTIC_RNG_SEED, FuzzerSettings, UndefinedRefPolicy};
+use crate::utils::throttle::Throttle;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -44,21 +67,48 @@ struct Args {
         help = "Path to initial corpus directory to ingest when starting from scratch"
     )]
     initial_corpus: Option<PathBuf>,
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "Synthesize a few simple seed programs and keep the ones with coverage if the corpus would be empty"
+    )]
+    bootstrap: Option<bool>,
     
     // resume from existing corpus
     #[arg(short, long, action=clap::ArgAction::SetTrue, help = "Resume progress from existing corpus directory")]
     resume: Option<bool>,
-    // the profile to use
-    #[arg(short, long, help = "Fuzzing profile to use")]
-    profile: String,
+    // the profile(s) to use; every mutated program is executed on each of them
+    #[arg(
+        short,
+        long,
+        alias = "profiles",
+        value_delimiter = ',',
+        required_unless_present = "capabilities",
+        help = "Fuzzing profile(s) to use, comma separated (e.g. v8,jsc)"
+    )]
+    profile: Vec<String>,
     // number of workers
     #[arg(
         short,
         long,
         default_value_t = 1,
-        help = "Number of worker processes to use"
+        help = "Number of worker processes to use per profile"
     )]
     workers: usize,
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "Add workers while their job queues stay full and retire them while idle, between --min-workers and --max-workers"
+    )]
+    auto_scale_workers: Option<bool>,
+    #[arg(long, default_value_t = 1, requires = "auto_scale_workers", help = "Lower bound of the workers per profile with --auto-scale-workers")]
+    min_workers: usize,
+    #[arg(
+        long,
+        requires = "auto_scale_workers",
+        help = "Upper bound of the workers per profile with --auto-scale-workers (defaults to the number of CPUs)"
+    )]
+    max_workers: Option<usize>,
     // single test mode
     #[arg(long, help = "DEBUG: Run tests with a single specified input file")]
     single_test: Option<String>,
@@ -68,20 +118,387 @@ struct Args {
         help = "DEBUG: Run a specified mutator on a given input file and output the result"
     )]
     mutator_test: Option<String>,
+    // mutator benchmark mode
+    #[arg(
+        long,
+        help = "DEBUG: Benchmark every mutator on a given input file without executing anything"
+    )]
+    bench_mutators: Option<String>,
+    // mutator self-test mode
+    #[arg(
+        long,
+        value_name = "ITERATIONS",
+        help = "DEBUG: Apply every mutator ITERATIONS times to built-in seeds, check that the output re-parses and exit nonzero if any doesn't"
+    )]
+    self_test: Option<usize>,
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "Print the profiles, mutators and execution backends supported by this build and exit"
+    )]
+    capabilities: Option<bool>,
+    // AST dump mode
+    #[arg(long, help = "DEBUG: Parse a file, pretty-print its AST and exit")]
+    dump_ast: Option<String>,
+    #[arg(long, action=clap::ArgAction::SetTrue, requires = "dump_ast", help = "Print the AST dumped by --dump-ast as JSON")]
+    json: Option<bool>,
+    // regression mode
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Execute every .js file under DIR once on the first profile, print the outcomes and exit nonzero if any still crash"
+    )]
+    replay: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Execute FILE --times times on a fresh single worker of the first profile and report how often it crashes"
+    )]
+    verify_crash: Option<PathBuf>,
+    #[arg(long, value_name = "N", default_value_t = 10, requires = "verify_crash", help = "Number of runs for --verify-crash")]
+    times: usize,
+    // coverage diagnostics
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Execute FILE once on the first profile and print the edges it hits and which of them are new"
+    )]
+    show_coverage: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "CORPUS",
+        requires = "show_coverage",
+        help = "Corpus (directory or metadata.json) whose edges count as already seen by --show-coverage"
+    )]
+    coverage_baseline: Option<PathBuf>,
+    // corpus comparison mode
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["A", "B"],
+        help = "Compare two corpora (directories or metadata.json files): unique entries, edge differences and totals"
+    )]
+    diff_corpus: Option<Vec<PathBuf>>,
+    // manual corpus tagging
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["ID", "TAG"],
+        help = "Label the corpus entry ID of the output directory with TAG and exit"
+    )]
+    tag_entry: Option<Vec<String>>,
+    // stall watchdog
+    #[arg(
+        long,
+        default_value_t = 300,
+        help = "Warn if no job completes for this many seconds (0 disables the watchdog)"
+    )]
+    stall_timeout: u64,
+    #[arg(long, action=clap::ArgAction::SetTrue, help = "Exit with a nonzero code when the stall watchdog fires")]
+    exit_on_stall: Option<bool>,
+    // reproduction mode
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "Run a single worker of the first profile and handle every result in order so corpus growth is reproducible with the same --seed (much slower)"
+    )]
+    deterministic: Option<bool>,
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "Re-parse every mutated program before executing it and discard the ones that don't parse"
+    )]
+    verify_roundtrip: Option<bool>,
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "Trust the first run of inputs with new coverage instead of re-running them to drop flaky edges (faster, but flaky edges may enter the corpus)"
+    )]
+    no_confirm_edges: Option<bool>,
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_positive_rate,
+        help = "Sleep as needed to schedule at most N executions per second (for shared or thermally limited hosts)"
+    )]
+    max_execs_per_sec: Option<f64>,
+    // engine diagnostics
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "DEBUG: Run a single worker per profile and show the engine's stdout/stderr instead of discarding it"
+    )]
+    debug_engine: Option<bool>,
+    #[arg(long, requires = "debug_engine", help = "Append the engine's stdout/stderr to this file instead of the terminal")]
+    engine_log: Option<PathBuf>,
+    // distributed fuzzing over a shared corpus directory
+    #[arg(
+        long,
+        value_name = "INDEX/TOTAL",
+        help = "Only select seeds owned by this shard (id % TOTAL == INDEX) and periodically pick up seeds added by other instances"
+    )]
+    shard: Option<Shard>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "JSON file with the mutator rewards per outcome (crash, new_coverage, timeout, stack_overflow, neutral), overriding the profile's"
+    )]
+    reward_config: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "EXT",
+        default_value = "js",
+        help = "Only ingest initial corpus files with this extension ('*' accepts every file)"
+    )]
+    seed_ext: String,
+    #[arg(long, help = "AFL-style dictionary of tokens for the literal mutators to splice in")]
+    dict: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_TIMEOUTS,
+        help = "Maximum number of distinct timeout repros written to the timeouts directory"
+    )]
+    max_timeouts: usize,
+    // corpus backups
+    #[arg(
+        long,
+        value_name = "SECS",
+        help = "Save the corpus metadata to a timestamped directory under snapshots every SECS seconds"
+    )]
+    snapshot_interval: Option<u64>,
+    #[arg(long, default_value_t = DEFAULT_SNAPSHOTS_KEPT, requires = "snapshot_interval", help = "Number of corpus snapshots to keep")]
+    snapshot_keep: usize,
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        requires = "snapshot_interval",
+        help = "Also copy the seeds into every snapshot so it can be resumed from"
+    )]
+    snapshot_seeds: Option<bool>,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        help = "Remember the results of the last N distinct programs and skip executing exact duplicates (0 disables)"
+    )]
+    exec_cache_size: usize,
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 0,
+        help = "Give up scheduling a program when the workers stay saturated for MS milliseconds (0 waits forever)"
+    )]
+    schedule_timeout: u64,
+    #[arg(long, action=clap::ArgAction::SetTrue, help = "Save programs that overflow the stack to the stackoverflow directory")]
+    save_stack_overflows: Option<bool>,
+    #[arg(
+        long,
+        action=clap::ArgAction::SetTrue,
+        help = "Name crash files after the seed and mutator they come from (also saved in a .origin.json sidecar)"
+    )]
+    crash_origin_in_name: Option<bool>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Keep ingesting the seeds other tools drop into DIR, processed files are moved to DIR/done"
+    )]
+    watch_incoming: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = DedupMode::Both,
+        help = "What makes a new corpus entry a duplicate: same edges (coverage), same program (source) or both"
+    )]
+    dedup_by: DedupMode,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CorpusLayout::Flat,
+        help = "Where new seeds are written: the corpus root (flat) or hashed subdirectories of seeds/ (sharded)"
+    )]
+    corpus_layout: CorpusLayout,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = UndefinedRefPolicy::Off,
+        help = "Statically check mutated programs for undefined identifiers and repair or discard them"
+    )]
+    undefined_refs: UndefinedRefPolicy,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SourceType::Script,
+        help = "Parse inputs as scripts, modules, or modules only when they use import/export (auto)"
+    )]
+    source_type: SourceType,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Append a JSONL record (seed id, mutator, program hash, outcome) for every executed mutation to FILE"
+    )]
+    mutation_log: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "SEED",
+        alias = "seed",
+        help = "Derive the RNG picking the seed and mutators of every iteration from SEED and the iteration index (--deterministic defaults it to 0)"
+    )]
+    rng_seed: Option<u64>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Append a JSONL record (iteration, seed id, mutators with their RNG seeds and splice donors) for every fuzz loop iteration to FILE"
+    )]
+    iteration_log: Option<PathBuf>,
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["FILE", "INDEX"],
+        help = "Rebuild the programs of iteration INDEX of the --iteration-log FILE from the corpus of the output directory, execute them on the first profile and exit"
+    )]
+    replay_iteration: Option<Vec<String>>,
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = parser::DEFAULT_MAX_PARSE_BYTES,
+        help = "Reject seeds and programs bigger than BYTES without parsing them"
+    )]
+    max_parse_bytes: usize,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = parser::DEFAULT_MAX_PARSE_DEPTH,
+        help = "Reject seeds and programs with brackets nested more than N deep without parsing them"
+    )]
+    max_parse_depth: usize,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
     let output_dir = args.output_dir.clone();
+    parser::set_source_type(args.source_type);
+    parser::set_parse_limits(parser::ParseLimits {
+        max_bytes: args.max_parse_bytes,
+        max_depth: args.max_parse_depth,
+    });
     
+    if args.capabilities.unwrap_or(false) {
+        print!("{}", render_capabilities());
+        return Ok(());
+    }
     if let Some(test_path) = args.single_test.as_deref() {
-        single_test(test_path, &args.profile).await;
+        single_test(test_path, &args.profile[0]).await;
         return Ok(());
     }
     if let Some(mutator) = args.mutator_test.as_deref() {
-        let mutator = get_mutator_by_name(mutator).expect("unknown mutator");
-        mutator_test("test.js", mutator, &args.profile).await;
+        let profile = profiles::get_profile(&args.profile[0])
+        .unwrap_or_else(|| panic!("unknown profile {}", args.profile[0]));
+        let mutator = get_mutator_by_name(mutator, profile.supports_natives()).expect("unknown mutator");
+        mutator_test("test.js", mutator, &args.profile[0]).await;
+        return Ok(());
+    }
+    if let Some(seed_path) = args.bench_mutators.as_deref() {
+        bench_mutators(seed_path)?;
+        return Ok(());
+    }
+    if let Some(iterations) = args.self_test {
+        if !self_test(iterations)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(script_path) = args.dump_ast.as_deref() {
+        let source = fs::read_to_string(script_path)
+        .with_context(|| format!("failed to read {:?}", script_path))?;
+        println!("{}", render_ast(source, args.json.unwrap_or(false))?);
+        return Ok(());
+    }
+    if let Some(paths) = args.diff_corpus.as_deref() {
+        let a = corpus::manager::load_metadata(&paths[0]).await?;
+        let b = corpus::manager::load_metadata(&paths[1]).await?;
+        print!("{}", corpus::diff::format_corpus_diff(&corpus::diff::diff_corpus(&a, &b)));
+        return Ok(());
+    }
+    if let Some(tag_args) = args.tag_entry.as_deref() {
+        let id: u64 = tag_args[0]
+        .parse()
+        .with_context(|| format!("invalid corpus entry id {:?}", tag_args[0]))?;
+        let mut corpus_manager = CorpusManager::load(output_dir.clone()).await?;
+        corpus_manager.tag_entry(id, &tag_args[1]).await?;
+        println!("Tagged corpus entry {} with {:?}", id, tag_args[1]);
+        return Ok(());
+    }
+    if let Some(replay_dir) = args.replay.as_deref() {
+        let profile = profiles::get_profile(&args.profile[0])
+        .unwrap_or_else(|| panic!("unknown profile {}", args.profile[0]));
+        let mut pool = FuzzPool::new(1, &profile)?;
+        let entries = replay::replay_dir(&mut pool, replay_dir).await?;
+        print!("{}", replay::format_replay_table(&entries));
+        let crashes = entries.iter().filter(|entry| entry.is_crash()).count();
+        println!("{} of {} files still crash", crashes, entries.len());
+        if crashes > 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(repro_path) = args.verify_crash.as_deref() {
+        let profile = profiles::get_profile(&args.profile[0])
+        .unwrap_or_else(|| panic!("unknown profile {}", args.profile[0]));
+        let mut pool = FuzzPool::new(1, &profile)?;
+        let verification = replay::verify_crash(&mut pool, repro_path, args.times).await?;
+        print!("{}", replay::format_crash_verification(&verification));
+        if verification.crashes == 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(replay_args) = args.replay_iteration.as_deref() {
+        let index: u64 = replay_args[1]
+        .parse()
+        .with_context(|| format!("invalid iteration index {:?}", replay_args[1]))?;
+        let record = find_iteration(std::path::Path::new(&replay_args[0]), index)?;
+        let mut corpus_manager = CorpusManager::load(output_dir.clone()).await?;
+        let profile = profiles::get_profile(&args.profile[0])
+        .unwrap_or_else(|| panic!("unknown profile {}", args.profile[0]));
+        // the mutators of the campaign, so every name in the chain resolves
+        let dictionary = match &args.dict {
+            Some(path) => Dictionary::load(path)?,
+            None => Dictionary::default(),
+        };
+        let mut mutators = get_ast_mutators_with_dictionary(Arc::new(dictionary));
+        if profile.supports_natives() {
+            mutators.push(get_natives_mutator());
+        }
+        let mut pool = FuzzPool::new(1, &profile)?;
+        let programs =
+            replay::replay_iteration(&mut pool, &mut corpus_manager, &record, &mutators, args.undefined_refs).await?;
+        println!("iteration {} (seed {})", record.iteration, record.seed_id);
+        for (step, (program, outcome)) in record.steps.iter().zip(programs) {
+            match step.donor_id {
+                Some(donor_id) => println!("{} with donor {}: {:?}", step.mutator, donor_id, outcome),
+                None => println!("{}: {:?}", step.mutator, outcome),
+            }
+            println!("{}", String::from_utf8_lossy(&program));
+        }
+        return Ok(());
+    }
+    if let Some(script_path) = args.show_coverage.as_deref() {
+        let mut tracker = EdgeTracker::new(1000);
+        if let Some(baseline) = args.coverage_baseline.as_deref() {
+            for entry in corpus::manager::load_metadata(baseline).await?.entries {
+                // the baseline may come from a campaign on several engines
+                tracker.record_new_edges(&entry.edges.edge_hits);
+                if let Some(edges) = entry.edges.engine_edges.get(&args.profile[0]) {
+                    tracker.record_new_edges(edges);
+                }
+            }
+        }
+        let profile = profiles::get_profile(&args.profile[0])
+        .unwrap_or_else(|| panic!("unknown profile {}", args.profile[0]));
+        let mut pool = FuzzPool::new(1, &profile)?;
+        let report = replay::show_coverage(&mut pool, script_path, &mut tracker).await?;
+        print!("{}", replay::format_coverage_report(&report));
         return Ok(());
     }
     
@@ -92,18 +509,108 @@ async fn main() -> Result<()> {
         .with_context(|| format!("failed to create output directory {:?}", output_dir))?;
     }
     
-    let corpus_manager = Arc::new(Mutex::new(CorpusManager::load(output_dir.clone()).await?));
-    let profile = profiles::get_profile(&args.profile)
-    .unwrap_or_else(|| panic!("unknown profile {}", args.profile));
-    let pool_size = args.workers;
-    let mut pool = FuzzPool::new(pool_size, &profile)?;
+    // the campaign's wall-clock time in the run report includes ingestion
+    let campaign_start = std::time::Instant::now();
+    let deterministic = args.deterministic.unwrap_or(false);
+    let settings = FuzzerSettings {
+        deterministic,
+        verify_roundtrip: args.verify_roundtrip.unwrap_or(false),
+        undefined_refs: args.undefined_refs,
+        debug_engine: args.debug_engine.unwrap_or(false),
+        max_execs_per_sec: args.max_execs_per_sec,
+        mutation_log: match &args.mutation_log {
+            Some(path) => Some(Arc::new(MutationLog::open(path)?)),
+            None => None,
+        },
+        // a deterministic run always draws from the per-iteration RNGs
+        rng_seed: args.rng_seed.or(deterministic.then_some(DETERMINISTIC_RNG_SEED)),
+        iteration_log: match &args.iteration_log {
+            Some(path) => Some(Arc::new(IterationLog::open(path)?)),
+            None => None,
+        },
+        ..Default::default()
+    };
+    let settings = FuzzerSettings {
+        // a single worker is forced in these modes
+        auto_scale: if args.auto_scale_workers.unwrap_or(false) && !settings.deterministic && !settings.debug_engine {
+            let max = args.max_workers.unwrap_or_else(|| {
+                std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+            });
+            if args.min_workers == 0 || args.min_workers > max {
+                bail!("--min-workers must be between 1 and --max-workers ({})", max);
+            }
+            Some(WorkerBounds {
+                min: args.min_workers,
+                max,
+            })
+        } else {
+            None
+        },
+        ..settings
+    };
+    let profile_names = if settings.deterministic {
+        println!("Deterministic mode, RNG seed {}", settings.rng_seed.unwrap_or(DETERMINISTIC_RNG_SEED));
+        if args.profile.len() > 1 {
+            println!("Deterministic mode only runs the first profile ({})", args.profile[0]);
+        }
+        &args.profile[..1]
+    } else {
+        &args.profile[..]
+    };
     
+    let mut corpus_manager = CorpusManager::load_sharded(output_dir.clone(), args.shard).await?;
+    let pruned = corpus_manager.validate().await?;
+    if pruned > 0 {
+        println!("[corpus] dropped {} entries whose file is missing", pruned);
+    }
+    let duplicates = corpus_manager.remove_duplicates().await?;
+    if duplicates > 0 {
+        println!("[corpus] removed {} duplicate entries", duplicates);
+    }
+    corpus_manager.set_max_timeouts(args.max_timeouts);
+    corpus_manager.set_save_stack_overflows(args.save_stack_overflows.unwrap_or(false));
+    corpus_manager.set_crash_origin_in_name(args.crash_origin_in_name.unwrap_or(false));
+    corpus_manager.set_dedup_mode(args.dedup_by);
+    corpus_manager.set_layout(args.corpus_layout);
+    let corpus_manager = Arc::new(Mutex::new(corpus_manager));
+    let pool_size = settings.workers_per_profile(args.workers);
+    let mut pool = FuzzPool::empty();
+    if settings.debug_engine {
+        pool.set_engine_stdio(match &args.engine_log {
+            Some(path) => EngineStdio::File(path.clone()),
+            None => EngineStdio::Inherit,
+        });
+    }
+    pool.set_confirm_edges(!args.no_confirm_edges.unwrap_or(false));
+    pool.set_exec_cache_size(args.exec_cache_size);
+    pool.set_schedule_timeout((args.schedule_timeout > 0).then(|| Duration::from_millis(args.schedule_timeout)));
+    // every program runs on every engine, so natives are only emitted if all support them
+    let mut supports_natives = true;
+    for profile_name in profile_names {
+        let profile = profiles::get_profile(profile_name)
+        .unwrap_or_else(|| panic!("unknown profile {}", profile_name));
+        supports_natives &= profile.supports_natives();
+        pool.add_engine(pool_size, &profile)?;
+    }
+    // results are rewarded the same whichever engine produced them, so the first profile decides
+    let rewards = match &args.reward_config {
+        Some(path) => RewardConfig::load(path)?,
+        None => profiles::get_profile(&profile_names[0])
+        .unwrap_or_else(|| panic!("unknown profile {}", profile_names[0]))
+        .reward_config(),
+    };
+    let settings = FuzzerSettings { rewards, ..settings };
+    
+    let bootstrap = args.bootstrap.unwrap_or(false);
     if args.overwrite.unwrap_or(false) {
-        let initial_corpus = args
-        .initial_corpus
-        .clone()
-        .expect("initial corpus directory is required when overwrite is set");
-        ingest_initial_corpus(&mut pool, Arc::clone(&corpus_manager), initial_corpus).await?;
+        match args.initial_corpus.clone() {
+            Some(initial_corpus) => {
+                ingest_initial_corpus(&mut pool, Arc::clone(&corpus_manager), initial_corpus, &args.seed_ext, settings.rewards)
+                .await?;
+            }
+            None if bootstrap => {}
+            None => panic!("initial corpus directory is required when overwrite is set"),
+        }
     } else if args.resume.unwrap_or(false) {
         let len = {
             let mgr = corpus_manager.lock().await;
@@ -112,6 +619,13 @@ async fn main() -> Result<()> {
         println!("Resuming with {} corpus entries loaded from disk", len);
     }
     
+    let is_empty = {
+        let mgr = corpus_manager.lock().await;
+        mgr.is_empty()
+    };
+    if is_empty && bootstrap {
+        bootstrap_corpus(&mut pool, Arc::clone(&corpus_manager), DEFAULT_BOOTSTRAP_SEEDS).await?;
+    }
     let is_empty = {
         let mgr = corpus_manager.lock().await;
         mgr.is_empty()
@@ -120,9 +634,121 @@ async fn main() -> Result<()> {
         println!("Corpus is empty; nothing to fuzz.");
         return Ok(());
     }
+    // from here on the fuzz loop doesn't wait for corpus writes
+    corpus_manager.lock().await.spawn_writer();
     
-    let mutators = get_ast_mutators();
-    run_fuzz_loop(&mut pool, Arc::clone(&corpus_manager), &mutators).await
+    if let Some(interval) = args.snapshot_interval.filter(|&secs| secs > 0) {
+        snapshot::spawn_snapshot_task(
+            Arc::clone(&corpus_manager),
+            Duration::from_secs(interval),
+            args.snapshot_seeds.unwrap_or(false),
+            args.snapshot_keep.max(1),
+        );
+    }
+    
+    let stall_watch = (args.stall_timeout > 0).then(|| {
+        tokio::spawn(pool.watchdog().monitor(
+            Duration::from_secs(args.stall_timeout),
+            args.exit_on_stall.unwrap_or(false),
+        ))
+    });
+    
+    let dictionary = match &args.dict {
+        Some(path) => {
+            let dictionary = Dictionary::load(path)?;
+            println!("Loaded {} dictionary tokens from {:?}", dictionary.len(), path);
+            dictionary
+        }
+        None => Dictionary::default(),
+    };
+    let mut mutators = get_ast_mutators_with_dictionary(Arc::new(dictionary));
+    if supports_natives {
+        mutators.push(get_natives_mutator());
+    }
+    let reporter = RunReporter::new(&pool, Arc::clone(&corpus_manager), &mutators, campaign_start);
+    spawn_shutdown_handler(Arc::clone(&corpus_manager), reporter.clone());
+    
+    let incoming_seeds = args
+    .watch_incoming
+    .map(|dir| incoming::spawn_incoming_watcher(dir, &args.seed_ext, INCOMING_SCAN_INTERVAL));
+    // owned here so the mutants still executing when the loop returns early on an error
+    // are awaited too
+    let mut handles: Vec<JoinHandle<()>> = vec![];
+    let fuzz_loop = run_fuzz_loop(
+        &mut pool,
+        Arc::clone(&corpus_manager),
+        &mutators,
+        &settings,
+        incoming_seeds,
+        &mut handles,
+    );
+    // only returns with --exit-on-stall, a wedged pool can keep the fuzz loop from ever
+    // getting back to check on it
+    let stalled = async {
+        match stall_watch {
+            Some(handle) => {
+                let _ = handle.await;
+            }
+            None => std::future::pending().await,
+        }
+    };
+    let result = tokio::select! {
+        result = fuzz_loop => result,
+        _ = stalled => {
+            eprintln!("[watchdog] Exiting because the fuzz pool is stalled");
+            shut_down(&corpus_manager, &reporter, 2).await
+        }
+    };
+    for handle in handles.drain(..) {
+        if let Err(err) = handle.await {
+            eprintln!("Fuzz loop task failed: {:?}", err);
+        }
+    }
+    close_corpus_writer(&corpus_manager).await;
+    report_run(&reporter).await;
+    result
+}
+
+/// On Ctrl-C, write the queued corpus changes and the run report to disk before exiting
+fn spawn_shutdown_handler(corpus_manager: Arc<Mutex<CorpusManager>>, reporter: RunReporter) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        println!("[corpus] interrupted, writing pending corpus changes");
+        shut_down(&corpus_manager, &reporter, 130).await
+    });
+}
+
+/// Write the queued corpus changes and the run report to disk, then exit with `code`
+async fn shut_down(corpus_manager: &Mutex<CorpusManager>, reporter: &RunReporter, code: i32) -> ! {
+    close_corpus_writer(corpus_manager).await;
+    report_run(reporter).await;
+    std::process::exit(code);
+}
+
+/// Write the queued corpus changes to disk
+async fn close_corpus_writer(corpus_manager: &Mutex<CorpusManager>) {
+    if let Err(err) = corpus_manager.lock().await.close_writer().await {
+        eprintln!("Failed to write pending corpus changes: {:?}", err);
+    }
+}
+
+async fn report_run(reporter: &RunReporter) {
+    match write_run_report(reporter).await {
+        Ok(path) => println!("[report] run report written to {:?}", path),
+        Err(err) => eprintln!("Failed to write the run report: {:?}", err),
+    }
+}
+
+fn parse_positive_rate(value: &str) -> std::result::Result<f64, String> {
+    let rate: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid rate {:?}", value))?;
+    if !(rate > 0.0 && rate.is_finite()) {
+        return Err(format!("rate must be positive, got {}", rate));
+    }
+    Ok(rate)
 }
 
 fn handle_overwrite(output_dir: &PathBuf) -> Result<()> {
@@ -151,15 +777,48 @@ fn handle_overwrite(output_dir: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// Execute `count` synthesized seeds and add the ones that hit new edges to the corpus
+async fn bootstrap_corpus(pool: &mut FuzzPool, corpus_manager: Arc<Mutex<CorpusManager>>, count: usize) -> Result<()> {
+    let mut ingested_edges = HashSet::new();
+    let mut accepted = 0;
+    for seed in bootstrap_seeds(count) {
+        let code = generate_js(seed)?;
+        let job_results = match pool.execute_job(code.clone()).await {
+            Ok(job_results) => job_results,
+            Err(err) => {
+                eprintln!("Failed to execute bootstrap seed: {:?}", err);
+                continue;
+            }
+        };
+        if job_results.outcome() != Outcome::NewCoverage {
+            continue;
+        }
+        let edges = job_results.new_edges();
+        let reward = initial_seed_reward(&edges, &mut ingested_edges);
+        let mut mgr = corpus_manager.lock().await;
+        let added = mgr
+        .add_entry(&code, edges, reward, job_results.exec_time_ms(), false)
+        .await?;
+        if added.is_some() {
+            accepted += 1;
+        }
+    }
+    println!("Bootstrap complete: {} of {} synthesized seeds added to the corpus", accepted, count);
+    Ok(())
+}
+
 async fn ingest_initial_corpus(
     pool: &mut FuzzPool,
     corpus_manager: Arc<Mutex<CorpusManager>>,
     corpus_dir: PathBuf,
+    seed_ext: &str,
+    rewards: RewardConfig,
 ) -> Result<()> {
     let start = std::time::Instant::now();
     let processed = Arc::new(AtomicUsize::new(0));
     let accepted = Arc::new(AtomicUsize::new(0));
     let skipped = Arc::new(AtomicUsize::new(0));
+    let ingested_edges = Arc::new(Mutex::new(HashSet::new()));
     let minifier = Minifier;
     let mut dir = async_fs::read_dir(&corpus_dir)
     .await
@@ -176,7 +835,7 @@ async fn ingest_initial_corpus(
         .file_type()
         .await
         .with_context(|| format!("failed to determine file type for {:?}", path))?;
-        if !file_type.is_file() {
+        if !file_type.is_file() || !has_seed_ext(&path, seed_ext) {
             continue;
         }
         
@@ -199,8 +858,8 @@ async fn ingest_initial_corpus(
             }
         };
         
-        let script = match parse_js(source_str) {
-            Ok(script) => script,
+        let (script, module_decls) = match parser::parse_js_with_decls(source_str, parser::source_type()) {
+            Ok(parsed) => parsed,
             Err(err) => {
                 // eprintln!("Failed to parse {:?}: {:?}", path, err);
                 skipped.fetch_add(1, Ordering::Relaxed);
@@ -217,7 +876,7 @@ async fn ingest_initial_corpus(
             }
         };
         
-        let new_code = match generate_js(minified) {
+        let new_code = match parser::generate_js_with_decls(minified, module_decls) {
             Ok(code) => code,
             Err(err) => {
                 eprintln!("Failed to regenerate code for {:?}: {:?}", path, err);
@@ -239,22 +898,22 @@ async fn ingest_initial_corpus(
         let corpus_manager_clone = Arc::clone(&corpus_manager);
         let accepted_clone = Arc::clone(&accepted);
         let skipped_clone = Arc::clone(&skipped);
+        let ingested_edges_clone = Arc::clone(&ingested_edges);
         let path_clone = path.clone();
         let handle = tokio::spawn(async move {
             let mut result_rx = result_rx;
-            let job_result = match result_rx.recv().await {
-                Some(Ok(res)) => res,
-                Some(Err(err)) => {
-                    eprintln!("Worker rejected {:?}: {:?}", path_clone, err);
-                    skipped_clone.fetch_add(1, Ordering::Relaxed);
-                    return;
-                }
-                None => {
-                    eprintln!("Worker dropped job for {:?}", path_clone);
-                    skipped_clone.fetch_add(1, Ordering::Relaxed);
-                    return;
-                }
-            };
+            // one result arrives per engine in the pool, the seed is added once for all
+            let (job_results, errors) = JobResults::collect(&mut result_rx).await;
+            if let Some(err) = errors.first() {
+                eprintln!("Worker rejected {:?}: {:?}", path_clone, err);
+                skipped_clone.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            if job_results.results.is_empty() {
+                eprintln!("Worker dropped job for {:?}", path_clone);
+                skipped_clone.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
             
             let exec_time = exec_start.elapsed();
             // if job_result.status_code != 0 {
@@ -262,15 +921,23 @@ async fn ingest_initial_corpus(
             //     return;
             // }
             
-            let reward = compute_reward(&job_result);
+            let outcome = job_results.outcome();
+            let edges = job_results.new_edges();
+            let reward = match outcome {
+                Outcome::Crash { .. } | Outcome::Timeout => compute_reward(&outcome, &rewards),
+                _ => {
+                    let mut ingested_edges = ingested_edges_clone.lock().await;
+                    initial_seed_reward(&edges, &mut ingested_edges)
+                }
+            };
             let mut manager = corpus_manager_clone.lock().await;
             match manager
             .add_entry(
                 &new_code,
-                job_result.edge_hits.clone(),
+                edges,
                 reward,
                 exec_time,
-                job_result.is_timeout,
+                job_results.is_timeout(),
             )
             .await
             {
@@ -316,58 +983,161 @@ async fn ingest_initial_corpus(
     Ok(())
 }
 
+/// How often a paused fuzz loop checks whether it got resumed
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Iterations between the stats reports of a deterministic run
+const DETERMINISTIC_REPORT_INTERVAL: u64 = 1000;
+
+/// How often `--auto-scale-workers` re-evaluates the worker count. Long enough for a new
+/// worker's effect on execs/sec to show.
+const AUTO_SCALE_INTERVAL: Duration = Duration::from_secs(10);
+
 async fn run_fuzz_loop(
     pool: &mut FuzzPool,
     corpus_manager: Arc<Mutex<CorpusManager>>,
     mutators: &[Arc<ManagedMutator>],
+    settings: &FuzzerSettings,
+    mut incoming_seeds: Option<mpsc::Receiver<IncomingSeed>>,
+    handles: &mut Vec<JoinHandle<()>>,
 ) -> Result<()> {
     let mut iteration: u64 = 0;
     let mut total_iterations: u64 = 0;
-    let mut handles = vec![];
     let mut start = Instant::now();
+    let mut throttle = settings
+        .max_execs_per_sec
+        .map(|rate| Throttle::new(rate, std::time::Instant::now()));
+    let mut auto_scaler = settings.auto_scale.map(AutoScaler::new);
+    let mut last_scale = Instant::now();
+    let mut completions_at_last_scale = pool.watchdog().completions();
+    if let Err(err) = spawn_pause_handler() {
+        eprintln!("Failed to install the SIGUSR1 handler, pausing is unavailable: {:?}", err);
+    }
     loop {
+        match FUZZ_PAUSE.check() {
+            PauseCheck::Run => {}
+            PauseCheck::Paused => {
+                for handle in handles.drain(..) {
+                    handle.await.expect("fuzz loop task failed");
+                }
+                print_status_dump(pool, &corpus_manager, mutators, total_iterations).await;
+                continue;
+            }
+            PauseCheck::StillPaused => {
+                sleep(PAUSE_POLL_INTERVAL).await;
+                continue;
+            }
+            PauseCheck::Resumed => {
+                println!("[pause] resumed");
+                // the pause doesn't count towards execs/sec
+                start = Instant::now();
+                iteration = 0;
+            }
+        }
         iteration += 1;
         total_iterations += 1;
         
-        let corpus_manager = corpus_manager.clone();
-        fuzz_sample(corpus_manager, mutators, &mut handles, pool)
+        if let Some(seed_rx) = incoming_seeds.as_mut() {
+            while let Ok(seed) = seed_rx.try_recv() {
+                let path = seed.path.clone();
+                match incoming::ingest_incoming_seed(pool, corpus_manager.clone(), seed, settings.rewards).await {
+                    Ok(handle) => handles.push(handle),
+                    Err(err) => eprintln!("Failed to schedule incoming seed {:?}: {:?}", path, err),
+                }
+            }
+        }
+        let scheduled = fuzz_sample(corpus_manager.clone(), mutators, handles, pool, settings, total_iterations)
             .await;
-        if handles.len() >= 10000 {
+        // the loop is the only producer, so sleeping here also keeps the job queues and
+        // in-flight handles from growing past the cap
+        if let Some(throttle) = throttle.as_mut() {
+            let wait = throttle.consume(scheduled, std::time::Instant::now());
+            if !wait.is_zero() {
+                sleep(wait).await;
+            }
+        }
+        if let Some(scaler) = auto_scaler.as_mut() {
+            if last_scale.elapsed() >= AUTO_SCALE_INTERVAL {
+                let completions = pool.watchdog().completions();
+                let metrics = LoadMetrics {
+                    workers: pool.worker_count(),
+                    queued_jobs: pool.queued_jobs(),
+                    queue_capacity: pool.queue_capacity(),
+                    execs_per_sec: (completions - completions_at_last_scale) as f64
+                        / last_scale.elapsed().as_secs_f64(),
+                };
+                match scaler.decide(&metrics) {
+                    ScaleDecision::Grow => {
+                        pool.add_worker()?;
+                        println!("[scale] {} -> {} workers per profile", metrics.workers, pool.worker_count());
+                    }
+                    ScaleDecision::Shrink => {
+                        pool.retire_worker().await?;
+                        println!("[scale] {} -> {} workers per profile", metrics.workers, pool.worker_count());
+                    }
+                    ScaleDecision::Hold => {}
+                }
+                last_scale = Instant::now();
+                completions_at_last_scale = pool.watchdog().completions();
+            }
+        }
+        // deterministic mode awaits every handle right away, so count samples instead
+        let report_due = if settings.deterministic {
+            total_iterations % DETERMINISTIC_REPORT_INTERVAL == 0
+        } else {
+            handles.len() >= 10000
+        };
+        if report_due {
             for handle in handles.drain(..) {
                 handle.await.expect("fuzz loop task failed");
             }
             pool.print_pool_stats().await;
+            if let Some(log) = &settings.mutation_log {
+                if let Err(err) = log.flush() {
+                    eprintln!("{:?}", err);
+                }
+            }
+            if let Some(log) = &settings.iteration_log {
+                if let Err(err) = log.flush() {
+                    eprintln!("{:?}", err);
+                }
+            }
             println!("executed {} iterations", total_iterations);
+            println!("[stages] {}", STAGE_TIMES.format());
             let elapsed = start.elapsed();
             println!(
                 "[{:?}] Execs/sec: {:.2}",
                 chrono::Utc::now().timestamp(),
                 (iteration) as f64 / elapsed.as_secs_f64()
             );
-            start = Instant::now();
-            iteration = 0;
-            for mutator in mutators {
-                let stats = mutator.stats_snapshot();
-                let success_rate = if stats.uses == 0 {
-                    0.0
-                } else {
-                    (stats.uses - stats.invalid_count) as f64 / stats.uses as f64 * 100.0
-                };
+            // a deterministic run reports totals over the whole run
+            if !settings.deterministic {
+                STAGE_TIMES.reset();
+                start = Instant::now();
+                iteration = 0;
+            }
+            {
+                let mut mgr = corpus_manager.lock().await;
                 println!(
-                    "[mut] {}: success rate: {:.2}%, reward: {:.2}, mean: {:.4}, uses: {}, timeouts: {}, invalids: {}",
-                    mutator.name(),
-                    success_rate,
-                    stats.total_reward,
-                    stats.mean_reward,
-                    stats.uses,
-                    stats.timeout_count,
-                    stats.invalid_count
+                    "[corpus] entries: {}, timeouts seen: {}, timeouts stored: {}",
+                    mgr.len(),
+                    mgr.timeouts_seen(),
+                    mgr.stored_timeouts()
                 );
+                println!("[corpus] sizes: {}", mgr.size_histogram().format());
+                // when other instances add their seeds depends on timing
+                if let Some(shard) = mgr.shard().filter(|_| !settings.deterministic) {
+                    match mgr.reload_metadata().await {
+                        Ok(added) => println!("[shard {}/{}] picked up {} new seeds", shard.index, shard.total, added),
+                        Err(err) => eprintln!("Failed to reload corpus metadata: {:?}", err),
+                    }
+                }
             }
+            print_mutator_stats(mutators);
         }
     }
     
-    for handle in handles {
+    for handle in handles.drain(..) {
         handle.await.expect("fuzz loop task failed");
     }
     let elapsed = start.elapsed();
@@ -381,18 +1151,87 @@ async fn run_fuzz_loop(
     Ok(())
 }
 
-fn compute_reward(result: &JobResult) -> f64 {
-    if result.is_crash {
-        5.0
-    } else if result.new_coverage {
-        1.0
-    } else if result.is_timeout {
-        -1.0
-    } else {
-        0.0
+fn print_mutator_stats(mutators: &[Arc<ManagedMutator>]) {
+    for mutator in mutators {
+        let stats = mutator.stats_snapshot();
+        let success_rate = if stats.uses == 0 {
+            0.0
+        } else {
+            stats.uses.saturating_sub(stats.invalid_count) as f64 / stats.uses as f64 * 100.0
+        };
+        println!(
+            "[mut] {}: success rate: {:.2}%, reward: {:.2}, mean: {:.4}, recent: {:.4}, uses: {}, timeouts: {}, invalids: {}, noops: {:.2}%",
+            mutator.name(),
+            success_rate,
+            stats.total_reward,
+            stats.mean_reward,
+            stats.recent_mean_reward,
+            stats.uses,
+            stats.timeout_count,
+            stats.invalid_count,
+            stats.noop_rate() * 100.0
+        );
     }
 }
 
+/// Everything known about the campaign, printed when the loop gets paused
+async fn print_status_dump(
+    pool: &FuzzPool,
+    corpus_manager: &Mutex<CorpusManager>,
+    mutators: &[Arc<ManagedMutator>],
+    total_iterations: u64,
+) {
+    println!("[pause] status after {} iterations", total_iterations);
+    pool.print_pool_stats().await;
+    {
+        let mgr = corpus_manager.lock().await;
+        println!(
+            "[corpus] entries: {}, timeouts seen: {}, timeouts stored: {}",
+            mgr.len(),
+            mgr.timeouts_seen(),
+            mgr.stored_timeouts()
+        );
+    }
+    println!("[stages] {}", STAGE_TIMES.format());
+    print_mutator_stats(mutators);
+    println!("[pause] send SIGUSR1 again to resume");
+}
+
+fn compute_reward(outcome: &Outcome, rewards: &RewardConfig) -> f64 {
+    match outcome {
+        Outcome::Crash { .. } => rewards.crash,
+        Outcome::NewCoverage => rewards.new_coverage,
+        Outcome::Timeout => rewards.timeout,
+        Outcome::StackOverflow => rewards.stack_overflow,
+        Outcome::NoCoverage | Outcome::RuntimeError => rewards.neutral,
+    }
+}
+
+/// Whether `path` has the extension `seed_ext` (case-insensitive, leading dot optional).
+/// `*` matches every file.
+fn has_seed_ext(path: &std::path::Path, seed_ext: &str) -> bool {
+    if seed_ext == "*" {
+        return true;
+    }
+    let seed_ext = seed_ext.trim_start_matches('.');
+    path.extension()
+    .and_then(|ext| ext.to_str())
+    .is_some_and(|ext| ext.eq_ignore_ascii_case(seed_ext))
+}
+
+/// Edges a seed has to contribute during ingestion to start with a reward of 1.0
+const INGEST_EDGES_PER_REWARD: f64 = 10.0;
+
+/// Initial reward of an ingested seed, proportional to the number of its edges that no
+/// previously ingested seed covered. The seed's edges are added to `ingested_edges`.
+fn initial_seed_reward(edges: &EntryEdges, ingested_edges: &mut HashSet<(Option<String>, u32)>) -> f64 {
+    let unique_edges = edges
+    .iter()
+    .filter(|&(engine, edge)| ingested_edges.insert((engine.map(str::to_string), edge)))
+    .count();
+    unique_edges as f64 / INGEST_EDGES_PER_REWARD
+}
+
 async fn persist_crash(path: &std::path::Path, contents: &[u8]) -> Result<()> {
     if let Some(parent) = path.parent() {
         async_fs::create_dir_all(parent)
@@ -450,7 +1289,7 @@ async fn single_test(script_path: &str, profile: &str) {
                 .await
                 .expect("failed to receive job result")
                 .expect("job execution failed");
-                if job_result.new_coverage && job_result.status_code == 0 {
+                if job_result.outcome() == Outcome::NewCoverage {
                     corpus_manager
                     .lock()
                     .await
@@ -503,10 +1342,11 @@ async fn single_test(script_path: &str, profile: &str) {
     let start = Instant::now();
     const TOTAL_ITERATIONS: usize = 30000;
     
+    let mut rng = unseeded_rng();
     for i in 0..TOTAL_ITERATIONS {
         for mutator in &mutators {
             let mutated_ast = mutator
-            .mutate(mutated_ast.clone())
+            .mutate(mutated_ast.clone(), &mut rng)
             .expect("numeric mutation failed");
             let mutated_code = generate_js(mutated_ast).expect("code generation failed");
             
@@ -539,10 +1379,102 @@ async fn single_test(script_path: &str, profile: &str) {
     // }
 }
 
+fn bench_mutators(seed_path: &str) -> Result<()> {
+    const BENCH_ITERATIONS: usize = 1000;
+    let source = fs::read_to_string(seed_path)
+    .with_context(|| format!("failed to read bench seed {:?}", seed_path))?;
+    let seed = parse_js(source)?;
+    let mutators = get_ast_mutators();
+    let results = mutators::bench::bench_mutators(&mutators, &seed, BENCH_ITERATIONS);
+    for result in results {
+        println!(
+            "[bench] {}: {:.2} mutations/sec, mean node delta: {:.2}, failures: {}/{}, elapsed: {:?}",
+            result.name,
+            result.mutations_per_sec,
+            result.mean_node_delta,
+            result.failures,
+            result.iterations,
+            result.elapsed
+        );
+    }
+    Ok(())
+}
+
+/// Returns false if any mutator produced code that doesn't parse
+fn self_test(iterations: usize) -> Result<bool> {
+    let seeds = mutators::self_test::parse_self_test_seeds()?;
+    let mutators = get_ast_mutators();
+    let results = mutators::self_test::self_test_mutators(&mutators, &seeds, iterations);
+    let mut passed = true;
+    for result in results {
+        println!(
+            "[self-test] {}: invalid outputs: {}/{} ({:.2}%), mutation errors: {}, panics: {}",
+            result.name,
+            result.invalid_outputs,
+            result.runs,
+            result.invalid_rate() * 100.0,
+            result.mutation_errors,
+            result.panics
+        );
+        if let Some(failure) = result.first_failure {
+            passed = false;
+            println!("  first failing seed:\n{}", failure.seed);
+            println!("  output:\n{}", failure.output);
+            println!("  error: {}", failure.error);
+        }
+    }
+    Ok(passed)
+}
+
+/// Pretty-print the AST of `source`, either as Rust debug output or as JSON
+fn render_ast(source: String, json: bool) -> Result<String> {
+    let ast = parse_js(source)?;
+    if json {
+        serde_json::to_string_pretty(&ast).context("failed to serialize AST")
+    } else {
+        Ok(format!("{:#?}", ast))
+    }
+}
+
+/// What this build supports: profiles, mutators, execution backends and optional parsers
+fn render_capabilities() -> String {
+    let mut report = format!("jellyfuzz {}\n", env!("CARGO_PKG_VERSION"));
+    report.push_str("profiles:\n");
+    for &name in profiles::PROFILE_NAMES {
+        match profiles::get_profile(name) {
+            Some(profile) => report.push_str(&format!(
+                "  {} (backend: {}, natives: {})\n",
+                name,
+                profile.exec_backend().name(),
+                if profile.supports_natives() { "yes" } else { "no" }
+            )),
+            None => report.push_str(&format!("  {} (unavailable)\n", name)),
+        }
+    }
+    report.push_str("execution backends:\n");
+    for backend in ExecBackendKind::ALL {
+        report.push_str(&format!("  {}: {}\n", backend.name(), backend.description()));
+    }
+    let mutators = get_ast_mutators();
+    report.push_str(&format!("mutators ({}):\n", mutators.len()));
+    for mutator in &mutators {
+        let splicer = if mutator.is_splicer() { " (splicer)" } else { "" };
+        report.push_str(&format!("  {}{}\n", mutator.name(), splicer));
+    }
+    report.push_str(&format!(
+        "  {} (only with profiles supporting natives syntax)\n",
+        get_natives_mutator().name()
+    ));
+    report.push_str("natives syntax: yes\n");
+    report.push_str("sanitizer report parsing: yes\n");
+    report.push_str("source types: script, module, auto\n");
+    report
+}
+
 async fn mutator_test(script_path: &str, mutator: Arc<ManagedMutator>, profile: &str) {
     let source = fs::read_to_string(script_path).expect("failed to read test script");
     let ast = parse_js(source).expect("failed to parse test script");
-    let mutated_ast = mutator.mutate(ast).expect("mutation failed");
+    let mutated_ast = mutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
     let mutated_code = generate_js(mutated_ast).expect("code generation failed");
     fs::write("test_out.js", &mutated_code).expect("failed to write mutated code");
     
@@ -568,6 +1500,96 @@ async fn mutator_test(script_path: &str, mutator: Arc<ManagedMutator>, profile:
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn dump_ast_renders_small_program() {
+        let source = "let v0 = [1, 2]; v0.push(3);".to_string();
+        let debug = render_ast(source.clone(), false).expect("failed to render AST");
+        assert!(debug.contains("VarDecl"));
+        let json = render_ast(source, true).expect("failed to render AST as JSON");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("dump is not valid JSON");
+        assert!(value.is_object());
+        assert!(render_ast("let = ;".to_string(), false).is_err());
+    }
+
+    #[test]
+    fn capabilities_list_every_mutator() {
+        let report = render_capabilities();
+        for mutator in get_ast_mutators() {
+            assert!(
+                report.lines().any(|line| line.trim_start().starts_with(mutator.name())),
+                "{} missing from the report",
+                mutator.name()
+            );
+        }
+        assert!(report.contains("NativesMutator"));
+        assert!(report.contains("  v8 (backend: reprl"));
+        assert!(report.contains("  tempfile: "));
+    }
+
+    #[test]
+    fn ingested_seed_reward_follows_unique_edges() {
+        let mut ingested_edges = HashSet::new();
+        let first = EntryEdges::from((0..20).collect::<Vec<u32>>());
+        // half of its edges are already covered by the first seed
+        let overlapping = EntryEdges::from((10..30).collect::<Vec<u32>>());
+        let redundant = EntryEdges::from((5..25).collect::<Vec<u32>>());
+
+        assert_eq!(initial_seed_reward(&first, &mut ingested_edges), 2.0);
+        assert_eq!(initial_seed_reward(&overlapping, &mut ingested_edges), 1.0);
+        assert_eq!(initial_seed_reward(&redundant, &mut ingested_edges), 0.0);
+        assert_eq!(ingested_edges.len(), 30);
+
+        // the same ids in another engine are other edges
+        let other_engine = EntryEdges {
+            edge_hits: Vec::new(),
+            engine_edges: [("jsc".to_string(), (0..10).collect())].into(),
+        };
+        assert_eq!(initial_seed_reward(&other_engine, &mut ingested_edges), 1.0);
+    }
+
+    #[test]
+    fn only_seeds_with_the_extension_are_ingested() {
+        let dir = std::env::temp_dir().join(format!("jellyfuzz_seed_ext_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["a.js", "b.JS", "README", "LICENSE.txt", "c.mjs"] {
+            fs::write(dir.join(name), "let v0 = 1;").unwrap();
+        }
+        let matching = |seed_ext: &str| {
+            let mut names: Vec<String> = fs::read_dir(&dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| has_seed_ext(path, seed_ext))
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(matching("js"), ["a.js", "b.JS"]);
+        assert_eq!(matching(".mjs"), ["c.mjs"]);
+        assert_eq!(matching("*").len(), 5);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn compute_reward_honors_the_reward_config() {
+        let rewards = RewardConfig {
+            crash: 20.0,
+            new_coverage: 3.0,
+            timeout: 0.5,
+            stack_overflow: 0.25,
+            neutral: -0.1,
+            ..RewardConfig::default()
+        };
+        assert_eq!(compute_reward(&Outcome::Crash { signal: 11 }, &rewards), 20.0);
+        assert_eq!(compute_reward(&Outcome::NewCoverage, &rewards), 3.0);
+        assert_eq!(compute_reward(&Outcome::Timeout, &rewards), 0.5);
+        assert_eq!(compute_reward(&Outcome::StackOverflow, &rewards), 0.25);
+        assert_eq!(compute_reward(&Outcome::NoCoverage, &rewards), -0.1);
+        assert_eq!(compute_reward(&Outcome::RuntimeError, &rewards), -0.1);
+        assert_eq!(compute_reward(&Outcome::Crash { signal: 6 }, &RewardConfig::default()), 5.0);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn run_single_script_test() {
         let script_path = "corpus/crashes/seed_3137.js";
@@ -0,0 +1,325 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::build_random_literal;
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_types::JsObjectType;
+use crate::mutators::scope::{NameCollector, VarRenamer};
+
+/// ArityMutator
+/// Adds arguments to or drops trailing arguments from a call, e.g. `f0(1, 2)` becomes
+/// `f0(1, 2, -0, "a")` or `f0(1)`, so the callee sees more or fewer arguments than it
+/// declares.
+///
+/// When the callee is a function defined exactly once in the script, its parameters may be
+/// adjusted to match: new parameters are appended, and trailing ones are dropped as long
+/// as the body doesn't reference them. Calls to anything else, like built-ins, only have
+/// their arguments changed.
+pub struct ArityMutator;
+
+const MAX_ADDED_ARGS: usize = 3;
+
+/// How the arguments of the mutated call changed
+enum ArityChange {
+    Add(usize),
+    Drop(usize),
+}
+
+/// Counts calls and the definitions of every function name
+struct CallCollector {
+    calls: usize,
+    definitions: HashMap<Atom, usize>,
+}
+
+impl CallCollector {
+    fn add_definition(&mut self, name: &Atom) {
+        *self.definitions.entry(name.clone()).or_insert(0) += 1;
+    }
+}
+
+impl Visit for CallCollector {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        call.visit_children_with(self);
+        if matches!(call.callee, Callee::Expr(_)) {
+            self.calls += 1;
+        }
+    }
+
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        decl.visit_children_with(self);
+        self.add_definition(&decl.ident.sym);
+    }
+
+    fn visit_var_declarator(&mut self, decl: &VarDeclarator) {
+        decl.visit_children_with(self);
+        if let Pat::Ident(binding) = &decl.name {
+            // any other binding of the name also makes the definition ambiguous
+            self.add_definition(&binding.id.sym);
+        }
+    }
+}
+
+/// Collects every identifier referenced in a node
+struct IdentCollector {
+    idents: HashSet<Atom>,
+}
+
+impl Visit for IdentCollector {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.idents.insert(ident.sym.clone());
+    }
+}
+
+struct ArityCallVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    /// Callee name and change of the mutated call
+    mutated: Option<(Option<Atom>, ArityChange)>,
+}
+
+impl ArityCallVisitor {
+    fn change_args(&mut self, args: &mut Vec<ExprOrSpread>) -> ArityChange {
+        if args.is_empty() || self.rng.random_bool(0.5) {
+            let count = self.rng.random_range(1..=MAX_ADDED_ARGS);
+            for _ in 0..count {
+                let ty = JsObjectType::random_primitive_type(&mut self.rng);
+                args.push(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(build_random_literal(&mut self.rng, ty)),
+                });
+            }
+            ArityChange::Add(count)
+        } else {
+            let count = self.rng.random_range(1..=args.len());
+            args.truncate(args.len() - count);
+            ArityChange::Drop(count)
+        }
+    }
+}
+
+impl VisitMut for ArityCallVisitor {
+    fn visit_mut_call_expr(&mut self, call: &mut CallExpr) {
+        call.visit_mut_children_with(self);
+        let Callee::Expr(callee) = &call.callee else {
+            return;
+        };
+        let is_target = self.crt_idx == self.idx_to_mutate;
+        self.crt_idx += 1;
+        if !is_target {
+            return;
+        }
+        let callee = match &**callee {
+            Expr::Ident(ident) => Some(ident.sym.clone()),
+            _ => None,
+        };
+        let change = self.change_args(&mut call.args);
+        self.mutated = Some((callee, change));
+    }
+}
+
+fn build_param(name: &str) -> Pat {
+    Pat::Ident(BindingIdent {
+        id: Ident {
+            span: DUMMY_SP,
+            sym: Atom::from(name),
+            optional: false,
+            ctxt: SyntaxContext::empty(),
+        },
+        type_ann: None,
+    })
+}
+
+/// Adjusts the parameters of the definition of `name`
+struct ArityDefinitionVisitor {
+    name: Atom,
+    change: ArityChange,
+    renamer: VarRenamer,
+}
+
+impl ArityDefinitionVisitor {
+    /// Append or drop trailing parameters. Nothing changes with a rest parameter, which
+    /// absorbs any number of arguments anyway.
+    fn adjust(&mut self, params: &mut Vec<Pat>, referenced: &HashSet<Atom>) {
+        if params.iter().any(|param| matches!(param, Pat::Rest(_))) {
+            return;
+        }
+        match self.change {
+            ArityChange::Add(count) => {
+                for _ in 0..count {
+                    params.push(build_param(&self.renamer.next_var_name()));
+                }
+            }
+            ArityChange::Drop(count) => {
+                for _ in 0..count {
+                    match params.last() {
+                        Some(Pat::Ident(binding)) if !referenced.contains(&binding.id.sym) => {
+                            params.pop();
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Identifiers used by the body or by destructuring and default parameters
+    fn referenced_idents<N: VisitWith<IdentCollector>>(body: &N, params: &[Pat]) -> HashSet<Atom> {
+        let mut collector = IdentCollector { idents: HashSet::new() };
+        body.visit_with(&mut collector);
+        for param in params {
+            if !matches!(param, Pat::Ident(_)) {
+                param.visit_with(&mut collector);
+            }
+        }
+        collector.idents
+    }
+
+    fn adjust_function(&mut self, function: &mut Function) {
+        let mut params: Vec<Pat> = function.params.iter().map(|param| param.pat.clone()).collect();
+        let referenced = Self::referenced_idents(&function.body, &params);
+        self.adjust(&mut params, &referenced);
+        function.params = params
+            .into_iter()
+            .map(|pat| Param {
+                span: DUMMY_SP,
+                decorators: Vec::new(),
+                pat,
+            })
+            .collect();
+    }
+
+    fn adjust_arrow(&mut self, arrow: &mut ArrowExpr) {
+        let referenced = Self::referenced_idents(&*arrow.body, &arrow.params);
+        let mut params = std::mem::take(&mut arrow.params);
+        self.adjust(&mut params, &referenced);
+        arrow.params = params;
+    }
+}
+
+impl VisitMut for ArityDefinitionVisitor {
+    fn visit_mut_fn_decl(&mut self, decl: &mut FnDecl) {
+        decl.visit_mut_children_with(self);
+        if decl.ident.sym == self.name {
+            self.adjust_function(&mut decl.function);
+        }
+    }
+
+    fn visit_mut_var_declarator(&mut self, decl: &mut VarDeclarator) {
+        decl.visit_mut_children_with(self);
+        let Pat::Ident(binding) = &decl.name else {
+            return;
+        };
+        if binding.id.sym != self.name {
+            return;
+        }
+        match decl.init.as_deref_mut() {
+            Some(Expr::Fn(FnExpr { function, .. })) => self.adjust_function(function),
+            Some(Expr::Arrow(arrow)) => self.adjust_arrow(arrow),
+            _ => {}
+        }
+    }
+}
+
+impl ArityMutator {
+    /// Mutate a random call, and with `match_definition` the definition of its callee too
+    fn mutate_arity(&self, mut ast: Script, match_definition: bool, rng: &mut StdRng) -> Result<Script> {
+        let mut collector = CallCollector {
+            calls: 0,
+            definitions: HashMap::new(),
+        };
+        ast.visit_with(&mut collector);
+        if collector.calls == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..collector.calls);
+        let mut visitor = ArityCallVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            mutated: None,
+        };
+        ast.visit_mut_with(&mut visitor);
+
+        let Some((Some(callee), change)) = visitor.mutated else {
+            return Ok(ast);
+        };
+        if !match_definition || collector.definitions.get(&callee) != Some(&1) {
+            return Ok(ast);
+        }
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let mut definition_visitor = ArityDefinitionVisitor {
+            name: callee,
+            change,
+            renamer: VarRenamer::new(names.var_names),
+        };
+        ast.visit_mut_with(&mut definition_visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for ArityMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let match_definition = rng.random_bool(0.5);
+        self.mutate_arity(ast, match_definition, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use crate::parsing::parser::parse_js;
+    use crate::utils::rand_utils::unseeded_rng;
+
+    fn call_arg_count(stmt: &Stmt) -> usize {
+        let Stmt::Expr(ExprStmt { expr, .. }) = stmt else {
+            panic!("expected a call statement");
+        };
+        let Expr::Call(call) = &**expr else {
+            panic!("expected a call statement");
+        };
+        call.args.len()
+    }
+
+    #[test]
+    fn builtin_calls_only_change_at_the_call_site() {
+        let mutants =
+            assert_mutations_reparse_with("print(1, 2);", 16, |ast, rng| ArityMutator.mutate_arity(ast, true, rng));
+        for (code, reparsed) in mutants {
+            assert_eq!(reparsed.body.len(), 1, "unexpected statements in {}", code);
+            assert_ne!(call_arg_count(&reparsed.body[0]), 2, "arity unchanged in {}", code);
+        }
+    }
+
+    #[test]
+    fn resolved_definitions_match_the_call() {
+        let source = "function f0(v0, v1, v2) { return v0; } f0(1, 2, 3);";
+        let mutants = assert_mutations_reparse_with(source, 16, |ast, rng| ArityMutator.mutate_arity(ast, true, rng));
+        for (code, reparsed) in mutants {
+            let Stmt::Decl(Decl::Fn(decl)) = &reparsed.body[0] else {
+                panic!("expected a function in {}", code);
+            };
+            let args = call_arg_count(&reparsed.body[1]);
+            // v0 is referenced by the body so it's never dropped
+            assert_eq!(decl.function.params.len(), args.max(1), "parameters don't match in {}", code);
+
+            let ast = parse_js(source.to_string()).expect("failed to parse test script");
+            let mutated = ArityMutator.mutate_arity(ast, false, &mut unseeded_rng()).expect("mutation failed");
+            let Stmt::Decl(Decl::Fn(decl)) = &mutated.body[0] else {
+                panic!("expected a function");
+            };
+            assert_eq!(decl.function.params.len(), 3, "definition changed without matching");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use swc_ecma_visit::swc_ecma_ast::Script;
+
+use crate::mutators::ManagedMutator;
+use crate::mutators::scope::count_ast_nodes;
+use crate::utils::rand_utils::unseeded_rng;
+
+/// Timing results for a single mutator, gathered without touching the engine
+#[derive(Debug, Clone)]
+pub struct MutatorBenchResult {
+    pub name: String,
+    pub iterations: usize,
+    pub failures: usize,
+    pub elapsed: Duration,
+    pub mutations_per_sec: f64,
+    /// Mean difference in AST node count between the seed and the mutated AST
+    pub mean_node_delta: f64,
+}
+
+/// Run `mutate` (or `splice` with the seed as its own donor for splicers) on `seed`
+/// `iterations` times and measure throughput.
+pub fn bench_mutator(
+    mutator: &ManagedMutator,
+    seed: &Script,
+    iterations: usize,
+) -> MutatorBenchResult {
+    let seed_nodes = count_ast_nodes(seed) as f64;
+    let mut failures = 0;
+    let mut total_delta = 0.0;
+
+    let mut rng = unseeded_rng();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let mutated = if mutator.is_splicer() {
+            mutator.splice(seed, seed, &mut rng)
+        } else {
+            mutator.mutate(seed.clone(), &mut rng)
+        };
+        match mutated {
+            Ok(ast) => total_delta += count_ast_nodes(&ast) as f64 - seed_nodes,
+            Err(_) => failures += 1,
+        }
+    }
+    let elapsed = start.elapsed();
+
+    let succeeded = iterations - failures;
+    MutatorBenchResult {
+        name: mutator.name().to_string(),
+        iterations,
+        failures,
+        elapsed,
+        mutations_per_sec: if elapsed.as_secs_f64() > 0.0 {
+            iterations as f64 / elapsed.as_secs_f64()
+        } else {
+            0.0
+        },
+        mean_node_delta: if succeeded == 0 {
+            0.0
+        } else {
+            total_delta / succeeded as f64
+        },
+    }
+}
+
+pub fn bench_mutators(
+    mutators: &[Arc<ManagedMutator>],
+    seed: &Script,
+    iterations: usize,
+) -> Vec<MutatorBenchResult> {
+    mutators
+        .iter()
+        .map(|mutator| bench_mutator(mutator, seed, iterations))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::get_ast_mutators;
+    use crate::parsing::parser::parse_js;
+
+    const SEED: &str = r#"
+        function f0(v0, v1) { return v0 + v1 * 2; }
+        let v2 = [1, 2.5, true, "foo"];
+        const v3 = { a: 1, b: v2 };
+        for (let v4 = 0; v4 < 10; v4++) { v2[v4] = f0(v4, v3.a); }
+        if (v2.length > 3 && !false) { v3.b = null; }
+    "#;
+
+    #[test]
+    fn bench_runs_over_all_mutators() {
+        let seed = parse_js(SEED.to_string()).expect("failed to parse seed");
+        let mutators = get_ast_mutators();
+        let results = bench_mutators(&mutators, &seed, 10);
+        assert_eq!(results.len(), mutators.len());
+        for (result, mutator) in results.iter().zip(mutators.iter()) {
+            assert_eq!(result.name, mutator.name());
+            assert_eq!(result.iterations, 10);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::swc_ecma_ast::*;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+
+use crate::code_generators::ast_helpers::build_random_primitive_literal;
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_for_stmt_visitor, scoped_visit_mut_methods};
+
+/// Most arguments passed to an injected call, more than a function declares are fine
+const MAX_ARGS: usize = 4;
+
+/// CallInjectMutator
+/// Replaces a random expression with a call to a function in scope, e.g.
+/// `let v2 = v0 + 1;` becomes `let v2 = f0(v1, "foo") + 1;`.
+///
+/// Unlike the function references of `ExpressionSwapDup` the function actually runs, at
+/// a new call site and with arguments of new types and counts, so the engine has to
+/// handle polymorphic and mismatched calls to it. Arguments are variables in scope or
+/// random literals.
+pub struct CallInjectMutator;
+
+struct CallInjectVisitor {
+    rng: StdRng,
+    /// None when only counting the expressions
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+}
+
+impl ScopedAstVisitor for CallInjectVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+
+    fn on_fn_decl_ident(&mut self, ident: &Ident) {
+        self.scope_stack().add_function_to_hoist(ident.clone());
+    }
+}
+
+impl CallInjectVisitor {
+    fn build_arg(&mut self, value_pool: &[Ident]) -> Expr {
+        if let Some(ident) = value_pool.choose(&mut self.rng) {
+            if self.rng.random_bool(0.5) {
+                return Expr::Ident(ident.clone());
+            }
+        }
+        build_random_primitive_literal(&mut self.rng)
+    }
+
+    fn build_call(&mut self, callee: Ident) -> Expr {
+        let value_pool = self.scope_state.scopes.collect_idents();
+        let arg_count = self.rng.random_range(0..=MAX_ARGS);
+        let args = (0..arg_count)
+            .map(|_| ExprOrSpread {
+                spread: None,
+                expr: Box::new(self.build_arg(&value_pool)),
+            })
+            .collect();
+        Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            callee: Callee::Expr(Box::new(Expr::Ident(callee))),
+            args,
+            type_args: None,
+        })
+    }
+}
+
+impl VisitMut for CallInjectVisitor {
+    scoped_visit_mut_methods!();
+    scoped_for_stmt_visitor!(mut);
+
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        node.visit_mut_children_with(self);
+        if self.in_for_stmt().is_some() {
+            return;
+        }
+        let functions = self.scope_state.scopes.collect_functions();
+        if functions.is_empty() {
+            return;
+        }
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        if !is_target {
+            return;
+        }
+        let callee = functions.choose(&mut self.rng).expect("there are functions").clone();
+        *node = self.build_call(callee);
+    }
+
+    // a call can't be assigned to, `f()++` and `for (f() in o)` don't parse
+    fn visit_mut_update_expr(&mut self, node: &mut UpdateExpr) {
+        node.arg.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_pat(&mut self, node: &mut Pat) {
+        match node {
+            Pat::Expr(expr) => expr.visit_mut_children_with(self),
+            _ => node.visit_mut_children_with(self),
+        }
+    }
+
+    // `new f()()` would construct `f` instead of the result of the call
+    fn visit_mut_new_expr(&mut self, node: &mut NewExpr) {
+        node.callee.visit_mut_children_with(self);
+        node.args.visit_mut_with(self);
+    }
+}
+
+impl AstMutator for CallInjectMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut visitor = CallInjectVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = CallInjectVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    /// Declared function names and the names called directly
+    #[derive(Default)]
+    struct Calls {
+        declared: Vec<String>,
+        called: Vec<String>,
+    }
+
+    impl Visit for Calls {
+        fn visit_fn_decl(&mut self, node: &FnDecl) {
+            self.declared.push(node.ident.sym.to_string());
+            node.visit_children_with(self);
+        }
+
+        fn visit_call_expr(&mut self, node: &CallExpr) {
+            if let Callee::Expr(callee) = &node.callee {
+                if let Expr::Ident(ident) = &**callee {
+                    self.called.push(ident.sym.to_string());
+                }
+            }
+            node.visit_children_with(self);
+        }
+    }
+
+    #[test]
+    fn injected_calls_target_declared_functions() {
+        let source = "function f0(a0) { return a0 + 1; } let v0 = 1; let v1 = [v0, 2]; v0 = v1.length * 3;";
+        for (code, reparsed) in assert_mutations_reparse(&CallInjectMutator, source, 32) {
+
+            let mut calls = Calls::default();
+            reparsed.visit_with(&mut calls);
+            assert_eq!(calls.called.len(), 1, "expected one injected call in {}", code);
+            assert!(
+                calls.called.iter().all(|name| calls.declared.contains(name)),
+                "call to an undeclared function in {}",
+                code
+            );
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{build_random_literal, build_random_primitive_literal, str_lit_value};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_types::JsObjectType;
+
+/// ClassFieldMutator
+/// Adds fields to an existing class: public (`f0 = 1;`), private (`#p0 = "a";`),
+/// computed-key (`["x"] = 2;`), any of them possibly `static`, and a `static { ... }`
+/// initialization block assigning to them.
+///
+/// Private names are only referenced from the static block of the class declaring them, and
+/// only if they are static, since `this` is the class there.
+pub struct ClassFieldMutator;
+
+struct CountClasses {
+    count: usize,
+}
+
+impl Visit for CountClasses {
+    fn visit_class(&mut self, node: &Class) {
+        self.count += 1;
+        node.visit_children_with(self);
+    }
+}
+
+struct ClassFieldVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+}
+
+/// Names already used by the members of a class, public and private
+fn member_names(class: &Class) -> (HashSet<String>, HashSet<String>) {
+    let mut public = HashSet::new();
+    let mut private = HashSet::new();
+    let mut add_key = |key: &PropName| match key {
+        PropName::Ident(ident) => {
+            public.insert(ident.sym.to_string());
+        }
+        PropName::Str(str) => {
+            public.insert(str_lit_value(str));
+        }
+        _ => {}
+    };
+    for member in &class.body {
+        match member {
+            ClassMember::ClassProp(prop) => add_key(&prop.key),
+            ClassMember::Method(method) => add_key(&method.key),
+            ClassMember::PrivateProp(prop) => {
+                private.insert(prop.key.name.to_string());
+            }
+            ClassMember::PrivateMethod(method) => {
+                private.insert(method.key.name.to_string());
+            }
+            _ => {}
+        }
+    }
+    (public, private)
+}
+
+/// First `<prefix><n>` not in `used`, which is updated
+fn fresh_name(prefix: &str, used: &mut HashSet<String>) -> String {
+    let name = (0..)
+        .map(|n| format!("{}{}", prefix, n))
+        .find(|name| !used.contains(name))
+        .expect("names are unbounded");
+    used.insert(name.clone());
+    name
+}
+
+fn build_class_prop(key: PropName, value: Expr, is_static: bool) -> ClassMember {
+    ClassMember::ClassProp(ClassProp {
+        span: DUMMY_SP,
+        key,
+        value: Some(Box::new(value)),
+        type_ann: None,
+        is_static,
+        decorators: Vec::new(),
+        accessibility: None,
+        is_abstract: false,
+        is_optional: false,
+        is_override: false,
+        readonly: false,
+        declare: false,
+        definite: false,
+    })
+}
+
+fn build_private_name(name: &str) -> PrivateName {
+    PrivateName {
+        span: DUMMY_SP,
+        name: Atom::from(name),
+    }
+}
+
+fn build_private_prop(name: &str, value: Expr, is_static: bool) -> ClassMember {
+    ClassMember::PrivateProp(PrivateProp {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        key: build_private_name(name),
+        value: Some(Box::new(value)),
+        type_ann: None,
+        is_static,
+        decorators: Vec::new(),
+        accessibility: None,
+        is_optional: false,
+        is_override: false,
+        readonly: false,
+        definite: false,
+    })
+}
+
+/// `this.<prop> = <value>;`
+fn build_this_assignment(prop: MemberProp, value: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::This(ThisExpr { span: DUMMY_SP })),
+                prop,
+            })),
+            right: Box::new(value),
+        })),
+    })
+}
+
+impl ClassFieldVisitor {
+    /// Add fields and possibly a static block to `class`
+    fn add_fields(&mut self, class: &mut Class) {
+        let (mut public, mut private) = member_names(class);
+        // fields the static block can assign to
+        let mut static_props: Vec<MemberProp> = Vec::new();
+
+        for _ in 0..self.rng.random_range(1..=3) {
+            let is_static = self.rng.random_bool(0.3);
+            let value = build_random_primitive_literal(&mut self.rng);
+            let member = match self.rng.random_range(0..3) {
+                0 => {
+                    let name = fresh_name("f", &mut public);
+                    if is_static {
+                        static_props.push(MemberProp::Ident(IdentName::new(Atom::from(name.as_str()), DUMMY_SP)));
+                    }
+                    build_class_prop(PropName::Ident(IdentName::new(Atom::from(name), DUMMY_SP)), value, is_static)
+                }
+                1 => {
+                    let name = fresh_name("p", &mut private);
+                    if is_static {
+                        static_props.push(MemberProp::PrivateName(build_private_name(&name)));
+                    }
+                    build_private_prop(&name, value, is_static)
+                }
+                _ => {
+                    let ty = *[JsObjectType::JsString, JsObjectType::Number]
+                        .choose(&mut self.rng)
+                        .unwrap();
+                    let key = PropName::Computed(ComputedPropName {
+                        span: DUMMY_SP,
+                        expr: Box::new(build_random_literal(&mut self.rng, ty)),
+                    });
+                    build_class_prop(key, value, is_static)
+                }
+            };
+            class.body.push(member);
+        }
+
+        if self.rng.random_bool(0.5) {
+            let mut stmts = Vec::new();
+            for _ in 0..self.rng.random_range(1..=3) {
+                let prop = match static_props.choose(&mut self.rng) {
+                    Some(prop) if self.rng.random_bool(0.7) => prop.clone(),
+                    _ => {
+                        let name = fresh_name("f", &mut public);
+                        MemberProp::Ident(IdentName::new(Atom::from(name), DUMMY_SP))
+                    }
+                };
+                let value = build_random_primitive_literal(&mut self.rng);
+                stmts.push(build_this_assignment(prop, value));
+            }
+            class.body.push(ClassMember::StaticBlock(StaticBlock {
+                span: DUMMY_SP,
+                body: BlockStmt {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    stmts,
+                },
+            }));
+        }
+    }
+}
+
+impl VisitMut for ClassFieldVisitor {
+    fn visit_mut_class(&mut self, node: &mut Class) {
+        let is_target = self.crt_idx == self.idx_to_mutate;
+        self.crt_idx += 1;
+        node.visit_mut_children_with(self);
+        if is_target {
+            self.add_fields(node);
+        }
+    }
+}
+
+impl AstMutator for ClassFieldMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountClasses { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = ClassFieldVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    #[test]
+    fn private_fields_and_static_blocks_round_trip() {
+        let mut saw_private = false;
+        let mut saw_static_block = false;
+        let mutants =
+            assert_mutations_reparse(&ClassFieldMutator, "class C0 { #p0 = 1; f0() { return this.#p0; } }", 64);
+        for (code, reparsed) in mutants {
+            let Stmt::Decl(Decl::Class(class)) = &reparsed.body[0] else {
+                panic!("class was replaced in {}", code);
+            };
+            assert!(class.class.body.len() > 2, "no field added in {}", code);
+            saw_private |= code.contains("#p1");
+            saw_static_block |= class
+                .class
+                .body
+                .iter()
+                .any(|member| matches!(member, ClassMember::StaticBlock(_)));
+        }
+        assert!(saw_private, "no private field was ever added");
+        assert!(saw_static_block, "no static block was ever added");
+    }
+}
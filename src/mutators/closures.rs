@@ -0,0 +1,333 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_block, build_expr_stmt, build_ident, build_ident_expr_from_str, build_property_call,
+    build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods};
+
+/// ClosureCaptureMutator
+/// Makes a loop allocate a closure capturing its variable on every iteration and calls
+/// them all once the loop is done, e.g.
+/// `let v3 = []; for (let v0 = 0; v0 < 3; v0++) { v3.push(() => v0); ... } for (const v4 of v3) { v4(); }`.
+///
+/// With `let` and `const` every closure sees its own per-iteration binding, with `var`
+/// they all share one. Both go through the engine's context allocation and the copying
+/// of per-iteration bindings, an area with a history of bugs.
+pub struct ClosureCaptureMutator;
+
+#[derive(Clone, Copy)]
+enum ClosureKind {
+    Arrow,    // () => v0
+    Function, // function () { return v0; }
+}
+
+/// The variable declared by the head of a loop, if it is a single identifier
+fn loop_variable(stmt: &Stmt) -> Option<String> {
+    let decl = match stmt {
+        Stmt::For(ForStmt {
+            init: Some(VarDeclOrExpr::VarDecl(decl)),
+            ..
+        }) => decl,
+        Stmt::ForIn(ForInStmt {
+            left: ForHead::VarDecl(decl),
+            ..
+        })
+        | Stmt::ForOf(ForOfStmt {
+            left: ForHead::VarDecl(decl),
+            ..
+        }) => decl,
+        _ => return None,
+    };
+    match decl.decls.as_slice() {
+        [VarDeclarator {
+            name: Pat::Ident(binding),
+            ..
+        }] => Some(binding.id.sym.to_string()),
+        _ => None,
+    }
+}
+
+fn loop_body(stmt: &mut Stmt) -> Option<&mut Box<Stmt>> {
+    match stmt {
+        Stmt::For(ForStmt { body, .. }) | Stmt::ForIn(ForInStmt { body, .. }) | Stmt::ForOf(ForOfStmt { body, .. }) => {
+            Some(body)
+        }
+        _ => None,
+    }
+}
+
+/// `for (const <f> of <closures>) { <f>(); }`
+fn build_call_all(closures: &str, f: &str) -> Stmt {
+    let call = Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        callee: Callee::Expr(Box::new(build_ident_expr_from_str(f))),
+        args: Vec::new(),
+        type_args: None,
+    });
+    Stmt::ForOf(ForOfStmt {
+        span: DUMMY_SP,
+        is_await: false,
+        left: ForHead::VarDecl(Box::new(VarDecl {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent {
+                    id: build_ident(f),
+                    type_ann: None,
+                }),
+                init: None,
+                definite: false,
+            }],
+        })),
+        right: Box::new(build_ident_expr_from_str(closures)),
+        body: Box::new(Stmt::Block(build_block(vec![build_expr_stmt(call)]))),
+    })
+}
+
+struct ClosureCaptureVisitor {
+    rng: StdRng,
+    /// None when only counting the loops
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+    kind: ClosureKind,
+}
+
+impl ScopedAstVisitor for ClosureCaptureVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl ClosureCaptureVisitor {
+    /// What the closure returns: the loop variable, sometimes next to a value of the
+    /// enclosing scope so the closure captures more than one context
+    fn build_captured_value(&mut self, var: &str) -> Expr {
+        let idents = self.scope_state.scopes.collect_idents();
+        match idents.choose(&mut self.rng) {
+            Some(ident) if &*ident.sym != var && self.rng.random_bool(0.3) => Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: [build_ident_expr_from_str(var), Expr::Ident(ident.clone())]
+                    .into_iter()
+                    .map(|expr| {
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(expr),
+                        })
+                    })
+                    .collect(),
+            }),
+            _ => build_ident_expr_from_str(var),
+        }
+    }
+
+    fn build_closure(&mut self, var: &str) -> Expr {
+        let value = self.build_captured_value(var);
+        match self.kind {
+            ClosureKind::Arrow => Expr::Arrow(ArrowExpr {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                params: Vec::new(),
+                body: Box::new(BlockStmtOrExpr::Expr(Box::new(value))),
+                is_async: false,
+                is_generator: false,
+                type_params: None,
+                return_type: None,
+            }),
+            ClosureKind::Function => Expr::Fn(FnExpr {
+                ident: None,
+                function: Box::new(Function {
+                    params: Vec::new(),
+                    decorators: Vec::new(),
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    body: Some(build_block(vec![Stmt::Return(ReturnStmt {
+                        span: DUMMY_SP,
+                        arg: Some(Box::new(value)),
+                    })])),
+                    is_generator: false,
+                    is_async: false,
+                    type_params: None,
+                    return_type: None,
+                }),
+            }),
+        }
+    }
+
+    /// Push a closure capturing `var` at the start of the body of `stmt`, so `continue`
+    /// doesn't skip it. Returns the statements to put around the loop.
+    fn capture_in_loop(&mut self, stmt: &mut Stmt, var: &str) -> (Stmt, Stmt) {
+        let closures = self.renamer.next_var_name();
+        let f = self.renamer.next_var_name();
+        let push = build_expr_stmt(build_property_call(&closures, "push", vec![self.build_closure(var)]));
+        if let Some(body) = loop_body(stmt) {
+            match &mut **body {
+                Stmt::Block(block) => block.stmts.insert(0, push),
+                other => {
+                    let old = std::mem::replace(other, Stmt::Empty(EmptyStmt { span: DUMMY_SP }));
+                    *other = Stmt::Block(build_block(vec![push, old]));
+                }
+            }
+        }
+        let decl = Stmt::Decl(Decl::Var(Box::new(build_var_decl(
+            &closures,
+            Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: Vec::new(),
+            }),
+        ))));
+        (decl, build_call_all(&closures, &f))
+    }
+}
+
+impl VisitMut for ClosureCaptureVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut i = 0;
+        while i < stmts.len() {
+            stmts[i].visit_mut_with(self);
+            let Some(var) = loop_variable(&stmts[i]) else {
+                i += 1;
+                continue;
+            };
+            let is_target = self.idx_to_mutate == Some(self.crt_idx);
+            self.crt_idx += 1;
+            if is_target {
+                let (decl, call_all) = self.capture_in_loop(&mut stmts[i], &var);
+                stmts.insert(i + 1, call_all);
+                stmts.insert(i, decl);
+                i += 2;
+            }
+            i += 1;
+        }
+    }
+}
+
+impl ClosureCaptureMutator {
+    fn mutate_with_kind(&self, mut ast: Script, kind: ClosureKind, rng: &mut StdRng) -> Result<Script> {
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let mut visitor = ClosureCaptureVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+            kind,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = ClosureCaptureVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for ClosureCaptureMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let kind = if rng.random_bool(0.5) {
+            ClosureKind::Arrow
+        } else {
+            ClosureKind::Function
+        };
+        self.mutate_with_kind(ast, kind, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use swc_ecma_visit::Visit;
+
+    /// Identifiers read by the closures pushed into an array
+    #[derive(Default)]
+    struct PushedClosures {
+        captured: Vec<String>,
+        count: usize,
+    }
+
+    impl Visit for PushedClosures {
+        fn visit_call_expr(&mut self, node: &CallExpr) {
+            node.visit_children_with(self);
+            let Callee::Expr(callee) = &node.callee else {
+                return;
+            };
+            let Expr::Member(MemberExpr {
+                prop: MemberProp::Ident(prop),
+                ..
+            }) = &**callee
+            else {
+                return;
+            };
+            if &*prop.sym != "push" {
+                return;
+            }
+            for arg in &node.args {
+                if matches!(&*arg.expr, Expr::Arrow(_) | Expr::Fn(_)) {
+                    self.count += 1;
+                    let mut idents = IdentNames::default();
+                    arg.expr.visit_with(&mut idents);
+                    self.captured.extend(idents.0);
+                }
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct IdentNames(Vec<String>);
+
+    impl Visit for IdentNames {
+        fn visit_ident(&mut self, node: &Ident) {
+            self.0.push(node.sym.to_string());
+        }
+    }
+
+    #[test]
+    fn closures_capture_the_loop_variable() {
+        let source = "let v0 = 1; for (let v1 = 0; v1 < 3; v1++) v0++; for (var v2 in [1, 2]) { continue; }";
+        for kind in [ClosureKind::Arrow, ClosureKind::Function] {
+            let mutants = assert_mutations_reparse_with(source, 16, |ast, rng| {
+                ClosureCaptureMutator.mutate_with_kind(ast, kind, rng)
+            });
+            for (code, reparsed) in mutants {
+                assert_eq!(reparsed.body.len(), 5, "expected the loop to be wrapped in {}", code);
+
+                let mut closures = PushedClosures::default();
+                reparsed.visit_with(&mut closures);
+                assert_eq!(closures.count, 1, "expected one closure in {}", code);
+                assert!(
+                    closures.captured.iter().any(|name| name == "v1" || name == "v2"),
+                    "closure doesn't capture the loop variable in {}",
+                    code
+                );
+                assert!(code.contains("v3 = []"), "no closure array in {}", code);
+                assert!(code.contains("for (const v4 of v3)"), "closures never called in {}", code);
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_assign, build_expr_stmt, build_ident_expr_from_str, build_member_expr, build_num,
+    build_random_primitive_literal,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods, visit_mut_stmts_inserting_after};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// CoercionMutator
+/// Overrides how an object from the current scope converts to a primitive, then forces the
+/// conversion, e.g.
+/// `v0.valueOf = function () { v1.length = 0; return {}; }; v0 + 1;` or
+/// `v0[Symbol.toPrimitive] = function (hint) { delete v1[0]; return "a"; }; v0 < v1;`
+///
+/// The conversion returns values of unexpected types and may change another object on the
+/// way, invalidating what the engine assumed about it before the implicit conversion.
+pub struct CoercionMutator;
+
+/// Operators forcing their operands to primitives
+const COERCING_OPS: &[BinaryOp] = &[
+    BinaryOp::Add,
+    BinaryOp::Sub,
+    BinaryOp::Mul,
+    BinaryOp::Lt,
+    BinaryOp::GtEq,
+    BinaryOp::EqEq,
+    BinaryOp::BitOr,
+];
+
+struct CoercionVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    /// Whether the catalog knows `Symbol.toPrimitive`
+    has_to_primitive: bool,
+}
+
+impl ScopedAstVisitor for CoercionVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+fn ident_prop(name: &str) -> MemberProp {
+    MemberProp::Ident(IdentName::new(Atom::from(name), DUMMY_SP))
+}
+
+fn computed_prop(expr: Expr) -> MemberProp {
+    MemberProp::Computed(ComputedPropName {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
+
+/// `function (<params>) { <stmts> }`
+fn build_function(params: Vec<&str>, stmts: Vec<Stmt>) -> Expr {
+    Expr::Fn(FnExpr {
+        ident: None,
+        function: Box::new(Function {
+            params: params
+                .into_iter()
+                .map(|name| Param {
+                    span: DUMMY_SP,
+                    decorators: Vec::new(),
+                    pat: Pat::Ident(BindingIdent {
+                        id: Ident {
+                            span: DUMMY_SP,
+                            sym: Atom::from(name),
+                            optional: false,
+                            ctxt: SyntaxContext::empty(),
+                        },
+                        type_ann: None,
+                    }),
+                })
+                .collect(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts,
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    })
+}
+
+impl CoercionVisitor {
+    /// A change to `victim` made while the conversion runs
+    fn build_side_effect(&mut self, victim: &str) -> Stmt {
+        let victim = build_ident_expr_from_str(victim);
+        let expr = match self.rng.random_range(0..3) {
+            0 => Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(build_member_expr(victim, ident_prop("length")))),
+                right: Box::new(build_num(0.0)),
+            }),
+            1 => {
+                let value = build_random_primitive_literal(&mut self.rng);
+                Expr::Assign(AssignExpr {
+                    span: DUMMY_SP,
+                    op: AssignOp::Assign,
+                    left: AssignTarget::Simple(SimpleAssignTarget::Member(build_member_expr(
+                        victim,
+                        computed_prop(build_num(0.0)),
+                    ))),
+                    right: Box::new(value),
+                })
+            }
+            _ => Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Delete,
+                arg: Box::new(Expr::Member(build_member_expr(victim, computed_prop(build_num(0.0))))),
+            }),
+        };
+        build_expr_stmt(expr)
+    }
+
+    /// What the conversion returns: usually a primitive of a surprising type, sometimes an
+    /// object, which makes the conversion fall back to the other method or throw
+    fn build_return_value(&mut self, values: &[Ident]) -> Expr {
+        match self.rng.random_range(0..5) {
+            0 => Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: Vec::new(),
+            }),
+            1 => match values.choose(&mut self.rng) {
+                Some(ident) => Expr::Ident(ident.clone()),
+                None => build_random_primitive_literal(&mut self.rng),
+            },
+            _ => build_random_primitive_literal(&mut self.rng),
+        }
+    }
+
+    /// `<target>.valueOf = function () { ... };`, or `toString` / `Symbol.toPrimitive`
+    fn build_override(&mut self, target: &str, victim: &str, values: &[Ident]) -> Stmt {
+        let mut body = Vec::new();
+        if self.rng.random_bool(0.8) {
+            body.push(self.build_side_effect(victim));
+        }
+        let value = self.build_return_value(values);
+        body.push(Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(value)),
+        }));
+
+        let choice = self.rng.random_range(0..if self.has_to_primitive { 3 } else { 2 });
+        let (prop, params) = match choice {
+            0 => (ident_prop("valueOf"), Vec::new()),
+            1 => (ident_prop("toString"), Vec::new()),
+            _ => {
+                let to_primitive = Expr::Member(MemberExpr {
+                    span: DUMMY_SP,
+                    obj: Box::new(build_ident_expr_from_str("Symbol")),
+                    prop: ident_prop("toPrimitive"),
+                });
+                (computed_prop(to_primitive), vec!["hint"])
+            }
+        };
+        let member = build_member_expr(build_ident_expr_from_str(target), prop);
+        build_expr_stmt(build_assign(AssignOp::Assign, member, build_function(params, body)))
+    }
+
+    /// `<target> <op> <operand>;` or `+<target>;`
+    fn build_coercion(&mut self, target: &str, values: &[Ident]) -> Stmt {
+        let target_expr = build_ident_expr_from_str(target);
+        if self.rng.random_bool(0.2) {
+            return build_expr_stmt(Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Plus,
+                arg: Box::new(target_expr),
+            }));
+        }
+        let op = *COERCING_OPS.choose(&mut self.rng).expect("there are coercing operators");
+        let operand = match values.choose(&mut self.rng) {
+            Some(ident) if self.rng.random_bool(0.5) => Expr::Ident(ident.clone()),
+            _ => build_random_primitive_literal(&mut self.rng),
+        };
+        let (left, right) = if self.rng.random_bool(0.5) {
+            (target_expr, operand)
+        } else {
+            (operand, target_expr)
+        };
+        build_expr_stmt(Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }))
+    }
+
+    fn build_coercion_stmts(&mut self) -> Vec<Stmt> {
+        let values = self.scope_state.scopes.collect_idents();
+        let Some(target) = values.choose(&mut self.rng).map(|ident| ident.sym.to_string()) else {
+            return Vec::new();
+        };
+        let victim = values
+            .choose(&mut self.rng)
+            .map(|ident| ident.sym.to_string())
+            .unwrap_or_else(|| target.clone());
+        vec![
+            self.build_override(&target, &victim, &values),
+            self.build_coercion(&target, &values),
+        ]
+    }
+}
+
+impl VisitMut for CoercionVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            if is_target { visitor.build_coercion_stmts() } else { Vec::new() }
+        });
+    }
+}
+
+impl AstMutator for CoercionMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let has_to_primitive = get_global_object("Symbol")
+            .is_some_and(|symbol| symbol.static_properties().iter().any(|p| p.sym() == "toPrimitive"));
+
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = CoercionVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            has_to_primitive,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    #[test]
+    fn coercion_round_trips() {
+        for (code, reparsed) in assert_mutations_reparse(&CoercionMutator, "let v0 = {}; let v1 = [1, 2];", 32) {
+            // v0 is in scope after either statement
+            assert_eq!(reparsed.body.len(), 4, "no coercion inserted in {}", code);
+            assert!(
+                code.contains(".valueOf =") || code.contains(".toString =") || code.contains("[Symbol.toPrimitive] ="),
+                "no conversion override in {}",
+                code
+            );
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_ctor_expr, build_expr_stmt, build_ident_expr_from_str, build_property_call,
+    build_random_primitive_literal, build_var_decl, is_valid_identifier_name,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::scope::{
+    NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods, visit_mut_stmts_inserting_after,
+};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// CollectionMutator
+/// Creates a `Map` or `Set` from values in scope and operates on it, e.g.
+/// `let v3 = new Map([[v0, 1], [NaN, "a"]]); v3.set(-0, v1); v3.has(0); v3.delete(v0);`
+///
+/// Keys are biased towards objects, `NaN` and `-0`/`0` to probe SameValueZero key
+/// comparison. Method names come from the global object catalog.
+pub struct CollectionMutator;
+
+const COLLECTIONS: &[&str] = &["Map", "Set"];
+
+/// Callback taking methods, left out since the arguments are built as plain values
+const SKIPPED_METHODS: &[&str] = &["forEach", "groupBy"];
+
+/// Set methods taking another set-like argument
+const SET_ALGEBRA_METHODS: &[&str] = &[
+    "union",
+    "intersection",
+    "difference",
+    "symmetricDifference",
+    "isSubsetOf",
+    "isSupersetOf",
+    "isDisjointFrom",
+];
+
+/// A collection from the catalog and its callable instance methods with their arity
+struct Collection {
+    sym: String,
+    methods: Vec<(String, usize)>,
+}
+
+/// Look up a collection in the catalog. None if it isn't there or has no usable method.
+fn lookup_collection(sym: &str) -> Option<Collection> {
+    let object = get_global_object(sym)?;
+    let methods: Vec<(String, usize)> = object
+        .instance_methods()
+        .into_iter()
+        .filter(|method| is_valid_identifier_name(method.sym()))
+        .filter(|method| !SKIPPED_METHODS.contains(&method.sym()))
+        .map(|method| {
+            let arity = method
+                .signatures()
+                .iter()
+                .map(|sig| sig.types().len())
+                .max()
+                .unwrap_or(0);
+            (method.sym().to_string(), arity)
+        })
+        .collect();
+    if methods.is_empty() {
+        return None;
+    }
+    Some(Collection {
+        sym: object.sym().to_string(),
+        methods,
+    })
+}
+
+struct CollectionMutatorVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+    collections: Vec<Collection>,
+}
+
+impl ScopedAstVisitor for CollectionMutatorVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+fn build_array(elems: Vec<Expr>) -> Expr {
+    Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: elems
+            .into_iter()
+            .map(|expr| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect(),
+    })
+}
+
+/// `0` or `-0`
+fn build_zero(negative: bool) -> Expr {
+    let zero = Expr::Lit(Lit::Num(Number {
+        span: DUMMY_SP,
+        value: 0.0,
+        raw: None,
+    }));
+    if !negative {
+        return zero;
+    }
+    Expr::Unary(UnaryExpr {
+        span: DUMMY_SP,
+        op: UnaryOp::Minus,
+        arg: Box::new(zero),
+    })
+}
+
+impl CollectionMutatorVisitor {
+    /// A key or value: a value from scope, a primitive, or one of the keys SameValueZero
+    /// treats specially
+    fn build_key(&mut self, scope_values: &[Ident]) -> Expr {
+        match self.rng.random_range(0..6) {
+            0 | 1 => match scope_values.choose(&mut self.rng) {
+                Some(ident) => Expr::Ident(ident.clone()),
+                None => build_random_primitive_literal(&mut self.rng),
+            },
+            2 => build_ident_expr_from_str("NaN"),
+            3 => build_zero(self.rng.random_bool(0.5)),
+            4 => Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: Vec::new(),
+            }),
+            _ => build_random_primitive_literal(&mut self.rng),
+        }
+    }
+
+    /// `new Map([[k, v], ...])` or `new Set([k, ...])`
+    fn build_collection(&mut self, sym: &str, keys: &[Expr], scope_values: &[Ident]) -> Expr {
+        let elems = keys
+            .iter()
+            .map(|key| {
+                if sym == "Map" {
+                    let value = self.build_key(scope_values);
+                    build_array(vec![key.clone(), value])
+                } else {
+                    key.clone()
+                }
+            })
+            .collect();
+        build_ctor_expr(sym, vec![build_array(elems)])
+    }
+
+    /// `<name>.<method>(...)` with arguments drawn from the keys the collection was
+    /// created with, so lookups actually hit
+    fn build_operation(&mut self, name: &str, collection: usize, keys: &[Expr], scope_values: &[Ident]) -> Stmt {
+        let (method, arity) = self.collections[collection]
+            .methods
+            .choose(&mut self.rng)
+            .cloned()
+            .expect("collections without methods are not looked up");
+        let args = (0..arity)
+            .map(|_| {
+                if SET_ALGEBRA_METHODS.contains(&method.as_str()) {
+                    if self.rng.random_bool(0.3) {
+                        return build_ident_expr_from_str(name);
+                    }
+                    let count = self.rng.random_range(0..=keys.len());
+                    let other: Vec<Expr> = keys.choose_multiple(&mut self.rng, count).cloned().collect();
+                    return build_ctor_expr("Set", vec![build_array(other)]);
+                }
+                match keys.choose(&mut self.rng) {
+                    Some(key) if self.rng.random_bool(0.7) => key.clone(),
+                    _ => self.build_key(scope_values),
+                }
+            })
+            .collect();
+        build_expr_stmt(build_property_call(name, &method, args))
+    }
+
+    fn build_collection_stmts(&mut self) -> Vec<Stmt> {
+        let scope_values = self.scope_state.scopes.collect_idents();
+        let collection = self.rng.random_range(0..self.collections.len());
+        let sym = self.collections[collection].sym.clone();
+        let name = self.renamer.next_var_name();
+
+        let key_count = self.rng.random_range(1..=4);
+        let mut keys: Vec<Expr> = (0..key_count).map(|_| self.build_key(&scope_values)).collect();
+        // look up a zero with the opposite sign of the one stored
+        if self.rng.random_bool(0.3) {
+            keys.push(build_zero(self.rng.random_bool(0.5)));
+        }
+
+        let init = self.build_collection(&sym, &keys, &scope_values);
+        let mut stmts = vec![Stmt::Decl(Decl::Var(Box::new(build_var_decl(&name, init))))];
+        for _ in 0..self.rng.random_range(1..=3) {
+            let operation = self.build_operation(&name, collection, &keys, &scope_values);
+            stmts.push(operation);
+        }
+        stmts
+    }
+}
+
+impl VisitMut for CollectionMutatorVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            if is_target { visitor.build_collection_stmts() } else { Vec::new() }
+        });
+    }
+}
+
+impl CollectionMutator {
+    fn mutate_with_collections(&self, mut ast: Script, collections: &[&str], rng: &mut StdRng) -> Result<Script> {
+        let collections: Vec<Collection> = collections
+            .iter()
+            .filter_map(|sym| lookup_collection(sym))
+            .collect();
+        if collections.is_empty() {
+            return Ok(ast);
+        }
+
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = CollectionMutatorVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+            collections,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for CollectionMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        self.mutate_with_collections(ast, COLLECTIONS, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use crate::parsing::parser::parse_js;
+    use crate::utils::rand_utils::unseeded_rng;
+
+    #[test]
+    fn collection_round_trips() {
+        for (code, reparsed) in assert_mutations_reparse(&CollectionMutator, "let v0 = {};", 16) {
+            assert!(
+                code.contains("new Map(") || code.contains("new Set("),
+                "no collection created in {}",
+                code
+            );
+
+            assert!(reparsed.body.len() >= 3, "no operation on the collection in {}", code);
+            let Stmt::Expr(ExprStmt { expr, .. }) = &reparsed.body[2] else {
+                panic!("expected a method call in {}", code);
+            };
+            let Expr::Call(CallExpr { callee: Callee::Expr(callee), .. }) = &**expr else {
+                panic!("expected a method call in {}", code);
+            };
+            let Expr::Member(MemberExpr { prop: MemberProp::Ident(method), .. }) = &**callee else {
+                panic!("expected a method call in {}", code);
+            };
+            let known = COLLECTIONS
+                .iter()
+                .filter_map(|sym| get_global_object(sym))
+                .any(|object| object.methods().iter().any(|m| m.sym() == &*method.sym));
+            assert!(known, "{} is not a catalog method", method.sym);
+        }
+    }
+
+    #[test]
+    fn unknown_collection_is_a_noop() {
+        let source = "let v0 = {};";
+        let ast = parse_js(source.to_string()).expect("failed to parse test script");
+        let mutated = CollectionMutator
+            .mutate_with_collections(ast, &["NoSuchCollection"], &mut unseeded_rng())
+            .expect("mutation failed");
+        assert_eq!(mutated.body.len(), 1);
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::DUMMY_SP;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::swc_ecma_ast::*;
+
+use crate::code_generators::ast_helpers::{build_ident_expr_from_str, build_random_primitive_literal, str_lit_value};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods};
+
+/// ComputedKeyMutator
+/// Turns the static key of an object literal property into a computed one, e.g.
+/// `{a: 1}` becomes `{["a"]: 1}`, `{[v0]: 1}` with a value from the current scope, or
+/// `{[(v0[0] = 2, "a")]: 1}` with a side effect running while the object is built.
+///
+/// Computed keys go through ToPropertyKey in the middle of the object construction,
+/// exercising other evaluation order paths than static keys. `__proto__` keys are left
+/// alone, computing them would make an own property instead of setting the prototype.
+pub struct ComputedKeyMutator;
+
+#[derive(Clone, Copy)]
+enum KeyKind {
+    Literal,    // {["a"]: 1}
+    ScopeValue, // {[v0]: 1}
+    SideEffect, // {[(v0[0] = 2, "a")]: 1}
+}
+
+/// The static key of a property, if it has one that can be computed
+fn static_key(prop: &Prop) -> Option<&PropName> {
+    let key = match prop {
+        Prop::KeyValue(KeyValueProp { key, .. })
+        | Prop::Method(MethodProp { key, .. })
+        | Prop::Getter(GetterProp { key, .. })
+        | Prop::Setter(SetterProp { key, .. }) => key,
+        _ => return None,
+    };
+    let is_proto = match key {
+        PropName::Ident(ident) => &*ident.sym == "__proto__",
+        PropName::Str(s) => str_lit_value(s) == "__proto__",
+        PropName::Computed(_) => return None,
+        _ => false,
+    };
+    (!is_proto).then_some(key)
+}
+
+/// The key as an expression evaluating to the same property key
+fn key_expr(key: &PropName) -> Expr {
+    match key {
+        PropName::Ident(ident) => Expr::Lit(Lit::Str(Str {
+            span: DUMMY_SP,
+            value: ident.sym.clone().into(),
+            raw: None,
+        })),
+        PropName::Str(s) => Expr::Lit(Lit::Str(s.clone())),
+        PropName::Num(n) => Expr::Lit(Lit::Num(n.clone())),
+        PropName::BigInt(b) => Expr::Lit(Lit::BigInt(b.clone())),
+        PropName::Computed(computed) => *computed.expr.clone(),
+    }
+}
+
+fn build_index_member(obj: &str) -> MemberExpr {
+    MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(build_ident_expr_from_str(obj)),
+        prop: MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Lit(Lit::Num(Number {
+                span: DUMMY_SP,
+                value: 0.0,
+                raw: None,
+            }))),
+        }),
+    }
+}
+
+struct ComputedKeyVisitor {
+    rng: StdRng,
+    /// None when only counting the properties with a static key
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    kind: KeyKind,
+}
+
+impl ScopedAstVisitor for ComputedKeyVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl ComputedKeyVisitor {
+    /// `<obj>[0] = <literal>` or `delete <obj>[0]`
+    fn build_side_effect(&mut self, obj: &str) -> Expr {
+        if self.rng.random_bool(0.5) {
+            let value = build_random_primitive_literal(&mut self.rng);
+            Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(build_index_member(obj))),
+                right: Box::new(value),
+            })
+        } else {
+            Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Delete,
+                arg: Box::new(Expr::Member(build_index_member(obj))),
+            })
+        }
+    }
+
+    fn build_computed_key(&mut self, key: &PropName) -> PropName {
+        let idents = self.scope_state.scopes.collect_idents();
+        let scope_value = idents.choose(&mut self.rng).map(|ident| ident.sym.to_string());
+        let expr = match (self.kind, scope_value) {
+            (KeyKind::ScopeValue, Some(name)) => build_ident_expr_from_str(&name),
+            // parenthesized, a computed key can't be a bare sequence
+            (KeyKind::SideEffect, Some(name)) => Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Seq(SeqExpr {
+                    span: DUMMY_SP,
+                    exprs: vec![Box::new(self.build_side_effect(&name)), Box::new(key_expr(key))],
+                })),
+            }),
+            // nothing in scope to use
+            _ => key_expr(key),
+        };
+        PropName::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+        })
+    }
+}
+
+impl VisitMut for ComputedKeyVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_prop(&mut self, node: &mut Prop) {
+        node.visit_mut_children_with(self);
+        let Some(key) = static_key(node) else {
+            return;
+        };
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        if !is_target {
+            return;
+        }
+        let computed = self.build_computed_key(key);
+        match node {
+            Prop::KeyValue(KeyValueProp { key, .. })
+            | Prop::Method(MethodProp { key, .. })
+            | Prop::Getter(GetterProp { key, .. })
+            | Prop::Setter(SetterProp { key, .. }) => *key = computed,
+            _ => {}
+        }
+    }
+}
+
+impl ComputedKeyMutator {
+    fn mutate_with_kind(&self, mut ast: Script, kind: KeyKind, rng: &mut StdRng) -> Result<Script> {
+        let mut visitor = ComputedKeyVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            kind,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = ComputedKeyVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for ComputedKeyMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let kind = match rng.random_range(0..3) {
+            0 => KeyKind::Literal,
+            1 => KeyKind::ScopeValue,
+            _ => KeyKind::SideEffect,
+        };
+        self.mutate_with_kind(ast, kind, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    #[derive(Default)]
+    struct ComputedKeys {
+        keys: Vec<Expr>,
+    }
+
+    impl Visit for ComputedKeys {
+        fn visit_computed_prop_name(&mut self, node: &ComputedPropName) {
+            self.keys.push(*node.expr.clone());
+        }
+    }
+
+    fn mutate_keys(kind: KeyKind) -> Vec<Expr> {
+        let source = "let v0 = [1]; let v1 = { a: 1, \"b\": 2, 3: 4, c() {}, __proto__: null };";
+        let (code, reparsed) =
+            assert_mutations_reparse_with(source, 1, |ast, rng| ComputedKeyMutator.mutate_with_kind(ast, kind, rng))
+                .remove(0);
+        assert!(code.contains("__proto__: null"), "__proto__ key computed in {}", code);
+        let mut keys = ComputedKeys::default();
+        reparsed.visit_with(&mut keys);
+        assert_eq!(keys.keys.len(), 1, "expected one computed key in {}", code);
+        keys.keys
+    }
+
+    #[test]
+    fn computed_keys_round_trip() {
+        for _ in 0..16 {
+            let keys = mutate_keys(KeyKind::Literal);
+            assert!(matches!(&keys[0], Expr::Lit(Lit::Str(_) | Lit::Num(_))), "not a literal key: {:?}", keys[0]);
+
+            // v1 isn't declared yet in its own initializer
+            let keys = mutate_keys(KeyKind::ScopeValue);
+            assert!(matches!(&keys[0], Expr::Ident(ident) if &*ident.sym == "v0"), "not an identifier key: {:?}", keys[0]);
+
+            let keys = mutate_keys(KeyKind::SideEffect);
+            let key = match &keys[0] {
+                Expr::Paren(ParenExpr { expr, .. }) => &**expr,
+                key => key,
+            };
+            assert!(matches!(key, Expr::Seq(_)), "no side effect in key: {:?}", key);
+        }
+    }
+}
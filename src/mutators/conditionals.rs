@@ -0,0 +1,237 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::rngs::StdRng;
+use swc_common::DUMMY_SP;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{build_assign, build_block, build_expr_stmt};
+use crate::mutators::AstMutator;
+
+/// ConditionalFormMutator
+/// Switches between the statement and expression forms of a conditional:
+/// `c ? a() : b();` <-> `if (c) { a(); } else { b(); }` and
+/// `v0 = c ? 1 : 2;` <-> `if (c) { v0 = 1; } else { v0 = 2; }`.
+///
+/// Only statements are rewritten, so the conditional's value is never needed elsewhere.
+pub struct ConditionalFormMutator;
+
+struct CountConvertible {
+    count: usize,
+}
+
+impl Visit for CountConvertible {
+    fn visit_stmt(&mut self, node: &Stmt) {
+        if convert(node).is_some() {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+struct ConditionalFormVisitor {
+    idx_to_mutate: usize,
+    crt_idx: usize,
+}
+
+fn paren(expr: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Paren(ParenExpr { span: DUMMY_SP, expr }))
+}
+
+/// Whether an expression statement starting with `expr` would be parsed as something else
+/// (a block, a function or a class declaration)
+fn starts_ambiguously(expr: &Expr) -> bool {
+    match expr {
+        Expr::Object(_) | Expr::Fn(_) | Expr::Class(_) => true,
+        Expr::Member(member) => starts_ambiguously(&member.obj),
+        Expr::Call(CallExpr { callee: Callee::Expr(callee), .. }) => starts_ambiguously(callee),
+        Expr::Bin(bin) => starts_ambiguously(&bin.left),
+        Expr::Seq(seq) => seq.exprs.first().is_some_and(|first| starts_ambiguously(first)),
+        Expr::Cond(cond) => starts_ambiguously(&cond.test),
+        Expr::Update(update) => !update.prefix && starts_ambiguously(&update.arg),
+        Expr::TaggedTpl(tpl) => starts_ambiguously(&tpl.tag),
+        _ => false,
+    }
+}
+
+/// Operand of a conditional expression, parenthesized if it binds looser than one
+fn cond_operand(expr: Box<Expr>) -> Box<Expr> {
+    match *expr {
+        Expr::Seq(_) | Expr::Assign(_) | Expr::Cond(_) | Expr::Arrow(_) | Expr::Yield(_) => paren(expr),
+        _ => expr,
+    }
+}
+
+/// Expression statement, parenthesized if it would otherwise parse as something else
+fn unambiguous_expr_stmt(expr: Box<Expr>) -> Stmt {
+    let expr = if starts_ambiguously(&expr) { paren(expr) } else { expr };
+    build_expr_stmt(*expr)
+}
+
+fn build_if(test: Box<Expr>, cons: Stmt, alt: Stmt) -> Stmt {
+    Stmt::If(IfStmt {
+        span: DUMMY_SP,
+        test,
+        cons: Box::new(Stmt::Block(build_block(vec![cons]))),
+        alt: Some(Box::new(Stmt::Block(build_block(vec![alt])))),
+    })
+}
+
+fn build_cond(test: Box<Expr>, cons: Box<Expr>, alt: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: cond_operand(test),
+        cons: cond_operand(cons),
+        alt: cond_operand(alt),
+    }))
+}
+
+/// The expression of a branch that is a single expression statement, possibly in a block
+fn branch_expr(stmt: &Stmt) -> Option<&Expr> {
+    match stmt {
+        Stmt::Expr(ExprStmt { expr, .. }) => Some(expr),
+        Stmt::Block(BlockStmt { stmts, .. }) if stmts.len() == 1 => branch_expr(&stmts[0]),
+        _ => None,
+    }
+}
+
+/// `v = <value>` with a plain identifier target
+fn ident_assignment(expr: &Expr) -> Option<(&Ident, &Expr)> {
+    let Expr::Assign(AssignExpr {
+        op: AssignOp::Assign,
+        left: AssignTarget::Simple(SimpleAssignTarget::Ident(target)),
+        right,
+        ..
+    }) = expr
+    else {
+        return None;
+    };
+    Some((&target.id, right))
+}
+
+/// The other form of a conditional statement, if it has one
+fn convert(stmt: &Stmt) -> Option<Stmt> {
+    match stmt {
+        Stmt::Expr(ExprStmt { expr, .. }) => match &**expr {
+            // c ? a : b;
+            Expr::Cond(cond) => Some(build_if(
+                cond.test.clone(),
+                unambiguous_expr_stmt(cond.cons.clone()),
+                unambiguous_expr_stmt(cond.alt.clone()),
+            )),
+            // x = c ? a : b;
+            Expr::Assign(AssignExpr { op: AssignOp::Assign, left, right, .. }) => {
+                let Expr::Cond(cond) = &**right else {
+                    return None;
+                };
+                Some(build_if(
+                    cond.test.clone(),
+                    unambiguous_expr_stmt(Box::new(build_assign(AssignOp::Assign, left.clone(), *cond.cons.clone()))),
+                    unambiguous_expr_stmt(Box::new(build_assign(AssignOp::Assign, left.clone(), *cond.alt.clone()))),
+                ))
+            }
+            _ => None,
+        },
+        Stmt::If(IfStmt { test, cons, alt: Some(alt), .. }) => {
+            let cons = branch_expr(cons)?;
+            let alt = branch_expr(alt)?;
+            let expr = match (ident_assignment(cons), ident_assignment(alt)) {
+                // if (c) { x = a; } else { x = b; }
+                (Some((cons_target, cons_value)), Some((alt_target, alt_value)))
+                    if cons_target.sym == alt_target.sym =>
+                {
+                    let target = AssignTarget::Simple(SimpleAssignTarget::Ident(BindingIdent {
+                        id: cons_target.clone(),
+                        type_ann: None,
+                    }));
+                    let value = build_cond(
+                        test.clone(),
+                        Box::new(cons_value.clone()),
+                        Box::new(alt_value.clone()),
+                    );
+                    Box::new(build_assign(AssignOp::Assign, target, *value))
+                }
+                // if (c) { a; } else { b; }
+                _ => build_cond(test.clone(), Box::new(cons.clone()), Box::new(alt.clone())),
+            };
+            Some(unambiguous_expr_stmt(expr))
+        }
+        _ => None,
+    }
+}
+
+impl VisitMut for ConditionalFormVisitor {
+    fn visit_mut_stmt(&mut self, node: &mut Stmt) {
+        if let Some(converted) = convert(node) {
+            let is_target = self.crt_idx == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                *node = converted;
+                return;
+            }
+        }
+        node.visit_mut_children_with(self);
+    }
+}
+
+impl AstMutator for ConditionalFormMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountConvertible { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = ConditionalFormVisitor {
+            idx_to_mutate,
+            crt_idx: 0,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    fn mutate_once(source: &str) -> (String, Script) {
+        assert_mutations_reparse(&ConditionalFormMutator, source, 1).remove(0)
+    }
+
+    #[test]
+    fn ternaries_become_if_else() {
+        let (code, reparsed) = mutate_once("v0 = v1 ? 1 : 2;");
+        let Stmt::If(if_stmt) = &reparsed.body[0] else {
+            panic!("expected an if statement in {}", code);
+        };
+        assert!(if_stmt.alt.is_some(), "else branch missing in {}", code);
+        assert!(code.contains("v0 = 1;") && code.contains("v0 = 2;"), "assignments lost in {}", code);
+
+        // an object literal branch must not turn into a block
+        let (code, reparsed) = mutate_once("v1 ? {} : 1;");
+        let Stmt::If(IfStmt { cons, .. }) = &reparsed.body[0] else {
+            panic!("expected an if statement in {}", code);
+        };
+        let Some(Expr::Paren(ParenExpr { expr, .. })) = branch_expr(cons) else {
+            panic!("object literal is not parenthesized in {}", code);
+        };
+        assert!(matches!(&**expr, Expr::Object(_)));
+    }
+
+    #[test]
+    fn if_else_becomes_ternary() {
+        let (code, reparsed) = mutate_once("if (v1) { v0 = 1; } else { v0 = 2; }");
+        assert!(code.contains("v0 = v1 ? 1 : 2"), "expected a ternary assignment in {}", code);
+        assert!(matches!(reparsed.body[0], Stmt::Expr(_)));
+
+        let (code, _) = mutate_once("if (v1) print(1); else v0 = (1, 2);");
+        assert!(code.contains("v1 ? print(1) : (v0 = (1, 2))"), "expected a ternary in {}", code);
+
+        // no else branch, nothing to convert
+        let (code, _) = mutate_once("if (v1) { v0 = 1; }");
+        assert!(code.contains("if (v1)"));
+    }
+}
@@ -0,0 +1,348 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::DUMMY_SP;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{build_bin, build_signed_num, build_str_lit, str_lit_value};
+use crate::mutators::AstMutator;
+
+/// ConstFoldMutator
+/// Folds constant subexpressions (`2 + 3` -> `5`, `"a" + "b"` -> `"ab"`) or un-folds a
+/// literal into an expression evaluating to the same value (`10` -> `(20 / 2)`,
+/// `"ab"` -> `("a" + "b")`), changing what the engine's own constant folder sees.
+///
+/// Only number and string literals are involved, so there are no side effects. Operations
+/// whose result might differ from the engine's (`**`, non-finite results) are not folded.
+pub struct ConstFoldMutator;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Const {
+    Num(f64),
+    Str(String),
+}
+
+/// ECMAScript ToInt32
+fn to_int32(value: f64) -> i32 {
+    if !value.is_finite() {
+        return 0;
+    }
+    value.trunc().rem_euclid(4294967296.0) as u32 as i32
+}
+
+/// Value of a constant expression made of number and string literals
+fn eval_const(expr: &Expr) -> Option<Const> {
+    match expr {
+        Expr::Lit(Lit::Num(num)) => Some(Const::Num(num.value)),
+        Expr::Lit(Lit::Str(str)) => {
+            let value = str_lit_value(str);
+            // lone surrogates don't survive the lossy conversion
+            if value.contains('\u{FFFD}') {
+                return None;
+            }
+            Some(Const::Str(value))
+        }
+        Expr::Paren(paren) => eval_const(&paren.expr),
+        Expr::Unary(UnaryExpr { op: UnaryOp::Minus, arg, .. }) => match eval_const(arg)? {
+            Const::Num(value) => Some(Const::Num(-value)),
+            Const::Str(_) => None,
+        },
+        Expr::Bin(bin) => {
+            let left = eval_const(&bin.left)?;
+            let right = eval_const(&bin.right)?;
+            let value = match (left, right) {
+                (Const::Str(left), Const::Str(right)) if bin.op == BinaryOp::Add => {
+                    return Some(Const::Str(left + right.as_str()));
+                }
+                (Const::Num(left), Const::Num(right)) => match bin.op {
+                    BinaryOp::Add => left + right,
+                    BinaryOp::Sub => left - right,
+                    BinaryOp::Mul => left * right,
+                    BinaryOp::Div => left / right,
+                    BinaryOp::Mod => left % right,
+                    BinaryOp::BitOr => (to_int32(left) | to_int32(right)) as f64,
+                    BinaryOp::BitAnd => (to_int32(left) & to_int32(right)) as f64,
+                    BinaryOp::BitXor => (to_int32(left) ^ to_int32(right)) as f64,
+                    BinaryOp::LShift => to_int32(left).wrapping_shl(to_int32(right) as u32 & 31) as f64,
+                    BinaryOp::RShift => (to_int32(left) >> (to_int32(right) as u32 & 31)) as f64,
+                    BinaryOp::ZeroFillRShift => ((to_int32(left) as u32) >> (to_int32(right) as u32 & 31)) as f64,
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            value.is_finite().then_some(Const::Num(value))
+        }
+        _ => None,
+    }
+}
+
+fn paren(expr: Expr) -> Expr {
+    Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
+
+/// A number literal, parenthesized when negative so it can be any operand
+fn build_operand_num(value: f64) -> Expr {
+    let num = build_signed_num(value);
+    if value.is_sign_negative() { paren(num) } else { num }
+}
+
+/// The literal `expr` evaluates to, if it is a constant binary expression
+fn fold(expr: &Expr) -> Option<Expr> {
+    if !matches!(expr, Expr::Bin(_)) {
+        return None;
+    }
+    match eval_const(expr)? {
+        Const::Num(value) => Some(build_operand_num(value)),
+        Const::Str(value) => Some(Expr::Lit(Lit::Str(build_str_lit(&value)))),
+    }
+}
+
+/// A parenthesized expression evaluating to the literal `expr`
+fn unfold(expr: &Expr, rng: &mut impl Rng) -> Option<Expr> {
+    if !matches!(expr, Expr::Lit(_)) {
+        return None;
+    }
+    let unfolded = match eval_const(expr)? {
+        // other mutators leave negative literals in the AST; -0 only survives the scaling below
+        Const::Num(value)
+            if value.fract() == 0.0
+                && (i32::MIN as f64..=i32::MAX as f64).contains(&value)
+                && !(value == 0.0 && value.is_sign_negative()) =>
+        {
+            match rng.random_range(0..3) {
+                0 => {
+                    let left = rng.random_range(value.min(0.0) as i64..=value.max(0.0) as i64) as f64;
+                    build_bin(BinaryOp::Add, build_operand_num(left), build_operand_num(value - left))
+                }
+                1 => {
+                    let mask = rng.random_range(0..=i32::MAX) as f64;
+                    let left = (value as i32 ^ mask as i32) as f64;
+                    build_bin(BinaryOp::BitXor, build_operand_num(left), build_operand_num(mask))
+                }
+                _ => {
+                    let factor = *[2.0, 3.0, 5.0, 10.0].choose(rng).unwrap();
+                    build_bin(BinaryOp::Div, build_operand_num(value * factor), build_operand_num(factor))
+                }
+            }
+        }
+        Const::Num(value) => {
+            // scaling by a power of two is exact unless it overflows
+            let factor = *[2.0, 4.0, 8.0].choose(rng).unwrap();
+            if !(value * factor).is_finite() {
+                return None;
+            }
+            build_bin(
+                BinaryOp::Div,
+                build_bin(BinaryOp::Mul, build_operand_num(value), build_operand_num(factor)),
+                build_operand_num(factor),
+            )
+        }
+        Const::Str(value) => {
+            let chars: Vec<char> = value.chars().collect();
+            if chars.len() < 2 {
+                return None;
+            }
+            let split = rng.random_range(1..chars.len());
+            let left: String = chars[..split].iter().collect();
+            let right: String = chars[split..].iter().collect();
+            build_bin(
+                BinaryOp::Add,
+                Expr::Lit(Lit::Str(build_str_lit(&left))),
+                Expr::Lit(Lit::Str(build_str_lit(&right))),
+            )
+        }
+    };
+    Some(paren(unfolded))
+}
+
+fn is_candidate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Bin(_) => eval_const(expr).is_some(),
+        Expr::Lit(Lit::Num(num)) => num.value.is_finite(),
+        Expr::Lit(Lit::Str(_)) => eval_const(expr).is_some(),
+        _ => false,
+    }
+}
+
+struct CountCandidates {
+    count: usize,
+}
+
+impl Visit for CountCandidates {
+    fn visit_expr(&mut self, node: &Expr) {
+        if is_candidate(node) {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_expr_stmt(&mut self, node: &ExprStmt) {
+        // a string statement may be a directive like "use strict"
+        if matches!(&*node.expr, Expr::Lit(Lit::Str(_))) {
+            return;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+struct ConstFoldVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+}
+
+impl VisitMut for ConstFoldVisitor {
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        if is_candidate(node) {
+            let is_target = self.crt_idx == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                let replacement = match node {
+                    Expr::Bin(_) => fold(node),
+                    _ => unfold(node, &mut self.rng),
+                };
+                if let Some(replacement) = replacement {
+                    *node = replacement;
+                }
+                return;
+            }
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_expr_stmt(&mut self, node: &mut ExprStmt) {
+        if matches!(&*node.expr, Expr::Lit(Lit::Str(_))) {
+            return;
+        }
+        node.visit_mut_children_with(self);
+    }
+}
+
+impl AstMutator for ConstFoldMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountCandidates { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = ConstFoldVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generators::ast_helpers::{build_expr_stmt, build_num};
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    fn parse_expr(source: &str) -> Expr {
+        let ast = parse_js(format!("({});", source)).expect("failed to parse test expression");
+        let Stmt::Expr(ExprStmt { expr, .. }) = &ast.body[0] else {
+            panic!("expected an expression statement");
+        };
+        let Expr::Paren(ParenExpr { expr, .. }) = &**expr else {
+            panic!("expected a parenthesized expression");
+        };
+        (**expr).clone()
+    }
+
+    fn emit(expr: Expr) -> String {
+        let script = Script {
+            span: DUMMY_SP,
+            body: vec![Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(expr),
+            })],
+            shebang: None,
+        };
+        String::from_utf8(generate_js(script).expect("code generation failed")).unwrap()
+    }
+
+    #[test]
+    fn constant_subexpressions_fold() {
+        assert_eq!(emit(fold(&parse_expr("2 + 3")).unwrap()).trim(), "5;");
+        assert_eq!(emit(fold(&parse_expr("\"a\" + \"b\"")).unwrap()).trim(), "\"ab\";");
+        assert_eq!(eval_const(&fold(&parse_expr("2 - 5")).unwrap()), Some(Const::Num(-3.0)));
+        assert_eq!(eval_const(&parse_expr("-1 >>> 28")), Some(Const::Num(15.0)));
+        assert_eq!(eval_const(&parse_expr("1 << 33")), Some(Const::Num(2.0)));
+
+        // not foldable: mixed types, side effects, non-finite results, `**`
+        assert!(fold(&parse_expr("\"a\" + 1")).is_none());
+        assert!(fold(&parse_expr("v0 + 1")).is_none());
+        assert!(fold(&parse_expr("1 / 0")).is_none());
+        assert!(fold(&parse_expr("2 ** 3")).is_none());
+    }
+
+    #[test]
+    fn unfolded_literals_evaluate_to_the_same_value() {
+        let mut rng = unseeded_rng();
+        for source in ["0", "5", "10", "2147483647", "1.5", "0.1", "\"ab\"", "\"hello\""] {
+            let literal = parse_expr(source);
+            for _ in 0..16 {
+                let unfolded = unfold(&literal, &mut rng).expect("literal was not unfolded");
+                assert!(matches!(unfolded, Expr::Paren(_)));
+                assert_eq!(eval_const(&unfolded), eval_const(&literal), "{} unfolded to {}", source, emit(unfolded.clone()));
+
+                let code = emit(unfolded);
+                let reparsed = parse_expr(code.trim().trim_end_matches(';'));
+                assert_eq!(eval_const(&reparsed), eval_const(&literal), "{} does not round-trip", code);
+            }
+        }
+        assert!(unfold(&parse_expr("\"a\""), &mut rng).is_none());
+    }
+
+    #[test]
+    fn negative_literals_unfold() {
+        // the parser never produces these, NumericTweaker does
+        let mut rng = unseeded_rng();
+        for value in [-5.0, -1.0, -0.0, i32::MIN as f64, -3_000_000_000.0, -0.5] {
+            let literal = build_num(value);
+            for _ in 0..16 {
+                let unfolded = unfold(&literal, &mut rng).expect("literal was not unfolded");
+                let Some(Const::Num(unfolded_value)) = eval_const(&unfolded) else {
+                    panic!("{} unfolded to a non-number", value);
+                };
+                assert_eq!(unfolded_value.to_bits(), value.to_bits(), "{} unfolded to {}", value, unfolded_value);
+            }
+        }
+
+        let array = Expr::Array(ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![Some(ExprOrSpread {
+                spread: None,
+                expr: Box::new(build_num(-5.0)),
+            })],
+        });
+        let ast = Script {
+            span: DUMMY_SP,
+            body: vec![build_expr_stmt(array)],
+            shebang: None,
+        };
+        for _ in 0..32 {
+            ConstFoldMutator.mutate(ast.clone(), &mut rng).expect("mutation failed");
+        }
+    }
+
+    #[test]
+    fn directives_are_left_alone() {
+        let ast = parse_js("\"use strict\";".to_string()).unwrap();
+        let mutated = ConstFoldMutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+        let code = String::from_utf8(generate_js(mutated).unwrap()).unwrap();
+        assert_eq!(code.trim(), "\"use strict\";");
+    }
+}
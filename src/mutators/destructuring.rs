@@ -0,0 +1,207 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use swc_common::DUMMY_SP;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::build_random_primitive_literal;
+use crate::mutators::AstMutator;
+
+/// DestructuringMutator
+/// Rewrites simple declarations reading an element or property into destructuring, e.g.
+/// `let a = arr[0]` -> `let [a] = arr` and `let x = obj.p` -> `let {p: x} = obj`.
+/// Member chains may be folded into nested patterns (`let x = o.p[1]` -> `let {p: [, x]} = o`)
+/// and the binding sometimes gets a default value, exercising the engine's destructuring lowering.
+pub struct DestructuringMutator;
+
+/// Largest array index turned into holes, `[, , , a]`
+const MAX_ARRAY_INDEX: f64 = 8.0;
+
+/// Returns the pattern that binds `inner` to the accessed element/property of `member`
+fn wrap_in_pattern(member: &MemberExpr, inner: Pat) -> Option<Pat> {
+    match &member.prop {
+        MemberProp::Ident(ident) => Some(object_pattern(PropName::Ident(ident.clone()), inner)),
+        MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+            Expr::Lit(Lit::Num(num))
+                if num.value.fract() == 0.0 && (0.0..=MAX_ARRAY_INDEX).contains(&num.value) =>
+            {
+                let mut elems: Vec<Option<Pat>> = vec![None; num.value as usize];
+                elems.push(Some(inner));
+                Some(Pat::Array(ArrayPat {
+                    span: DUMMY_SP,
+                    elems,
+                    optional: false,
+                    type_ann: None,
+                }))
+            }
+            Expr::Lit(Lit::Str(lit)) => Some(object_pattern(PropName::Str(lit.clone()), inner)),
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
+
+fn object_pattern(key: PropName, value: Pat) -> Pat {
+    Pat::Object(ObjectPat {
+        span: DUMMY_SP,
+        props: vec![ObjectPatProp::KeyValue(KeyValuePatProp {
+            key,
+            value: Box::new(value),
+        })],
+        optional: false,
+        type_ann: None,
+    })
+}
+
+fn is_convertible(decl: &VarDeclarator) -> bool {
+    if !matches!(decl.name, Pat::Ident(_)) {
+        return false;
+    }
+    match decl.init.as_deref() {
+        Some(Expr::Member(member)) => {
+            wrap_in_pattern(member, Pat::Invalid(Invalid { span: DUMMY_SP })).is_some()
+        }
+        _ => false,
+    }
+}
+
+struct CountDestructurable {
+    count: usize,
+}
+
+impl Visit for CountDestructurable {
+    fn visit_var_declarator(&mut self, node: &VarDeclarator) {
+        if is_convertible(node) {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+struct DestructuringVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+}
+
+impl DestructuringVisitor {
+    fn destructure(&mut self, decl: &mut VarDeclarator) {
+        let Some(Expr::Member(member)) = decl.init.as_deref() else {
+            return;
+        };
+        let mut binding = decl.name.clone();
+        if self.rng.random_bool(0.3) {
+            binding = Pat::Assign(AssignPat {
+                span: DUMMY_SP,
+                left: Box::new(binding),
+                right: Box::new(build_random_primitive_literal(&mut self.rng)),
+            });
+        }
+
+        let Some(mut pattern) = wrap_in_pattern(member, binding) else {
+            return;
+        };
+        let mut base = member.obj.clone();
+
+        // fold further member accesses into nested patterns
+        while let Expr::Member(inner) = &*base {
+            if !self.rng.random_bool(0.5) {
+                break;
+            }
+            let Some(nested) = wrap_in_pattern(inner, pattern.clone()) else {
+                break;
+            };
+            pattern = nested;
+            base = inner.obj.clone();
+        }
+
+        decl.name = pattern;
+        decl.init = Some(base);
+    }
+}
+
+impl VisitMut for DestructuringVisitor {
+    fn visit_mut_var_declarator(&mut self, node: &mut VarDeclarator) {
+        if is_convertible(node) {
+            if self.crt_idx == self.idx_to_mutate {
+                self.destructure(node);
+            }
+            self.crt_idx += 1;
+        }
+        node.visit_mut_children_with(self);
+    }
+}
+
+impl AstMutator for DestructuringMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountDestructurable { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = DestructuringVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::scope::collect_binding_idents_from_pat;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    /// Mutates `source` and returns the re-parsed last declaration with the emitted code
+    fn mutate_last_decl(source: &str) -> (VarDeclarator, String) {
+        let (code, reparsed) = assert_mutations_reparse(&DestructuringMutator, source, 1).remove(0);
+        let decl = reparsed
+            .body
+            .iter()
+            .rev()
+            .find_map(|stmt| match stmt {
+                Stmt::Decl(Decl::Var(var)) => Some(var.decls[0].clone()),
+                _ => None,
+            })
+            .expect("declaration disappeared");
+        (decl, code)
+    }
+
+    fn binding_names(pat: &Pat) -> Vec<String> {
+        let mut idents = Vec::new();
+        collect_binding_idents_from_pat(pat, &mut idents);
+        idents.iter().map(|ident| ident.sym.to_string()).collect()
+    }
+
+    #[test]
+    fn array_index_becomes_array_destructuring() {
+        // `v0` is initialized with a literal, so only `v1` is a candidate
+        let (decl, code) = mutate_last_decl("let v0 = [1, 2, 3]; let v1 = v0[1];");
+        assert!(matches!(decl.name, Pat::Array(_)), "not array destructuring: {}", code);
+        assert_eq!(binding_names(&decl.name), vec!["v1".to_string()]);
+        assert!(matches!(decl.init.as_deref(), Some(Expr::Ident(ident)) if &*ident.sym == "v0"));
+    }
+
+    #[test]
+    fn property_read_becomes_object_destructuring() {
+        let (decl, code) = mutate_last_decl("let v1 = v0.p;");
+        assert!(matches!(decl.name, Pat::Object(_)), "not object destructuring: {}", code);
+        assert_eq!(binding_names(&decl.name), vec!["v1".to_string()]);
+    }
+
+    #[test]
+    fn member_chain_keeps_binding_name() {
+        for _ in 0..32 {
+            let (decl, code) = mutate_last_decl("const v1 = v0.p[2].q;");
+            assert!(matches!(decl.name, Pat::Object(_)), "not destructured: {}", code);
+            assert_eq!(binding_names(&decl.name), vec!["v1".to_string()]);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_assign, build_bin, build_block, build_expr_stmt, build_ident, build_ident_expr_from_str,
+    build_num, build_property_call, build_return, build_str_lit, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{NameCollector, VarRenamer};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// Iterations of the loop schedule, the transitions happen at evenly spaced iterations
+const LOOP_ITERATIONS: usize = 100;
+
+/// ElementKindMutator
+/// Inserts an array that goes through V8's elements kinds one transition at a time,
+/// with a reader function using it before and after every step:
+/// ```js
+/// let v3 = [1, 2, 3];    // PACKED_SMI_ELEMENTS
+/// let v4 = function (v5) { return v5[0] + v5[v5.length - 1]; };
+/// v4(v3);
+/// v3.push(1.5);          // PACKED_DOUBLE_ELEMENTS
+/// v4(v3);
+/// v3.push({});           // PACKED_ELEMENTS
+/// v4(v3);
+/// v3[v3.length + 2] = 1; // HOLEY_ELEMENTS
+/// v4(v3);
+/// ```
+///
+/// Kinds only ever move down the lattice: SMI -> DOUBLE -> ELEMENTS, and packed ->
+/// holey at any point, so every step is a real transition. With the loop schedule the
+/// reader runs every iteration and the steps happen at fixed iterations, after the
+/// reader got optimized for the previous kind. `ArrayMutator` mixes element types at
+/// random instead, which mostly starts arrays in their final kind.
+pub struct ElementKindMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StartKind {
+    Smi,    // [1, 2, 3]
+    Double, // [1.5, 2.5]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transition {
+    ToDouble,   // v0.push(1.5)
+    ToElements, // v0.push({})
+    ToHoley,    // v0[v0.length + 2] = 1
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Schedule {
+    Sequence, // reader calls between the steps
+    Loop,     // steps at fixed iterations of a loop calling the reader
+}
+
+const SCHEDULES: [Schedule; 2] = [Schedule::Sequence, Schedule::Loop];
+
+const DOUBLES: &[f64] = &[1.5, 0.1, 2.5, 1e-300, 4294967296.5];
+
+/// `<array>.length`
+fn build_length(array: &str) -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(build_ident_expr_from_str(array)),
+        prop: MemberProp::Ident(IdentName::new(Atom::from("length"), DUMMY_SP)),
+    })
+}
+
+/// `<array>[<index>]`
+fn build_index(array: &str, index: Expr) -> MemberExpr {
+    MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(build_ident_expr_from_str(array)),
+        prop: MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(index),
+        }),
+    }
+}
+
+fn build_call(callee: &str, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        callee: Callee::Expr(Box::new(build_ident_expr_from_str(callee))),
+        args: args
+            .into_iter()
+            .map(|arg| ExprOrSpread {
+                spread: None,
+                expr: Box::new(arg),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+/// `for (let <counter> = 0; <counter> < <bound>; <counter>++) { <body> }`
+fn build_counted_loop(counter: &str, bound: Expr, body: Vec<Stmt>) -> Stmt {
+    Stmt::For(ForStmt {
+        span: DUMMY_SP,
+        init: Some(VarDeclOrExpr::VarDecl(Box::new(build_var_decl(counter, build_num(0.0))))),
+        test: Some(Box::new(build_bin(op!("<"), build_ident_expr_from_str(counter), bound))),
+        update: Some(Box::new(Expr::Update(UpdateExpr {
+            span: DUMMY_SP,
+            op: UpdateOp::PlusPlus,
+            prefix: false,
+            arg: Box::new(build_ident_expr_from_str(counter)),
+        }))),
+        body: Box::new(Stmt::Block(build_block(body))),
+    })
+}
+
+fn build_function(param: &str, stmts: Vec<Stmt>) -> Expr {
+    Expr::Fn(FnExpr {
+        ident: None,
+        function: Box::new(Function {
+            params: vec![Param {
+                span: DUMMY_SP,
+                decorators: Vec::new(),
+                pat: Pat::Ident(BindingIdent {
+                    id: build_ident(param),
+                    type_ann: None,
+                }),
+            }],
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(build_block(stmts)),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    })
+}
+
+fn let_stmt(name: &str, init: Expr) -> Stmt {
+    Stmt::Decl(Decl::Var(Box::new(build_var_decl(name, init))))
+}
+
+/// The transitions after starting in `start`, in lattice order except for the move to
+/// holey which can come at any point
+fn plan_transitions(rng: &mut impl Rng, start: StartKind) -> Vec<Transition> {
+    let mut transitions = Vec::new();
+    if start == StartKind::Smi && rng.random_bool(0.7) {
+        transitions.push(Transition::ToDouble);
+    }
+    if rng.random_bool(0.7) {
+        transitions.push(Transition::ToElements);
+    }
+    if transitions.is_empty() || rng.random_bool(0.5) {
+        let at = rng.random_range(0..=transitions.len());
+        transitions.insert(at, Transition::ToHoley);
+    }
+    transitions
+}
+
+struct ElementKindVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    renamer: VarRenamer,
+    schedule: Schedule,
+}
+
+impl ElementKindVisitor {
+    fn random_double(&mut self) -> Expr {
+        build_num(*DOUBLES.choose(&mut self.rng).expect("there are doubles"))
+    }
+
+    fn build_start_array(&mut self, start: StartKind) -> Expr {
+        let count = self.rng.random_range(1..=4);
+        let elems = (0..count)
+            .map(|_| match start {
+                StartKind::Smi => build_num(self.rng.random_range(0..100) as f64),
+                StartKind::Double => self.random_double(),
+            })
+            .map(|expr| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect();
+        Expr::Array(ArrayLit { span: DUMMY_SP, elems })
+    }
+
+    /// A function reading the elements, so the engine has a compiled access to deopt
+    fn build_reader(&mut self) -> Expr {
+        let param = self.renamer.next_var_name();
+        let stmts = if self.rng.random_bool(0.5) {
+            // <p>[0] + <p>[<p>.length - 1]
+            let first = Expr::Member(build_index(&param, build_num(0.0)));
+            let last = Expr::Member(build_index(&param, build_bin(op!(bin, "-"), build_length(&param), build_num(1.0))));
+            vec![build_return(build_bin(op!(bin, "+"), first, last))]
+        } else {
+            // let <sum> = 0; for (...) { <sum> += <p>[<i>]; } return <sum>;
+            let sum = self.renamer.next_var_name();
+            let counter = self.renamer.next_var_name();
+            let add = build_assign(
+                AssignOp::AddAssign,
+                build_ident(&sum),
+                Expr::Member(build_index(&param, build_ident_expr_from_str(&counter))),
+            );
+            vec![
+                let_stmt(&sum, build_num(0.0)),
+                build_counted_loop(&counter, build_length(&param), vec![build_expr_stmt(add)]),
+                build_return(build_ident_expr_from_str(&sum)),
+            ]
+        };
+        build_function(&param, stmts)
+    }
+
+    fn build_transition(&mut self, array: &str, transition: Transition) -> Stmt {
+        let expr = match transition {
+            Transition::ToDouble => {
+                let value = self.random_double();
+                if self.rng.random_bool(0.5) {
+                    build_property_call(array, "push", vec![value])
+                } else {
+                    build_assign(AssignOp::Assign, build_index(array, build_num(0.0)), value)
+                }
+            }
+            Transition::ToElements => {
+                let value = match self.rng.random_range(0..3) {
+                    0 => Expr::Object(ObjectLit {
+                        span: DUMMY_SP,
+                        props: Vec::new(),
+                    }),
+                    1 => Expr::Lit(Lit::Str(build_str_lit("foo"))),
+                    _ => Expr::Lit(Lit::Null(Null { span: DUMMY_SP })),
+                };
+                if self.rng.random_bool(0.5) {
+                    build_property_call(array, "push", vec![value])
+                } else {
+                    build_assign(AssignOp::Assign, build_index(array, build_num(0.0)), value)
+                }
+            }
+            Transition::ToHoley => match self.rng.random_range(0..3) {
+                0 => {
+                    let gap = self.rng.random_range(1..=4) as f64;
+                    let index = build_bin(op!(bin, "+"), build_length(array), build_num(gap));
+                    build_assign(AssignOp::Assign, build_index(array, index), build_num(1.0))
+                }
+                1 => Expr::Unary(UnaryExpr {
+                    span: DUMMY_SP,
+                    op: UnaryOp::Delete,
+                    arg: Box::new(Expr::Member(build_index(array, build_num(0.0)))),
+                }),
+                _ => {
+                    let gap = self.rng.random_range(1..=4) as f64;
+                    let length = match build_length(array) {
+                        Expr::Member(member) => member,
+                        _ => unreachable!("length is a member expression"),
+                    };
+                    build_assign(AssignOp::AddAssign, length, build_num(gap))
+                }
+            },
+        };
+        build_expr_stmt(expr)
+    }
+
+    fn build_transition_stmts(&mut self) -> Vec<Stmt> {
+        let start = if self.rng.random_bool(0.7) { StartKind::Smi } else { StartKind::Double };
+        let transitions = plan_transitions(&mut self.rng, start);
+        let array = self.renamer.next_var_name();
+        let reader = self.renamer.next_var_name();
+        let start_array = self.build_start_array(start);
+        let reader_fn = self.build_reader();
+        let mut stmts = vec![let_stmt(&array, start_array), let_stmt(&reader, reader_fn)];
+        let read = || build_expr_stmt(build_call(&reader, vec![build_ident_expr_from_str(&array)]));
+
+        match self.schedule {
+            Schedule::Sequence => {
+                stmts.push(read());
+                for transition in transitions {
+                    stmts.push(self.build_transition(&array, transition));
+                    stmts.push(read());
+                }
+            }
+            Schedule::Loop => {
+                let counter = self.renamer.next_var_name();
+                let mut body = vec![read()];
+                let step = LOOP_ITERATIONS / (transitions.len() + 1);
+                for (i, transition) in transitions.into_iter().enumerate() {
+                    let at = build_bin(op!("==="), build_ident_expr_from_str(&counter), build_num((step * (i + 1)) as f64));
+                    body.push(Stmt::If(IfStmt {
+                        span: DUMMY_SP,
+                        test: Box::new(at),
+                        cons: Box::new(Stmt::Block(build_block(vec![self.build_transition(&array, transition)]))),
+                        alt: None,
+                    }));
+                }
+                stmts.push(build_counted_loop(&counter, build_num(LOOP_ITERATIONS as f64), body));
+            }
+        }
+        stmts
+    }
+}
+
+impl VisitMut for ElementKindVisitor {
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut i = 0;
+        while i < stmts.len() {
+            stmts[i].visit_mut_with(self);
+            let is_target = self.crt_idx == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                let inserted = self.build_transition_stmts();
+                let count = inserted.len();
+                stmts.splice(i + 1..i + 1, inserted);
+                i += count;
+            }
+            i += 1;
+        }
+    }
+}
+
+impl ElementKindMutator {
+    fn mutate_with_schedule(&self, mut ast: Script, schedule: Schedule, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = ElementKindVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            renamer: VarRenamer::new(names.var_names),
+            schedule,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for ElementKindMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let schedule = *SCHEDULES.choose(rng).expect("there are schedules");
+        self.mutate_with_schedule(ast, schedule, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use crate::utils::rand_utils::unseeded_rng;
+    use swc_ecma_visit::Visit;
+
+    #[derive(Default)]
+    struct CountIfs(usize);
+
+    impl Visit for CountIfs {
+        fn visit_if_stmt(&mut self, node: &IfStmt) {
+            self.0 += 1;
+            node.visit_children_with(self);
+        }
+    }
+
+    #[test]
+    fn element_kind_transitions_round_trip() {
+        let source = "let v0 = [1, 2]; function f0(a0) { return a0.length; } f0(v0);";
+        for schedule in SCHEDULES {
+            let mutants = assert_mutations_reparse_with(source, 16, |ast, rng| {
+                ElementKindMutator.mutate_with_schedule(ast, schedule, rng)
+            });
+            for (code, reparsed) in mutants {
+                assert_eq!(code.matches("function").count(), 2, "no reader in {}", code);
+                assert!(code.contains("f0(v0)"), "original code lost in {}", code);
+                // every transition of the loop schedule is guarded by its iteration
+                let mut ifs = CountIfs::default();
+                reparsed.visit_with(&mut ifs);
+                assert_eq!(ifs.0 > 0, schedule == Schedule::Loop, "wrong schedule in {}", code);
+            }
+        }
+    }
+
+    #[test]
+    fn transitions_follow_the_lattice() {
+        let mut rng = unseeded_rng();
+        for _ in 0..64 {
+            for start in [StartKind::Smi, StartKind::Double] {
+                let transitions = plan_transitions(&mut rng, start);
+                assert!(!transitions.is_empty());
+                let kinds: Vec<Transition> = transitions
+                    .into_iter()
+                    .filter(|transition| *transition != Transition::ToHoley)
+                    .collect();
+                let allowed: &[Transition] = match start {
+                    StartKind::Smi => &[Transition::ToDouble, Transition::ToElements],
+                    StartKind::Double => &[Transition::ToElements],
+                };
+                assert!(kinds.windows(2).all(|pair| pair[0] == Transition::ToDouble && pair[1] == Transition::ToElements));
+                assert!(kinds.iter().all(|kind| allowed.contains(kind)));
+            }
+        }
+    }
+}
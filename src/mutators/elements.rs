@@ -1,11 +1,13 @@
 use anyhow::Result;
 use rand::Rng;
+use rand::rngs::StdRng;
 use rand::seq::IndexedRandom;
 use swc_atoms::Atom;
 use swc_common::{DUMMY_SP, SyntaxContext};
 use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
 use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
 
+use crate::code_generators::ast_helpers::{build_str_lit, is_valid_identifier_name, str_lit_value};
 use crate::mutators::AstMutator;
 use crate::mutators::js_objects::js_types::{JsObjectType, STATIC_PROPERTIES, StaticPropertySig, get_property_list};
 use crate::mutators::scope::{
@@ -374,7 +376,7 @@ use crate::mutators::scope::{
 /// converting it back to a simple identifier `obj`.
 pub struct RemovePropMutator;
 impl AstMutator for RemovePropMutator {
-    fn mutate(&self, mut ast: Script) -> Result<Script> {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
         struct RemovePropVisitor {
             counter_mode: bool,
             counter: usize,
@@ -411,10 +413,134 @@ impl AstMutator for RemovePropMutator {
         let mut remover = RemovePropVisitor {
             counter_mode: false,
             counter: 0,
-            idx_to_remove: rand::rng().random_range(0..collector.counter),
+            idx_to_remove: rng.random_range(0..collector.counter),
         };
         ast.visit_mut_with(&mut remover);
 
         Ok(ast)
     }
 }
+
+/// Rewrites `a.b` into `a["b"]` and `a["b"]` back into `a.b`, exercising the engine's keyed
+/// vs named property access paths. Computed keys are only turned into dot access when the
+/// string is a valid identifier name.
+pub struct AccessFormMutator;
+
+/// Returns the other access form of `prop`, or None if it can't be converted
+fn flip_access_form(prop: &MemberProp) -> Option<MemberProp> {
+    match prop {
+        MemberProp::Ident(ident) => Some(MemberProp::Computed(ComputedPropName {
+            span: ident.span,
+            expr: Box::new(Expr::Lit(Lit::Str(build_str_lit(&ident.sym)))),
+        })),
+        MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+            Expr::Lit(Lit::Str(lit)) => {
+                let value = str_lit_value(lit);
+                if is_valid_identifier_name(&value) {
+                    Some(MemberProp::Ident(IdentName::new(Atom::from(value), DUMMY_SP)))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
+
+struct CountConvertibleMembers {
+    count: usize,
+}
+
+impl Visit for CountConvertibleMembers {
+    fn visit_member_expr(&mut self, node: &MemberExpr) {
+        if flip_access_form(&node.prop).is_some() {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+struct AccessFormVisitor {
+    idx_to_mutate: usize,
+    crt_idx: usize,
+}
+
+impl VisitMut for AccessFormVisitor {
+    fn visit_mut_member_expr(&mut self, node: &mut MemberExpr) {
+        if let Some(flipped) = flip_access_form(&node.prop) {
+            if self.crt_idx == self.idx_to_mutate {
+                node.prop = flipped;
+            }
+            self.crt_idx += 1;
+        }
+        node.visit_mut_children_with(self);
+    }
+}
+
+impl AstMutator for AccessFormMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountConvertibleMembers { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut visitor = AccessFormVisitor {
+            idx_to_mutate: rng.random_range(0..counter.count),
+            crt_idx: 0,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    fn first_member_prop(ast: &Script) -> MemberProp {
+        let Stmt::Expr(ExprStmt { expr, .. }) = &ast.body[0] else {
+            panic!("expected an expression statement");
+        };
+        let Expr::Member(member) = &**expr else {
+            panic!("expected a member expression");
+        };
+        member.prop.clone()
+    }
+
+    fn mutate_and_reparse(source: &str) -> Script {
+        let (_, reparsed) = assert_mutations_reparse(&AccessFormMutator, source, 1).remove(0);
+        reparsed
+    }
+
+    #[test]
+    fn dot_access_becomes_computed() {
+        let ast = mutate_and_reparse("a.b;");
+        match first_member_prop(&ast) {
+            MemberProp::Computed(ComputedPropName { expr, .. }) => {
+                let Expr::Lit(Lit::Str(lit)) = &*expr else {
+                    panic!("expected a string key");
+                };
+                assert_eq!(str_lit_value(lit), "b");
+            }
+            other => panic!("expected computed access, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn computed_access_becomes_dot() {
+        let ast = mutate_and_reparse("a[\"b\"];");
+        match first_member_prop(&ast) {
+            MemberProp::Ident(ident) => assert_eq!(&*ident.sym, "b"),
+            other => panic!("expected dot access, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn invalid_identifier_is_skipped() {
+        let ast = mutate_and_reparse("a[\"foo-bar\"];");
+        assert!(matches!(first_member_prop(&ast), MemberProp::Computed(_)));
+    }
+}
@@ -0,0 +1,274 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_block, build_expr_stmt, build_ident, build_ident_expr_from_str, build_property_call,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::scope::{
+    NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods, visit_mut_stmts_inserting_after,
+};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// EnumerationMutator
+/// Iterates over the keys of an object from the current scope and touches every property,
+/// e.g. `for (const v3 in v0) { v0[v3]; delete v0[v3]; }`,
+/// `Object.keys(Object(v0)).forEach((v3) => { v0[v3] = v0[v3]; });` or
+/// `for (const v3 of Reflect.ownKeys(Object(v0))) { String(v3); }`, to stress
+/// enumeration order and the property iteration fast paths.
+///
+/// Objects have finitely many keys, so the loops always terminate. The key listing methods
+/// come from the global object catalog.
+pub struct EnumerationMutator;
+
+/// Static methods listing the own keys of their argument
+const KEY_SOURCES: &[(&str, &str)] = &[
+    ("Object", "keys"),
+    ("Object", "getOwnPropertyNames"),
+    ("Object", "getOwnPropertySymbols"),
+    ("Reflect", "ownKeys"),
+];
+
+/// The key sources of `KEY_SOURCES` present in the catalog
+fn key_sources() -> Vec<(&'static str, &'static str)> {
+    KEY_SOURCES
+        .iter()
+        .copied()
+        .filter(|(object, method)| {
+            get_global_object(object)
+                .is_some_and(|obj| obj.static_methods().iter().any(|m| m.sym() == *method))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+enum LoopKind {
+    ForIn,   // for (const k in x) { ... }
+    ForEach, // Object.keys(Object(x)).forEach((k) => { ... });
+    ForOf,   // for (const k of Reflect.ownKeys(Object(x))) { ... }
+}
+
+struct EnumerationVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+    key_sources: Vec<(&'static str, &'static str)>,
+}
+
+impl ScopedAstVisitor for EnumerationVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+/// `<obj>[<key>]`
+fn build_computed_member(obj: &str, key: &str) -> MemberExpr {
+    MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(build_ident_expr_from_str(obj)),
+        prop: MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(build_ident_expr_from_str(key)),
+        }),
+    }
+}
+
+/// `const <name>` as the head of a for-in/for-of loop
+fn build_loop_head(name: &str) -> ForHead {
+    ForHead::VarDecl(Box::new(VarDecl {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: build_ident(name),
+                type_ann: None,
+            }),
+            init: None,
+            definite: false,
+        }],
+    }))
+}
+
+impl EnumerationVisitor {
+    /// A small operation on the property `<obj>[<key>]`
+    fn build_key_operation(&mut self, obj: &str, key: &str) -> Stmt {
+        let expr = match self.rng.random_range(0..4) {
+            0 => Expr::Member(build_computed_member(obj, key)),
+            1 => Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(build_computed_member(obj, key))),
+                right: Box::new(Expr::Member(build_computed_member(obj, key))),
+            }),
+            2 => Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Delete,
+                arg: Box::new(Expr::Member(build_computed_member(obj, key))),
+            }),
+            // String() rather than concatenation, the key may be a symbol
+            _ => Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                callee: Callee::Expr(Box::new(build_ident_expr_from_str("String"))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(build_ident_expr_from_str(key)),
+                }],
+                type_args: None,
+            }),
+        };
+        build_expr_stmt(expr)
+    }
+
+    /// `<Object|Reflect>.<method>(Object(<obj>))`, boxing primitives so the call can't throw
+    fn build_key_list(&mut self, obj: &str) -> Option<Expr> {
+        let (object, method) = *self.key_sources.choose(&mut self.rng)?;
+        let boxed = Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            callee: Callee::Expr(Box::new(build_ident_expr_from_str("Object"))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(build_ident_expr_from_str(obj)),
+            }],
+            type_args: None,
+        });
+        Some(build_property_call(object, method, vec![boxed]))
+    }
+
+    fn build_enumeration(&mut self) -> Option<Stmt> {
+        let idents = self.scope_state.scopes.collect_idents();
+        let obj = idents.choose(&mut self.rng)?.sym.to_string();
+        let key = self.renamer.next_var_name();
+        let body = (0..self.rng.random_range(1..=2))
+            .map(|_| self.build_key_operation(&obj, &key))
+            .collect();
+
+        let kind = match self.rng.random_range(0..3) {
+            0 => LoopKind::ForIn,
+            1 => LoopKind::ForEach,
+            _ => LoopKind::ForOf,
+        };
+        let stmt = match kind {
+            LoopKind::ForIn => Stmt::ForIn(ForInStmt {
+                span: DUMMY_SP,
+                left: build_loop_head(&key),
+                right: Box::new(build_ident_expr_from_str(&obj)),
+                body: Box::new(Stmt::Block(build_block(body))),
+            }),
+            LoopKind::ForEach => {
+                let callback = Expr::Arrow(ArrowExpr {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    params: vec![Pat::Ident(BindingIdent {
+                        id: build_ident(&key),
+                        type_ann: None,
+                    })],
+                    body: Box::new(BlockStmtOrExpr::BlockStmt(build_block(body))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                });
+                let keys = self.build_key_list(&obj)?;
+                build_expr_stmt(Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                        span: DUMMY_SP,
+                        obj: Box::new(keys),
+                        prop: MemberProp::Ident(IdentName::new(Atom::from("forEach"), DUMMY_SP)),
+                    }))),
+                    args: vec![ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(callback),
+                    }],
+                    type_args: None,
+                }))
+            }
+            LoopKind::ForOf => Stmt::ForOf(ForOfStmt {
+                span: DUMMY_SP,
+                is_await: false,
+                left: build_loop_head(&key),
+                right: Box::new(self.build_key_list(&obj)?),
+                body: Box::new(Stmt::Block(build_block(body))),
+            }),
+        };
+        Some(stmt)
+    }
+}
+
+impl VisitMut for EnumerationVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            if is_target { visitor.build_enumeration() } else { None }
+        });
+    }
+}
+
+impl AstMutator for EnumerationMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let key_sources = key_sources();
+        if key_sources.is_empty() {
+            return Ok(ast);
+        }
+
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = EnumerationVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+            key_sources,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    #[test]
+    fn enumeration_round_trips() {
+        for (code, reparsed) in assert_mutations_reparse(&EnumerationMutator, "let v0 = { a: 1, b: 2 };", 32) {
+            assert_eq!(reparsed.body.len(), 2, "no enumeration inserted in {}", code);
+
+            let is_enumeration = match &reparsed.body[1] {
+                Stmt::ForIn(_) => true,
+                Stmt::ForOf(ForOfStmt { right, .. }) => matches!(&**right, Expr::Call(_)),
+                Stmt::Expr(_) => code.contains(".forEach("),
+                _ => false,
+            };
+            assert!(is_enumeration, "expected an enumeration loop in {}", code);
+        }
+    }
+}
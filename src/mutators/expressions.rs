@@ -1,6 +1,6 @@
-use std::mem;
-
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::IndexedRandom;
 use swc_common::SyntaxContext;
 use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
@@ -60,7 +60,7 @@ enum Mode {
 }
 
 struct ExpressionSwapDupVisitor {
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
     mode: Mode,
     current_idx: usize,
     swap_state: Option<SwapState>,
@@ -79,13 +79,13 @@ impl ScopedAstVisitor for ExpressionSwapDupVisitor {
 }
 
 impl ExpressionSwapDupVisitor {
-    fn new(rng: rand::rngs::ThreadRng, mode: SwapDupMode) -> Self {
+    fn new(rng: &mut StdRng, mode: SwapDupMode) -> Self {
         let (mode_flag, swap_state, dup_state) = match mode {
             SwapDupMode::Swap(state) => (Mode::Swap, Some(state), None),
             SwapDupMode::Dup(state) => (Mode::Dup, None, Some(state)),
         };
         Self {
-            rng,
+            rng: StdRng::from_rng(rng),
             mode: mode_flag,
             current_idx: 0,
             swap_state,
@@ -126,10 +126,9 @@ impl ExpressionSwapDupVisitor {
             return candidates.choose(&mut self.rng).cloned();
         }
 
-        let mut rng = mem::take(&mut self.rng);
-        let choice = self.scope_stack().choose_expr(&mut rng);
-        self.rng = rng;
-        choice
+        // the scope stack borrows the whole visitor, so it picks from a fork of the rng
+        let mut rng = StdRng::from_rng(&mut self.rng);
+        self.scope_stack().choose_expr(&mut rng)
     }
 }
 
@@ -225,13 +224,13 @@ impl VisitMut for ExpressionSwapDupVisitor {
 }
 
 /// Swap identifiers in the given expression with other compatible identifiers from the candidates list
-fn swap_idents_in_expr(expr: &mut Expr, rng: &mut rand::rngs::ThreadRng, candidates: &[Ident]) {
+fn swap_idents_in_expr(expr: &mut Expr, rng: &mut StdRng, candidates: &[Ident]) {
     if candidates.len() < 2 {
         return;
     }
 
     struct IdentRewriter<'a> {
-        rng: &'a mut rand::rngs::ThreadRng,
+        rng: &'a mut StdRng,
         candidates: &'a [Ident],
     }
 
@@ -269,7 +268,7 @@ fn swap_idents_in_expr(expr: &mut Expr, rng: &mut rand::rngs::ThreadRng, candida
 }
 
 impl AstMutator for ExpressionSwapDup {
-    fn mutate(&self, mut ast: Script) -> anyhow::Result<Script> {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> anyhow::Result<Script> {
         // println!("{:#?}", ast);
         // std::process::exit(0);
 
@@ -280,7 +279,6 @@ impl AstMutator for ExpressionSwapDup {
             return Ok(ast);
         }
 
-        let mut rng = rand::rng();
         let use_swap = rng.random_bool(0.1);
 
         let mode = if use_swap {
@@ -312,9 +310,9 @@ impl AstMutator for ExpressionSwapDup {
 pub struct IdentSwapMutator;
 
 impl AstMutator for IdentSwapMutator {
-    fn mutate(&self, mut ast: Script) -> anyhow::Result<Script> {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> anyhow::Result<Script> {
         struct IdentSwapVisitor {
-            rng: rand::rngs::ThreadRng,
+            rng: StdRng,
             candidates: Vec<Ident>,
             counter_mode: bool,
             current_idx: usize,
@@ -363,7 +361,7 @@ impl AstMutator for IdentSwapMutator {
         }
 
         let mut collector = IdentSwapVisitor {
-            rng: rand::rng(),
+            rng: StdRng::from_rng(rng),
             candidates: Vec::new(),
             counter_mode: true,
             current_idx: 0,
@@ -376,9 +374,9 @@ impl AstMutator for IdentSwapMutator {
             return Ok(ast);
         }
 
-        let idx_to_swap = rand::rng().random_range(0..total_idents);
+        let idx_to_swap = rng.random_range(0..total_idents);
         let mut swapper = IdentSwapVisitor {
-            rng: rand::rng(),
+            rng: StdRng::from_rng(rng),
             candidates: collector.candidates,
             counter_mode: false,
             current_idx: 0,
@@ -0,0 +1,375 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_block, build_expr_stmt, build_ident_expr_from_str, build_num, build_random_primitive_literal,
+    build_str_lit, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::scope::{NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// FreezeMutator
+/// Freezes, seals or prevents extensions of an object in scope and then tries to change
+/// it, e.g. after `let v0 = [1, 2];`:
+/// ```js
+/// try {
+///     Object.freeze(v0);
+///     v0.a = 5;
+///     delete v0[0];
+/// } catch {}
+/// ```
+///
+/// In sloppy mode the writes fail silently, in strict mode they go through a
+/// `"use strict"` function where each of them throws. Writes to an object whose
+/// integrity level just changed have to leave the property store fast paths and get
+/// checked against the new level. The integrity methods come from the `Object` catalog
+/// entry.
+pub struct FreezeMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteMode {
+    Sloppy, // the writes fail silently
+    Strict, // the writes throw, each is caught on its own
+}
+
+const WRITE_MODES: [WriteMode; 2] = [WriteMode::Sloppy, WriteMode::Strict];
+
+const INTEGRITY_METHODS: &[&str] = &["freeze", "seal", "preventExtensions"];
+
+/// Queried after the integrity change, the answers are cached on the object's shape
+const CHECK_METHODS: &[&str] = &["isFrozen", "isSealed", "isExtensible"];
+
+const PROPERTY_KEYS: &[&str] = &["a", "x", "length", "constructor", "prototype"];
+
+#[derive(Debug, Clone, Copy)]
+enum PropertyKey {
+    Name(&'static str), // v0.a
+    Index(f64),         // v0[0]
+}
+
+/// The methods of `names` the `Object` catalog entry has
+fn object_methods(names: &[&str]) -> Vec<String> {
+    get_global_object("Object")
+        .map(|object| {
+            object
+                .static_methods()
+                .into_iter()
+                .map(|method| method.sym().to_string())
+                .filter(|name| names.contains(&name.as_str()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `try { <stmts> } catch {}`
+fn build_try(stmts: Vec<Stmt>) -> Stmt {
+    Stmt::Try(Box::new(TryStmt {
+        span: DUMMY_SP,
+        block: build_block(stmts),
+        handler: Some(CatchClause {
+            span: DUMMY_SP,
+            param: None,
+            body: build_block(Vec::new()),
+        }),
+        finalizer: None,
+    }))
+}
+
+/// `Object.<method>(<args>)`
+fn build_object_call(method: &str, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(build_ident_expr_from_str("Object")),
+            prop: MemberProp::Ident(IdentName::new(Atom::from(method), DUMMY_SP)),
+        }))),
+        args: args
+            .into_iter()
+            .map(|arg| ExprOrSpread {
+                spread: None,
+                expr: Box::new(arg),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+fn build_property(target: &str, key: PropertyKey) -> MemberExpr {
+    let prop = match key {
+        PropertyKey::Name(name) => MemberProp::Ident(IdentName::new(Atom::from(name), DUMMY_SP)),
+        PropertyKey::Index(index) => MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(build_num(index)),
+        }),
+    };
+    MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(build_ident_expr_from_str(target)),
+        prop,
+    }
+}
+
+/// `(function () { "use strict"; <stmts> })()`
+fn build_strict_iife(stmts: Vec<Stmt>) -> Expr {
+    let mut body = vec![build_expr_stmt(Expr::Lit(Lit::Str(build_str_lit("use strict"))))];
+    body.extend(stmts);
+    let function = Expr::Fn(FnExpr {
+        ident: None,
+        function: Box::new(Function {
+            params: Vec::new(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(build_block(body)),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    });
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(function),
+        }))),
+        args: Vec::new(),
+        type_args: None,
+    })
+}
+
+struct FreezeVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+    integrity_methods: Vec<String>,
+    check_methods: Vec<String>,
+    can_define: bool,
+    mode: WriteMode,
+}
+
+impl ScopedAstVisitor for FreezeVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl FreezeVisitor {
+    fn random_key(&mut self) -> PropertyKey {
+        if self.rng.random_bool(0.5) {
+            PropertyKey::Name(*PROPERTY_KEYS.choose(&mut self.rng).expect("there are property keys"))
+        } else {
+            PropertyKey::Index(self.rng.random_range(0..4) as f64)
+        }
+    }
+
+    /// A fresh object to freeze, `{ a: 1 }` or `[1, 2]`
+    fn build_fresh_target(&mut self) -> Expr {
+        let count = self.rng.random_range(1..=3);
+        if self.rng.random_bool(0.5) {
+            let elems = (0..count)
+                .map(|_| {
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(build_random_primitive_literal(&mut self.rng)),
+                    })
+                })
+                .collect();
+            Expr::Array(ArrayLit { span: DUMMY_SP, elems })
+        } else {
+            let props = PROPERTY_KEYS[..count]
+                .iter()
+                .map(|key| {
+                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(IdentName::new(Atom::from(*key), DUMMY_SP)),
+                        value: Box::new(build_random_primitive_literal(&mut self.rng)),
+                    })))
+                })
+                .collect();
+            Expr::Object(ObjectLit { span: DUMMY_SP, props })
+        }
+    }
+
+    /// A write, delete or redefinition of a property of `target`
+    fn build_change(&mut self, target: &str) -> Expr {
+        let choices = if self.can_define { 4 } else { 3 };
+        match self.rng.random_range(0..choices) {
+            0 | 1 => {
+                let key = self.random_key();
+                let value = build_random_primitive_literal(&mut self.rng);
+                Expr::Assign(AssignExpr {
+                    span: DUMMY_SP,
+                    op: AssignOp::Assign,
+                    left: AssignTarget::Simple(SimpleAssignTarget::Member(build_property(target, key))),
+                    right: Box::new(value),
+                })
+            }
+            2 => {
+                let key = self.random_key();
+                Expr::Unary(UnaryExpr {
+                    span: DUMMY_SP,
+                    op: UnaryOp::Delete,
+                    arg: Box::new(Expr::Member(build_property(target, key))),
+                })
+            }
+            _ => {
+                // throws in sloppy mode as well
+                let key = match self.random_key() {
+                    PropertyKey::Name(name) => Expr::Lit(Lit::Str(build_str_lit(name))),
+                    PropertyKey::Index(index) => build_num(index),
+                };
+                let value = build_random_primitive_literal(&mut self.rng);
+                let descriptor = Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(IdentName::new(Atom::from("value"), DUMMY_SP)),
+                        value: Box::new(value),
+                    })))],
+                });
+                build_object_call("defineProperty", vec![build_ident_expr_from_str(target), key, descriptor])
+            }
+        }
+    }
+
+    fn build_freeze_stmts(&mut self) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        let idents = self.scope_state.scopes.collect_idents();
+        let target = match idents.choose(&mut self.rng) {
+            Some(ident) if self.rng.random_bool(0.75) => ident.sym.to_string(),
+            _ => {
+                let name = self.renamer.next_var_name();
+                let init = self.build_fresh_target();
+                stmts.push(Stmt::Decl(Decl::Var(Box::new(build_var_decl(&name, init)))));
+                name
+            }
+        };
+
+        let method = self
+            .integrity_methods
+            .choose(&mut self.rng)
+            .expect("there are integrity methods")
+            .clone();
+        // freezing a typed array with elements throws
+        let mut guarded = vec![build_expr_stmt(build_object_call(&method, vec![build_ident_expr_from_str(&target)]))];
+        if let Some(check) = self.check_methods.choose(&mut self.rng).cloned() {
+            if self.rng.random_bool(0.5) {
+                guarded.push(build_expr_stmt(build_object_call(&check, vec![build_ident_expr_from_str(&target)])));
+            }
+        }
+
+        let change_count = self.rng.random_range(1..=3);
+        let changes: Vec<Expr> = (0..change_count).map(|_| self.build_change(&target)).collect();
+        match self.mode {
+            WriteMode::Sloppy => {
+                guarded.extend(changes.into_iter().map(build_expr_stmt));
+                stmts.push(build_try(guarded));
+            }
+            WriteMode::Strict => {
+                stmts.push(build_try(guarded));
+                let attempts = changes.into_iter().map(|change| build_try(vec![build_expr_stmt(change)])).collect();
+                stmts.push(build_expr_stmt(build_strict_iife(attempts)));
+            }
+        }
+        stmts
+    }
+}
+
+impl VisitMut for FreezeVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut i = 0;
+        while i < stmts.len() {
+            stmts[i].visit_mut_with(self);
+            let is_target = self.crt_idx == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                let inserted = self.build_freeze_stmts();
+                let count = inserted.len();
+                stmts.splice(i + 1..i + 1, inserted);
+                i += count;
+            }
+            i += 1;
+        }
+    }
+}
+
+impl FreezeMutator {
+    fn mutate_with_mode(&self, mut ast: Script, mode: WriteMode, rng: &mut StdRng) -> Result<Script> {
+        let integrity_methods = object_methods(INTEGRITY_METHODS);
+        if integrity_methods.is_empty() {
+            return Ok(ast);
+        }
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = FreezeVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+            integrity_methods,
+            check_methods: object_methods(CHECK_METHODS),
+            can_define: !object_methods(&["defineProperty"]).is_empty(),
+            mode,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for FreezeMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mode = *WRITE_MODES.choose(rng).expect("there are write modes");
+        self.mutate_with_mode(ast, mode, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+
+    #[test]
+    fn frozen_objects_round_trip() {
+        let integrity_methods = object_methods(INTEGRITY_METHODS);
+        assert!(!integrity_methods.is_empty(), "the Object catalog has no integrity methods");
+        let source = "let v0 = { a: 1, b: [2, 3] }; function f0(a0) { return a0.a; } f0(v0);";
+        for mode in WRITE_MODES {
+            let mutants =
+                assert_mutations_reparse_with(source, 16, |ast, rng| FreezeMutator.mutate_with_mode(ast, mode, rng));
+            for (code, _) in mutants {
+                assert!(
+                    integrity_methods
+                        .iter()
+                        .any(|method| code.contains(&format!("Object.{}(", method))),
+                    "no integrity change in {}",
+                    code
+                );
+                assert!(code.contains("try"), "unguarded changes in {}", code);
+                assert_eq!(code.contains("use strict"), mode == WriteMode::Strict, "wrong mode in {}", code);
+            }
+        }
+    }
+}
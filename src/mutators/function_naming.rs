@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::build_ident;
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{NameCollector, VarRenamer};
+
+/// FunctionNamingMutator
+/// Changes how a function is named or invoked:
+/// ```js
+/// let v0 = function () {};            // -> let v0 = function v3() {};
+/// let v1 = function f0() {};          // -> let v1 = function () {};
+/// let v2 = function () {};            // -> let v2 = ((function () { return function () {}; })());
+/// function f1() { ... }               // -> (function f1() { ... })();
+/// ```
+///
+/// Whether a function is named changes its `.name`, its stack frames and some inlining
+/// heuristics. Bindings stay intact: added names are fresh and only bound inside the
+/// function itself, names are only removed from expressions that never use them, and
+/// only declarations that nothing refers to are turned into immediate calls.
+pub struct FunctionNamingMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NamingAction {
+    AddName,    // function () {} -> function v3() {}
+    RemoveName, // function f0() {} -> function () {}
+    WrapIife,   // function () {} -> ((function () { return function () {}; })())
+    DeclToIife, // function f1() {} -> (function f1() {})();
+}
+
+const NAMING_ACTIONS: [NamingAction; 4] = [
+    NamingAction::AddName,
+    NamingAction::RemoveName,
+    NamingAction::WrapIife,
+    NamingAction::DeclToIife,
+];
+
+/// How often every identifier occurs, bindings included
+#[derive(Default)]
+struct NameUses {
+    counts: HashMap<Atom, usize>,
+}
+
+impl Visit for NameUses {
+    fn visit_ident(&mut self, node: &Ident) {
+        *self.counts.entry(node.sym.clone()).or_default() += 1;
+    }
+}
+
+fn name_uses<N: VisitWith<NameUses>>(node: &N) -> NameUses {
+    let mut uses = NameUses::default();
+    node.visit_with(&mut uses);
+    uses
+}
+
+fn build_paren(expr: Expr) -> Expr {
+    Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
+
+/// `(<callee>)()`
+fn build_paren_call(callee: Expr) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        callee: Callee::Expr(Box::new(build_paren(callee))),
+        args: Vec::new(),
+        type_args: None,
+    })
+}
+
+/// `((function () { return <function>; })())`. The outer parens keep the call from
+/// binding to a surrounding `new` or member access.
+fn build_iife_returning(function: Expr) -> Expr {
+    let wrapper = Expr::Fn(FnExpr {
+        ident: None,
+        function: Box::new(Function {
+            params: Vec::new(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts: vec![Stmt::Return(ReturnStmt {
+                    span: DUMMY_SP,
+                    arg: Some(Box::new(function)),
+                })],
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    });
+    build_paren(build_paren_call(wrapper))
+}
+
+struct FunctionNamingVisitor {
+    action: NamingAction,
+    /// None when only counting the candidates
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    renamer: VarRenamer,
+    /// Occurrences of every identifier in the whole program
+    uses: HashMap<Atom, usize>,
+}
+
+impl FunctionNamingVisitor {
+    fn next_is_target(&mut self) -> bool {
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        is_target
+    }
+}
+
+impl VisitMut for FunctionNamingVisitor {
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        node.visit_mut_children_with(self);
+        let Expr::Fn(fn_expr) = node else {
+            return;
+        };
+        match self.action {
+            NamingAction::AddName => {
+                if fn_expr.ident.is_none() && self.next_is_target() {
+                    fn_expr.ident = Some(build_ident(&self.renamer.next_var_name()));
+                }
+            }
+            NamingAction::RemoveName => {
+                // recursive calls go through the name
+                let Some(ident) = &fn_expr.ident else {
+                    return;
+                };
+                let used = name_uses(&*fn_expr.function).counts.contains_key(&ident.sym);
+                if !used && self.next_is_target() {
+                    fn_expr.ident = None;
+                }
+            }
+            NamingAction::WrapIife => {
+                if self.next_is_target() {
+                    let function = std::mem::replace(node, Expr::Invalid(Invalid { span: DUMMY_SP }));
+                    *node = build_iife_returning(function);
+                }
+            }
+            NamingAction::DeclToIife => {}
+        }
+    }
+
+    fn visit_mut_stmt(&mut self, node: &mut Stmt) {
+        node.visit_mut_children_with(self);
+        if self.action != NamingAction::DeclToIife {
+            return;
+        }
+        let Stmt::Decl(Decl::Fn(decl)) = node else {
+            return;
+        };
+        // the declaration is the only occurrence of the name, so no call site loses it
+        if self.uses.get(&decl.ident.sym) != Some(&1) || !self.next_is_target() {
+            return;
+        }
+        let fn_expr = Expr::Fn(FnExpr {
+            ident: Some(decl.ident.clone()),
+            function: decl.function.clone(),
+        });
+        *node = Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(build_paren_call(fn_expr)),
+        });
+    }
+}
+
+impl FunctionNamingMutator {
+    /// Apply `action` to one of its candidates. Returns None if there are none.
+    fn mutate_with_action(&self, mut ast: Script, action: NamingAction, rng: &mut StdRng) -> Option<Script> {
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let mut visitor = FunctionNamingVisitor {
+            action,
+            idx_to_mutate: None,
+            crt_idx: 0,
+            renamer: VarRenamer::new(names.var_names),
+            uses: name_uses(&ast).counts,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return None;
+        }
+
+        let idx_to_mutate = rng.random_range(0..visitor.crt_idx);
+        let mut visitor = FunctionNamingVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Some(ast)
+    }
+}
+
+impl AstMutator for FunctionNamingMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut actions = NAMING_ACTIONS;
+        actions.shuffle(rng);
+        for action in actions {
+            if let Some(mutated) = self.mutate_with_action(ast.clone(), action, rng) {
+                return Ok(mutated);
+            }
+        }
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    fn mutate_code(source: &str, action: NamingAction) -> Option<String> {
+        let ast = parse_js(source.to_string()).expect("failed to parse test script");
+        let mutated = FunctionNamingMutator.mutate_with_action(ast, action, &mut unseeded_rng())?;
+        let code = String::from_utf8(generate_js(mutated).expect("code generation failed")).unwrap();
+        parse_js(code.clone()).expect("mutated code does not parse");
+        Some(code)
+    }
+
+    #[test]
+    fn unreferenced_declarations_become_iifes() {
+        let source = "function f0() { return 1; } function f1() { return f1; } f2(); function f2() {}";
+        for _ in 0..8 {
+            let code = mutate_code(source, NamingAction::DeclToIife).expect("no candidate");
+            let reparsed = parse_js(code.clone()).unwrap();
+            let Stmt::Expr(ExprStmt { expr, .. }) = &reparsed.body[0] else {
+                panic!("f0 was not turned into an expression in {}", code);
+            };
+            assert!(matches!(&**expr, Expr::Call(_)), "f0 is not called in {}", code);
+            // f1 refers to itself and f2 is called, both stay declarations
+            assert!(code.contains("function f1()") && code.contains("function f2()"), "{}", code);
+            assert!(matches!(reparsed.body[1], Stmt::Decl(Decl::Fn(_))), "{}", code);
+            assert!(matches!(reparsed.body[3], Stmt::Decl(Decl::Fn(_))), "{}", code);
+        }
+        assert!(mutate_code("function f0() {} f0();", NamingAction::DeclToIife).is_none());
+    }
+
+    #[test]
+    fn naming_changes_keep_call_sites() {
+        let source = "let v0 = function () { return 1; }; let v1 = function f0(a0) { return a0 ? f0(0) : 2; }; \
+                      let v2 = function f1() { return 3; }; v0(); v1(1); v2(); new (function () {})();";
+        for action in [NamingAction::AddName, NamingAction::RemoveName, NamingAction::WrapIife] {
+            for _ in 0..16 {
+                let code = mutate_code(source, action).expect("no candidate");
+                for call in ["v0()", "v1(1)", "v2()"] {
+                    assert!(code.contains(call), "{} lost in {}", call, code);
+                }
+                // the recursive call still has the name to go through
+                assert!(code.contains("function f0(a0)"), "f0 lost its name in {}", code);
+                if action == NamingAction::RemoveName {
+                    assert!(!code.contains("function f1"), "f1 kept its name in {}", code);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::rngs::StdRng;
+use swc_atoms::Atom;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods};
+use crate::mutators::validity::DeclaredNames;
+
+/// HoistSinkMutator
+/// Moves declarations around without changing what the program computes, to exercise
+/// the engine's scope allocation and closure capture:
+/// - hoists a `var` or function declaration to the top of its function (or script)
+/// - sinks a `let`/`const` into the only block that references it
+///
+/// Every move is checked conservatively and skipped if any reference could resolve
+/// differently or observe a different value.
+pub struct HoistSinkMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Move {
+    /// Move `stmts[from]` to `stmts[to]`
+    Hoist { from: usize, to: usize },
+    /// Move `stmts[from]` to the start of the block at `stmts[into]`
+    Sink { from: usize, into: usize },
+}
+
+struct HoistSinkVisitor {
+    // None while counting candidates
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+}
+
+impl ScopedAstVisitor for HoistSinkVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+/// Whether `name` appears as any identifier (binding or reference) in a node
+struct NameMentions {
+    name: Atom,
+    found: bool,
+}
+
+impl Visit for NameMentions {
+    fn visit_ident(&mut self, node: &Ident) {
+        if node.sym == self.name {
+            self.found = true;
+        }
+    }
+}
+
+fn mentions_in<N: VisitWith<NameMentions>>(node: &N, name: &Atom) -> bool {
+    let mut visitor = NameMentions {
+        name: name.clone(),
+        found: false,
+    };
+    node.visit_with(&mut visitor);
+    visitor.found
+}
+
+fn mentions(stmts: &[Stmt], name: &Atom) -> bool {
+    stmts.iter().any(|stmt| mentions_in(stmt, name))
+}
+
+/// Whether `name` appears inside a function or closure in a node. Such a function can
+/// be called before or after any statement, so what it sees doesn't follow their order.
+struct ClosureMentions {
+    name: Atom,
+    depth: usize,
+    found: bool,
+}
+
+impl ClosureMentions {
+    fn enter<N: VisitWith<Self>>(&mut self, node: &N) {
+        self.depth += 1;
+        node.visit_children_with(self);
+        self.depth -= 1;
+    }
+}
+
+impl Visit for ClosureMentions {
+    fn visit_function(&mut self, node: &Function) {
+        self.enter(node);
+    }
+
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        self.enter(node);
+    }
+
+    fn visit_constructor(&mut self, node: &Constructor) {
+        self.enter(node);
+    }
+
+    fn visit_getter_prop(&mut self, node: &GetterProp) {
+        self.enter(node);
+    }
+
+    fn visit_setter_prop(&mut self, node: &SetterProp) {
+        self.enter(node);
+    }
+
+    fn visit_ident(&mut self, node: &Ident) {
+        if self.depth > 0 && node.sym == self.name {
+            self.found = true;
+        }
+    }
+}
+
+/// Whether a function or closure in `stmts`, other than in `stmts[skip]`, mentions `name`
+fn closures_mention(stmts: &[Stmt], skip: usize, name: &Atom) -> bool {
+    let mut visitor = ClosureMentions {
+        name: name.clone(),
+        depth: 0,
+        found: false,
+    };
+    for (idx, stmt) in stmts.iter().enumerate() {
+        if idx != skip {
+            stmt.visit_with(&mut visitor);
+        }
+    }
+    visitor.found
+}
+
+#[derive(Default)]
+struct IdentNames {
+    names: Vec<Atom>,
+}
+
+impl Visit for IdentNames {
+    fn visit_ident(&mut self, node: &Ident) {
+        self.names.push(node.sym.clone());
+    }
+}
+
+/// Initializers that can be evaluated at another position with the same result and
+/// without observable side effects: literals, functions and literal containers of those
+fn is_movable_init(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Fn(_) | Expr::Arrow(_) => true,
+        Expr::Paren(paren) => is_movable_init(&paren.expr),
+        Expr::Unary(unary) => unary.op != UnaryOp::Delete && matches!(&*unary.arg, Expr::Lit(_)),
+        Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+        Expr::Array(array) => array.elems.iter().all(|elem| match elem {
+            Some(ExprOrSpread { spread: None, expr }) => is_movable_init(expr),
+            Some(_) => false,
+            None => true,
+        }),
+        Expr::Object(object) => object.props.iter().all(|prop| match prop {
+            PropOrSpread::Prop(prop) => match &**prop {
+                Prop::KeyValue(kv) => !matches!(kv.key, PropName::Computed(_)) && is_movable_init(&kv.value),
+                _ => false,
+            },
+            PropOrSpread::Spread(_) => false,
+        }),
+        _ => false,
+    }
+}
+
+/// The names declared by a var declaration, if every declarator binds a plain identifier
+/// and has a movable initializer
+fn movable_decl_names(var: &VarDecl) -> Option<Vec<Atom>> {
+    var.decls
+        .iter()
+        .map(|decl| {
+            let Pat::Ident(binding) = &decl.name else {
+                return None;
+            };
+            match &decl.init {
+                Some(init) if !is_movable_init(init) => None,
+                _ => Some(binding.id.sym.clone()),
+            }
+        })
+        .collect()
+}
+
+fn is_directive(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(_))))
+}
+
+fn hoist_candidate(stmts: &[Stmt], from: usize, to: usize) -> bool {
+    match &stmts[from] {
+        // function declarations are hoisted anyway, only their order matters if a name is
+        // declared twice
+        Stmt::Decl(Decl::Fn(func)) => !stmts.iter().enumerate().any(|(idx, stmt)| {
+            idx != from && matches!(stmt, Stmt::Decl(Decl::Fn(other)) if other.ident.sym == func.ident.sym)
+        }),
+        // nothing before the declaration may see the initialized value, including
+        // functions declared after it but called before
+        Stmt::Decl(Decl::Var(var)) if var.kind == VarDeclKind::Var => match movable_decl_names(var) {
+            Some(names) => names
+                .iter()
+                .all(|name| !mentions(&stmts[to..from], name) && !closures_mention(stmts, from, name)),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// The block `stmt` would receive a sunk declaration of `name`: a plain block, or the
+/// only branch of an `if` that references it
+fn sink_target<'a>(stmt: &'a mut Stmt, name: &Atom) -> Option<&'a mut BlockStmt> {
+    match stmt {
+        Stmt::Block(block) => Some(block),
+        Stmt::If(if_stmt) => {
+            let in_test = mentions_in(&*if_stmt.test, name);
+            let in_cons = mentions_in(&*if_stmt.cons, name);
+            let in_alt = if_stmt.alt.as_deref().is_some_and(|alt| mentions_in(alt, name));
+            match (in_test, in_cons, in_alt) {
+                (false, true, false) => match &mut *if_stmt.cons {
+                    Stmt::Block(block) => Some(block),
+                    _ => None,
+                },
+                (false, false, true) => match if_stmt.alt.as_deref_mut() {
+                    Some(Stmt::Block(block)) => Some(block),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Index of the statement a `let`/`const` at `from` can be sunk into
+fn sink_candidate(stmts: &mut [Stmt], from: usize) -> Option<usize> {
+    let Stmt::Decl(Decl::Var(var)) = &stmts[from] else {
+        return None;
+    };
+    if var.kind == VarDeclKind::Var || var.decls.len() != 1 {
+        return None;
+    }
+    let name = movable_decl_names(var)?.pop()?;
+    // names the initializer's closures capture must resolve the same inside the block
+    let mut captured = IdentNames::default();
+    if let Some(init) = &var.decls[0].init {
+        init.visit_with(&mut captured);
+    }
+
+    // the declaration must be the only thing outside the block to mention the name, a
+    // function declared before it would lose the binding
+    let into = (from + 1..stmts.len()).find(|&idx| mentions_in(&stmts[idx], &name))?;
+    if mentions(&stmts[..from], &name) || mentions(&stmts[into + 1..], &name) {
+        return None;
+    }
+    let block = sink_target(&mut stmts[into], &name)?;
+    let mut declared = DeclaredNames::default();
+    block.visit_with(&mut declared);
+    if declared.names.contains(&name) || captured.names.iter().any(|name| declared.names.contains(name)) {
+        return None;
+    }
+    Some(into)
+}
+
+fn find_moves(stmts: &mut [Stmt], at_function_top_level: bool) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let prologue = stmts.iter().take_while(|stmt| is_directive(stmt)).count();
+    for from in 0..stmts.len() {
+        if at_function_top_level && from > prologue && hoist_candidate(stmts, from, prologue) {
+            moves.push(Move::Hoist { from, to: prologue });
+        }
+        if let Some(into) = sink_candidate(stmts, from) {
+            moves.push(Move::Sink { from, into });
+        }
+    }
+    moves
+}
+
+fn apply_move(stmts: &mut Vec<Stmt>, mv: Move) {
+    match mv {
+        Move::Hoist { from, to } => {
+            let stmt = stmts.remove(from);
+            stmts.insert(to, stmt);
+        }
+        Move::Sink { from, into } => {
+            let Stmt::Decl(Decl::Var(var)) = &stmts[from] else {
+                return;
+            };
+            let Pat::Ident(binding) = &var.decls[0].name else {
+                return;
+            };
+            let name = binding.id.sym.clone();
+            let stmt = stmts.remove(from);
+            // the target shifted down by one
+            if let Some(block) = sink_target(&mut stmts[into - 1], &name) {
+                block.stmts.insert(0, stmt);
+            }
+        }
+    }
+}
+
+impl VisitMut for HoistSinkVisitor {
+    scoped_visit_mut_methods!();
+
+    // case clauses share the scope of the whole switch, so a declaration can be referenced
+    // from other clauses; leave them alone
+    fn visit_mut_switch_case(&mut self, node: &mut SwitchCase) {
+        node.test.visit_mut_with(self);
+        for stmt in node.cons.iter_mut() {
+            stmt.visit_mut_with(self);
+        }
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let at_function_top_level = self.scope_state.scopes.at_function_top_level();
+        for mv in find_moves(stmts, at_function_top_level) {
+            let is_target = Some(self.crt_idx) == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                apply_move(stmts, mv);
+                break;
+            }
+        }
+        for stmt in stmts.iter_mut() {
+            stmt.visit_mut_with(self);
+        }
+    }
+}
+
+impl AstMutator for HoistSinkMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = HoistSinkVisitor {
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+        };
+        ast.visit_mut_with(&mut counter);
+        if counter.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let mut visitor = HoistSinkVisitor {
+            idx_to_mutate: Some(rng.random_range(0..counter.crt_idx)),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    fn mutate(source: &str) -> String {
+        let ast = parse_js(source.to_string()).expect("failed to parse test script");
+        let mutated = HoistSinkMutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+        String::from_utf8(generate_js(mutated).unwrap()).unwrap()
+    }
+
+    fn normalize(source: &str) -> String {
+        String::from_utf8(generate_js(parse_js(source.to_string()).unwrap()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn safe_hoists_are_applied() {
+        assert_eq!(
+            mutate("f0(); function f0() { return 1; }"),
+            normalize("function f0() { return 1; } f0();")
+        );
+        assert_eq!(
+            mutate("function f0() { \"use strict\"; print(1); var v0 = [1, 2]; return v0; }"),
+            normalize("function f0() { \"use strict\"; var v0 = [1, 2]; print(1); return v0; }")
+        );
+    }
+
+    #[test]
+    fn safe_sink_is_applied() {
+        assert_eq!(
+            mutate("let v0 = 1; print(2); { print(v0); }"),
+            normalize("print(2); { let v0 = 1; print(v0); }")
+        );
+    }
+
+    #[test]
+    fn unsafe_moves_are_skipped() {
+        let unchanged = [
+            // referenced after the block
+            "let v0 = 1; { print(v0); } print(v0);",
+            // the block declares the same name
+            "let v0 = 1; { let v0 = 2; print(v0); }",
+            // the initializer has side effects
+            "let v0 = f(); { print(v0); }",
+            // the if test references it
+            "let v0 = 1; if (v0) { print(v0); }",
+            // observed before the declaration
+            "print(v0); var v0 = 1;",
+            // a later declaration of the same function wins
+            "function f0() { return 1; } print(f0()); function f0() { return 2; }",
+            // blocks aren't hoisting targets
+            "{ print(1); var v0 = 1; }",
+            // other case clauses can reference the declaration
+            "switch (1) { case 1: let v0 = 1; { print(v0); } case 2: print(v0); }",
+            // a function declared before it uses the binding
+            "function g(){ return v0 } let v0 = 1; { print(v0); g(); }",
+        ];
+        for source in unchanged {
+            assert_eq!(mutate(source), normalize(source), "moved in {}", source);
+        }
+
+        // a function declared after the var is called before the initialization, only the
+        // function may move
+        let source = "g(); var v0 = 1; function g(){ print(v0) }";
+        for _ in 0..8 {
+            let code = mutate(source);
+            assert!(code.find("g();") < code.find("var v0"), "v0 was hoisted in {}", code);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_bin, build_block, build_ident, build_ident_expr_from_str, build_num, build_property_call,
+    build_random_literal, build_random_primitive_literal, build_return, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_types::JsObjectType;
+use crate::mutators::scope::{NameCollector, VarRenamer};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// IteratorMutator
+/// Defines an object implementing the iterator protocol and consumes it, e.g.
+/// `let v3 = { [Symbol.iterator]() { let v4 = 0; return { next() { ... } }; } };`
+/// followed by `[...v3]`, `for (const v5 of v3) {}` or `Array.from(v3)`.
+///
+/// The iterators lie about `done`/`value`, may throw midway or have a bogus `return()`,
+/// but `next` always reports `done: true` after at most `MAX_STEPS` calls so that consuming
+/// them terminates.
+pub struct IteratorMutator;
+
+const MAX_STEPS: u32 = 8;
+
+struct IteratorMutatorVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    renamer: VarRenamer,
+}
+
+/// `<name>() { <body> }`
+fn build_method(key: PropName, body: Vec<Stmt>) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
+        key,
+        function: Box::new(Function {
+            params: Vec::new(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(build_block(body)),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    })))
+}
+
+fn build_object(props: Vec<PropOrSpread>) -> Expr {
+    Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props,
+    })
+}
+
+/// `{ value: <value>, done: <done> }`
+fn build_iter_result(value: Expr, done: Expr) -> Expr {
+    let prop = |key: &str, value: Expr| {
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(IdentName::new(Atom::from(key), DUMMY_SP)),
+            value: Box::new(value),
+        })))
+    };
+    build_object(vec![prop("value", value), prop("done", done)])
+}
+
+fn build_if(test: Expr, cons: Stmt) -> Stmt {
+    Stmt::If(IfStmt {
+        span: DUMMY_SP,
+        test: Box::new(test),
+        cons: Box::new(cons),
+        alt: None,
+    })
+}
+
+impl IteratorMutatorVisitor {
+    /// Mostly honest `done: false`, sometimes an early `true` or a non-boolean
+    fn random_done(&mut self) -> Expr {
+        match self.rng.random_range(0..6) {
+            0 => build_random_literal(&mut self.rng, JsObjectType::Boolean),
+            1 => build_random_primitive_literal(&mut self.rng),
+            _ => Expr::Lit(Lit::Bool(Bool {
+                span: DUMMY_SP,
+                value: false,
+            })),
+        }
+    }
+
+    /// `next() { <step>++; if (<step> > N) return { value: undefined, done: true }; ... }`
+    fn build_next(&mut self, step: &str) -> PropOrSpread {
+        let limit = self.rng.random_range(0..=MAX_STEPS);
+        let mut body = vec![
+            Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Update(UpdateExpr {
+                    span: DUMMY_SP,
+                    op: UpdateOp::PlusPlus,
+                    prefix: false,
+                    arg: Box::new(build_ident_expr_from_str(step)),
+                })),
+            }),
+            build_if(
+                build_bin(BinaryOp::Gt, build_ident_expr_from_str(step), build_num(limit as f64)),
+                build_return(build_iter_result(
+                    build_ident_expr_from_str("undefined"),
+                    Expr::Lit(Lit::Bool(Bool {
+                        span: DUMMY_SP,
+                        value: true,
+                    })),
+                )),
+            ),
+        ];
+        if limit > 0 && self.rng.random_bool(0.3) {
+            let throw_at = self.rng.random_range(1..=limit);
+            let thrown = build_random_primitive_literal(&mut self.rng);
+            body.push(build_if(
+                build_bin(BinaryOp::EqEqEq, build_ident_expr_from_str(step), build_num(throw_at as f64)),
+                Stmt::Throw(ThrowStmt {
+                    span: DUMMY_SP,
+                    arg: Box::new(thrown),
+                }),
+            ));
+        }
+        let value = build_random_primitive_literal(&mut self.rng);
+        let done = self.random_done();
+        body.push(build_return(build_iter_result(value, done)));
+        build_method(PropName::Ident(IdentName::new(Atom::from("next"), DUMMY_SP)), body)
+    }
+
+    /// `let <name> = { [Symbol.iterator]() { let <step> = 0; return { next() {...} }; } };`
+    fn build_iterable(&mut self, name: &str, step: &str) -> Stmt {
+        let mut iterator_props = vec![self.build_next(step)];
+        // called when the consumer stops early; returning a non-object is a TypeError
+        if self.rng.random_bool(0.3) {
+            let returned = build_random_primitive_literal(&mut self.rng);
+            iterator_props.push(build_method(
+                PropName::Ident(IdentName::new(Atom::from("return"), DUMMY_SP)),
+                vec![build_return(returned)],
+            ));
+        }
+        let iterator_method = build_method(
+            PropName::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Member(MemberExpr {
+                    span: DUMMY_SP,
+                    obj: Box::new(build_ident_expr_from_str("Symbol")),
+                    prop: MemberProp::Ident(IdentName::new(Atom::from("iterator"), DUMMY_SP)),
+                })),
+            }),
+            vec![
+                Stmt::Decl(Decl::Var(Box::new(build_var_decl(step, build_num(0.0))))),
+                build_return(build_object(iterator_props)),
+            ],
+        );
+        Stmt::Decl(Decl::Var(Box::new(build_var_decl(name, build_object(vec![iterator_method])))))
+    }
+
+    /// A statement iterating over `name`
+    fn build_consumer(&mut self, name: &str) -> Stmt {
+        let iterable = build_ident_expr_from_str(name);
+        let expr = match self.rng.random_range(0..4) {
+            0 => Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: vec![Some(ExprOrSpread {
+                    spread: Some(DUMMY_SP),
+                    expr: Box::new(iterable),
+                })],
+            }),
+            1 => build_property_call("Array", "from", vec![iterable]),
+            2 => {
+                let item = self.renamer.next_var_name();
+                let mut decl = build_var_decl(&item, iterable);
+                decl.kind = VarDeclKind::Const;
+                decl.decls[0].init = None;
+                return Stmt::ForOf(ForOfStmt {
+                    span: DUMMY_SP,
+                    is_await: false,
+                    left: ForHead::VarDecl(Box::new(decl)),
+                    right: Box::new(build_ident_expr_from_str(name)),
+                    body: Box::new(Stmt::Block(build_block(Vec::new()))),
+                });
+            }
+            _ => {
+                // `let [a, b] = <name>;` stops early and triggers `return()`
+                let elems = (0..self.rng.random_range(1..=3))
+                    .map(|_| {
+                        Some(Pat::Ident(BindingIdent {
+                            id: build_ident(&self.renamer.next_var_name()),
+                            type_ann: None,
+                        }))
+                    })
+                    .collect();
+                let mut decl = build_var_decl(name, iterable);
+                decl.decls[0].name = Pat::Array(ArrayPat {
+                    span: DUMMY_SP,
+                    elems,
+                    optional: false,
+                    type_ann: None,
+                });
+                return Stmt::Decl(Decl::Var(Box::new(decl)));
+            }
+        };
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+        })
+    }
+
+    /// The iterable and a consumer wrapped in `try {} catch {}` since the iterator may throw
+    fn build_iterator_stmts(&mut self) -> Vec<Stmt> {
+        let name = self.renamer.next_var_name();
+        let step = self.renamer.next_var_name();
+        let iterable = self.build_iterable(&name, &step);
+        let consumer = self.build_consumer(&name);
+        vec![
+            iterable,
+            Stmt::Try(Box::new(TryStmt {
+                span: DUMMY_SP,
+                block: build_block(vec![consumer]),
+                handler: Some(CatchClause {
+                    span: DUMMY_SP,
+                    param: None,
+                    body: build_block(Vec::new()),
+                }),
+                finalizer: None,
+            })),
+        ]
+    }
+}
+
+impl VisitMut for IteratorMutatorVisitor {
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut i = 0;
+        while i < stmts.len() {
+            stmts[i].visit_mut_with(self);
+            let is_target = self.crt_idx == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                let new_stmts = self.build_iterator_stmts();
+                let count = new_stmts.len();
+                stmts.splice(i + 1..i + 1, new_stmts);
+                i += count;
+            }
+            i += 1;
+        }
+    }
+}
+
+impl AstMutator for IteratorMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = IteratorMutatorVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            renamer: VarRenamer::new(names.var_names),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    #[test]
+    fn iterator_round_trips() {
+        for (code, reparsed) in assert_mutations_reparse(&IteratorMutator, "let v0 = 1;", 16) {
+            assert!(code.contains("[Symbol.iterator]"), "no iterator defined in {}", code);
+            assert!(code.contains("done: true"), "iterator is unbounded in {}", code);
+
+            assert_eq!(reparsed.body.len(), 3, "unexpected statements in {}", code);
+            assert!(matches!(reparsed.body[2], Stmt::Try(_)), "consumer is not guarded in {}", code);
+            // the existing name is not reused
+            assert!(!code.contains("let v0 = {"), "v0 was redeclared in {}", code);
+        }
+    }
+}
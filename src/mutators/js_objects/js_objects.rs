@@ -10,7 +10,7 @@ use crate::mutators::js_objects::js_types::*;
 pub struct JsGlobalObject {
     sym: String,
     methods: Vec<JsMethod>,
-    properties: Vec<String>,
+    properties: Vec<JsProperty>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -27,7 +27,7 @@ pub struct JsMethod {
     returns: Option<JsObjectType>,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct JsProperty {
     sym: String,
     kind: JsMethodKind
@@ -65,7 +65,13 @@ impl JsGlobalObject {
                     returns: *returns,
                 })
                 .collect(),
-            properties: properties.iter().map(|(sym, _)| sym.to_string()).collect(),
+            properties: properties
+                .iter()
+                .map(|(sym, kind)| JsProperty {
+                    sym: sym.to_string(),
+                    kind: *kind,
+                })
+                .collect(),
         }
     }
 
@@ -107,6 +113,17 @@ impl JsGlobalObject {
             .collect()
     }
 
+    pub fn properties(&self) -> &[JsProperty] {
+        &self.properties
+    }
+
+    pub fn static_properties(&self) -> Vec<&JsProperty> {
+        self.properties
+            .iter()
+            .filter(|property| property.kind() == JsMethodKind::Static)
+            .collect()
+    }
+
     pub fn from_js_type(ty: JsObjectType) -> JsGlobalObject {
         match ty {
             JsObjectType::Array => get_global_object("Array").unwrap(),
@@ -148,6 +165,16 @@ impl JsMethod {
     }
 }
 
+impl JsProperty {
+    pub fn sym(&self) -> &str {
+        &self.sym
+    }
+
+    pub fn kind(&self) -> JsMethodKind {
+        self.kind
+    }
+}
+
 pub fn get_random_global_object(rng: &mut rand::rngs::ThreadRng) -> JsGlobalObject {
     let global_objects = JS_GLOBAL_OBJECTS.clone();
     // global_objects.choose(rng)
@@ -160,6 +187,11 @@ pub fn get_global_object(sym: &str) -> Option<JsGlobalObject> {
     JS_GLOBAL_OBJECTS.iter().find(|obj| obj.sym == sym).cloned()
 }
 
+/// Names of every global object in the catalog
+pub fn get_global_object_names() -> Vec<&'static str> {
+    JS_GLOBAL_OBJECTS.iter().map(|obj| obj.sym()).collect()
+}
+
 use JsMethodKind::*;
 use JsObjectType::*;
 
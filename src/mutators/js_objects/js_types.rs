@@ -18,7 +18,7 @@ pub enum JsObjectType {
 }
 
 impl JsObjectType {
-    pub fn random_primitive_type(rng: &mut rand::rngs::ThreadRng) -> JsObjectType {
+    pub fn random_primitive_type(rng: &mut impl Rng) -> JsObjectType {
         let choice = rng.random_range(0..5);
         match choice {
             0 => JsObjectType::Boolean,
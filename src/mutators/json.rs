@@ -0,0 +1,389 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_assign, build_expr_stmt, build_ident, build_ident_expr_from_str, build_num, build_random_literal,
+    build_random_primitive_literal, build_str_lit, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::js_objects::js_types::JsObjectType;
+use crate::mutators::scope::{
+    NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods, visit_mut_stmts_inserting_after,
+};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// JsonRoundtripMutator
+/// Sends a value of the program through JSON, e.g.
+/// `let v2 = JSON.parse(JSON.stringify(v0));`,
+/// `let v2 = JSON.stringify(v0, function (v3, v4) { delete this[v3]; return v4; }, 2);` or
+/// `let v2 = JSON.parse(JSON.stringify(v0), function (v3, v4) { v1.length = 0; return v4; });`.
+///
+/// Plain round trips take the fast paths of the serializer and the parser. Replacers and
+/// revivers run user code for every key while the engine walks the value, and change the
+/// holder or another object under its feet. Which JSON methods and arities are used
+/// comes from the catalog.
+pub struct JsonRoundtripMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonKind {
+    RoundTrip, // JSON.parse(JSON.stringify(v0))
+    Replacer,  // JSON.stringify(v0, function (k, v) { ... })
+    Reviver,   // JSON.parse(JSON.stringify(v0), function (k, v) { ... })
+}
+
+const JSON_KINDS: [JsonKind; 3] = [JsonKind::RoundTrip, JsonKind::Replacer, JsonKind::Reviver];
+
+/// Keys kept by array replacers
+const REPLACER_KEYS: &[&str] = &["0", "1", "a", "b", "length", "x"];
+
+impl JsonKind {
+    /// The `(method, argument count)` calls the kind makes at most
+    fn calls(self) -> &'static [(&'static str, usize)] {
+        match self {
+            JsonKind::RoundTrip => &[("stringify", 1), ("parse", 1)],
+            JsonKind::Replacer => &[("stringify", 3)],
+            JsonKind::Reviver => &[("stringify", 1), ("parse", 2)],
+        }
+    }
+}
+
+/// Kinds whose calls the `JSON` catalog entry has signatures for
+fn supported_kinds() -> Vec<JsonKind> {
+    let Some(json) = get_global_object("JSON") else {
+        return Vec::new();
+    };
+    let supports = |name: &str, argc: usize| {
+        json.static_methods()
+            .iter()
+            .any(|method| method.sym() == name && method.signatures().iter().any(|sig| sig.types().len() == argc))
+    };
+    JSON_KINDS
+        .into_iter()
+        .filter(|kind| kind.calls().iter().all(|&(name, argc)| supports(name, argc)))
+        .collect()
+}
+
+fn build_json_call(method: &str, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(build_ident_expr_from_str("JSON")),
+            prop: MemberProp::Ident(IdentName::new(Atom::from(method), DUMMY_SP)),
+        }))),
+        args: args
+            .into_iter()
+            .map(|arg| ExprOrSpread {
+                spread: None,
+                expr: Box::new(arg),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+/// `this[<key>]`, the holder of the key being visited
+fn build_holder_member(key: &str) -> MemberExpr {
+    MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(Expr::This(ThisExpr { span: DUMMY_SP })),
+        prop: MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(build_ident_expr_from_str(key)),
+        }),
+    }
+}
+
+/// `function (<key>, <value>) { <stmts> }`
+fn build_callback(key: &str, value: &str, stmts: Vec<Stmt>) -> Expr {
+    Expr::Fn(FnExpr {
+        ident: None,
+        function: Box::new(Function {
+            params: [key, value]
+                .into_iter()
+                .map(|name| Param {
+                    span: DUMMY_SP,
+                    decorators: Vec::new(),
+                    pat: Pat::Ident(BindingIdent {
+                        id: build_ident(name),
+                        type_ann: None,
+                    }),
+                })
+                .collect(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts,
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    })
+}
+
+struct JsonVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+    kind: JsonKind,
+}
+
+impl ScopedAstVisitor for JsonVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl JsonVisitor {
+    /// A change made while the engine walks the value: to the holder of the current key,
+    /// or to another value of the program
+    fn build_side_effect(&mut self, key: &str, values: &[Ident]) -> Option<Stmt> {
+        let expr = match self.rng.random_range(0..5) {
+            0 => Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Delete,
+                arg: Box::new(Expr::Member(build_holder_member(key))),
+            }),
+            1 => {
+                let value = build_random_primitive_literal(&mut self.rng);
+                build_assign(AssignOp::Assign, build_holder_member(key), value)
+            }
+            2 => {
+                // a new key on the holder, visited or not depending on the engine
+                let value = build_random_primitive_literal(&mut self.rng);
+                let member = MemberExpr {
+                    span: DUMMY_SP,
+                    obj: Box::new(Expr::This(ThisExpr { span: DUMMY_SP })),
+                    prop: MemberProp::Ident(IdentName::new(Atom::from("x"), DUMMY_SP)),
+                };
+                build_assign(AssignOp::Assign, member, value)
+            }
+            3 => {
+                let victim = values.choose(&mut self.rng)?;
+                let member = MemberExpr {
+                    span: DUMMY_SP,
+                    obj: Box::new(Expr::Ident(victim.clone())),
+                    prop: MemberProp::Ident(IdentName::new(Atom::from("length"), DUMMY_SP)),
+                };
+                build_assign(AssignOp::Assign, member, build_num(0.0))
+            }
+            _ => return None,
+        };
+        Some(build_expr_stmt(expr))
+    }
+
+    /// What the callback returns for a key: the value most of the time, otherwise
+    /// something replacing it, or `undefined` dropping the key
+    fn build_callback_result(&mut self, value: &str) -> Expr {
+        match self.rng.random_range(0..6) {
+            0 => build_ident_expr_from_str("undefined"),
+            1 => build_random_primitive_literal(&mut self.rng),
+            2 => Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: vec![Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(build_ident_expr_from_str(value)),
+                })],
+            }),
+            _ => build_ident_expr_from_str(value),
+        }
+    }
+
+    fn build_callback(&mut self, values: &[Ident]) -> Expr {
+        let key = self.renamer.next_var_name();
+        let value = self.renamer.next_var_name();
+        let mut stmts: Vec<Stmt> = self.build_side_effect(&key, values).into_iter().collect();
+        let result = self.build_callback_result(&value);
+        stmts.push(Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(result)),
+        }));
+        build_callback(&key, &value, stmts)
+    }
+
+    /// A replacer function, or an array of the keys to keep
+    fn build_replacer(&mut self, values: &[Ident]) -> Expr {
+        if self.rng.random_bool(0.2) {
+            let count = self.rng.random_range(1..=3);
+            let elems = REPLACER_KEYS
+                .choose_multiple(&mut self.rng, count)
+                .map(|key| {
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(Expr::Lit(Lit::Str(build_str_lit(key)))),
+                    })
+                })
+                .collect();
+            return Expr::Array(ArrayLit { span: DUMMY_SP, elems });
+        }
+        self.build_callback(values)
+    }
+
+    /// The indentation argument of `JSON.stringify`
+    fn build_space(&mut self) -> Expr {
+        match self.rng.random_range(0..3) {
+            0 => build_num(self.rng.random_range(0..=12) as f64),
+            1 => Expr::Lit(Lit::Str(build_str_lit("\t"))),
+            _ => build_random_primitive_literal(&mut self.rng),
+        }
+    }
+
+    fn build_json_stmt(&mut self) -> Stmt {
+        let values = self.scope_state.scopes.collect_idents();
+        let subject = match values.choose(&mut self.rng) {
+            Some(ident) => Expr::Ident(ident.clone()),
+            None => build_random_literal(&mut self.rng, JsObjectType::Object),
+        };
+        let value = match self.kind {
+            JsonKind::RoundTrip => build_json_call("parse", vec![build_json_call("stringify", vec![subject])]),
+            JsonKind::Replacer => {
+                let mut args = vec![subject, self.build_replacer(&values)];
+                if self.rng.random_bool(0.5) {
+                    args.push(self.build_space());
+                }
+                build_json_call("stringify", args)
+            }
+            JsonKind::Reviver => {
+                let text = build_json_call("stringify", vec![subject]);
+                let reviver = self.build_callback(&values);
+                build_json_call("parse", vec![text, reviver])
+            }
+        };
+        let name = self.renamer.next_var_name();
+        Stmt::Decl(Decl::Var(Box::new(build_var_decl(&name, value))))
+    }
+}
+
+impl VisitMut for JsonVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            is_target.then(|| visitor.build_json_stmt())
+        });
+    }
+}
+
+impl JsonRoundtripMutator {
+    fn mutate_with_kind(&self, mut ast: Script, kind: JsonKind, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = JsonVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+            kind,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for JsonRoundtripMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let Some(&kind) = supported_kinds().choose(rng) else {
+            return Ok(ast);
+        };
+        self.mutate_with_kind(ast, kind, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use swc_ecma_visit::Visit;
+
+    /// Methods of the `JSON.<method>(...)` calls and the functions passed to them
+    #[derive(Default)]
+    struct JsonCalls {
+        methods: Vec<String>,
+        callbacks: Vec<Function>,
+    }
+
+    impl Visit for JsonCalls {
+        fn visit_call_expr(&mut self, node: &CallExpr) {
+            node.visit_children_with(self);
+            let Callee::Expr(callee) = &node.callee else {
+                return;
+            };
+            let Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop),
+                ..
+            }) = &**callee
+            else {
+                return;
+            };
+            if !matches!(&**obj, Expr::Ident(ident) if &*ident.sym == "JSON") {
+                return;
+            }
+            self.methods.push(prop.sym.to_string());
+            for arg in &node.args {
+                if let Expr::Fn(FnExpr { function, .. }) = &*arg.expr {
+                    self.callbacks.push((**function).clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn json_round_trips_with_valid_callbacks() {
+        assert_eq!(supported_kinds(), JSON_KINDS.to_vec(), "catalog lacks JSON signatures");
+        let source = "let v0 = { a: 1, b: [1, 2] }; let v1 = [v0];";
+        for kind in JSON_KINDS {
+            let mutants = assert_mutations_reparse_with(source, 32, |ast, rng| {
+                JsonRoundtripMutator.mutate_with_kind(ast, kind, rng)
+            });
+            for (code, reparsed) in mutants {
+                assert_eq!(reparsed.body.len(), 3, "no statement inserted in {}", code);
+
+                let mut calls = JsonCalls::default();
+                reparsed.visit_with(&mut calls);
+                assert!(calls.methods.iter().any(|method| method == "stringify"), "no stringify in {}", code);
+                if kind != JsonKind::Replacer {
+                    assert!(calls.methods.iter().any(|method| method == "parse"), "no parse in {}", code);
+                }
+                if kind == JsonKind::Reviver {
+                    assert_eq!(calls.callbacks.len(), 1, "no reviver in {}", code);
+                }
+                for callback in &calls.callbacks {
+                    assert_eq!(callback.params.len(), 2, "callback doesn't take key and value in {}", code);
+                    let body = callback.body.as_ref().expect("callback without body");
+                    assert!(
+                        matches!(body.stmts.last(), Some(Stmt::Return(ReturnStmt { arg: Some(_), .. }))),
+                        "callback doesn't return a value in {}",
+                        code
+                    );
+                }
+            }
+        }
+    }
+}
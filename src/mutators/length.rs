@@ -0,0 +1,333 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::DUMMY_SP;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_bin, build_block, build_expr_stmt, build_ident_expr_from_str, build_member, build_num, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::{JsMethodKind, get_global_object};
+use crate::mutators::scope::{
+    NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods, visit_mut_stmts_inserting_after,
+};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// LengthMutator
+/// Inserts `.length` writes on arrays and `.length` reads on arrays and strings, e.g.
+/// `try { v0.length = v0.length + 3; } catch {}`, `v1[v1.length - 1];` or
+/// `for (let v2 = 0; v2 < v0.length && v2 < 64; v2++) { v0[v2]; }`.
+///
+/// Writes grow, shrink, clear or set a non-integer length (a RangeError). Loops are also
+/// bounded by a constant since their body or a getter may grow the length.
+pub struct LengthMutator;
+
+/// Trip count cap of the inserted loops
+const MAX_TRIP_COUNT: f64 = 64.0;
+
+/// Lengths that aren't valid array lengths
+const INVALID_LENGTHS: &[f64] = &[-1.0, 1.5, f64::NAN, 4294967296.0];
+
+/// The name of the `length` instance property of `object` in the catalog
+fn length_property(object: &str) -> Option<String> {
+    get_global_object(object)?
+        .properties()
+        .iter()
+        .find(|property| property.sym() == "length" && property.kind() == JsMethodKind::Instance)
+        .map(|property| property.sym().to_string())
+}
+
+/// Names declared with an array or a string as initializer
+struct LengthTargets {
+    arrays: HashSet<Atom>,
+    strings: HashSet<Atom>,
+}
+
+fn is_array_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Array(_) => true,
+        Expr::New(NewExpr { callee, .. }) => matches!(&**callee, Expr::Ident(ident) if &*ident.sym == "Array"),
+        Expr::Call(CallExpr {
+            callee: Callee::Expr(callee),
+            ..
+        }) => match &**callee {
+            Expr::Ident(ident) => &*ident.sym == "Array",
+            Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop),
+                ..
+            }) => {
+                matches!(&**obj, Expr::Ident(ident) if &*ident.sym == "Array")
+                    && (&*prop.sym == "from" || &*prop.sym == "of")
+            }
+            _ => false,
+        },
+        Expr::Paren(ParenExpr { expr, .. }) => is_array_expr(expr),
+        _ => false,
+    }
+}
+
+fn is_string_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(Lit::Str(_)) | Expr::Tpl(_) => true,
+        Expr::Paren(ParenExpr { expr, .. }) => is_string_expr(expr),
+        _ => false,
+    }
+}
+
+impl Visit for LengthTargets {
+    fn visit_var_declarator(&mut self, decl: &VarDeclarator) {
+        decl.visit_children_with(self);
+        let (Pat::Ident(binding), Some(init)) = (&decl.name, &decl.init) else {
+            return;
+        };
+        if is_array_expr(init) {
+            self.arrays.insert(binding.id.sym.clone());
+        } else if is_string_expr(init) {
+            self.strings.insert(binding.id.sym.clone());
+        }
+    }
+}
+
+struct LengthMutatorVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+    targets: LengthTargets,
+    array_length: Option<String>,
+    string_length: Option<String>,
+}
+
+impl ScopedAstVisitor for LengthMutatorVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+/// `<obj>[<index>]`
+fn build_index(obj: &Ident, index: Expr) -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(Expr::Ident(obj.clone())),
+        prop: MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(index),
+        }),
+    })
+}
+
+impl LengthMutatorVisitor {
+    /// `try { <array>.length = <new length>; } catch {}`
+    fn build_write(&mut self, array: &Ident, length: &str) -> Stmt {
+        let new_length = match self.rng.random_range(0..5) {
+            0 => build_bin(
+                BinaryOp::Add,
+                build_member(Expr::Ident(array.clone()), length),
+                build_num(self.rng.random_range(1..=16) as f64),
+            ),
+            1 => build_bin(
+                BinaryOp::Sub,
+                build_member(Expr::Ident(array.clone()), length),
+                build_num(self.rng.random_range(1..=4) as f64),
+            ),
+            2 => build_num(0.0),
+            3 => build_num(self.rng.random_range(0..=32) as f64),
+            _ => {
+                let invalid = *INVALID_LENGTHS.choose(&mut self.rng).expect("INVALID_LENGTHS is not empty");
+                if invalid.is_nan() {
+                    build_ident_expr_from_str("NaN")
+                } else if invalid < 0.0 {
+                    Expr::Unary(UnaryExpr {
+                        span: DUMMY_SP,
+                        op: UnaryOp::Minus,
+                        arg: Box::new(build_num(-invalid)),
+                    })
+                } else {
+                    build_num(invalid)
+                }
+            }
+        };
+        let assign = Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(array.clone())),
+                prop: MemberProp::Ident(IdentName::new(Atom::from(length), DUMMY_SP)),
+            })),
+            right: Box::new(new_length),
+        });
+        Stmt::Try(Box::new(TryStmt {
+            span: DUMMY_SP,
+            block: build_block(vec![build_expr_stmt(assign)]),
+            handler: Some(CatchClause {
+                span: DUMMY_SP,
+                param: None,
+                body: build_block(Vec::new()),
+            }),
+            finalizer: None,
+        }))
+    }
+
+    /// `<target>[<target>.length - k];`, reading the last elements or just past the end
+    fn build_indexed_read(&mut self, target: &Ident, length: &str) -> Stmt {
+        let offset = self.rng.random_range(0..=2);
+        let index = if offset == 0 {
+            build_member(Expr::Ident(target.clone()), length)
+        } else {
+            build_bin(BinaryOp::Sub, build_member(Expr::Ident(target.clone()), length), build_num(offset as f64))
+        };
+        build_expr_stmt(build_index(target, index))
+    }
+
+    /// `for (let i = 0; i < <target>.length && i < MAX_TRIP_COUNT; i++) { <target>[i]; }`
+    fn build_loop(&mut self, target: &Ident, length: &str) -> Stmt {
+        let counter = self.renamer.next_var_name();
+        let target_length = build_member(Expr::Ident(target.clone()), length);
+        let test = build_bin(
+            BinaryOp::LogicalAnd,
+            build_bin(BinaryOp::Lt, build_ident_expr_from_str(&counter), target_length),
+            build_bin(BinaryOp::Lt, build_ident_expr_from_str(&counter), build_num(MAX_TRIP_COUNT)),
+        );
+        let element = build_index(target, build_ident_expr_from_str(&counter));
+        Stmt::For(ForStmt {
+            span: DUMMY_SP,
+            init: Some(VarDeclOrExpr::VarDecl(Box::new(build_var_decl(&counter, build_num(0.0))))),
+            test: Some(Box::new(test)),
+            update: Some(Box::new(Expr::Update(UpdateExpr {
+                span: DUMMY_SP,
+                op: UpdateOp::PlusPlus,
+                prefix: false,
+                arg: Box::new(build_ident_expr_from_str(&counter)),
+            }))),
+            body: Box::new(Stmt::Block(build_block(vec![build_expr_stmt(element)]))),
+        })
+    }
+
+    /// A length write or read on an array or string in scope, None if there is none
+    fn build_length_stmt(&mut self) -> Option<Stmt> {
+        let in_scope = self.scope_state.scopes.collect_idents();
+        let arrays: Vec<Ident> = match &self.array_length {
+            Some(_) => in_scope
+                .iter()
+                .filter(|ident| self.targets.arrays.contains(&ident.sym))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        let strings: Vec<Ident> = match &self.string_length {
+            Some(_) => in_scope
+                .iter()
+                .filter(|ident| self.targets.strings.contains(&ident.sym))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let use_array = match (arrays.is_empty(), strings.is_empty()) {
+            (true, true) => return None,
+            (false, true) => true,
+            (true, false) => false,
+            (false, false) => self.rng.random_bool(0.7),
+        };
+        if use_array {
+            let array = arrays.choose(&mut self.rng).cloned()?;
+            let length = self.array_length.clone()?;
+            Some(match self.rng.random_range(0..3) {
+                0 => self.build_write(&array, &length),
+                1 => self.build_indexed_read(&array, &length),
+                _ => self.build_loop(&array, &length),
+            })
+        } else {
+            let string = strings.choose(&mut self.rng).cloned()?;
+            let length = self.string_length.clone()?;
+            Some(if self.rng.random_bool(0.5) {
+                self.build_indexed_read(&string, &length)
+            } else {
+                self.build_loop(&string, &length)
+            })
+        }
+    }
+}
+
+impl VisitMut for LengthMutatorVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            if is_target { visitor.build_length_stmt() } else { None }
+        });
+    }
+}
+
+impl AstMutator for LengthMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut targets = LengthTargets {
+            arrays: HashSet::new(),
+            strings: HashSet::new(),
+        };
+        ast.visit_with(&mut targets);
+        if targets.arrays.is_empty() && targets.strings.is_empty() {
+            return Ok(ast);
+        }
+
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = LengthMutatorVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+            targets,
+            array_length: length_property("Array"),
+            string_length: length_property("String"),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    #[test]
+    fn length_round_trips() {
+        let source = "let v0 = [1, 2, 3]; let v1 = \"abc\";";
+        for (code, reparsed) in assert_mutations_reparse(&LengthMutator, source, 32) {
+            // v0 is in scope after either statement
+            assert_eq!(reparsed.body.len(), 3, "nothing inserted in {}", code);
+            assert!(code.contains(".length"), "no length access in {}", code);
+            assert!(!code.contains("v1.length ="), "string length written in {}", code);
+            if code.contains("for (") {
+                assert!(code.contains("< 64"), "loop is not bounded in {}", code);
+            }
+        }
+    }
+
+    #[test]
+    fn length_comes_from_the_catalog() {
+        assert_eq!(length_property("Array").as_deref(), Some("length"));
+        assert_eq!(length_property("NoSuchObject"), None);
+    }
+}
@@ -1,5 +1,7 @@
 use anyhow::Result;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::IndexedRandom;
 use swc_atoms::Atom;
 use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
@@ -14,7 +16,7 @@ use crate::mutators::scope::*;
 /// other variables from the current context.
 pub struct ArrayMutator;
 pub struct ArrayMutatorVisitor {
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
     idx_to_mutate: usize,
     crt_idx: usize,
     scope_state: ScopeState,
@@ -49,40 +51,43 @@ impl VisitMut for ArrayMutatorVisitor {
         self.crt_idx += 1;
 
         let original_len = node.elems.len();
-        let mut rng = rand::rng();
-        let new_len = if rng.random_bool(0.5) {
+        let new_len = if self.rng.random_bool(0.5) {
             // increase length
-            original_len + rng.random_range(1..=5)
+            original_len + self.rng.random_range(1..=5)
         } else {
             // decrease length
             if original_len == 0 {
                 0
             } else {
-                rng.random_range(0..original_len)
+                self.rng.random_range(0..original_len)
             }
         };
 
         if new_len > original_len {
             // add undefined elements
             // decide what type of elements to add
-            let choice = random_weighted_choice(
-                &mut self.rng,
-                &[
-                    ("smi", 30),
-                    ("float", 20),
-                    ("bigint", 20),
-                    ("nan", 5),
-                    ("null", 5),
-                    ("undefined", 5),
-                    ("boolean", 5),
-                    ("objects", 10),
-                    ("context_obj", 10)
-                ],
-            );
+            let obj_list = self.scope_state.scopes.collect_idents_and_functions();
+            let mut choices = vec![
+                ("smi", 30),
+                ("float", 20),
+                ("bigint", 20),
+                ("nan", 5),
+                ("null", 5),
+                ("undefined", 5),
+                ("boolean", 5),
+                ("objects", 10),
+                ("context_obj", 10)
+            ];
+            if obj_list.is_empty() {
+                // nothing in scope to swap in, so fall back to the other value types
+                // instead of leaving the targeted array untouched
+                choices.retain(|&(name, _)| name != "context_obj");
+            }
+            let choice = random_weighted_choice(&mut self.rng, &choices);
             match choice {
                 "smi" => {
                     for _ in original_len..new_len {
-                        let val = rng.random_range(-100i32..=100i32);
+                        let val = self.rng.random_range(-100i32..=100i32);
                         let lit = Lit::Num(Number {
                             span: Default::default(),
                             value: val as f64,
@@ -96,7 +101,7 @@ impl VisitMut for ArrayMutatorVisitor {
                 }
                 "float" => {
                     for _ in original_len..new_len {
-                        let val = rng.random_range(-100.0f64..=100.0f64);
+                        let val = self.rng.random_range(-100.0f64..=100.0f64);
                         let lit = Lit::Num(Number {
                             span: Default::default(),
                             value: val,
@@ -137,10 +142,6 @@ impl VisitMut for ArrayMutatorVisitor {
                 }
                 // pick random stuff from scope and swap in
                 "context_obj" => {
-                    let obj_list = self.scope_state.scopes.collect_idents_and_functions();
-                    if obj_list.len() == 0 {
-                        return;
-                    }
                     for _ in original_len..new_len {
                         let obj = obj_list.choose(&mut self.rng)
                             .expect("ArrayMutator: Failed to pick random context obj");
@@ -171,7 +172,7 @@ impl VisitMut for ArrayMutatorVisitor {
 }
 
 impl AstMutator for ArrayMutator {
-    fn mutate(&self, mut ast: Script) -> Result<Script> {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
         let mut counter = CountArrayLiterals { count: 0 };
         ast.visit_with(&mut counter);
         if counter.count == 0 {
@@ -180,10 +181,9 @@ impl AstMutator for ArrayMutator {
         }
 
         // randomly choose a literal index to mutate
-        let mut rng = rand::rng();
         let idx_to_mutate = rng.random_range(0..counter.count);
         let mut visitor = ArrayMutatorVisitor {
-            rng,
+            rng: StdRng::from_rng(rng),
             idx_to_mutate,
             crt_idx: 0,
             scope_state: ScopeState::new(),
@@ -192,3 +192,25 @@ impl AstMutator for ArrayMutator {
         Ok(ast)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::parse_js;
+    use crate::utils::rand_utils::unseeded_rng;
+
+    #[test]
+    fn array_is_mutated_with_empty_scope() {
+        for _ in 0..100 {
+            let ast = parse_js("[1, 2, 3];".to_string()).expect("failed to parse test script");
+            let mutated = ArrayMutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+            let Stmt::Expr(ExprStmt { expr, .. }) = &mutated.body[0] else {
+                panic!("expected an expression statement");
+            };
+            let Expr::Array(array) = &**expr else {
+                panic!("expected an array literal");
+            };
+            assert_ne!(array.elems.len(), 3);
+        }
+    }
+}
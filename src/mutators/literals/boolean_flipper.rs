@@ -1,5 +1,6 @@
 use anyhow::Result;
 use rand::Rng;
+use rand::rngs::StdRng;
 use rand::seq::IndexedRandom;
 use swc_atoms::Atom;
 use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
@@ -45,7 +46,7 @@ impl VisitMut for BooleanFlipperVisitor {
 }
 
 impl AstMutator for BooleanFlipper {
-    fn mutate(&self, mut ast: Script) -> Result<Script> {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
         let mut counter = CountBooleanLiterals { count: 0 };
         ast.visit_with(&mut counter);
         if counter.count == 0 {
@@ -54,7 +55,6 @@ impl AstMutator for BooleanFlipper {
         }
 
         // randomly choose a literal index to mutate
-        let mut rng = rand::rng();
         let idx_to_mutate = rng.random_range(0..counter.count);
         let mut visitor = BooleanFlipperVisitor {
             idx_to_mutate,
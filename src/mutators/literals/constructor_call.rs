@@ -1,497 +1,315 @@
-// use rand::prelude::IndexedRandom;
-// use rand::Rng;
-// use swc_atoms::Atom;
-// use swc_common::DUMMY_SP;
-// use swc_ecma_visit::{Visit, VisitWith, swc_ecma_ast::*};
-// use swc_ecma_visit::{VisitMut, VisitMutWith};
-
-// use crate::mutators::AstMutator;
-// use crate::mutators::js_objects::js_objects::{get_global_object, get_random_global_object, JsMethodKind};
-// use crate::mutators::js_objects::js_types::JsObjectType;
-// use crate::mutators::scope::IdentCollector;
-
-// /// ConstructorCall mutator
-// /// Picks random idents and wraps them in a constructor call
-// /// eg. let x = 5; => let x = new Array(5);
-// pub struct ConstructorCall;
-// struct ConstructorCallVisitor {
-//     rng: rand::rngs::ThreadRng,
-//     idx_to_mutate: usize,
-//     crt_idx: usize,
-//     replaced: bool,
-//     mode: MutatorMode,
-//     scope_values: Vec<Expr>,
-//     in_for_header: bool,
-// }
-
-// enum MutatorMode {
-//     WrapIdent,  // wraps idents in a random constructor
-//     InsertCode, // chooses a random point to insert a new code line 
-//     ReplaceDecl, // turns let x = ... into let x = new Object(whatever)
-// }
-
-// #[derive(Default)]
-// struct DeclCounter {
-//     count: usize,
-// }
-
-// impl Visit for DeclCounter {
-//     fn visit_var_declarator(&mut self, node: &VarDeclarator) {
-//         if node.init.is_some() {
-//             self.count += 1;
-//         }
-//         node.visit_children_with(self);
-//     }
-// }
-
-// impl VisitMut for ConstructorCallVisitor {
-//     // fn visit_mut_expr(&mut self, node: &mut Expr) {
-//     //     match self.mode {
-//     //         MutatorMode::WrapIdent => {
-//     //             if self.in_for_header {
-//     //                 node.visit_mut_children_with(self);
-//     //                 return;
-//     //             }
-//     //             if self.replaced {
-//     //                 node.visit_mut_children_with(self);
-//     //                 return;
-//     //             }
-                
-//     //             if let Expr::Ident(ident) = node {
-//     //                 if self.crt_idx == self.idx_to_mutate {
-//     //                     let ctor_name = get_random_global_object(&mut self.rng);
-//     //                     let new_expr = Expr::New(NewExpr {
-//     //                         span: DUMMY_SP,
-//     //                         ctxt: Default::default(),
-//     //                         callee: Box::new(Expr::Ident(Ident {
-//     //                             span: DUMMY_SP,
-//     //                             sym: ctor_name.clone().into(),
-//     //                             ctxt: Default::default(),
-//     //                             optional: false,
-//     //                         })),
-//     //                         args: Some(vec![ExprOrSpread {
-//     //                             spread: None,
-//     //                             expr: Box::new(Expr::Ident(ident.clone())),
-//     //                         }]),
-//     //                         type_args: None,
-//     //                     });
-//     //                     let wrapped = self.build_chained_constructors(
-//     //                         ctor_name,
-//     //                         new_expr,
-//     //                         vec![Expr::Ident(ident.clone())],
-//     //                     );
-//     //                     *node = wrapped;
-//     //                     self.replaced = true;
-//     //                     return;
-//     //                 }
-//     //                 self.crt_idx += 1;
-//     //             }
-                
-//     //             node.visit_mut_children_with(self);
-//     //         }
-
-//     //         MutatorMode::ReplaceDecl => {
-//     //             if self.in_for_header {
-//     //                 node.visit_mut_children_with(self);
-//     //                 return;
-//     //             }
-//     //             node.visit_mut_children_with(self);
-//     //         }
-
-//     //         _ => {
-//     //             unreachable!()
-//     //         }
-//     //     }
-//     // }
-
-//     // fn visit_mut_var_declarator(&mut self, node: &mut VarDeclarator) {
-//     //     match self.mode {
-//     //         MutatorMode::ReplaceDecl => {
-//     //             if self.in_for_header {
-//     //                 node.visit_mut_children_with(self);
-//     //                 return;
-//     //             }
-//     //             if self.replaced {
-//     //                 node.visit_mut_children_with(self);
-//     //                 return;
-//     //             }
-
-//     //             if node.init.is_some() {
-//     //                 if self.crt_idx == self.idx_to_mutate {
-//     //                     let original_init = node.init.take().unwrap();
-//     //                     let init_clone = (*original_init).clone();
-//     //                     let ctor_name = get_random_global_object(&mut self.rng);
-//     //                     let new_expr = Expr::New(NewExpr {
-//     //                         span: DUMMY_SP,
-//     //                         ctxt: Default::default(),
-//     //                         callee: Box::new(Expr::Ident(Ident {
-//     //                             span: DUMMY_SP,
-//     //                             sym: ctor_name.clone().into(),
-//     //                             ctxt: Default::default(),
-//     //                             optional: false,
-//     //                         })),
-//     //                         args: Some(vec![ExprOrSpread {
-//     //                             spread: None,
-//     //                             expr: original_init,
-//     //                         }]),
-//     //                         type_args: None,
-//     //                     });
-//     //                     let wrapped = self.build_chained_constructors(ctor_name, new_expr, vec![init_clone]);
-//     //                     node.init = Some(Box::new(wrapped));
-//     //                     self.replaced = true;
-//     //                     return;
-//     //                 }
-//     //                 self.crt_idx += 1;
-//     //             }
-
-//     //             node.visit_mut_children_with(self);
-//     //         }
-//     //         _ => node.visit_mut_children_with(self),
-//     //     }
-//     // }
-
-//     // fn visit_mut_for_stmt(&mut self, node: &mut ForStmt) {
-//     //     // Do not mutate the header (init/test/update).
-//     //     let saved = self.in_for_header;
-//     //     self.in_for_header = true;
-//     //     if let Some(init) = &mut node.init {
-//     //         init.visit_mut_children_with(self);
-//     //     }
-//     //     if let Some(test) = &mut node.test {
-//     //         test.visit_mut_children_with(self);
-//     //     }
-//     //     if let Some(update) = &mut node.update {
-//     //         update.visit_mut_children_with(self);
-//     //     }
-//     //     self.in_for_header = saved;
-//     //     node.body.visit_mut_with(self);
-//     // }
-
-//     // fn visit_mut_for_in_stmt(&mut self, node: &mut ForInStmt) {
-//     //     let saved = self.in_for_header;
-//     //     self.in_for_header = true;
-//     //     node.left.visit_mut_with(self);
-//     //     node.right.visit_mut_with(self);
-//     //     self.in_for_header = saved;
-//     //     node.body.visit_mut_with(self);
-//     // }
-
-//     // fn visit_mut_for_of_stmt(&mut self, node: &mut ForOfStmt) {
-//     //     let saved = self.in_for_header;
-//     //     self.in_for_header = true;
-//     //     node.left.visit_mut_with(self);
-//     //     node.right.visit_mut_with(self);
-//     //     self.in_for_header = saved;
-//     //     node.body.visit_mut_with(self);
-//     // }
-// }
-
-// impl ConstructorCallVisitor {
-//     fn wrap_with_method_calls(
-//         &mut self,
-//         ctor_expr: Expr,
-//         object_sym: &str,
-//         mut value_pool: Vec<Expr>,
-//     ) -> Expr {
-//         value_pool.extend_from_slice(&self.scope_values);
-//         let Some(obj) = get_global_object(object_sym) else {
-//             return ctor_expr;
-//         };
-
-//         let mut instance_methods: Vec<_> = obj
-//             .methods()
-//             .iter()
-//             .filter(|m| matches!(m.kind(), JsMethodKind::Instance))
-//             .cloned()
-//             .collect();
-
-//         if instance_methods.is_empty() {
-//             return ctor_expr;
-//         }
-
-//         let call_count = std::cmp::min(self.rng.random_range(0..=5), instance_methods.len());
-
-//         // build IIFE that constructs the object, calls a few methods, then returns it
-//         let tmp_ident = Ident {
-//             span: DUMMY_SP,
-//             sym: format!("__tmp{}", self.rng.random_range(0..10_000)).into(),
-//             ctxt: Default::default(),
-//             optional: false,
-//         };
-
-//         let decl = VarDeclarator {
-//             span: DUMMY_SP,
-//             name: Pat::Ident(BindingIdent {
-//                 id: tmp_ident.clone(),
-//                 type_ann: None,
-//             }),
-//             init: Some(Box::new(ctor_expr)),
-//             definite: false,
-//         };
-
-//         let mut stmts: Vec<Stmt> = vec![Stmt::Decl(Decl::Var(Box::new(VarDecl {
-//             span: DUMMY_SP,
-//             ctxt: Default::default(),
-//             kind: VarDeclKind::Const,
-//             decls: vec![decl],
-//             declare: false,
-//         })))];
-
-//         value_pool.push(Expr::Ident(tmp_ident.clone()));
-
-//         for _ in 0..call_count {
-//             if let Some(method) = instance_methods.choose(&mut self.rng).cloned() {
-//                 if let Some(sig) = method.signatures().choose(&mut self.rng) {
-//                     let args = self.build_args(sig.types(), &mut value_pool);
-//                     let member_ident = IdentName::new(Atom::from(method.sym()), DUMMY_SP);
-//                     let call_expr = Expr::Call(CallExpr {
-//                         span: DUMMY_SP,
-//                         ctxt: Default::default(),
-//                         callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
-//                             span: DUMMY_SP,
-//                             obj: Box::new(Expr::Ident(tmp_ident.clone())),
-//                             prop: MemberProp::Ident(member_ident),
-//                         }))),
-//                         args,
-//                         type_args: None,
-//                     });
-//                     stmts.push(Stmt::Expr(ExprStmt {
-//                         span: DUMMY_SP,
-//                         expr: Box::new(call_expr),
-//                     }));
-//                 }
-//             }
-//         }
-
-//         stmts.push(Stmt::Return(ReturnStmt {
-//             span: DUMMY_SP,
-//             arg: Some(Box::new(Expr::Ident(tmp_ident))),
-//         }));
-
-//         Expr::Call(CallExpr {
-//             span: DUMMY_SP,
-//             ctxt: Default::default(),
-//             callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
-//                 span: DUMMY_SP,
-//                 expr: Box::new(Expr::Arrow(ArrowExpr {
-//                     span: DUMMY_SP,
-//                     ctxt: Default::default(),
-//                     params: Vec::new(),
-//                     body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
-//                         span: DUMMY_SP,
-//                         ctxt: Default::default(),
-//                         stmts,
-//                     })),
-//                     is_async: false,
-//                     is_generator: false,
-//                     type_params: None,
-//                     return_type: None,
-//                 })),
-//             }))),
-//             args: Vec::new(),
-//             type_args: None,
-//         })
-//     }
-
-//     fn build_chained_constructors(
-//         &mut self,
-//         ctor_name: String,
-//         ctor_expr: Expr,
-//         value_pool: Vec<Expr>,
-//     ) -> Expr {
-//         let depth = self.rng.random_range(0..=4);
-//         let mut current_expr = self.wrap_with_method_calls(ctor_expr, &ctor_name, value_pool.clone());
-
-//         for _ in 1..depth {
-//             let next_ctor_name = get_random_global_object(&mut self.rng);
-//             let new_ctor = Expr::New(NewExpr {
-//                 span: DUMMY_SP,
-//                 ctxt: Default::default(),
-//                 callee: Box::new(Expr::Ident(Ident {
-//                     span: DUMMY_SP,
-//                     sym: next_ctor_name.clone().into(),
-//                     ctxt: Default::default(),
-//                     optional: false,
-//                 })),
-//                 args: Some(vec![ExprOrSpread {
-//                     spread: None,
-//                     expr: Box::new(current_expr),
-//                 }]),
-//                 type_args: None,
-//             });
-//             current_expr =
-//                 self.wrap_with_method_calls(new_ctor, &next_ctor_name, value_pool.clone());
-//         }
-
-//         current_expr
-//     }
-
-//     fn build_args(
-//         &mut self,
-//         types: &[JsObjectType],
-//         value_pool: &mut Vec<Expr>,
-//     ) -> Vec<ExprOrSpread> {
-//         types
-//             .iter()
-//             .map(|ty| {
-//                 let expr = self.build_arg_expr(*ty, value_pool);
-//                 value_pool.push(expr.clone());
-//                 ExprOrSpread {
-//                     spread: None,
-//                     expr: Box::new(expr),
-//                 }
-//             })
-//             .collect()
-//     }
-
-//     fn build_arg_expr(&mut self, ty: JsObjectType, value_pool: &[Expr]) -> Expr {
-//         if !value_pool.is_empty() && self.rng.random_bool(0.35) {
-//             if let Some(existing) = value_pool.choose(&mut self.rng) {
-//                 return existing.clone();
-//             }
-//         }
-
-//         match ty {
-//             JsObjectType::Number => {
-//                 // Bias toward interesting edge numbers.
-//                 let specials = [0.0f64, -0.0, -1.0, 1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 0xffff_ffffu32 as f64];
-//                 let v = if self.rng.random_bool(0.35) {
-//                     *specials.choose(&mut self.rng).unwrap_or(&0.0)
-//                 } else {
-//                     self.rng.random_range(-16i32..=16i32) as f64
-//                 };
-//                 Expr::Lit(Lit::Num(Number {
-//                     span: DUMMY_SP,
-//                     value: v,
-//                     raw: None,
-//                 }))
-//             }
-//             JsObjectType::JsString => {
-//                 let choices = ["foo", "bar", "baz", "qux", "こんにちは"];
-//                 let s = choices.choose(&mut self.rng).copied().unwrap_or("s");
-//                 Expr::Lit(Lit::Str(Str {
-//                     span: DUMMY_SP,
-//                     value: Atom::from(s).into(),
-//                     raw: None,
-//                 }))
-//             }
-//             JsObjectType::Object => Expr::Object(ObjectLit {
-//                 span: DUMMY_SP,
-//                 props: Vec::new(),
-//             }),
-//             JsObjectType::Array => Expr::Array(ArrayLit {
-//                 span: DUMMY_SP,
-//                 elems: Vec::new(),
-//             }),
-//             JsObjectType::Function => {
-//                 // Prefer reusing a value from scope/value pool when asked for a function.
-//                 if !value_pool.is_empty() && self.rng.random_bool(0.5) {
-//                     if let Some(existing) = value_pool.choose(&mut self.rng) {
-//                         return existing.clone();
-//                     }
-//                 }
-
-//                 Expr::Fn(FnExpr {
-//                     ident: None,
-//                     function: Box::new(Function {
-//                         params: Vec::new(),
-//                         decorators: Vec::new(),
-//                         span: DUMMY_SP,
-//                         ctxt: Default::default(),
-//                         body: Some(BlockStmt {
-//                             span: DUMMY_SP,
-//                             ctxt: Default::default(),
-//                             stmts: Vec::new(),
-//                         }),
-//                         is_generator: false,
-//                         is_async: false,
-//                         type_params: None,
-//                         return_type: None,
-//                     }),
-//                 })
-//             }
-//             JsObjectType::Any => {
-//                 let choice = self.rng.random_range(0..5);
-//                 let concrete = match choice {
-//                     0 => JsObjectType::Number,
-//                     1 => JsObjectType::JsString,
-//                     2 => JsObjectType::Object,
-//                     3 => JsObjectType::Function,
-//                     _ => JsObjectType::Array,
-//                 };
-//                 self.build_arg_expr(concrete, value_pool)
-//             }
-//         }
-//     }
-// }
-
-// impl AstMutator for ConstructorCall {
-//     fn mutate(&self, mut ast: Script) -> anyhow::Result<Script> {
-//         let mut rng = rand::rng();
-//         let mut ident_collector = IdentCollector::default();
-//         ast.visit_with(&mut ident_collector);
-//         let scope_values: Vec<Expr> = ident_collector
-//             .idents
-//             .iter()
-//             .map(|id| Expr::Ident(id.clone()))
-//             .collect();
-//         match rng.random_range(0..3) {
-//             // WrapIdent
-//             0 => {
-//                 let mut collector = IdentCollector::default();
-//                 ast.visit_with(&mut collector);
-//                 let ident_cnt = collector.idents.len();
-//                 if ident_cnt == 0 {
-//                     return Ok(ast);
-//                 }
-//                 let idx_to_mutate = rng.random_range(0..ident_cnt);
-//                 let mut visitor = ConstructorCallVisitor {
-//                     rng,
-//                     idx_to_mutate,
-//                     crt_idx: 0,
-//                     replaced: false,
-//                     mode: MutatorMode::WrapIdent,
-//                     scope_values: scope_values.clone(),
-//                     in_for_header: false,
-//                 };
-//                 ast.visit_mut_with(&mut visitor);
-//                 Ok(ast)
-//             }
-//             // // InsertCode
-//             // 1 => {
-//             //     todo!();
-//             // }
-//             // ReplaceDecl
-//             1 | 2 => {
-//                 let mut collector = DeclCounter::default();
-//                 ast.visit_with(&mut collector);
-//                 if collector.count == 0 {
-//                     return Ok(ast);
-//                 }
-
-//                 let idx_to_mutate = rng.random_range(0..collector.count);
-//                 let mut visitor = ConstructorCallVisitor {
-//                     rng,
-//                     idx_to_mutate,
-//                     crt_idx: 0,
-//                     replaced: false,
-//                     mode: MutatorMode::ReplaceDecl,
-//                     scope_values: scope_values.clone(),
-//                     in_for_header: false,
-//                 };
-//                 ast.visit_mut_with(&mut visitor);
-//                 Ok(ast)
-//             }
-//             _ => {
-//                 unreachable!();
-//             }
-//         }
-//         // if rng.random_bool(1.0) {
-//         //     let mut collector = CountNumericLiterals { count : 0 };
-//         //     ast.visit_with(&mut collector);
-//         //     let lit_count = collector.count;
-        
-//         // }
-        
-//     }
-// }
+use rand::Rng;
+use rand::SeedableRng;
+use rand::prelude::IndexedRandom;
+use rand::rngs::StdRng;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::swc_ecma_ast::*;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+
+use crate::code_generators::ast_helpers::{
+    build_args, build_ctor_expr, build_ident_expr_from_str, build_property_call, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::{JsGlobalObject, get_global_object, get_global_object_names};
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods, visit_mut_stmts_inserting_after};
+
+/// ConstructorCall mutator
+/// Picks random idents and wraps them in a constructor call
+/// eg. let x = 5; => let x = new Array(5);
+/// or inserts a standalone statement constructing a fresh object and calling a few of its
+/// methods.
+pub struct ConstructorCall;
+
+/// Constructors of the catalog that aren't reachable as globals
+const NOT_GLOBAL: &[&str] = &["AsyncFunction", "AsyncGeneratorFunction", "GeneratorFunction"];
+
+struct ConstructorCallVisitor {
+    rng: StdRng,
+    /// None when only counting the mutation points
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    mode: MutatorMode,
+    constructors: Vec<JsGlobalObject>,
+    scope_state: ScopeState,
+    in_for_header: bool,
+}
+
+#[derive(Clone, Copy)]
+enum MutatorMode {
+    WrapIdent,   // wraps idents in a random constructor
+    InsertCode,  // chooses a random point to insert a new code line
+    ReplaceDecl, // turns let x = ... into let x = new Object(whatever)
+}
+
+impl ScopedAstVisitor for ConstructorCallVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+/// Global objects of the catalog that can be called with `new`
+pub(crate) fn global_constructors() -> Vec<JsGlobalObject> {
+    get_global_object_names()
+        .into_iter()
+        .filter(|name| !NOT_GLOBAL.contains(name))
+        .filter_map(get_global_object)
+        .filter(|obj| !obj.get_constructor_signatures().is_empty())
+        .collect()
+}
+
+impl ConstructorCallVisitor {
+    /// Whether the current mutation point is the one to mutate. Always advances the index.
+    fn next_is_target(&mut self) -> bool {
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        is_target
+    }
+
+    fn value_pool(&self) -> Vec<String> {
+        self.scope_state
+            .scopes
+            .collect_idents()
+            .iter()
+            .map(|ident| ident.sym.to_string())
+            .collect()
+    }
+
+    /// IIFE that constructs the object, calls a few of its instance methods, then returns it
+    fn wrap_with_method_calls(&mut self, ctor_expr: Expr, obj: &JsGlobalObject) -> Expr {
+        let instance_methods = obj.instance_methods();
+        if instance_methods.is_empty() {
+            return ctor_expr;
+        }
+        let call_count = std::cmp::min(self.rng.random_range(0..=5), instance_methods.len());
+
+        let tmp_name = format!("__tmp{}", self.rng.random_range(0..10_000));
+        let mut value_pool = self.value_pool();
+        value_pool.push(tmp_name.clone());
+
+        let mut stmts = vec![Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            kind: VarDeclKind::Const,
+            ..build_var_decl(&tmp_name, ctor_expr)
+        })))];
+        for _ in 0..call_count {
+            let Some(method) = instance_methods.choose(&mut self.rng) else {
+                continue;
+            };
+            let Some(sig) = method.signatures().choose(&mut self.rng) else {
+                continue;
+            };
+            let call_expr = build_property_call(&tmp_name, method.sym(), build_args(&mut self.rng, sig, &value_pool));
+            stmts.push(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(call_expr),
+            }));
+        }
+        stmts.push(Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(build_ident_expr_from_str(&tmp_name))),
+        }));
+
+        Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Arrow(ArrowExpr {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    params: Vec::new(),
+                    body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                        span: DUMMY_SP,
+                        ctxt: SyntaxContext::empty(),
+                        stmts,
+                    })),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                })),
+            }))),
+            args: Vec::new(),
+            type_args: None,
+        })
+    }
+
+    /// `new <ctor>(<value>)` wrapped in up to 3 more random constructors
+    fn build_chained_constructors(&mut self, value: Expr) -> Option<Expr> {
+        let depth = self.rng.random_range(1..=4);
+        let mut current_expr = value;
+        for _ in 0..depth {
+            let ctor = self.constructors.choose(&mut self.rng)?.clone();
+            let new_ctor = build_ctor_expr(ctor.sym(), vec![current_expr]);
+            current_expr = self.wrap_with_method_calls(new_ctor, &ctor);
+        }
+        Some(current_expr)
+    }
+
+    /// A fresh object built with the arguments of one of its constructor signatures
+    fn build_insert_stmt(&mut self) -> Option<Stmt> {
+        let ctor = self.constructors.choose(&mut self.rng)?.clone();
+        let signatures = ctor.get_constructor_signatures();
+        let sig = signatures.choose(&mut self.rng)?;
+        let value_pool = self.value_pool();
+        let new_ctor = build_ctor_expr(ctor.sym(), build_args(&mut self.rng, sig, &value_pool));
+        let expr = self.wrap_with_method_calls(new_ctor, &ctor);
+        Some(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+        }))
+    }
+}
+
+impl VisitMut for ConstructorCallVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        node.visit_mut_children_with(self);
+        if !matches!(self.mode, MutatorMode::WrapIdent) || self.in_for_header {
+            return;
+        }
+        if !matches!(node, Expr::Ident(_)) || !self.next_is_target() {
+            return;
+        }
+        if let Some(wrapped) = self.build_chained_constructors(node.clone()) {
+            *node = wrapped;
+        }
+    }
+
+    fn visit_mut_update_expr(&mut self, node: &mut UpdateExpr) {
+        // `x++` can't become `new Array(x)++`
+        if !matches!(*node.arg, Expr::Ident(_)) {
+            node.visit_mut_children_with(self);
+        }
+    }
+
+    fn visit_mut_var_declarator(&mut self, node: &mut VarDeclarator) {
+        node.visit_mut_children_with(self);
+        if !matches!(self.mode, MutatorMode::ReplaceDecl) || self.in_for_header {
+            return;
+        }
+        if node.init.is_none() || !self.next_is_target() {
+            return;
+        }
+        let init = node.init.as_deref().cloned().expect("declarator has an initializer");
+        if let Some(wrapped) = self.build_chained_constructors(init) {
+            node.init = Some(Box::new(wrapped));
+        }
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        if !matches!(self.mode, MutatorMode::InsertCode) {
+            stmts.visit_mut_children_with(self);
+            return;
+        }
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            if visitor.next_is_target() { visitor.build_insert_stmt() } else { None }
+        });
+    }
+
+    fn visit_mut_for_stmt(&mut self, node: &mut ForStmt) {
+        // Do not mutate the header (init/test/update).
+        let saved = self.in_for_header;
+        self.in_for_header = true;
+        if let Some(init) = &mut node.init {
+            init.visit_mut_with(self);
+        }
+        if let Some(test) = &mut node.test {
+            test.visit_mut_with(self);
+        }
+        if let Some(update) = &mut node.update {
+            update.visit_mut_with(self);
+        }
+        self.in_for_header = saved;
+        node.body.visit_mut_with(self);
+    }
+
+    fn visit_mut_for_in_stmt(&mut self, node: &mut ForInStmt) {
+        let saved = self.in_for_header;
+        self.in_for_header = true;
+        node.left.visit_mut_with(self);
+        node.right.visit_mut_with(self);
+        self.in_for_header = saved;
+        node.body.visit_mut_with(self);
+    }
+
+    fn visit_mut_for_of_stmt(&mut self, node: &mut ForOfStmt) {
+        let saved = self.in_for_header;
+        self.in_for_header = true;
+        node.left.visit_mut_with(self);
+        node.right.visit_mut_with(self);
+        self.in_for_header = saved;
+        node.body.visit_mut_with(self);
+    }
+}
+
+impl ConstructorCall {
+    fn mutate_with_mode(&self, mut ast: Script, mode: MutatorMode, rng: &mut StdRng) -> anyhow::Result<Script> {
+        let mut visitor = ConstructorCallVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            mode,
+            constructors: global_constructors(),
+            scope_state: ScopeState::new(),
+            in_for_header: false,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 || visitor.constructors.is_empty() {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = ConstructorCallVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for ConstructorCall {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> anyhow::Result<Script> {
+        let mode = match rng.random_range(0..3) {
+            0 => MutatorMode::WrapIdent,
+            1 => MutatorMode::InsertCode,
+            _ => MutatorMode::ReplaceDecl,
+        };
+        self.mutate_with_mode(ast, mode, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::symbols::CountStmtSlots;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use crate::parsing::parser::parse_js;
+    use swc_ecma_visit::VisitWith;
+
+    fn stmt_count(script: &Script) -> usize {
+        let mut counter = CountStmtSlots { count: 0 };
+        script.visit_with(&mut counter);
+        counter.count
+    }
+
+    #[test]
+    fn inserted_code_round_trips() {
+        let source = r#"
+            let v0 = [1, 2, 3];
+            function f0(v1) { const v2 = v1 + 1; return v2; }
+            for (let v3 = 0; v3 < 2; v3++) { f0(v3); }
+        "#;
+        let mutants = assert_mutations_reparse_with(source, 32, |ast, rng| {
+            ConstructorCall.mutate_with_mode(ast, MutatorMode::InsertCode, rng)
+        });
+        for (code, reparsed) in mutants {
+            let original = parse_js(source.to_string()).expect("failed to parse test script");
+            assert!(stmt_count(&reparsed) > stmt_count(&original), "no statement inserted in {}", code);
+            assert!(code.contains("new "), "no constructor call in {}", code);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::swc_ecma_ast::*;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+
+use crate::code_generators::ast_helpers::{build_bin, build_ident_expr_from_str};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::JsGlobalObject;
+use crate::mutators::literals::constructor_call::global_constructors;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods};
+
+/// LiteralCombineMutator
+/// Combines a literal with a value from the current scope, e.g. `1` becomes `(1 + v0)`,
+/// `(v0 * 1)`, `("a" in Object(v0))` or `(v0 instanceof Map ? "a" : v0)`, so constants
+/// meet values of any type.
+///
+/// `in` and `instanceof` would throw on most right-hand sides, so the scope value goes
+/// through `Object()` and the constructor comes from the catalog.
+pub struct LiteralCombineMutator;
+
+/// Binary operators that accept operands of any type
+const COMBINE_OPS: &[BinaryOp] = &[
+    BinaryOp::Add,
+    BinaryOp::Sub,
+    BinaryOp::Mul,
+    BinaryOp::Div,
+    BinaryOp::Mod,
+    BinaryOp::Exp,
+    BinaryOp::EqEq,
+    BinaryOp::EqEqEq,
+    BinaryOp::NotEq,
+    BinaryOp::Lt,
+    BinaryOp::GtEq,
+    BinaryOp::BitAnd,
+    BinaryOp::BitOr,
+    BinaryOp::BitXor,
+    BinaryOp::LShift,
+    BinaryOp::ZeroFillRShift,
+    BinaryOp::LogicalAnd,
+    BinaryOp::LogicalOr,
+    BinaryOp::NullishCoalescing,
+];
+
+fn is_combinable_lit(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Lit(Lit::Num(_) | Lit::Str(_) | Lit::Bool(_) | Lit::Null(_) | Lit::BigInt(_))
+    )
+}
+
+fn build_paren(expr: Expr) -> Expr {
+    Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
+
+struct LiteralCombineVisitor {
+    rng: StdRng,
+    /// None when only counting the literals
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    constructors: Vec<JsGlobalObject>,
+}
+
+impl ScopedAstVisitor for LiteralCombineVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl LiteralCombineVisitor {
+    fn combine(&mut self, lit: Expr, value: Ident) -> Expr {
+        let value = Expr::Ident(value);
+        let combined = match self.rng.random_range(0..6) {
+            0 => {
+                let object = Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    callee: Callee::Expr(Box::new(build_ident_expr_from_str("Object"))),
+                    args: vec![ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(value),
+                    }],
+                    type_args: None,
+                });
+                build_bin(BinaryOp::In, lit, object)
+            }
+            1 if !self.constructors.is_empty() => {
+                let ctor = self.constructors.choose(&mut self.rng).expect("constructors is not empty");
+                let test = build_bin(BinaryOp::InstanceOf, value.clone(), build_ident_expr_from_str(ctor.sym()));
+                Expr::Cond(CondExpr {
+                    span: DUMMY_SP,
+                    test: Box::new(test),
+                    cons: Box::new(lit),
+                    alt: Box::new(value),
+                })
+            }
+            _ => {
+                let op = *COMBINE_OPS.choose(&mut self.rng).expect("COMBINE_OPS is not empty");
+                if self.rng.random_bool(0.5) {
+                    build_bin(op, lit, value)
+                } else {
+                    build_bin(op, value, lit)
+                }
+            }
+        };
+        build_paren(combined)
+    }
+}
+
+impl VisitMut for LiteralCombineVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_expr_stmt(&mut self, node: &mut ExprStmt) {
+        // a string literal statement may be a directive like "use strict"
+        if !matches!(&*node.expr, Expr::Lit(Lit::Str(_))) {
+            node.visit_mut_children_with(self);
+        }
+    }
+
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        if !is_combinable_lit(node) {
+            node.visit_mut_children_with(self);
+            return;
+        }
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        if !is_target {
+            return;
+        }
+        let idents = self.scope_state.scopes.collect_idents();
+        if let Some(value) = idents.choose(&mut self.rng).cloned() {
+            let lit = std::mem::replace(node, Expr::Invalid(Invalid { span: DUMMY_SP }));
+            *node = self.combine(lit, value);
+        }
+    }
+}
+
+impl AstMutator for LiteralCombineMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut visitor = LiteralCombineVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            constructors: Vec::new(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = LiteralCombineVisitor {
+            rng: visitor.rng,
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            constructors: global_constructors(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    #[derive(Default)]
+    struct CombinedLiterals {
+        count: usize,
+    }
+
+    impl Visit for CombinedLiterals {
+        fn visit_paren_expr(&mut self, node: &ParenExpr) {
+            if matches!(&*node.expr, Expr::Bin(_) | Expr::Cond(_)) {
+                self.count += 1;
+            }
+            node.visit_children_with(self);
+        }
+    }
+
+    #[test]
+    fn literals_are_combined_with_scope_values() {
+        let source = r#"
+            "use strict";
+            function f0(v0, v1) { return [1, "a", true, null, 2n]; }
+        "#;
+        for (code, reparsed) in assert_mutations_reparse(&LiteralCombineMutator, source, 32) {
+
+            let mut combined = CombinedLiterals::default();
+            reparsed.visit_with(&mut combined);
+            assert_eq!(combined.count, 1, "expected one combined literal in {}", code);
+            assert!(code.contains("use strict"), "directive was mutated in {}", code);
+        }
+    }
+}
@@ -1,4 +1,8 @@
 pub mod numeric_tweaker;
 pub mod boolean_flipper;
 pub mod array_mutator;
-pub mod constructor_call;
\ No newline at end of file
+pub mod constructor_call;
+pub mod literal_combine;
+pub mod string_mutator;
+pub mod template_literal;
+pub mod number_format;
\ No newline at end of file
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::mutators::AstMutator;
+use crate::mutators::scope::CountNumericLiterals;
+
+/// NumberFormatMutator
+/// Rewrites a numeric literal into an equivalent textual form (hex, octal, binary,
+/// exponential, numeric separators) to exercise the engine's literal parsing. Integral
+/// literals can also turn into the BigInt of the same value.
+pub struct NumberFormatMutator;
+
+struct NumberFormatVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+}
+
+/// Group the digits of `digits` with `_` every `group` digits from the right
+fn insert_separators(digits: &str, group: usize) -> String {
+    let mut out = String::with_capacity(digits.len() * 2);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % group == 0 {
+            out.push('_');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Raw representations of `value` other than plain decimal. BigInt forms end in `n`.
+fn alternative_raws(value: f64, rng: &mut impl Rng) -> Vec<String> {
+    let mut raws = Vec::new();
+    if !value.is_finite() || value < 0.0 {
+        return raws;
+    }
+    if value != 0.0 {
+        raws.push(format!("{:e}", value));
+    }
+
+    let decimal = format!("{}", value);
+    let (int_part, frac_part) = match decimal.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (decimal.as_str(), None),
+    };
+    // separators can't follow a leading zero
+    if int_part.len() > 1 {
+        let group = rng.random_range(1..=(int_part.len() - 1).min(3));
+        let separated = insert_separators(int_part, group);
+        raws.push(match frac_part {
+            Some(frac_part) => format!("{}.{}", separated, frac_part),
+            None => separated,
+        });
+    }
+
+    if value.fract() == 0.0 && value <= u64::MAX as f64 {
+        let int = value as u64;
+        if rng.random_bool(0.5) {
+            raws.push(format!("0x{:x}", int));
+        } else {
+            raws.push(format!("0X{:X}", int));
+        }
+        raws.push(format!("0o{:o}", int));
+        raws.push(format!("0b{:b}", int));
+        raws.push(format!("{}n", int));
+    }
+    raws
+}
+
+impl VisitMut for NumberFormatVisitor {
+    fn visit_mut_lit(&mut self, node: &mut Lit) {
+        node.visit_mut_children_with(self);
+
+        let Lit::Num(num) = node else {
+            return;
+        };
+        if self.crt_idx != self.idx_to_mutate {
+            self.crt_idx += 1;
+            return;
+        }
+        self.crt_idx += 1;
+
+        let raws = alternative_raws(num.value, &mut self.rng);
+        let Some(raw) = raws.choose(&mut self.rng) else {
+            return;
+        };
+        if let Some(digits) = raw.strip_suffix('n') {
+            let value: u64 = digits.parse().expect("BigInt raw is always decimal");
+            let span = num.span;
+            *node = Lit::BigInt(BigInt {
+                span,
+                value: Box::new(BigIntValue::from(value)),
+                raw: Some(Atom::from(raw.as_str())),
+            });
+        } else {
+            num.raw = Some(Atom::from(raw.as_str()));
+        }
+    }
+}
+
+impl AstMutator for NumberFormatMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountNumericLiterals { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = NumberFormatVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    fn parse_literal(source: &str) -> Lit {
+        let script = parse_js(format!("{};", source)).expect("failed to parse literal");
+        match &script.body[0] {
+            Stmt::Expr(ExprStmt { expr, .. }) => match &**expr {
+                Expr::Lit(lit) => lit.clone(),
+                other => panic!("{} parsed to {:?}", source, other),
+            },
+            other => panic!("{} parsed to {:?}", source, other),
+        }
+    }
+
+    #[test]
+    fn alternative_forms_keep_the_value() {
+        let mut rng = unseeded_rng();
+        for value in [0.0, 7.0, 42.0, 255.0, 1_000_000.0, 1234.5, 0.125, 9007199254740991.0] {
+            for _ in 0..8 {
+                for raw in alternative_raws(value, &mut rng) {
+                    match parse_literal(&raw) {
+                        Lit::Num(num) => assert_eq!(num.value, value, "{} is not {}", raw, value),
+                        Lit::BigInt(big) => {
+                            assert_eq!(*big.value, BigIntValue::from(value as u64), "{} is not {}", raw, value)
+                        }
+                        other => panic!("{} parsed to {:?}", raw, other),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rewritten_raw_survives_codegen() {
+        let ast = parse_js("let v0 = 255;".to_string()).unwrap();
+        let mutated = NumberFormatMutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+        let code = String::from_utf8(generate_js(mutated).unwrap()).unwrap();
+        assert!(!code.contains("255"), "literal was not rewritten: {}", code);
+        let Stmt::Decl(Decl::Var(var)) = &parse_js(code.clone()).unwrap().body[0] else {
+            panic!("unexpected output {}", code);
+        };
+        match var.decls[0].init.as_deref() {
+            Some(Expr::Lit(Lit::Num(num))) => assert_eq!(num.value, 255.0),
+            Some(Expr::Lit(Lit::BigInt(big))) => assert_eq!(*big.value, BigIntValue::from(255u64)),
+            other => panic!("unexpected initializer {:?}", other),
+        }
+    }
+}
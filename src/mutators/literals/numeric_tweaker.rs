@@ -1,5 +1,9 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::IndexedRandom;
 use swc_atoms::Atom;
 use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
@@ -7,38 +11,42 @@ use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
 
 use crate::mutators::AstMutator;
 use crate::mutators::scope::CountNumericLiterals;
+use crate::utils::dictionary::Dictionary;
 use crate::utils::rand_utils::{random_weighted_choice, small_delta};
 
 /// NumericTweaker
 /// TODO: I am getting a lot of timeouts when modifying for loop counters. Maybe avoid mutating those or
 /// detect them and mutate to smaller ranges?
-pub struct NumericTweaker;
+pub struct NumericTweaker {
+    dictionary: Arc<Dictionary>,
+}
 
 struct NumericTweakerVisitor {
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
     idx_to_mutate: usize,
     crt_idx: usize,
     in_for_stmt: Option<&'static str>, // know if I'm visiting the literals of the init/test/update of a for statement
     in_array_index: bool,              // true while visiting a computed member index, e.g. arr[<here>]
+    dictionary: Arc<Dictionary>,
 }
 
 impl NumericTweakerVisitor {
     const FOR_TEST_MAX_ABS: f64 = 1_000.0;
     const ARRAY_INDEX_MAX: f64 = 1_024.0;
 
-    fn new(lit_count: usize) -> Self {
-        let mut rng = rand::rng();
+    fn new(lit_count: usize, dictionary: Arc<Dictionary>, rng: &mut StdRng) -> Self {
         let idx_to_mutate = rng.random_range(0..lit_count);
         // println!(
         //     "NumericTweaker: chosen literal index to mutate: {}",
         //     idx_to_mutate
         // );
         Self {
-            rng,
+            rng: StdRng::from_rng(rng),
             idx_to_mutate,
             crt_idx: 0,
             in_for_stmt: None,
             in_array_index: false,
+            dictionary,
         }
     }
 
@@ -275,27 +283,28 @@ impl VisitMut for NumericTweakerVisitor {
             // Bias towards small, local changes; keep extreme and
             // exceptional values at lower probability so they still
             // occur but don't dominate.
-            let choice = random_weighted_choice(
-                &mut self.rng,
-                &[
-                    ("small_delta", 18),
-                    ("inc", 15),
-                    ("dec", 15),
-                    ("flip_sign", 10),
-                    ("truncate_int", 8),
-                    ("random_fraction", 8),
-                    ("scale_mult", 8),
-                    ("to_neg_zero", 5),
-                    // ("pow2", 4),
-                    ("to_extreme_large", 3),
-                    ("to_extreme_small", 3),
-                    ("to_nan", 2),
-                    ("to_infinity", 2),
-                    ("to_neg_infinity", 2),
-                    ("to_undefined", 1),
-                    ("to_null", 1),
-                ],
-            );
+            let mut choices = vec![
+                ("small_delta", 18),
+                ("inc", 15),
+                ("dec", 15),
+                ("flip_sign", 10),
+                ("truncate_int", 8),
+                ("random_fraction", 8),
+                ("scale_mult", 8),
+                ("to_neg_zero", 5),
+                // ("pow2", 4),
+                ("to_extreme_large", 3),
+                ("to_extreme_small", 3),
+                ("to_nan", 2),
+                ("to_infinity", 2),
+                ("to_neg_infinity", 2),
+                ("to_undefined", 1),
+                ("to_null", 1),
+            ];
+            if self.dictionary.has_numbers() {
+                choices.push(("dictionary", 6));
+            }
+            let choice = random_weighted_choice(&mut self.rng, &choices);
 
             match choice {
                 "small_delta" => {
@@ -356,6 +365,11 @@ impl VisitMut for NumericTweakerVisitor {
                 "random_fraction" => {
                     new_value = self.rng.random_range(0.0f64..=1.0f64);
                 }
+                "dictionary" => {
+                    if let Some(value) = self.dictionary.random_number(&mut self.rng) {
+                        new_value = value;
+                    }
+                }
                 "truncate_int" => {
                     new_value = new_value.trunc();
                 }
@@ -386,7 +400,7 @@ impl VisitMut for NumericTweakerVisitor {
 }
 
 impl AstMutator for NumericTweaker {
-    fn mutate(&self, mut ast: Script) -> Result<Script> {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
         // println!("{:#?}", ast);
         let mut counter = CountNumericLiterals { count: 0 };
         ast.visit_with(&mut counter);
@@ -396,7 +410,7 @@ impl AstMutator for NumericTweaker {
         }
 
         // randomly choose a literal index to mutate
-        let mut visitor = NumericTweakerVisitor::new(counter.count);
+        let mut visitor = NumericTweakerVisitor::new(counter.count, self.dictionary.clone(), rng);
         ast.visit_mut_with(&mut visitor);
 
         // TODO: log telemetry about mutations
@@ -406,7 +420,8 @@ impl AstMutator for NumericTweaker {
 }
 
 impl NumericTweaker {
-    pub fn new() -> Self {
-        Self
+    /// Occasionally replace literals with numeric tokens from `dictionary`
+    pub fn new(dictionary: Arc<Dictionary>) -> Self {
+        Self { dictionary }
     }
 }
\ No newline at end of file
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::str_lit_value;
+use crate::mutators::AstMutator;
+use crate::utils::dictionary::Dictionary;
+use crate::utils::rand_utils::random_weighted_choice;
+
+/// StringMutator
+/// Replaces or tweaks string literals, splicing in tokens from the user supplied dictionary when
+/// there is one.
+pub struct StringMutator {
+    dictionary: Arc<Dictionary>,
+}
+
+impl StringMutator {
+    pub fn new(dictionary: Arc<Dictionary>) -> Self {
+        Self { dictionary }
+    }
+}
+
+/// Strings that commonly hit special paths in engines
+const SPECIAL_STRINGS: &[&str] = &[
+    "__proto__",
+    "constructor",
+    "prototype",
+    "length",
+    "toString",
+    "valueOf",
+    "-0",
+    "1e21",
+    "4294967295",
+    "\u{0}",
+    "\u{ffff}",
+    "\u{1d306}",
+];
+
+struct CountStrLiterals {
+    count: usize,
+}
+
+impl Visit for CountStrLiterals {
+    fn visit_lit(&mut self, node: &Lit) {
+        if let Lit::Str(_) = node {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+struct StringMutatorVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    dictionary: Arc<Dictionary>,
+}
+
+impl StringMutatorVisitor {
+    fn mutate_value(&mut self, original: &str) -> String {
+        let mut choices = vec![
+            ("special", 10),
+            ("empty", 4),
+            ("duplicate", 6),
+            ("truncate", 6),
+            ("append_char", 6),
+        ];
+        if !self.dictionary.is_empty() {
+            choices.push(("dictionary", 30));
+        }
+
+        match random_weighted_choice(&mut self.rng, &choices) {
+            "dictionary" => {
+                let Some(token) = self.dictionary.random_token(&mut self.rng) else {
+                    return original.to_string();
+                };
+                // keep the rest of the value, the token alone rarely reaches the same code
+                let boundaries: Vec<usize> = original
+                    .char_indices()
+                    .map(|(idx, _)| idx)
+                    .chain(std::iter::once(original.len()))
+                    .collect();
+                let at = boundaries[self.rng.random_range(0..boundaries.len())];
+                format!("{}{}{}", &original[..at], token, &original[at..])
+            }
+            "empty" => String::new(),
+            "duplicate" => original.repeat(2),
+            "truncate" => {
+                let chars: Vec<char> = original.chars().collect();
+                let keep = if chars.is_empty() { 0 } else { self.rng.random_range(0..chars.len()) };
+                chars[..keep].iter().collect()
+            }
+            "append_char" => {
+                let c = char::from_u32(self.rng.random_range(0x20..0x7f)).unwrap_or('a');
+                format!("{}{}", original, c)
+            }
+            _ => SPECIAL_STRINGS.choose(&mut self.rng).unwrap_or(&"").to_string(),
+        }
+    }
+}
+
+impl VisitMut for StringMutatorVisitor {
+    fn visit_mut_lit(&mut self, node: &mut Lit) {
+        node.visit_mut_children_with(self);
+
+        if let Lit::Str(str_lit) = node {
+            if self.crt_idx != self.idx_to_mutate {
+                self.crt_idx += 1;
+                return;
+            }
+            self.crt_idx += 1;
+
+            let new_value = self.mutate_value(&str_lit_value(str_lit));
+            str_lit.value = Atom::from(new_value).into();
+            str_lit.raw = None;
+        }
+    }
+}
+
+impl AstMutator for StringMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountStrLiterals { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = StringMutatorVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            dictionary: self.dictionary.clone(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    #[test]
+    fn draws_tokens_from_dictionary() {
+        let dictionary = Dictionary::parse("token=\"jellyfuzz_token\"").expect("failed to parse dictionary");
+        let mutator = StringMutator::new(Arc::new(dictionary));
+        let drew_token = (0..64).any(|_| {
+            let ast = parse_js("let v0 = \"foo\";".to_string()).expect("failed to parse test script");
+            let mutated = mutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+            let code = generate_js(mutated).expect("code generation failed");
+            String::from_utf8_lossy(&code).contains("jellyfuzz_token")
+        });
+        assert!(drew_token);
+    }
+
+    #[test]
+    fn dictionary_tokens_are_spliced_into_the_value() {
+        let dictionary = Dictionary::parse("token=\"TOKEN\"").expect("failed to parse dictionary");
+        let mut visitor = StringMutatorVisitor {
+            rng: unseeded_rng(),
+            idx_to_mutate: 0,
+            crt_idx: 0,
+            dictionary: Arc::new(dictionary),
+        };
+        for original in ["foo", "\u{e9}\u{20ac}\u{1d306}", ""] {
+            let spliced: Vec<String> = (0..64)
+                .map(|_| visitor.mutate_value(original))
+                .filter(|value| value.contains("TOKEN"))
+                .collect();
+            assert!(!spliced.is_empty(), "the dictionary was never used on {:?}", original);
+            for value in spliced {
+                assert_eq!(value.replacen("TOKEN", "", 1), original, "{:?} replaced the value", value);
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{build_ident_expr_from_str, build_random_primitive_literal, str_lit_value};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods};
+use crate::mutators::strict_mode::directive_prologue_len;
+
+/// TemplateLiteralMutator
+/// Turns string literals and `x + "str"` concatenations into template literals with an
+/// interpolation (`` `foo${x}bar` ``), sometimes tagged with `String.raw`. This exercises
+/// the engine's template object caching and tag call machinery.
+pub struct TemplateLiteralMutator;
+
+fn is_str_lit(expr: &Expr) -> bool {
+    matches!(expr, Expr::Lit(Lit::Str(_)))
+}
+
+/// String literals and string concatenations `a + "str"` / `"str" + a`
+fn is_template_candidate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(Lit::Str(_)) => true,
+        Expr::Bin(BinExpr { op: BinaryOp::Add, left, right, .. }) => {
+            is_str_lit(left) || is_str_lit(right)
+        }
+        _ => false,
+    }
+}
+
+/// Escape a cooked string so it can be used as the raw text of a template element
+fn escape_template_raw(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+fn build_tpl_element(value: &str, tail: bool) -> TplElement {
+    TplElement {
+        span: DUMMY_SP,
+        tail,
+        cooked: Some(Atom::from(value).into()),
+        raw: Atom::from(escape_template_raw(value)),
+    }
+}
+
+/// `` `<head>${expr}<tail>` ``
+fn build_template(head: &str, expr: Expr, tail: &str) -> Tpl {
+    Tpl {
+        span: DUMMY_SP,
+        exprs: vec![Box::new(expr)],
+        quasis: vec![build_tpl_element(head, false), build_tpl_element(tail, true)],
+    }
+}
+
+fn string_raw_available() -> bool {
+    get_global_object("String")
+        .map(|string| string.static_methods().iter().any(|method| method.sym() == "raw"))
+        .unwrap_or(false)
+}
+
+struct CountTemplateCandidates {
+    count: usize,
+}
+
+impl Visit for CountTemplateCandidates {
+    fn visit_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in &stmts[directive_prologue_len(stmts)..] {
+            stmt.visit_with(self);
+        }
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        if is_template_candidate(node) {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+struct TemplateLiteralVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    tag_with_raw: bool,
+}
+
+impl ScopedAstVisitor for TemplateLiteralVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl TemplateLiteralVisitor {
+    /// A value from the current scope, or a random literal if there is none
+    fn interpolation(&mut self) -> Expr {
+        let idents = self.scope_state.scopes.collect_idents();
+        match idents.choose(&mut self.rng) {
+            Some(ident) if self.rng.random_bool(0.8) => Expr::Ident(ident.clone()),
+            _ => build_random_primitive_literal(&mut self.rng),
+        }
+    }
+
+    fn to_template(&mut self, node: &mut Expr) -> Option<Tpl> {
+        let placeholder = Expr::Invalid(Invalid { span: DUMMY_SP });
+        match std::mem::replace(node, placeholder) {
+            Expr::Lit(Lit::Str(lit)) => {
+                let value: Vec<char> = str_lit_value(&lit).chars().collect();
+                let split = self.rng.random_range(0..=value.len());
+                let head: String = value[..split].iter().collect();
+                let tail: String = value[split..].iter().collect();
+                let expr = self.interpolation();
+                Some(build_template(&head, expr, &tail))
+            }
+            Expr::Bin(BinExpr { op: BinaryOp::Add, left, right, .. }) => {
+                let tpl = match (*left, *right) {
+                    (Expr::Lit(Lit::Str(lit)), other) => build_template(&str_lit_value(&lit), other, ""),
+                    (other, Expr::Lit(Lit::Str(lit))) => build_template("", other, &str_lit_value(&lit)),
+                    _ => unreachable!("candidate concatenation without a string literal"),
+                };
+                Some(tpl)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for TemplateLiteralVisitor {
+    scoped_visit_mut_methods!();
+
+    // leading strings are directives in script and function bodies, a template there
+    // would silently drop them, so they are skipped at the start of every statement list
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let prologue = directive_prologue_len(stmts);
+        for stmt in &mut stmts[prologue..] {
+            stmt.visit_mut_with(self);
+        }
+    }
+
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        if is_template_candidate(node) {
+            if self.crt_idx == self.idx_to_mutate {
+                if let Some(tpl) = self.to_template(node) {
+                    *node = if self.tag_with_raw && self.rng.random_bool(0.2) {
+                        Expr::TaggedTpl(TaggedTpl {
+                            span: DUMMY_SP,
+                            ctxt: SyntaxContext::empty(),
+                            tag: Box::new(Expr::Member(MemberExpr {
+                                span: DUMMY_SP,
+                                obj: Box::new(build_ident_expr_from_str("String")),
+                                prop: MemberProp::Ident(IdentName::new(Atom::from("raw"), DUMMY_SP)),
+                            })),
+                            type_params: None,
+                            tpl: Box::new(tpl),
+                        })
+                    } else {
+                        Expr::Tpl(tpl)
+                    };
+                }
+            }
+            self.crt_idx += 1;
+        }
+        node.visit_mut_children_with(self);
+    }
+}
+
+impl AstMutator for TemplateLiteralMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountTemplateCandidates { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = TemplateLiteralVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            tag_with_raw: string_raw_available(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::strict_mode::use_strict_position;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    /// Mutates `source` and returns the re-parsed template of the last declaration
+    fn mutate_to_template(source: &str) -> (Tpl, String) {
+        let (code, reparsed) = assert_mutations_reparse(&TemplateLiteralMutator, source, 1).remove(0);
+        let Some(Stmt::Decl(Decl::Var(var))) = reparsed.body.last() else {
+            panic!("expected a declaration in {}", code);
+        };
+        let tpl = match var.decls[0].init.as_deref() {
+            Some(Expr::Tpl(tpl)) => tpl.clone(),
+            Some(Expr::TaggedTpl(tagged)) => (*tagged.tpl).clone(),
+            other => panic!("expected a template literal, got {:?} in {}", other, code),
+        };
+        (tpl, code)
+    }
+
+    #[test]
+    fn string_literal_interpolation_survives_roundtrip() {
+        for _ in 0..32 {
+            let (tpl, code) = mutate_to_template("let v0 = 1; let v1 = \"foo`${bar}\";");
+            assert_eq!(tpl.exprs.len(), 1, "lost the interpolation in {}", code);
+            assert_eq!(tpl.quasis.len(), 2);
+        }
+    }
+
+    #[test]
+    fn directives_are_left_alone() {
+        let source = "\"use strict\"; function f0() { \"use strict\"; return \"foo\"; }";
+        for (code, reparsed) in assert_mutations_reparse(&TemplateLiteralMutator, source, 32) {
+            assert_eq!(use_strict_position(&reparsed.body), Some(0), "script directive lost in {}", code);
+            let Some(Stmt::Decl(Decl::Fn(decl))) = reparsed.body.last() else {
+                panic!("expected a function in {}", code);
+            };
+            let body = decl.function.body.as_ref().expect("function lost its body");
+            assert_eq!(use_strict_position(&body.stmts), Some(0), "function directive lost in {}", code);
+            assert!(code.contains('`'), "returned string not converted in {}", code);
+        }
+    }
+
+    #[derive(Default)]
+    struct TplCollector {
+        tpls: Vec<Tpl>,
+    }
+
+    impl Visit for TplCollector {
+        fn visit_tpl(&mut self, node: &Tpl) {
+            self.tpls.push(node.clone());
+            node.visit_children_with(self);
+        }
+    }
+
+    #[test]
+    fn concatenation_becomes_template() {
+        // either the whole concatenation or just its string operand gets converted
+        let mut converted_concat = false;
+        for (code, reparsed) in assert_mutations_reparse(&TemplateLiteralMutator, "v0 + \"px\";", 64) {
+            let mut collector = TplCollector::default();
+            reparsed.visit_with(&mut collector);
+            assert_eq!(collector.tpls.len(), 1, "expected one template in {}", code);
+
+            let tpl = &collector.tpls[0];
+            assert_eq!(tpl.exprs.len(), 1);
+            if matches!(&*tpl.exprs[0], Expr::Ident(ident) if &*ident.sym == "v0") {
+                let tail = tpl.quasis[1].cooked.as_ref().expect("missing cooked value");
+                assert_eq!(tail.to_string_lossy(), "px");
+                converted_concat = true;
+            }
+        }
+        assert!(converted_concat);
+    }
+}
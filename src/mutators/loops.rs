@@ -0,0 +1,608 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use swc_common::{DUMMY_SP, EqIgnoreSpan, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_block, build_expr_stmt, build_ident, build_ident_expr_from_str, build_signed_num,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{NameCollector, VarRenamer};
+
+/// LoopTransformMutator
+/// Unrolls `for` loops with a constant trip count, either fully into straight-line code,
+/// e.g. `for (let v0 = 0; v0 < 2; v0++) { f(v0); }` becomes
+/// `{ { let v0 = 0; { f(v0); } } { let v0 = 1; { f(v0); } } }`, or partially by repeating
+/// the body 2 or 3 times per iteration. The other way around, a run of statements that
+/// only differ by a number counting up, e.g. `f(0); f(1); f(2);`, is rolled into
+/// `for (let v3 = 0; v3 < 3; v3++) { f(v3); }`.
+///
+/// Both change the loops the JIT gets to optimize. Only loops over a counter that the
+/// body neither writes nor jumps out of with `break`/`continue` are unrolled.
+pub struct LoopTransformMutator;
+
+/// Loops running more iterations than this are left alone
+const MAX_TRIP_COUNT: usize = 64;
+/// Loops running more iterations than this are only unrolled partially
+const MAX_FULL_UNROLL: usize = 8;
+const MAX_UNROLL_FACTOR: usize = 3;
+/// Fewest similar statements rolled into a loop
+const MIN_REROLL: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransformKind {
+    Unroll,        // straight-line copies of the body, one per iteration
+    PartialUnroll, // the body repeated 2 or 3 times per iteration
+    Reroll,        // similar statements rolled into a loop
+}
+
+const TRANSFORM_KINDS: [TransformKind; 3] = [
+    TransformKind::Unroll,
+    TransformKind::PartialUnroll,
+    TransformKind::Reroll,
+];
+
+/// A `for` loop over a counter with a constant start, bound and step
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CountedLoop {
+    pub var: String,
+    pub kind: VarDeclKind,
+    /// Value of the counter in every iteration
+    pub values: Vec<f64>,
+    /// Value of the counter once the loop is done
+    pub end: f64,
+}
+
+fn num_value(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Lit(Lit::Num(num)) if num.value.is_finite() => Some(num.value),
+        _ => None,
+    }
+}
+
+fn is_ident(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Ident(ident) if &*ident.sym == name)
+}
+
+/// How much `update` adds to the counter `var`
+fn counter_step(update: &Expr, var: &str) -> Option<f64> {
+    let step = match update {
+        Expr::Update(UpdateExpr { op, arg, .. }) if is_ident(arg, var) => match op {
+            UpdateOp::PlusPlus => 1.0,
+            UpdateOp::MinusMinus => -1.0,
+        },
+        Expr::Assign(AssignExpr {
+            op,
+            left: AssignTarget::Simple(SimpleAssignTarget::Ident(ident)),
+            right,
+            ..
+        }) if &*ident.id.sym == var => match op {
+            AssignOp::AddAssign => num_value(right)?,
+            AssignOp::SubAssign => -num_value(right)?,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    (step != 0.0).then_some(step)
+}
+
+/// Set when the body of a loop writes its counter or jumps with `break`/`continue`
+struct WritesOrJumps<'a> {
+    var: &'a str,
+    found: bool,
+}
+
+impl Visit for WritesOrJumps<'_> {
+    fn visit_update_expr(&mut self, node: &UpdateExpr) {
+        self.found |= is_ident(&node.arg, self.var);
+        node.visit_children_with(self);
+    }
+
+    fn visit_simple_assign_target(&mut self, node: &SimpleAssignTarget) {
+        self.found |= matches!(node, SimpleAssignTarget::Ident(ident) if &*ident.id.sym == self.var);
+        node.visit_children_with(self);
+    }
+
+    fn visit_binding_ident(&mut self, node: &BindingIdent) {
+        self.found |= &*node.id.sym == self.var;
+    }
+
+    fn visit_break_stmt(&mut self, _node: &BreakStmt) {
+        self.found = true;
+    }
+
+    fn visit_continue_stmt(&mut self, _node: &ContinueStmt) {
+        self.found = true;
+    }
+}
+
+/// The counter of `node` and its values, if the loop runs at most `max_iterations` times
+/// over a counter its body leaves alone
+pub(crate) fn counted_loop(node: &ForStmt, max_iterations: usize) -> Option<CountedLoop> {
+    let Some(VarDeclOrExpr::VarDecl(decl)) = &node.init else {
+        return None;
+    };
+    if !matches!(decl.kind, VarDeclKind::Let | VarDeclKind::Var) {
+        return None;
+    }
+    let [
+        VarDeclarator {
+            name: Pat::Ident(binding),
+            init: Some(init),
+            ..
+        },
+    ] = decl.decls.as_slice()
+    else {
+        return None;
+    };
+    let var = binding.id.sym.to_string();
+    let start = num_value(init)?;
+
+    let Expr::Bin(test) = &**node.test.as_ref()? else {
+        return None;
+    };
+    if !is_ident(&test.left, &var) {
+        return None;
+    }
+    let bound = num_value(&test.right)?;
+    let holds = |value: f64| match test.op {
+        BinaryOp::Lt => Some(value < bound),
+        BinaryOp::LtEq => Some(value <= bound),
+        BinaryOp::Gt => Some(value > bound),
+        BinaryOp::GtEq => Some(value >= bound),
+        BinaryOp::NotEq | BinaryOp::NotEqEq => Some(value != bound),
+        _ => None,
+    };
+    let step = counter_step(node.update.as_ref()?, &var)?;
+
+    let mut values = Vec::new();
+    let mut value = start;
+    while holds(value)? {
+        if values.len() == max_iterations {
+            return None;
+        }
+        values.push(value);
+        value += step;
+    }
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut body = WritesOrJumps { var: &var, found: false };
+    node.body.visit_with(&mut body);
+    if body.found {
+        return None;
+    }
+    Some(CountedLoop {
+        var,
+        kind: decl.kind,
+        values,
+        end: value,
+    })
+}
+
+fn block_stmt(stmts: Vec<Stmt>) -> Stmt {
+    Stmt::Block(build_block(stmts))
+}
+
+fn build_counter(kind: VarDeclKind, name: &str, value: f64) -> VarDecl {
+    VarDecl {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        kind,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: build_ident(name),
+                type_ann: None,
+            }),
+            init: Some(Box::new(build_signed_num(value))),
+            definite: false,
+        }],
+    }
+}
+
+fn build_counter_decl(kind: VarDeclKind, name: &str, value: f64) -> Stmt {
+    Stmt::Decl(Decl::Var(Box::new(build_counter(kind, name, value))))
+}
+
+fn build_counter_assign(name: &str, value: f64) -> Stmt {
+    build_expr_stmt(Expr::Assign(AssignExpr {
+        span: DUMMY_SP,
+        op: AssignOp::Assign,
+        left: AssignTarget::Simple(SimpleAssignTarget::Ident(BindingIdent {
+            id: build_ident(name),
+            type_ann: None,
+        })),
+        right: Box::new(build_signed_num(value)),
+    }))
+}
+
+/// The loop as straight-line code. `let` counters get a block per iteration so closures
+/// still capture one binding each, `var` counters are assigned between the copies.
+fn unroll_fully(node: &ForStmt, counted: &CountedLoop) -> Stmt {
+    let body = *node.body.clone();
+    let mut stmts = Vec::new();
+    match counted.kind {
+        VarDeclKind::Var => {
+            for (i, &value) in counted.values.iter().enumerate() {
+                stmts.push(if i == 0 {
+                    build_counter_decl(VarDeclKind::Var, &counted.var, value)
+                } else {
+                    build_counter_assign(&counted.var, value)
+                });
+                stmts.push(body.clone());
+            }
+            stmts.push(build_counter_assign(&counted.var, counted.end));
+        }
+        _ => {
+            for &value in &counted.values {
+                stmts.push(block_stmt(vec![
+                    build_counter_decl(VarDeclKind::Let, &counted.var, value),
+                    body.clone(),
+                ]));
+            }
+        }
+    }
+    block_stmt(stmts)
+}
+
+/// Repeat the body `factor` times per iteration, updating the counter and leaving the
+/// loop in between when the test fails
+fn unroll_partially(node: &mut ForStmt, factor: usize) {
+    let (Some(test), Some(update)) = (node.test.clone(), node.update.clone()) else {
+        return;
+    };
+    let body = *node.body.clone();
+    let mut stmts = vec![body.clone()];
+    for _ in 1..factor {
+        stmts.push(build_expr_stmt(*update.clone()));
+        stmts.push(Stmt::If(IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: op!("!"),
+                arg: Box::new(Expr::Paren(ParenExpr {
+                    span: DUMMY_SP,
+                    expr: test.clone(),
+                })),
+            })),
+            cons: Box::new(Stmt::Break(BreakStmt {
+                span: DUMMY_SP,
+                label: None,
+            })),
+            alt: None,
+        }));
+        stmts.push(body.clone());
+    }
+    node.body = Box::new(block_stmt(stmts));
+}
+
+/// Values of the number literals of an expression statement, in visiting order.
+/// Number keys such as `{ 1: x }` aren't expressions and don't count.
+#[derive(Default)]
+struct NumberLiterals {
+    values: Vec<f64>,
+}
+
+impl Visit for NumberLiterals {
+    fn visit_expr(&mut self, node: &Expr) {
+        match node {
+            Expr::Lit(Lit::Num(num)) => self.values.push(num.value),
+            _ => node.visit_children_with(self),
+        }
+    }
+}
+
+/// Replaces the number literals visited by `NumberLiterals`, all of them with 0 or only
+/// the one at `target` with `with`
+struct ReplaceNumbers {
+    target: Option<usize>,
+    crt_idx: usize,
+    with: Expr,
+}
+
+impl VisitMut for ReplaceNumbers {
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        if !matches!(node, Expr::Lit(Lit::Num(_))) {
+            node.visit_mut_children_with(self);
+            return;
+        }
+        if self.target.is_none_or(|target| target == self.crt_idx) {
+            *node = self.with.clone();
+        }
+        self.crt_idx += 1;
+    }
+}
+
+fn number_literals(stmt: &Stmt) -> Vec<f64> {
+    let mut literals = NumberLiterals::default();
+    stmt.visit_with(&mut literals);
+    literals.values
+}
+
+/// `stmt` with every number literal set to 0
+fn without_numbers(stmt: &Stmt) -> Stmt {
+    let mut stmt = stmt.clone();
+    stmt.visit_mut_with(&mut ReplaceNumbers {
+        target: None,
+        crt_idx: 0,
+        with: build_signed_num(0.0),
+    });
+    stmt
+}
+
+/// A run of statements that can be rolled into a loop
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RerollRun {
+    pub len: usize,
+    /// Position of the literal counting up, its first value and its step. None when the
+    /// statements are all the same.
+    pub counter: Option<(usize, f64, f64)>,
+}
+
+/// The longest run of expression statements from `stmts[start]` that only differ by one
+/// integer literal counting up by a constant step
+pub(crate) fn reroll_run(stmts: &[Stmt], start: usize) -> Option<RerollRun> {
+    let first = &stmts[start];
+    if !matches!(first, Stmt::Expr(_)) {
+        return None;
+    }
+    let shape = without_numbers(first);
+    let first_values = number_literals(first);
+    let mut counter: Option<(usize, f64, f64)> = None;
+    let mut len = 1;
+    for (i, stmt) in stmts[start..].iter().enumerate().skip(1) {
+        if !without_numbers(stmt).eq_ignore_span(&shape) {
+            break;
+        }
+        let values = number_literals(stmt);
+        let differing: Vec<usize> = (0..values.len()).filter(|&p| values[p] != first_values[p]).collect();
+        if i == 1 {
+            match differing.as_slice() {
+                [] => {}
+                &[p] => {
+                    let step = values[p] - first_values[p];
+                    if first_values[p].fract() != 0.0 || step.fract() != 0.0 {
+                        break;
+                    }
+                    counter = Some((p, first_values[p], step));
+                }
+                _ => break,
+            }
+        } else {
+            let follows = match counter {
+                None => differing.is_empty(),
+                Some((p, value, step)) => differing == [p] && values[p] == value + step * i as f64,
+            };
+            if !follows {
+                break;
+            }
+        }
+        len += 1;
+    }
+    (len >= MIN_REROLL).then_some(RerollRun { len, counter })
+}
+
+/// `for (let <var> = <start>; <var> < <end>; <var>++) { <template> }` counting like `run`
+fn reroll(template: &Stmt, run: &RerollRun, var: &str) -> Stmt {
+    let (position, start, step) = match run.counter {
+        Some((position, start, step)) => (Some(position), start, step),
+        None => (None, 0.0, 1.0),
+    };
+    let mut body = template.clone();
+    if let Some(position) = position {
+        body.visit_mut_with(&mut ReplaceNumbers {
+            target: Some(position),
+            crt_idx: 0,
+            with: build_ident_expr_from_str(var),
+        });
+    }
+    let end = start + step * run.len as f64;
+    let update = if step.abs() == 1.0 {
+        Expr::Update(UpdateExpr {
+            span: DUMMY_SP,
+            op: if step > 0.0 { op!("++") } else { op!("--") },
+            prefix: false,
+            arg: Box::new(build_ident_expr_from_str(var)),
+        })
+    } else {
+        Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: if step > 0.0 { op!("+=") } else { op!("-=") },
+            left: AssignTarget::Simple(SimpleAssignTarget::Ident(BindingIdent {
+                id: build_ident(var),
+                type_ann: None,
+            })),
+            right: Box::new(build_signed_num(step.abs())),
+        })
+    };
+    Stmt::For(ForStmt {
+        span: DUMMY_SP,
+        init: Some(VarDeclOrExpr::VarDecl(Box::new(build_counter(VarDeclKind::Let, var, start)))),
+        test: Some(Box::new(Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: if step > 0.0 { op!("<") } else { op!(">") },
+            left: Box::new(build_ident_expr_from_str(var)),
+            right: Box::new(build_signed_num(end)),
+        }))),
+        update: Some(Box::new(update)),
+        body: Box::new(block_stmt(vec![body])),
+    })
+}
+
+struct LoopTransformVisitor {
+    rng: StdRng,
+    /// None when only counting the candidates
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    kind: TransformKind,
+    renamer: VarRenamer,
+}
+
+impl LoopTransformVisitor {
+    fn is_target(&mut self) -> bool {
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        is_target
+    }
+}
+
+impl VisitMut for LoopTransformVisitor {
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut i = 0;
+        while i < stmts.len() {
+            stmts[i].visit_mut_with(self);
+            match self.kind {
+                TransformKind::Unroll | TransformKind::PartialUnroll => {
+                    let Stmt::For(node) = &mut stmts[i] else {
+                        i += 1;
+                        continue;
+                    };
+                    let Some(counted) = counted_loop(node, MAX_TRIP_COUNT) else {
+                        i += 1;
+                        continue;
+                    };
+                    let fits = self.kind == TransformKind::PartialUnroll || counted.values.len() <= MAX_FULL_UNROLL;
+                    if fits && self.is_target() {
+                        if self.kind == TransformKind::Unroll {
+                            let unrolled = unroll_fully(node, &counted);
+                            stmts[i] = unrolled;
+                        } else {
+                            let factor = self.rng.random_range(2..=MAX_UNROLL_FACTOR);
+                            unroll_partially(node, factor);
+                        }
+                    }
+                    i += 1;
+                }
+                TransformKind::Reroll => {
+                    let Some(run) = reroll_run(stmts, i) else {
+                        i += 1;
+                        continue;
+                    };
+                    if self.is_target() {
+                        let var = self.renamer.next_var_name();
+                        let rolled = reroll(&stmts[i], &run, &var);
+                        stmts.splice(i..i + run.len, [rolled]);
+                        i += 1;
+                    } else {
+                        i += run.len;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl LoopTransformMutator {
+    /// Returns None if the program has nothing to transform this way
+    fn mutate_with_kind(&self, ast: &Script, kind: TransformKind, rng: &mut StdRng) -> Option<Script> {
+        let mut ast = ast.clone();
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let mut visitor = LoopTransformVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            kind,
+            renamer: VarRenamer::new(names.var_names),
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return None;
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = LoopTransformVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Some(ast)
+    }
+}
+
+impl AstMutator for LoopTransformMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut kinds = TRANSFORM_KINDS;
+        kinds.shuffle(rng);
+        for kind in kinds {
+            if let Some(mutated) = self.mutate_with_kind(&ast, kind, rng) {
+                return Ok(mutated);
+            }
+        }
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    fn transform(source: &str, kind: TransformKind) -> String {
+        let ast = parse_js(source.to_string()).expect("failed to parse test script");
+        let mutated = LoopTransformMutator
+            .mutate_with_kind(&ast, kind, &mut unseeded_rng())
+            .expect("nothing to transform");
+        let code = String::from_utf8(generate_js(mutated).expect("code generation failed")).unwrap();
+        parse_js(code.clone()).expect("transformed code does not parse");
+        code
+    }
+
+    #[test]
+    fn small_loops_unroll_to_straight_line_code() {
+        let code = transform("let v0 = 0; for (let v1 = 0; v1 < 3; v1++) { v0 = v0 + v1; }", TransformKind::Unroll);
+        assert!(!code.contains("for ("), "loop left in {}", code);
+        for value in 0..3 {
+            assert!(code.contains(&format!("let v1 = {};", value)), "iteration {} missing in {}", value, code);
+        }
+        assert!(!code.contains("let v1 = 3;"), "one iteration too many in {}", code);
+
+        let code = transform("for (var v0 = 4; v0 > 0; v0 -= 2) f(v0);", TransformKind::Unroll);
+        assert!(code.contains("var v0 = 4;") && code.contains("v0 = 2;") && code.contains("v0 = 0;"), "{}", code);
+
+        let code = transform("for (let v0 = 0; v0 < 10; v0++) { f(v0); }", TransformKind::PartialUnroll);
+        assert!(code.contains("break;"), "not unrolled in {}", code);
+        assert!(code.matches("f(v0)").count() >= 2, "body not repeated in {}", code);
+
+        // the counter is written by the body, or the loop is too long
+        for source in [
+            "for (let v0 = 0; v0 < 3; v0++) { v0 += 1; }",
+            "for (let v0 = 0; v0 < 3; v0++) { if (v0) break; }",
+            "for (let v0 = 0; v0 < 1000; v0++) {}",
+            "for (let v0 = 0; v0 < v1; v0++) {}",
+        ] {
+            let ast = parse_js(source.to_string()).unwrap();
+            let unrolled = LoopTransformMutator.mutate_with_kind(&ast, TransformKind::Unroll, &mut unseeded_rng());
+            assert!(unrolled.is_none(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn repeated_statements_reroll_into_a_loop() {
+        let stmts = parse_js("f0(1, 7); f0(3, 7); f0(5, 7); f0(6, 7); g();".to_string()).unwrap().body;
+        assert_eq!(
+            reroll_run(&stmts, 0),
+            Some(RerollRun {
+                len: 3,
+                counter: Some((0, 1.0, 2.0)),
+            })
+        );
+        assert_eq!(reroll_run(&stmts, 1), None);
+        let stmts = parse_js("g(); g(); g(); h();".to_string()).unwrap().body;
+        assert_eq!(reroll_run(&stmts, 0), Some(RerollRun { len: 3, counter: None }));
+
+        let code = transform("let v0 = []; v0.push(0); v0.push(1); v0.push(2); v0.push(3);", TransformKind::Reroll);
+        assert!(code.contains("for (let v1 = 0; v1 < 4; v1++)"), "not rolled in {}", code);
+        assert_eq!(code.matches(".push(").count(), 1, "statements left in {}", code);
+        assert!(code.contains("v0.push(v1)"), "counter unused in {}", code);
+    }
+}
@@ -0,0 +1,322 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_ident_expr_from_str, build_member, build_num, build_paren_bin, build_var_decl, is_valid_identifier_name,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::scope::{
+    NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods, visit_mut_stmts_inserting_after,
+};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// MathEdgeMutator
+/// Inserts a computation with a result defined by a corner of the spec, e.g.
+/// `let v3 = Math.max();`, `let v3 = Math.pow(0, -Infinity);`, `let v3 = 0 / 0;` or
+/// `let v3 = Math.round(-0.5);`.
+///
+/// Arguments are NaN, infinities, -0, halves and the limits of the number
+/// representations. Constant folding, the JIT and the runtime all have to agree on the
+/// result, so these are both crash and differential targets. Math methods and their
+/// arities come from the catalog.
+pub struct MathEdgeMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    Known,      // Math.hypot(Infinity, NaN)
+    Call,       // Math.atan2(-0, -Infinity)
+    Arithmetic, // -0 % 1
+}
+
+const EDGE_KINDS: [EdgeKind; 3] = [EdgeKind::Known, EdgeKind::Call, EdgeKind::Arithmetic];
+
+const EDGE_NUMBERS: &[f64] = &[
+    0.0,
+    -0.0,
+    0.5,
+    -0.5,
+    1.5,
+    -1.0,
+    f64::NAN,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    5e-324,
+    2147483647.0,
+    -2147483648.0,
+    4294967295.0,
+    9007199254740992.0,
+    1.7976931348623157e308,
+];
+
+/// Properties of `Number` holding representation limits
+const NUMBER_CONSTANTS: &[&str] = &["MAX_VALUE", "MIN_VALUE", "EPSILON", "MAX_SAFE_INTEGER", "MIN_SAFE_INTEGER"];
+
+/// Calls with a result the spec spells out
+const KNOWN_CORNERS: &[(&str, &[f64])] = &[
+    ("max", &[]),
+    ("min", &[]),
+    ("pow", &[0.0, f64::NEG_INFINITY]),
+    ("pow", &[f64::NAN, 0.0]),
+    ("pow", &[1.0, f64::INFINITY]),
+    ("round", &[-0.5]),
+    ("round", &[0.49999999999999994]),
+    ("hypot", &[f64::INFINITY, f64::NAN]),
+    ("atan2", &[-0.0, -0.0]),
+    ("sign", &[-0.0]),
+    ("ceil", &[-0.5]),
+    ("max", &[-0.0, 0.0]),
+    ("min", &[0.0, -0.0]),
+    ("fround", &[5e-324]),
+    ("imul", &[4294967295.0, 5.0]),
+    ("clz32", &[-0.0]),
+];
+
+const ARITHMETIC_OPS: [BinaryOp; 6] = [
+    op!("/"),
+    op!("%"),
+    op!("**"),
+    op!("*"),
+    op!(bin, "+"),
+    op!(bin, "-"),
+];
+
+/// Math methods of the catalog with the argument counts of their signatures. `random`
+/// is left out, a differential run couldn't compare its result.
+fn math_methods() -> Vec<(String, Vec<usize>)> {
+    get_global_object("Math")
+        .map(|math| {
+            math.static_methods()
+                .into_iter()
+                .filter(|method| method.sym() != "random" && is_valid_identifier_name(method.sym()))
+                .map(|method| {
+                    let arities = method.signatures().iter().map(|sig| sig.types().len()).collect();
+                    (method.sym().to_string(), arities)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn build_neg(expr: Expr) -> Expr {
+    Expr::Unary(UnaryExpr {
+        span: DUMMY_SP,
+        op: op!(unary, "-"),
+        arg: Box::new(expr),
+    })
+}
+
+/// A number as the program would spell it, the printer can't write NaN, the infinities
+/// or -0 as literals
+fn build_edge_number(value: f64) -> Expr {
+    if value.is_nan() {
+        build_ident_expr_from_str("NaN")
+    } else if value.is_infinite() {
+        let infinity = build_ident_expr_from_str("Infinity");
+        if value < 0.0 { build_neg(infinity) } else { infinity }
+    } else if value.is_sign_negative() {
+        build_neg(build_num(-value))
+    } else {
+        build_num(value)
+    }
+}
+
+fn build_math_call(method: &str, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        callee: Callee::Expr(Box::new(build_member(build_ident_expr_from_str("Math"), method))),
+        args: args
+            .into_iter()
+            .map(|arg| ExprOrSpread {
+                spread: None,
+                expr: Box::new(arg),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+struct MathEdgeVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+    methods: Vec<(String, Vec<usize>)>,
+    kind: EdgeKind,
+}
+
+impl ScopedAstVisitor for MathEdgeVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl MathEdgeVisitor {
+    /// An edge number, a limit of `Number`, or now and then a variable in scope
+    fn edge_value(&mut self) -> Expr {
+        let idents = self.scope_state.scopes.collect_idents();
+        match self.rng.random_range(0..10) {
+            0 if !idents.is_empty() => Expr::Ident(idents.choose(&mut self.rng).expect("there are idents").clone()),
+            1 => {
+                let constant = NUMBER_CONSTANTS.choose(&mut self.rng).expect("there are constants");
+                build_member(build_ident_expr_from_str("Number"), constant)
+            }
+            _ => build_edge_number(*EDGE_NUMBERS.choose(&mut self.rng).expect("there are edge numbers")),
+        }
+    }
+
+    fn arities(&self, method: &str) -> Option<&[usize]> {
+        self.methods
+            .iter()
+            .find(|(name, _)| name == method)
+            .map(|(_, arities)| arities.as_slice())
+    }
+
+    fn build_known(&mut self) -> Expr {
+        // only the corners whose arity the catalog agrees with
+        let corners: Vec<&(&str, &[f64])> = KNOWN_CORNERS
+            .iter()
+            .filter(|(method, args)| self.arities(method).is_some_and(|arities| arities.contains(&args.len())))
+            .collect();
+        match corners.choose(&mut self.rng) {
+            Some((method, args)) => build_math_call(method, args.iter().map(|&arg| build_edge_number(arg)).collect()),
+            None => self.build_call(),
+        }
+    }
+
+    fn build_call(&mut self) -> Expr {
+        let Some((method, arities)) = self.methods.choose(&mut self.rng).cloned() else {
+            return self.build_arithmetic();
+        };
+        let arity = arities.choose(&mut self.rng).copied().unwrap_or(0);
+        let args = (0..arity).map(|_| self.edge_value()).collect();
+        build_math_call(&method, args)
+    }
+
+    fn build_arithmetic(&mut self) -> Expr {
+        let op = *ARITHMETIC_OPS.choose(&mut self.rng).expect("there are operators");
+        let left = self.edge_value();
+        let right = self.edge_value();
+        build_paren_bin(op, left, right)
+    }
+
+    fn build_edge_stmt(&mut self) -> Stmt {
+        let value = match self.kind {
+            EdgeKind::Known => self.build_known(),
+            EdgeKind::Call => self.build_call(),
+            EdgeKind::Arithmetic => self.build_arithmetic(),
+        };
+        let name = self.renamer.next_var_name();
+        Stmt::Decl(Decl::Var(Box::new(build_var_decl(&name, value))))
+    }
+}
+
+impl VisitMut for MathEdgeVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            is_target.then(|| visitor.build_edge_stmt())
+        });
+    }
+}
+
+impl MathEdgeMutator {
+    fn mutate_with_kind(&self, mut ast: Script, kind: EdgeKind, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = MathEdgeVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+            methods: math_methods(),
+            kind,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for MathEdgeMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let kind = *EDGE_KINDS.choose(rng).expect("there are edge kinds");
+        self.mutate_with_kind(ast, kind, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use swc_ecma_visit::Visit;
+
+    /// Method names and argument counts of the `Math.<method>(...)` calls
+    #[derive(Default)]
+    struct MathCalls(Vec<(String, usize)>);
+
+    impl Visit for MathCalls {
+        fn visit_call_expr(&mut self, node: &CallExpr) {
+            node.visit_children_with(self);
+            let Callee::Expr(callee) = &node.callee else {
+                return;
+            };
+            if let Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop),
+                ..
+            }) = &**callee
+            {
+                if matches!(&**obj, Expr::Ident(ident) if &*ident.sym == "Math") {
+                    self.0.push((prop.sym.to_string(), node.args.len()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn math_edges_round_trip_with_catalog_arities() {
+        let methods = math_methods();
+        for kind in EDGE_KINDS {
+            let mutants = assert_mutations_reparse_with("let v0 = 1; let v1 = v0 * 2;", 32, |ast, rng| {
+                MathEdgeMutator.mutate_with_kind(ast, kind, rng)
+            });
+            for (code, reparsed) in mutants {
+                assert_eq!(reparsed.body.len(), 3, "no statement inserted in {}", code);
+                assert!(code.contains("let v2 = "), "no fresh variable in {}", code);
+
+                let mut calls = MathCalls::default();
+                reparsed.visit_with(&mut calls);
+                if kind != EdgeKind::Arithmetic {
+                    assert_eq!(calls.0.len(), 1, "expected one Math call in {}", code);
+                }
+                for (method, argc) in &calls.0 {
+                    let arities = methods
+                        .iter()
+                        .find(|(name, _)| name == method)
+                        .map(|(_, arities)| arities.clone())
+                        .unwrap_or_else(|| panic!("Math.{} is not in the catalog: {}", method, code));
+                    assert!(arities.contains(argc), "Math.{} called with {} arguments in {}", method, argc, code);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::swc_ecma_ast::*;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+
+use crate::code_generators::ast_helpers::{
+    build_ctor_expr, build_ident_expr_from_str, build_member, build_num, build_random_literal,
+    build_random_primitive_literal, build_str_lit, is_valid_identifier_name,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::js_objects::js_types::JsObjectType;
+
+/// MethodBorrowMutator
+/// Calls an array method on something that isn't an array, e.g. `v0.map(f0)` becomes
+/// `Array.prototype.map.call("foo", f0)` or
+/// `Array.prototype.map.apply(new Float64Array(3), [f0])`.
+///
+/// The array methods are generic and only need a `length` and indexed properties, but
+/// engines special-case array receivers. Strings, array-likes, `arguments` objects and
+/// typed arrays go through the generic paths and their bounds checks instead.
+pub struct MethodBorrowMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReceiverKind {
+    String,     // "foo"
+    ArrayLike,  // { length: 3, 0: 1, 1: "foo" }
+    Arguments,  // (function () { return arguments; })(1, 2)
+    TypedArray, // new Uint8Array([1, 2])
+}
+
+const RECEIVER_KINDS: [ReceiverKind; 4] = [
+    ReceiverKind::String,
+    ReceiverKind::ArrayLike,
+    ReceiverKind::Arguments,
+    ReceiverKind::TypedArray,
+];
+
+const STRING_RECEIVERS: &[&str] = &["", "a", "foo", "こんにちは", "\u{1F600}x"];
+
+const TYPED_ARRAYS: &[&str] = &[
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "BigInt64Array",
+];
+
+/// Names of the instance methods of the `Array` catalog entry
+fn array_methods() -> Vec<String> {
+    get_global_object("Array")
+        .map(|array| {
+            array
+                .instance_methods()
+                .into_iter()
+                .map(|method| method.sym().to_string())
+                .filter(|name| is_valid_identifier_name(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn build_array(elems: Vec<Expr>) -> Expr {
+    Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: elems
+            .into_iter()
+            .map(|expr| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect(),
+    })
+}
+
+fn random_elements(rng: &mut impl Rng, count: usize) -> Vec<Expr> {
+    (0..count)
+        .map(|_| build_random_primitive_literal(rng))
+        .collect()
+}
+
+fn build_receiver(rng: &mut impl Rng, kind: ReceiverKind) -> Expr {
+    match kind {
+        ReceiverKind::String => {
+            let value = *STRING_RECEIVERS.choose(rng).expect("there are string receivers");
+            Expr::Lit(Lit::Str(build_str_lit(value)))
+        }
+        ReceiverKind::ArrayLike => {
+            // the length can be past the last index, leaving holes
+            let count = rng.random_range(0..=4);
+            let length = count + rng.random_range(0..=2);
+            let mut props = vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName::new(Atom::from("length"), DUMMY_SP)),
+                value: Box::new(build_num(length as f64)),
+            })))];
+            for (i, value) in random_elements(rng, count).into_iter().enumerate() {
+                props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Num(Number {
+                        span: DUMMY_SP,
+                        value: i as f64,
+                        raw: None,
+                    }),
+                    value: Box::new(value),
+                }))));
+            }
+            Expr::Object(ObjectLit { span: DUMMY_SP, props })
+        }
+        ReceiverKind::Arguments => {
+            let function = Expr::Fn(FnExpr {
+                ident: None,
+                function: Box::new(Function {
+                    params: Vec::new(),
+                    decorators: Vec::new(),
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    body: Some(BlockStmt {
+                        span: DUMMY_SP,
+                        ctxt: SyntaxContext::empty(),
+                        stmts: vec![Stmt::Return(ReturnStmt {
+                            span: DUMMY_SP,
+                            arg: Some(Box::new(build_ident_expr_from_str("arguments"))),
+                        })],
+                    }),
+                    is_generator: false,
+                    is_async: false,
+                    type_params: None,
+                    return_type: None,
+                }),
+            });
+            let count = rng.random_range(0..=4);
+            Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+                    span: DUMMY_SP,
+                    expr: Box::new(function),
+                }))),
+                args: random_elements(rng, count)
+                    .into_iter()
+                    .map(|expr| ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(expr),
+                    })
+                    .collect(),
+                type_args: None,
+            })
+        }
+        ReceiverKind::TypedArray => {
+            let name = *TYPED_ARRAYS.choose(rng).expect("there are typed arrays");
+            let arg = if name == "BigInt64Array" || rng.random_bool(0.5) {
+                build_num(rng.random_range(0..=8) as f64)
+            } else {
+                let count = rng.random_range(0..=4);
+                build_array((0..count).map(|_| build_random_literal(rng, JsObjectType::Number)).collect())
+            };
+            build_ctor_expr(name, vec![arg])
+        }
+    }
+}
+
+/// `<receiver>.<method>(...)` with a method of `methods`
+fn borrowed_method<'a>(call: &CallExpr, methods: &'a [String]) -> Option<&'a str> {
+    let Callee::Expr(callee) = &call.callee else {
+        return None;
+    };
+    let Expr::Member(MemberExpr {
+        prop: MemberProp::Ident(prop),
+        ..
+    }) = &**callee
+    else {
+        return None;
+    };
+    methods
+        .iter()
+        .find(|method| method.as_str() == &*prop.sym)
+        .map(String::as_str)
+}
+
+struct MethodBorrowVisitor {
+    rng: StdRng,
+    /// None when only counting the calls
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    methods: Vec<String>,
+    kind: ReceiverKind,
+}
+
+impl MethodBorrowVisitor {
+    /// `Array.prototype.<method>.call(<receiver>, ...args)`, or `.apply` with the
+    /// arguments in an array
+    fn borrow(&mut self, call: &mut CallExpr, method: &str) {
+        let function = build_member(
+            build_member(build_ident_expr_from_str("Array"), "prototype"),
+            method,
+        );
+        let receiver = ExprOrSpread {
+            spread: None,
+            expr: Box::new(build_receiver(&mut self.rng, self.kind)),
+        };
+        let args = std::mem::take(&mut call.args);
+        let has_spread = args.iter().any(|arg| arg.spread.is_some());
+        let (invoke, args) = if !has_spread && self.rng.random_bool(0.3) {
+            let packed = Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: args.into_iter().map(Some).collect(),
+            });
+            let packed = ExprOrSpread {
+                spread: None,
+                expr: Box::new(packed),
+            };
+            ("apply", vec![receiver, packed])
+        } else {
+            ("call", std::iter::once(receiver).chain(args).collect())
+        };
+        call.callee = Callee::Expr(Box::new(build_member(function, invoke)));
+        call.args = args;
+    }
+}
+
+impl VisitMut for MethodBorrowVisitor {
+    fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
+        node.visit_mut_children_with(self);
+        let Some(method) = borrowed_method(node, &self.methods).map(str::to_string) else {
+            return;
+        };
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        if is_target {
+            self.borrow(node, &method);
+        }
+    }
+}
+
+impl MethodBorrowMutator {
+    fn mutate_with_kind(&self, mut ast: Script, kind: ReceiverKind, rng: &mut StdRng) -> Result<Script> {
+        let mut visitor = MethodBorrowVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            methods: array_methods(),
+            kind,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = MethodBorrowVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for MethodBorrowMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let kind = *RECEIVER_KINDS.choose(rng).expect("there are receiver kinds");
+        self.mutate_with_kind(ast, kind, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+
+    #[test]
+    fn borrowed_methods_round_trip() {
+        let source = "let v0 = [3, 1, 2]; let v1 = v0.map((a0) => a0 + 1); v0.forEach((a0) => a0, v1);";
+        for (kind, marker) in [
+            (ReceiverKind::String, "\""),
+            (ReceiverKind::ArrayLike, "length: "),
+            (ReceiverKind::Arguments, "return arguments"),
+            (ReceiverKind::TypedArray, "Array("),
+        ] {
+            let mutants = assert_mutations_reparse_with(source, 16, |ast, rng| {
+                MethodBorrowMutator.mutate_with_kind(ast, kind, rng)
+            });
+            for (code, reparsed) in mutants {
+                assert_eq!(reparsed.body.len(), 3, "statements lost in {}", code);
+                assert!(
+                    code.contains("Array.prototype.map.") || code.contains("Array.prototype.forEach."),
+                    "no borrowed method in {}",
+                    code
+                );
+                assert!(code.contains(".call(") || code.contains(".apply("), "method not invoked in {}", code);
+                assert!(code.contains(marker), "no {:?} receiver in {}", kind, code);
+            }
+        }
+    }
+}
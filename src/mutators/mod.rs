@@ -1,21 +1,65 @@
+pub mod arity;
+pub mod bench;
+pub mod calls;
+pub mod classes;
+pub mod closures;
+pub mod coercion;
+pub mod collections;
+pub mod computed_keys;
+pub mod conditionals;
+pub mod const_fold;
+pub mod destructuring;
+pub mod element_kinds;
 pub mod elements;
+pub mod enumeration;
 pub mod expressions;
+pub mod freeze;
+pub mod function_naming;
+pub mod hoist_sink;
+pub mod iterators;
+pub mod json;
+pub mod length;
 pub mod literals;
+pub mod loops;
+pub mod math_edges;
+pub mod method_borrow;
 pub mod minifier;
+pub mod natives;
+pub mod nesting;
 pub mod operators;
+pub mod overflow;
+pub mod recursion;
 pub mod scope;
+pub mod self_test;
+pub mod species;
 pub mod splice;
+pub mod spread;
+pub mod spread_holes;
+pub mod strict_mode;
+pub mod suspension;
+pub mod symbols;
+#[cfg(test)]
+pub mod test_utils;
+pub mod type_guards;
+pub mod validity;
 pub mod js_objects;
 
-use std::sync::{Arc, Mutex};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use swc_ecma_visit::swc_ecma_ast::Script;
+use rand::rngs::StdRng;
 use rand::seq::{IndexedRandom, SliceRandom};
 
+use crate::utils::dictionary::Dictionary;
 use crate::utils::rand_utils::random_weighted_choice;
 
+/// Mutators draw every random choice from the `rng` they are given, so the same RNG
+/// state reproduces the same mutation
 pub trait AstMutator: Send + Sync {
-    fn mutate(&self, ast: Script) -> anyhow::Result<Script>;
-    fn splice(&self, _ast: &Script, _donor: &Script) -> anyhow::Result<Script> {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> anyhow::Result<Script>;
+    fn splice(&self, _ast: &Script, _donor: &Script, _rng: &mut StdRng) -> anyhow::Result<Script> {
         Err(anyhow::anyhow!("splice not implemented for this mutator"))
     }
 }
@@ -24,10 +68,25 @@ pub trait AstMutator: Send + Sync {
 pub struct MutatorStats {
     pub mean_reward: f64,
     pub total_reward: f64,
+    /// Mean of the rewards with the older ones decayed, see `RewardConfig::decay`
+    pub recent_mean_reward: f64,
     pub uses: u64,
     pub last_reward: f64,
     pub invalid_count: u64,
     pub timeout_count: u64,
+    /// Mutations that returned the program unchanged
+    pub noop_count: u64,
+}
+
+impl MutatorStats {
+    /// Share of the uses that left the program unchanged
+    pub fn noop_rate(&self) -> f64 {
+        if self.uses == 0 {
+            0.0
+        } else {
+            self.noop_count as f64 / self.uses as f64
+        }
+    }
 }
 
 impl Default for MutatorStats {
@@ -35,19 +94,66 @@ impl Default for MutatorStats {
         Self {
             mean_reward: 0.0,
             total_reward: 0.0,
+            recent_mean_reward: 0.0,
             uses: 0,
             last_reward: 0.0,
             invalid_count: 0,
             timeout_count: 0,
+            noop_count: 0,
         }
     }
 }
 
+/// Fixed-point scale of the reward accumulator (6 decimal places)
+const REWARD_SCALE: f64 = 1_000_000.0;
+
+/// Average of the rewards where every new reward scales the earlier ones by the decay
+/// factor, so successes fade once a mutator stops finding anything
+#[derive(Debug, Default, Clone, Copy)]
+struct DecayedReward {
+    sum: f64,
+    weight: f64,
+}
+
+impl DecayedReward {
+    fn record(&mut self, reward: f64, decay: f64) {
+        self.sum = self.sum * decay + reward;
+        self.weight = self.weight * decay + 1.0;
+    }
+
+    fn mean(&self) -> f64 {
+        if self.weight == 0.0 { 0.0 } else { self.sum / self.weight }
+    }
+}
+
+/// Lock-free counters behind `MutatorStats`. Rewards are accumulated in fixed point so
+/// they can be summed with a single atomic add.
+#[derive(Default)]
+struct AtomicMutatorStats {
+    uses: AtomicU64,
+    total_reward_fp: AtomicI64,
+    last_reward_bits: AtomicU64,
+    invalid_count: AtomicU64,
+    timeout_count: AtomicU64,
+    noop_count: AtomicU64,
+    /// Decaying needs a read-modify-write of two floats, too rare to bother with atomics
+    recent: std::sync::Mutex<DecayedReward>,
+}
+
+/// Structural hash of a program, cheap enough to tell whether a mutation changed anything
+fn ast_hash(ast: &Script) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ast.hash(&mut hasher);
+    hasher.finish()
+}
+
 pub struct ManagedMutator {
     name: String,
     mutator: Box<dyn AstMutator>,
-    stats: Mutex<MutatorStats>,
+    stats: AtomicMutatorStats,
     splicer: bool,
+    /// Scales the probability of picking this mutator
+    weight: f64,
 }
 
 impl ManagedMutator {
@@ -55,61 +161,124 @@ impl ManagedMutator {
         Self {
             name: name.into(),
             mutator,
-            stats: Mutex::new(MutatorStats::default()),
+            stats: AtomicMutatorStats::default(),
             splicer,
+            weight: 1.0,
         }
     }
 
+    /// Pick this mutator `weight` times as often as a mutator with the same rewards
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn mutate(&self, ast: Script) -> anyhow::Result<Script> {
-        // TODO: consider not locking everytime and changing this to an atomic update
-        self.stats.lock().expect("mutator stats poisoned").uses += 1;
-        self.mutator.mutate(ast)
+    pub fn mutate(&self, ast: Script, rng: &mut StdRng) -> anyhow::Result<Script> {
+        self.mutate_tracked(ast, rng).map(|(ast, _)| ast)
     }
 
-    pub fn splice(&self, ast: &Script, donor: &Script) -> anyhow::Result<Script> {
-        self.stats.lock().expect("mutator stats poisoned").uses += 1;
-        self.mutator.splice(ast, donor)
+    /// Like `mutate`, but None if the mutator left the program unchanged, e.g. because
+    /// it found nothing to mutate. Executing it again would be a wasted engine run.
+    pub fn mutate_if_changed(&self, ast: Script, rng: &mut StdRng) -> anyhow::Result<Option<Script>> {
+        self.mutate_tracked(ast, rng).map(|(ast, changed)| changed.then_some(ast))
+    }
+
+    /// Mutate and tell whether the program changed, counting the no-ops
+    fn mutate_tracked(&self, ast: Script, rng: &mut StdRng) -> anyhow::Result<(Script, bool)> {
+        self.stats.uses.fetch_add(1, Ordering::Relaxed);
+        let before = ast_hash(&ast);
+        let mutated = self.mutator.mutate(ast, rng)?;
+        let changed = ast_hash(&mutated) != before;
+        if !changed {
+            self.stats.noop_count.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok((mutated, changed))
+    }
+
+    pub fn splice(&self, ast: &Script, donor: &Script, rng: &mut StdRng) -> anyhow::Result<Script> {
+        self.stats.uses.fetch_add(1, Ordering::Relaxed);
+        self.mutator.splice(ast, donor, rng)
     }
 
     pub fn is_splicer(&self) -> bool {
         self.splicer
     }
 
-    pub fn record_reward(&self, reward: f64) {
-        let mut stats = self.stats.lock().expect("mutator stats poisoned");
-        stats.uses += 1;
-        stats.total_reward += reward;
-        stats.mean_reward = if stats.uses == 0 {
-            0.0
-        } else {
-            stats.total_reward / stats.uses as f64
-        };
-        stats.last_reward = reward;
+    /// Uses are counted by `mutate`/`splice`, so this only accumulates the reward. The
+    /// recent mean scales the earlier rewards by `decay` first.
+    pub fn record_reward(&self, reward: f64, decay: f64) {
+        self.stats
+            .total_reward_fp
+            .fetch_add((reward * REWARD_SCALE).round() as i64, Ordering::Relaxed);
+        self.stats.last_reward_bits.store(reward.to_bits(), Ordering::Relaxed);
+        self.stats.recent.lock().expect("mutator stats poisoned").record(reward, decay);
+    }
+
+    /// A mutant that turned out to be a program executed recently. It brought nothing
+    /// new, so it counts as a no-op instead of being rewarded.
+    pub fn record_noop(&self) {
+        self.stats.noop_count.fetch_add(1, Ordering::Relaxed);
     }
 
     pub fn record_invalid(&self, is_timeout: bool) {
-        let mut stats = self.stats.lock().expect("mutator stats poisoned");
-        stats.invalid_count += 1;
+        self.stats.invalid_count.fetch_add(1, Ordering::Relaxed);
         if is_timeout {
-            stats.timeout_count += 1;
+            self.stats.timeout_count.fetch_add(1, Ordering::Relaxed);
         }
     }
 
+    /// Every counter is read once, so the snapshot is internally consistent (the mean is
+    /// derived from the reported totals) even if it races with concurrent updates
     #[allow(dead_code)]
     pub fn stats_snapshot(&self) -> MutatorStats {
-        self.stats.lock().expect("mutator stats poisoned").clone()
+        let uses = self.stats.uses.load(Ordering::Relaxed);
+        let total_reward = self.stats.total_reward_fp.load(Ordering::Relaxed) as f64 / REWARD_SCALE;
+        MutatorStats {
+            mean_reward: if uses == 0 { 0.0 } else { total_reward / uses as f64 },
+            total_reward,
+            recent_mean_reward: self.stats.recent.lock().expect("mutator stats poisoned").mean(),
+            uses,
+            last_reward: f64::from_bits(self.stats.last_reward_bits.load(Ordering::Relaxed)),
+            invalid_count: self.stats.invalid_count.load(Ordering::Relaxed),
+            timeout_count: self.stats.timeout_count.load(Ordering::Relaxed),
+            noop_count: self.stats.noop_count.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Weight of this mutator in `get_weighted_ast_mutator_choice`. Unused mutators get
+    /// a chance first, then the recent rewards decide.
+    pub fn selection_weight(&self) -> f64 {
+        let stats = self.stats_snapshot();
+        let weight = if stats.uses == 0 {
+            1.0
+        } else if stats.recent_mean_reward > 0.0 {
+            stats.recent_mean_reward
+        } else {
+            0.1
+        };
+        weight * self.weight
     }
 }
 
 pub fn get_ast_mutators() -> Vec<Arc<ManagedMutator>> {
+    get_ast_mutators_with_dictionary(Arc::new(Dictionary::default()))
+}
+
+/// Same as `get_ast_mutators`, with the literal mutators drawing from `dictionary`
+pub fn get_ast_mutators_with_dictionary(dictionary: Arc<Dictionary>) -> Vec<Arc<ManagedMutator>> {
     vec![
         Arc::new(ManagedMutator::new(
             "NumericTweaker",
-            Box::new(literals::numeric_tweaker::NumericTweaker::new()),
+            Box::new(literals::numeric_tweaker::NumericTweaker::new(dictionary.clone())),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "StringMutator",
+            Box::new(literals::string_mutator::StringMutator::new(dictionary)),
             false,
         )),
         Arc::new(ManagedMutator::new(
@@ -122,16 +291,21 @@ pub fn get_ast_mutators() -> Vec<Arc<ManagedMutator>> {
             Box::new(literals::array_mutator::ArrayMutator {}),
             false,
         )),
-        // Arc::new(ManagedMutator::new(
-        //     "ConstructorCall",
-        //     Box::new(literals::constructor_call::ConstructorCall {}),
-        //     false,
-        // )),
+        Arc::new(ManagedMutator::new(
+            "ConstructorCall",
+            Box::new(literals::constructor_call::ConstructorCall {}),
+            false,
+        )),
         Arc::new(ManagedMutator::new(
             "OperatorSwap",
             Box::new(operators::OperatorSwap {}),
             false,
         )),
+        Arc::new(ManagedMutator::new(
+            "EqualityMutator",
+            Box::new(operators::EqualityMutator {}),
+            false,
+        )),
         Arc::new(ManagedMutator::new(
             "ExpressionSwapDup",
             Box::new(expressions::ExpressionSwapDup {}),
@@ -157,6 +331,181 @@ pub fn get_ast_mutators() -> Vec<Arc<ManagedMutator>> {
             Box::new(elements::RemovePropMutator {}),
             false,
         )),
+        Arc::new(ManagedMutator::new(
+            "AccessFormMutator",
+            Box::new(elements::AccessFormMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "SymbolMutator",
+            Box::new(symbols::SymbolMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "DestructuringMutator",
+            Box::new(destructuring::DestructuringMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "TemplateLiteralMutator",
+            Box::new(literals::template_literal::TemplateLiteralMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "NumberFormatMutator",
+            Box::new(literals::number_format::NumberFormatMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "HoistSinkMutator",
+            Box::new(hoist_sink::HoistSinkMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "IteratorMutator",
+            Box::new(iterators::IteratorMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "CollectionMutator",
+            Box::new(collections::CollectionMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "ClassFieldMutator",
+            Box::new(classes::ClassFieldMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "ConditionalFormMutator",
+            Box::new(conditionals::ConditionalFormMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "ConstFoldMutator",
+            Box::new(const_fold::ConstFoldMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "LengthMutator",
+            Box::new(length::LengthMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "SuspensionMutator",
+            Box::new(suspension::SuspensionMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "ArityMutator",
+            Box::new(arity::ArityMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "LiteralCombineMutator",
+            Box::new(literals::literal_combine::LiteralCombineMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "SpreadMutator",
+            Box::new(spread::SpreadMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "StrictModeMutator",
+            Box::new(strict_mode::StrictModeMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "EnumerationMutator",
+            Box::new(enumeration::EnumerationMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "CoercionMutator",
+            Box::new(coercion::CoercionMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "ComputedKeyMutator",
+            Box::new(computed_keys::ComputedKeyMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "OverflowMutator",
+            Box::new(overflow::OverflowMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "ClosureCaptureMutator",
+            Box::new(closures::ClosureCaptureMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "LoopTransformMutator",
+            Box::new(loops::LoopTransformMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "TypeGuardMutator",
+            Box::new(type_guards::TypeGuardMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "CallInjectMutator",
+            Box::new(calls::CallInjectMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "MathEdgeMutator",
+            Box::new(math_edges::MathEdgeMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "JsonRoundtripMutator",
+            Box::new(json::JsonRoundtripMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "ElementKindMutator",
+            Box::new(element_kinds::ElementKindMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "FreezeMutator",
+            Box::new(freeze::FreezeMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "RecursionMutator",
+            Box::new(recursion::RecursionMutator {}),
+            false,
+        )),
+        Arc::new(ManagedMutator::new(
+            "FunctionNamingMutator",
+            Box::new(function_naming::FunctionNamingMutator {}),
+            false,
+        )),
+        // deep programs are slow to parse and rarely get far in the engine
+        Arc::new(
+            ManagedMutator::new("NestingMutator", Box::new(nesting::NestingMutator {}), false)
+                .with_weight(0.1),
+        ),
+        // the generic paths are slow and throw on most receivers
+        Arc::new(
+            ManagedMutator::new("MethodBorrowMutator", Box::new(method_borrow::MethodBorrowMutator {}), false)
+                .with_weight(0.2),
+        ),
+        // the programs are large and mostly end up in the same species paths
+        Arc::new(
+            ManagedMutator::new("SpeciesMutator", Box::new(species::SpeciesMutator {}), false)
+                .with_weight(0.2),
+        ),
+        // a niche of argument evaluation, and the throwing iterables end the program
+        Arc::new(
+            ManagedMutator::new("SpreadHoleMutator", Box::new(spread_holes::SpreadHoleMutator {}), false)
+                .with_weight(0.2),
+        ),
         Arc::new(ManagedMutator::new(
             "SpliceMutator",
             Box::new(splice::SpliceMutator {}),
@@ -165,8 +514,22 @@ pub fn get_ast_mutators() -> Vec<Arc<ManagedMutator>> {
     ]
 }
 
-pub fn get_mutator_by_name(name: &str) -> Option<Arc<ManagedMutator>> {
-    let mutators = get_ast_mutators();
+/// Only usable when every engine in the pool supports natives syntax
+pub fn get_natives_mutator() -> Arc<ManagedMutator> {
+    Arc::new(ManagedMutator::new(
+        "NativesMutator",
+        Box::new(natives::NativesMutator {}),
+        false,
+    ))
+}
+
+/// Look up a mutator by name. `NativesMutator` is only found when `supports_natives` is
+/// set, i.e. the profile it will run with supports natives syntax.
+pub fn get_mutator_by_name(name: &str, supports_natives: bool) -> Option<Arc<ManagedMutator>> {
+    let mut mutators = get_ast_mutators();
+    if supports_natives {
+        mutators.push(get_natives_mutator());
+    }
     for m in mutators {
         if m.name() == name {
             return Some(m.clone());
@@ -179,30 +542,20 @@ pub fn get_mutator_by_name(name: &str) -> Option<Arc<ManagedMutator>> {
 /// Does NOT return splicers
 pub fn get_weighted_ast_mutator_choice(
     mutators: &[Arc<ManagedMutator>],
+    rng: &mut impl rand::Rng,
 ) -> Arc<ManagedMutator> {
     let mut choices: Vec<(Arc<ManagedMutator>, f64)> = Vec::new();
     for m in mutators {
         if m.is_splicer() {
             continue;
         }
-        let stats = m.stats_snapshot();
-        let weight = if stats.uses == 0 {
-            1.0
-        } else {
-            // stats.mean_reward + 1.0 / (stats.invalid_count as f64 + 1.0)s
-            if stats.mean_reward > 0.0 {
-                stats.mean_reward
-            } else {
-                0.1
-            }
-        };
-        choices.push((m.clone(), weight));
+        choices.push((m.clone(), m.selection_weight()));
     }
-    random_weighted_choice(&mut rand::rng(), &choices)
+    random_weighted_choice(rng, &choices)
 }
 
 // Returns a random splicer mutator
-pub fn get_random_splicer(mutators: &[Arc<ManagedMutator>]) -> Option<Arc<ManagedMutator>> {
+pub fn get_random_splicer(mutators: &[Arc<ManagedMutator>], rng: &mut impl rand::Rng) -> Option<Arc<ManagedMutator>> {
     let splicers: Vec<Arc<ManagedMutator>> = mutators
         .iter()
         .filter(|m| m.is_splicer())
@@ -212,7 +565,7 @@ pub fn get_random_splicer(mutators: &[Arc<ManagedMutator>]) -> Option<Arc<Manage
     if splicers.is_empty() {
         None
     } else {
-        splicers.choose(&mut rand::rng()).cloned()
+        splicers.choose(rng).cloned()
     }
 }
 
@@ -220,8 +573,82 @@ pub fn get_random_splicer(mutators: &[Arc<ManagedMutator>]) -> Option<Arc<Manage
 mod tests {
     use super::*;
     use crate::parsing::parser::*;
+    use crate::utils::rand_utils::unseeded_rng;
     use std::fs;
 
+    struct NopMutator;
+
+    impl AstMutator for NopMutator {
+        fn mutate(&self, ast: Script, _rng: &mut StdRng) -> anyhow::Result<Script> {
+            Ok(ast)
+        }
+    }
+
+    #[test]
+    fn one_use_per_mutation() {
+        let mutator = ManagedMutator::new("NopMutator", Box::new(NopMutator), false);
+        let ast = parse_js("let v0 = 1;".to_string()).unwrap();
+        for reward in [1.0, 0.0, -1.0, 5.0] {
+            mutator.mutate(ast.clone(), &mut unseeded_rng()).unwrap();
+            mutator.record_reward(reward, 1.0);
+        }
+        mutator.record_invalid(true);
+
+        let stats = mutator.stats_snapshot();
+        assert_eq!(stats.uses, 4);
+        assert_eq!(stats.total_reward, 5.0);
+        assert_eq!(stats.mean_reward, 1.25);
+        assert_eq!(stats.last_reward, 5.0);
+        assert_eq!(stats.invalid_count, 1);
+        assert_eq!(stats.timeout_count, 1);
+    }
+
+    #[test]
+    fn old_rewards_decay() {
+        let ast = parse_js("let v0 = 1;".to_string()).unwrap();
+        let early = ManagedMutator::new("NopMutator", Box::new(NopMutator), false);
+        let recent = ManagedMutator::new("NopMutator", Box::new(NopMutator), false);
+        let rewards: Vec<f64> = std::iter::repeat_n(1.0, 10).chain(std::iter::repeat_n(0.0, 200)).collect();
+        for (&old, &new) in rewards.iter().zip(rewards.iter().rev()) {
+            early.mutate(ast.clone(), &mut unseeded_rng()).unwrap();
+            early.record_reward(old, 0.99);
+            recent.mutate(ast.clone(), &mut unseeded_rng()).unwrap();
+            recent.record_reward(new, 0.99);
+        }
+
+        let (early_stats, recent_stats) = (early.stats_snapshot(), recent.stats_snapshot());
+        // same rewards overall, only their age differs
+        assert_eq!(early_stats.mean_reward, recent_stats.mean_reward);
+        assert!(early_stats.recent_mean_reward < recent_stats.recent_mean_reward);
+        assert!(early.selection_weight() < recent.selection_weight());
+    }
+
+    #[test]
+    fn unchanged_programs_are_noops() {
+        let nop = ManagedMutator::new("NopMutator", Box::new(NopMutator), false);
+        let ast = parse_js("let v0 = 1;".to_string()).unwrap();
+        assert!(nop.mutate_if_changed(ast.clone(), &mut unseeded_rng()).unwrap().is_none());
+
+        let boolean_flipper = get_mutator_by_name("BooleanFlipper", false).expect("unknown mutator");
+        let flipper_ast = parse_js("let v0 = true;".to_string()).unwrap();
+        let flipped = boolean_flipper.mutate_if_changed(flipper_ast, &mut unseeded_rng());
+        assert!(flipped.unwrap().is_some());
+        // no boolean to flip
+        assert!(boolean_flipper.mutate_if_changed(ast, &mut unseeded_rng()).unwrap().is_none());
+
+        let stats = nop.stats_snapshot();
+        assert_eq!((stats.uses, stats.noop_count), (1, 1));
+        assert_eq!(boolean_flipper.stats_snapshot().noop_rate(), 0.5);
+    }
+
+    #[test]
+    fn natives_mutator_is_only_found_with_natives_support() {
+        assert!(get_mutator_by_name("NativesMutator", false).is_none());
+        let natives = get_mutator_by_name("NativesMutator", true).expect("unknown mutator");
+        assert_eq!(natives.name(), "NativesMutator");
+        assert!(get_mutator_by_name("BooleanFlipper", true).is_some());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn print_ast() {
         let script_path = "./test_out.js";
@@ -241,8 +668,8 @@ mod tests {
         // let minifier = Minifier;
         // let mutated_ast = minifier.mutate(ast).expect("minification failed");
         
-        let mutator = get_mutator_by_name("ConstructorCall").expect("unknown mutator");
-        let mutated_ast = mutator.mutate(ast).expect("mutation failed");
+        let mutator = get_mutator_by_name("ConstructorCall", false).expect("unknown mutator");
+        let mutated_ast = mutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
         let mutated_code = generate_js(mutated_ast).expect("code generation failed");
 
         println!("Original code:\n{}", source);
@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::build_ident_expr_from_str;
+use crate::mutators::AstMutator;
+use crate::mutators::scope::NameCollector;
+
+/// NativesMutator
+/// Surrounds a call of a declared function with V8's optimization intrinsics:
+/// ```js
+/// %PrepareFunctionForOptimization(f0);
+/// f0(1); f0(1);
+/// %OptimizeFunctionOnNextCall(f0);
+/// f0(1);
+/// ```
+/// optionally followed by a deoptimization and another call. Natives syntax is a syntax
+/// error without `--allow-natives-syntax`, so this mutator must only be used when every
+/// engine's profile `supports_natives()`.
+pub struct NativesMutator;
+
+/// Counts expression statements calling one of `functions`
+struct CountFunctionCalls<'a> {
+    functions: &'a HashSet<String>,
+    count: usize,
+}
+
+struct NativesVisitor<'a> {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    functions: &'a HashSet<String>,
+}
+
+/// The function called by `stmt` if it is a plain `f(...)` of a declared function
+fn called_function<'a>(stmt: &'a Stmt, functions: &HashSet<String>) -> Option<&'a Ident> {
+    let Stmt::Expr(ExprStmt { expr, .. }) = stmt else {
+        return None;
+    };
+    let Expr::Call(CallExpr { callee: Callee::Expr(callee), .. }) = &**expr else {
+        return None;
+    };
+    match &**callee {
+        Expr::Ident(ident) if functions.contains(&*ident.sym) => Some(ident),
+        _ => None,
+    }
+}
+
+impl Visit for CountFunctionCalls<'_> {
+    fn visit_stmt(&mut self, node: &Stmt) {
+        if called_function(node, self.functions).is_some() {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+/// `%<intrinsic>(<function>);`
+///
+/// swc can't represent natives syntax, so this is emitted as `<invalid> % <intrinsic>(...)`
+/// which `generate_js` rewrites to `%<intrinsic>(...)`.
+pub fn build_intrinsic_call(intrinsic: &str, function: &Ident) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: BinaryOp::Mod,
+            left: Box::new(Expr::Invalid(Invalid { span: DUMMY_SP })),
+            right: Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                callee: Callee::Expr(Box::new(build_ident_expr_from_str(intrinsic))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Ident(Ident {
+                        span: DUMMY_SP,
+                        sym: Atom::from(&*function.sym),
+                        optional: false,
+                        ctxt: SyntaxContext::empty(),
+                    })),
+                }],
+                type_args: None,
+            })),
+        })),
+    })
+}
+
+impl NativesVisitor<'_> {
+    /// The optimization dance around `call`, which calls `function`
+    fn build_optimization(&mut self, call: Stmt, function: &Ident) -> Vec<Stmt> {
+        let mut stmts = vec![build_intrinsic_call("PrepareFunctionForOptimization", function)];
+        // warm up the feedback
+        for _ in 0..self.rng.random_range(1..=3) {
+            stmts.push(call.clone());
+        }
+        stmts.push(build_intrinsic_call("OptimizeFunctionOnNextCall", function));
+        stmts.push(call.clone());
+        if self.rng.random_bool(0.3) {
+            stmts.push(build_intrinsic_call("DeoptimizeFunction", function));
+            stmts.push(call);
+        }
+        stmts
+    }
+}
+
+impl VisitMut for NativesVisitor<'_> {
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut i = 0;
+        while i < stmts.len() {
+            let function = called_function(&stmts[i], self.functions).cloned();
+            let Some(function) = function else {
+                stmts[i].visit_mut_with(self);
+                i += 1;
+                continue;
+            };
+            let is_target = self.crt_idx == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                let new_stmts = self.build_optimization(stmts[i].clone(), &function);
+                let count = new_stmts.len();
+                stmts.splice(i..=i, new_stmts);
+                i += count;
+            } else {
+                stmts[i].visit_mut_with(self);
+                i += 1;
+            }
+        }
+    }
+}
+
+impl AstMutator for NativesMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let functions = names.func_names;
+
+        let mut counter = CountFunctionCalls {
+            functions: &functions,
+            count: 0,
+        };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = NativesVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            functions: &functions,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    #[test]
+    fn intrinsics_are_emitted_around_calls() {
+        let ast = parse_js("function f0(a) { return a + 1; } f0(1);".to_string())
+            .expect("failed to parse test script");
+        let mutated = NativesMutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+        let code = String::from_utf8(generate_js(mutated).expect("code generation failed")).unwrap();
+
+        let prepare = code
+            .find("%PrepareFunctionForOptimization(f0);")
+            .unwrap_or_else(|| panic!("missing prepare intrinsic in {}", code));
+        let optimize = code
+            .find("%OptimizeFunctionOnNextCall(f0);")
+            .unwrap_or_else(|| panic!("missing optimize intrinsic in {}", code));
+        assert!(prepare < optimize);
+        assert!(code[optimize..].contains("f0(1);"), "no call after optimizing in {}", code);
+        assert!(!code.contains("<invalid>"), "natives syntax was not rewritten in {}", code);
+
+        // mutants go back into the corpus, so they have to parse again
+        let reparsed = parse_js(code.clone()).unwrap_or_else(|err| panic!("{} does not reparse: {}", code, err));
+        let regenerated = String::from_utf8(generate_js(reparsed).expect("code generation failed")).unwrap();
+        assert_eq!(regenerated, code);
+    }
+
+    #[test]
+    fn calls_of_undeclared_functions_are_ignored() {
+        let source = "print(1);";
+        let ast = parse_js(source.to_string()).unwrap();
+        let mutated = NativesMutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+        let code = String::from_utf8(generate_js(mutated).unwrap()).unwrap();
+        assert!(!code.contains('%'), "unexpected intrinsic in {}", code);
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+use swc_ecma_visit::swc_ecma_ast::*;
+
+use crate::code_generators::ast_helpers::{build_ident_expr_from_str, build_random_primitive_literal};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::count_ast_nodes;
+
+/// NestingMutator
+/// Wraps an expression in several layers of nesting, e.g. `[[[[v0]]]]`,
+/// `Object(Object(Object(v0)))` or `(true ? (0 ? v0 : "a") : 1)`, to stress the parser and
+/// compiler recursion limits of the engine.
+///
+/// The fuzzer parses, visits and prints programs recursively too, so the expression depth
+/// of a program never grows past `MAX_EXPR_DEPTH`, and programs over `MAX_PROGRAM_NODES`
+/// nodes are left alone.
+pub struct NestingMutator;
+
+/// Deepest nesting of expressions a mutated program may have
+const MAX_EXPR_DEPTH: usize = 48;
+/// Programs larger than this are not nested any further
+const MAX_PROGRAM_NODES: usize = 20_000;
+/// Layers added by a single mutation
+const MIN_LAYERS: usize = 4;
+const MAX_LAYERS: usize = 16;
+
+/// Global functions that take a value and don't care much what it is
+const NESTING_CALLEES: &[&str] = &["Object", "Array", "String", "Number", "Boolean"];
+
+#[derive(Clone, Copy)]
+enum NestingKind {
+    Array,   // [[[x]]]
+    Call,    // f(f(f(x)))
+    Ternary, // (c ? (c ? x : y) : y)
+}
+
+impl NestingKind {
+    /// Expression nodes added by one layer
+    fn depth_per_layer(self) -> usize {
+        match self {
+            NestingKind::Array | NestingKind::Call => 1,
+            NestingKind::Ternary => 2,
+        }
+    }
+}
+
+/// Measures the deepest chain of nested expressions
+#[derive(Default)]
+struct ExprDepth {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Visit for ExprDepth {
+    fn visit_expr(&mut self, node: &Expr) {
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+        node.visit_children_with(self);
+        self.depth -= 1;
+    }
+}
+
+fn expr_depth(ast: &Script) -> usize {
+    let mut depth = ExprDepth::default();
+    ast.visit_with(&mut depth);
+    depth.max_depth
+}
+
+struct NestingVisitor {
+    rng: StdRng,
+    /// None when only counting the expressions that can be nested
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    kind: NestingKind,
+    layers: usize,
+}
+
+impl NestingVisitor {
+    fn nest(&mut self, expr: Expr) -> Expr {
+        let mut current = expr;
+        for _ in 0..self.layers {
+            current = match self.kind {
+                NestingKind::Array => Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: vec![Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(current),
+                    })],
+                }),
+                NestingKind::Call => {
+                    let callee = NESTING_CALLEES.choose(&mut self.rng).copied().unwrap_or("Object");
+                    Expr::Call(CallExpr {
+                        span: DUMMY_SP,
+                        ctxt: SyntaxContext::empty(),
+                        callee: Callee::Expr(Box::new(build_ident_expr_from_str(callee))),
+                        args: vec![ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(current),
+                        }],
+                        type_args: None,
+                    })
+                }
+                NestingKind::Ternary => {
+                    let test = build_random_primitive_literal(&mut self.rng);
+                    let alt = build_random_primitive_literal(&mut self.rng);
+                    Expr::Paren(ParenExpr {
+                        span: DUMMY_SP,
+                        expr: Box::new(Expr::Cond(CondExpr {
+                            span: DUMMY_SP,
+                            test: Box::new(test),
+                            cons: Box::new(current),
+                            alt: Box::new(alt),
+                        })),
+                    })
+                }
+            };
+        }
+        current
+    }
+}
+
+impl VisitMut for NestingVisitor {
+    fn visit_mut_expr_stmt(&mut self, node: &mut ExprStmt) {
+        // a string literal statement may be a directive like "use strict"
+        if !matches!(&*node.expr, Expr::Lit(Lit::Str(_))) {
+            node.visit_mut_children_with(self);
+        }
+    }
+
+    fn visit_mut_update_expr(&mut self, node: &mut UpdateExpr) {
+        // `x++` can't become `[x]++`, only what's inside the operand can be nested
+        node.arg.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        node.visit_mut_children_with(self);
+        // a sequence would need parentheses to stay a single element or operand
+        if matches!(node, Expr::Seq(_)) {
+            return;
+        }
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        if is_target {
+            let expr = std::mem::replace(node, Expr::Invalid(Invalid { span: DUMMY_SP }));
+            *node = self.nest(expr);
+        }
+    }
+}
+
+impl NestingMutator {
+    fn mutate_with_kind(&self, mut ast: Script, kind: NestingKind, rng: &mut StdRng) -> Result<Script> {
+        if count_ast_nodes(&ast) > MAX_PROGRAM_NODES {
+            return Ok(ast);
+        }
+        let room = MAX_EXPR_DEPTH.saturating_sub(expr_depth(&ast)) / kind.depth_per_layer();
+        let layers = rng.random_range(MIN_LAYERS..=MAX_LAYERS).min(room);
+        if layers == 0 {
+            return Ok(ast);
+        }
+
+        let mut visitor = NestingVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            kind,
+            layers,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = NestingVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for NestingMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let kind = match rng.random_range(0..3) {
+            0 => NestingKind::Array,
+            1 => NestingKind::Call,
+            _ => NestingKind::Ternary,
+        };
+        self.mutate_with_kind(ast, kind, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+
+    #[test]
+    fn nesting_depth_is_bounded() {
+        let source = "let v0 = 1; v0++; const v1 = v0 + 2;";
+        for kind in [NestingKind::Array, NestingKind::Call, NestingKind::Ternary] {
+            let mut code = source.to_string();
+            // keep nesting the same program, it must stop growing at the limit
+            for _ in 0..12 {
+                let ast = parse_js(code.clone()).expect("failed to parse test script");
+                let mutated = NestingMutator.mutate_with_kind(ast, kind, &mut unseeded_rng()).expect("mutation failed");
+                code = String::from_utf8(generate_js(mutated).expect("code generation failed")).unwrap();
+                let reparsed = parse_js(code.clone()).expect("mutated code does not parse");
+                assert!(expr_depth(&reparsed) <= MAX_EXPR_DEPTH, "nested too deep: {}", code);
+            }
+            let original = parse_js(source.to_string()).expect("failed to parse test script");
+            let nested = parse_js(code.clone()).expect("mutated code does not parse");
+            assert!(expr_depth(&nested) >= expr_depth(&original) + MIN_LAYERS, "nothing nested in {}", code);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use anyhow::Result;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::IndexedRandom;
 use swc_atoms::Atom;
 use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
@@ -19,7 +21,7 @@ pub struct CountOperators {
     in_for_stmt: Option<&'static str>,
 }
 pub struct OperatorSwapVisitor {
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
     idx_to_mutate: usize,
     current_idx: usize,
     in_for_stmt: Option<&'static str>,
@@ -117,16 +119,15 @@ impl VisitMut for OperatorSwapVisitor {
 }
 
 impl AstMutator for OperatorSwap {
-    fn mutate(&self, ast: Script) -> Result<Script> {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
         let mut counter = CountOperators { count: 0, in_for_stmt: None };
         ast.visit_with(&mut counter);
         if counter.count == 0 {
             return Ok(ast);
         }
-        let mut rng = rand::rng();
         let idx_to_mutate = rng.random_range(0..counter.count);
         let mut visitor = OperatorSwapVisitor {
-            rng,
+            rng: StdRng::from_rng(rng),
             idx_to_mutate,
             current_idx: 0,
             in_for_stmt: None,
@@ -136,3 +137,127 @@ impl AstMutator for OperatorSwap {
         Ok(ast)
     }
 }
+
+/// EqualityMutator
+/// Swaps a comparison for another one of the same kind: `==`, `!=`, `===` and `!==`
+/// between each other, and `<`, `<=`, `>` and `>=` between each other. Loose and
+/// strict equality coerce differently, which makes this a cheap source of differential
+/// behavior. Unlike OperatorSwap it leaves every other operator alone, so its reward
+/// is tracked on its own.
+pub struct EqualityMutator;
+
+const COMPARISON_GROUPS: &[&[BinaryOp]] = &[
+    &[op!("=="), op!("!="), op!("==="), op!("!==")],
+    &[op!("<"), op!("<="), op!(">"), op!(">=")],
+];
+
+fn comparison_group(op: BinaryOp) -> Option<&'static [BinaryOp]> {
+    COMPARISON_GROUPS.iter().copied().find(|group| group.contains(&op))
+}
+
+struct CountComparisons {
+    count: usize,
+    in_for_stmt: Option<&'static str>,
+}
+
+impl Visit for CountComparisons {
+    for_stmt_visitor!();
+    fn visit_bin_expr(&mut self, n: &BinExpr) {
+        n.visit_children_with(self);
+        // loop conditions are left alone, a flipped one rarely terminates
+        if self.in_for_stmt.is_none() && comparison_group(n.op).is_some() {
+            self.count += 1;
+        }
+    }
+}
+
+struct EqualityVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    current_idx: usize,
+    in_for_stmt: Option<&'static str>,
+}
+
+impl VisitMut for EqualityVisitor {
+    for_stmt_visitor!(mut);
+
+    fn visit_mut_bin_expr(&mut self, n: &mut BinExpr) {
+        n.visit_mut_children_with(self);
+        if self.in_for_stmt.is_some() {
+            return;
+        }
+        let Some(group) = comparison_group(n.op) else {
+            return;
+        };
+        if self.current_idx == self.idx_to_mutate {
+            let choices: Vec<BinaryOp> = group.iter().copied().filter(|&op| op != n.op).collect();
+            n.op = *choices.choose(&mut self.rng).expect("comparison groups have several operators");
+        }
+        self.current_idx += 1;
+    }
+}
+
+impl AstMutator for EqualityMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountComparisons { count: 0, in_for_stmt: None };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = EqualityVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            current_idx: 0,
+            in_for_stmt: None,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use crate::parsing::parser::parse_js;
+
+    #[derive(Default)]
+    struct CollectOps {
+        ops: Vec<BinaryOp>,
+    }
+
+    impl Visit for CollectOps {
+        fn visit_bin_expr(&mut self, n: &BinExpr) {
+            n.visit_children_with(self);
+            self.ops.push(n.op);
+        }
+    }
+
+    fn collect_ops(ast: &Script) -> Vec<BinaryOp> {
+        let mut collector = CollectOps::default();
+        ast.visit_with(&mut collector);
+        collector.ops
+    }
+
+    #[test]
+    fn only_comparisons_are_swapped() {
+        let source = "let v0 = 1 + 2; v0 == 3; v0 < 4 && v0 !== 5; for (let i = 0; i < 3; i++) { v0 * i; }";
+        let original = collect_ops(&parse_js(source.to_string()).unwrap());
+        for (code, reparsed) in assert_mutations_reparse(&EqualityMutator, source, 32) {
+            let ops = collect_ops(&reparsed);
+            assert_eq!(ops.len(), original.len());
+
+            let changed: Vec<(BinaryOp, BinaryOp)> = original
+                .iter()
+                .zip(&ops)
+                .filter(|(before, after)| before != after)
+                .map(|(&before, &after)| (before, after))
+                .collect();
+            assert_eq!(changed.len(), 1, "expected a single swap in {}", code);
+            let (before, after) = changed[0];
+            assert!(comparison_group(before).is_some_and(|group| group.contains(&after)), "{:?} became {:?}", before, after);
+            assert!(code.contains("i < 3"), "loop condition swapped in {}", code);
+        }
+    }
+}
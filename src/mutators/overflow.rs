@@ -0,0 +1,173 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_assign, build_expr_stmt, build_ident, build_ident_expr_from_str, build_num, build_paren_bin, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{
+    NameCollector, ScopeState, ScopedAstVisitor, VarRenamer, scoped_visit_mut_methods, visit_mut_stmts_inserting_after,
+};
+use crate::mutators::symbols::CountStmtSlots;
+use crate::utils::rand_utils::interesting_integer;
+
+/// OverflowMutator
+/// Inserts a chain of arithmetic on a fresh variable starting near a representation
+/// boundary, e.g.
+/// `let v3 = 1073741823; v3 = ((v3 * 2) + 1) | 0; v3 = v3 >>> 0; v3 = v3 + 2147483648;`
+/// so the value keeps moving between small integers, int32, uint32 and doubles. This
+/// targets the representation changes and the range analysis of the JIT.
+///
+/// The chain sometimes runs in a short loop so it gets optimized.
+pub struct OverflowMutator;
+
+const MIN_STEPS: usize = 2;
+const MAX_STEPS: usize = 5;
+/// Iterations of the loop around a chain
+const MAX_LOOP_ITERATIONS: f64 = 64.0;
+
+struct OverflowVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    renamer: VarRenamer,
+}
+
+impl ScopedAstVisitor for OverflowVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl OverflowVisitor {
+    fn boundary(&mut self) -> Expr {
+        build_num(interesting_integer(&mut self.rng))
+    }
+
+    /// The next value of `x` in the chain
+    fn build_step(&mut self, x: &str) -> Expr {
+        let var = || build_ident_expr_from_str(x);
+        match self.rng.random_range(0..9) {
+            // the classic int32 wrap-around
+            0 => build_paren_bin(
+                op!("|"),
+                build_paren_bin(op!(bin, "+"), build_paren_bin(op!("*"), var(), build_num(2.0)), build_num(1.0)),
+                build_num(0.0),
+            ),
+            1 => build_paren_bin(op!(bin, "+"), var(), build_num(1.0)),
+            2 => build_paren_bin(op!(bin, "-"), var(), build_num(1.0)),
+            3 => build_paren_bin(op!("*"), var(), build_num(2.0)),
+            4 => build_paren_bin(op!("|"), var(), build_num(0.0)),
+            5 => build_paren_bin(op!(">>>"), var(), build_num(0.0)),
+            6 => build_paren_bin(op!("<<"), var(), build_num(1.0)),
+            7 => {
+                let boundary = self.boundary();
+                build_paren_bin(op!(bin, "+"), var(), boundary)
+            }
+            _ => {
+                let boundary = self.boundary();
+                build_paren_bin(op!("^"), boundary, var())
+            }
+        }
+    }
+
+    fn build_chain(&mut self) -> Vec<Stmt> {
+        let x = self.renamer.next_var_name();
+        let idents = self.scope_state.scopes.collect_idents();
+        let init = match idents.choose(&mut self.rng) {
+            // a value of the program forced to int32 first
+            Some(ident) if self.rng.random_bool(0.3) => {
+                build_paren_bin(op!("|"), Expr::Ident(ident.clone()), build_num(0.0))
+            }
+            _ => self.boundary(),
+        };
+
+        let steps: Vec<Stmt> = (0..self.rng.random_range(MIN_STEPS..=MAX_STEPS))
+            .map(|_| {
+                let step = self.build_step(&x);
+                build_expr_stmt(build_assign(AssignOp::Assign, build_ident(&x), step))
+            })
+            .collect();
+        let mut stmts = vec![Stmt::Decl(Decl::Var(Box::new(build_var_decl(&x, init))))];
+        if self.rng.random_bool(0.3) {
+            let i = self.renamer.next_var_name();
+            let iterations = self.rng.random_range(2.0..=MAX_LOOP_ITERATIONS).round();
+            stmts.push(Stmt::For(ForStmt {
+                span: DUMMY_SP,
+                init: Some(VarDeclOrExpr::VarDecl(Box::new(build_var_decl(&i, build_num(0.0))))),
+                test: Some(Box::new(build_paren_bin(op!("<"), build_ident_expr_from_str(&i), build_num(iterations)))),
+                update: Some(Box::new(Expr::Update(UpdateExpr {
+                    span: DUMMY_SP,
+                    op: op!("++"),
+                    prefix: false,
+                    arg: Box::new(build_ident_expr_from_str(&i)),
+                }))),
+                body: Box::new(Stmt::Block(BlockStmt {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    stmts: steps,
+                })),
+            }));
+        } else {
+            stmts.extend(steps);
+        }
+        stmts
+    }
+}
+
+impl VisitMut for OverflowVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            if is_target { visitor.build_chain() } else { Vec::new() }
+        });
+    }
+}
+
+impl AstMutator for OverflowMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = OverflowVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            renamer: VarRenamer::new(names.var_names),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    #[test]
+    fn overflow_chains_round_trip() {
+        for (code, reparsed) in assert_mutations_reparse(&OverflowMutator, "let v0 = 1;", 32) {
+            assert!(reparsed.body.len() >= 3, "no chain inserted in {}", code);
+            assert!(code.contains("let v1 = "), "no fresh variable in {}", code);
+            assert!(code.matches("v1 = ").count() > MIN_STEPS, "no arithmetic on the chain in {}", code);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_bin, build_expr_stmt, build_ident, build_ident_expr_from_str, build_num, build_str_lit,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{NameCollector, VarRenamer};
+use crate::mutators::strict_mode::use_strict_position;
+
+/// Bounds of the recursion depth. Mutating the same function again multiplies the calls
+/// of both counters, so the bound stays small.
+const MIN_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 6;
+
+/// RecursionMutator
+/// Makes a function call itself a bounded number of times, e.g.
+/// `function f0(a0) { return a0 + 1; }` becomes
+/// ```js
+/// function f0(a0, v1) {
+///     v1 = typeof v1 === "number" && v1 <= 4 ? v1 : 4;
+///     if (v1 <= 0) {
+///         return;
+///     }
+///     f0(a0, v1 - 1);
+///     return a0 + 1;
+/// }
+/// ```
+///
+/// The depth is an extra parameter: callers that don't pass a number start it at the
+/// bound, every recursive call passes one less and the call at zero returns right away,
+/// so the worker never recurses without end. Anonymous function expressions get a name
+/// to call themselves by, in sloppy code `arguments.callee` is used sometimes instead.
+pub struct RecursionMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CalleeKind {
+    Name,            // f0(a0, v1 - 1)
+    ArgumentsCallee, // arguments.callee(a0, v1 - 1)
+}
+
+/// `<depth> = typeof <depth> === "number" && <depth> <= <bound> ? <depth> : <bound>;`
+fn build_depth_reset(depth: &str, bound: usize) -> Stmt {
+    let is_number = build_bin(
+        op!("==="),
+        Expr::Unary(UnaryExpr {
+            span: DUMMY_SP,
+            op: op!("typeof"),
+            arg: Box::new(build_ident_expr_from_str(depth)),
+        }),
+        Expr::Lit(Lit::Str(build_str_lit("number"))),
+    );
+    // NaN fails the comparison as well
+    let in_bounds = build_bin(op!("<="), build_ident_expr_from_str(depth), build_num(bound as f64));
+    let value = Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(build_bin(op!("&&"), is_number, in_bounds)),
+        cons: Box::new(build_ident_expr_from_str(depth)),
+        alt: Box::new(build_num(bound as f64)),
+    });
+    build_expr_stmt(Expr::Assign(AssignExpr {
+        span: DUMMY_SP,
+        op: AssignOp::Assign,
+        left: AssignTarget::Simple(SimpleAssignTarget::Ident(BindingIdent {
+            id: build_ident(depth),
+            type_ann: None,
+        })),
+        right: Box::new(value),
+    }))
+}
+
+/// `if (<depth> <= 0) { return; }`
+fn build_base_case(depth: &str) -> Stmt {
+    Stmt::If(IfStmt {
+        span: DUMMY_SP,
+        test: Box::new(build_bin(op!("<="), build_ident_expr_from_str(depth), build_num(0.0))),
+        cons: Box::new(Stmt::Block(BlockStmt {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            stmts: vec![Stmt::Return(ReturnStmt {
+                span: DUMMY_SP,
+                arg: None,
+            })],
+        })),
+        alt: None,
+    })
+}
+
+/// Parameter names if every parameter is a plain identifier, the recursive call passes
+/// them on unchanged
+fn simple_param_names(function: &Function) -> Option<Vec<String>> {
+    function
+        .params
+        .iter()
+        .map(|param| match &param.pat {
+            Pat::Ident(ident) => Some(ident.id.sym.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Functions that can be made recursive: generators only create an iterator when
+/// called, and the depth can't go after a rest parameter or into a destructuring
+fn is_candidate(function: &Function) -> bool {
+    !function.is_generator && function.body.is_some() && simple_param_names(function).is_some()
+}
+
+struct RecursionVisitor {
+    rng: StdRng,
+    /// None when only counting the functions
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    renamer: VarRenamer,
+    /// Nesting of strict code around the visited node, `arguments.callee` throws there
+    strict_depth: usize,
+}
+
+impl RecursionVisitor {
+    /// Add the depth parameter, the base case and the recursive call to `function`,
+    /// which is called `name` in its body
+    fn make_recursive(&mut self, function: &mut Function, name: Option<&str>, strict: bool) {
+        let params = simple_param_names(function).expect("candidates have simple parameters");
+        let depth = self.renamer.next_var_name();
+        let bound = self.rng.random_range(MIN_DEPTH..=MAX_DEPTH);
+        let callee_kind = match name {
+            Some(_) if strict || self.rng.random_bool(0.7) => CalleeKind::Name,
+            _ => CalleeKind::ArgumentsCallee,
+        };
+        let callee = match (callee_kind, name) {
+            (CalleeKind::Name, Some(name)) => build_ident_expr_from_str(name),
+            _ => Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(build_ident_expr_from_str("arguments")),
+                prop: MemberProp::Ident(IdentName::new(Atom::from("callee"), DUMMY_SP)),
+            }),
+        };
+        let args = params
+            .iter()
+            .map(|param| build_ident_expr_from_str(param))
+            .chain(std::iter::once(build_bin(
+                op!(bin, "-"),
+                build_ident_expr_from_str(&depth),
+                build_num(1.0),
+            )))
+            .map(|arg| ExprOrSpread {
+                spread: None,
+                expr: Box::new(arg),
+            })
+            .collect();
+        let call = Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            callee: Callee::Expr(Box::new(callee)),
+            args,
+            type_args: None,
+        });
+
+        function.params.push(Param {
+            span: DUMMY_SP,
+            decorators: Vec::new(),
+            pat: Pat::Ident(BindingIdent {
+                id: build_ident(&depth),
+                type_ann: None,
+            }),
+        });
+        let body = function.body.as_mut().expect("candidates have a body");
+        // after the directive prologue
+        let prologue = body
+            .stmts
+            .iter()
+            .take_while(|stmt| matches!(stmt, Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(_)))))
+            .count();
+        body.stmts.splice(
+            prologue..prologue,
+            [build_depth_reset(&depth, bound), build_base_case(&depth), build_expr_stmt(call)],
+        );
+    }
+
+    /// Count `function`, and make it recursive if it is the target. Returns whether an
+    /// anonymous function expression needs `name` to call itself.
+    fn visit_function_target(&mut self, function: &mut Function, name: Option<&str>, fresh_name: &str) -> bool {
+        let strict = self.strict_depth > 0 || has_strict_body(function);
+        if !is_candidate(function) {
+            return false;
+        }
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        if !is_target {
+            return false;
+        }
+        // anonymous functions in strict code can only call themselves by a new name
+        let (name, named) = match name {
+            Some(name) => (Some(name), false),
+            None if strict || self.rng.random_bool(0.5) => (Some(fresh_name), true),
+            None => (None, false),
+        };
+        self.make_recursive(function, name, strict);
+        named
+    }
+
+    fn visit_strict_children<N: VisitMutWith<Self>>(&mut self, node: &mut N, strict: bool) {
+        if strict {
+            self.strict_depth += 1;
+        }
+        node.visit_mut_children_with(self);
+        if strict {
+            self.strict_depth -= 1;
+        }
+    }
+}
+
+fn has_strict_body(function: &Function) -> bool {
+    function
+        .body
+        .as_ref()
+        .is_some_and(|body| use_strict_position(&body.stmts).is_some())
+}
+
+impl VisitMut for RecursionVisitor {
+    fn visit_mut_fn_decl(&mut self, node: &mut FnDecl) {
+        let strict = has_strict_body(&node.function);
+        self.visit_strict_children(&mut *node.function, strict);
+        let name = node.ident.sym.to_string();
+        self.visit_function_target(&mut node.function, Some(&name), &name);
+    }
+
+    fn visit_mut_fn_expr(&mut self, node: &mut FnExpr) {
+        let strict = has_strict_body(&node.function);
+        self.visit_strict_children(&mut *node.function, strict);
+        let name = node.ident.as_ref().map(|ident| ident.sym.to_string());
+        let fresh_name = match &name {
+            Some(name) => name.clone(),
+            None if self.idx_to_mutate == Some(self.crt_idx) => self.renamer.next_var_name(),
+            None => String::new(),
+        };
+        if self.visit_function_target(&mut node.function, name.as_deref(), &fresh_name) {
+            node.ident = Some(build_ident(&fresh_name));
+        }
+    }
+
+    // class bodies are strict code
+    fn visit_mut_class(&mut self, node: &mut Class) {
+        self.visit_strict_children(node, true);
+    }
+}
+
+impl AstMutator for RecursionMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let strict_depth = usize::from(use_strict_position(&ast.body).is_some());
+        let mut visitor = RecursionVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            renamer: VarRenamer::new(names.var_names),
+            strict_depth,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = RecursionVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            strict_depth,
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use swc_ecma_visit::Visit;
+
+    /// Functions with a `<param> <= 0` guard returning right away, by their parameters
+    #[derive(Default)]
+    struct BaseCases(Vec<String>);
+
+    impl Visit for BaseCases {
+        fn visit_function(&mut self, node: &Function) {
+            node.visit_children_with(self);
+            let Some(Pat::Ident(depth)) = node.params.last().map(|param| &param.pat) else {
+                return;
+            };
+            let has_base_case = node.body.iter().flat_map(|body| &body.stmts).any(|stmt| {
+                let Stmt::If(IfStmt { test, cons, .. }) = stmt else {
+                    return false;
+                };
+                let guards_depth = matches!(
+                    &**test,
+                    Expr::Bin(BinExpr { op: BinaryOp::LtEq, left, .. })
+                        if matches!(&**left, Expr::Ident(ident) if ident.sym == depth.id.sym)
+                );
+                let returns = matches!(&**cons, Stmt::Block(block) if matches!(block.stmts.first(), Some(Stmt::Return(_))));
+                guards_depth && returns
+            });
+            if has_base_case {
+                self.0.push(depth.id.sym.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn recursion_has_a_base_case() {
+        let source = "function f0(a0) { return a0 + 1; } let v0 = function () { 'use strict'; return f0(2); }; \
+                      class C0 { m() { return [1].map(function (a1) { return a1; }); } } v0();";
+        for (code, reparsed) in assert_mutations_reparse(&RecursionMutator, source, 32) {
+
+            let mut base_cases = BaseCases::default();
+            reparsed.visit_with(&mut base_cases);
+            assert_eq!(base_cases.0.len(), 1, "expected one bounded function in {}", code);
+            let depth = &base_cases.0[0];
+            assert!(code.contains(&format!("{} - 1", depth)), "depth not decremented in {}", code);
+            // strict code can't use arguments.callee, only f0 is sloppy
+            if code.contains("arguments.callee") {
+                assert!(
+                    code.contains(&format!("function f0(a0, {})", depth)),
+                    "arguments.callee in strict code: {}",
+                    code
+                );
+            }
+        }
+    }
+}
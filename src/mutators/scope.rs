@@ -51,6 +51,16 @@ impl ScopeStack {
         }
     }
 
+    /// True in the global scope and directly in a function body, i.e. where `var` and
+    /// function declarations are hoisted to
+    pub fn at_function_top_level(&self) -> bool {
+        match self.scopes.as_slice() {
+            [.., last] if last.kind == ScopeKind::Global => true,
+            [.., parent, last] => last.kind == ScopeKind::Block && parent.kind == ScopeKind::Function,
+            _ => false,
+        }
+    }
+
     fn current_scope_mut(&mut self) -> Option<&mut ScopeRecord> {
         self.scopes.last_mut()
     }
@@ -145,7 +155,7 @@ impl ScopeStack {
             .collect()
     }
 
-    pub fn choose_expr(&self, rng: &mut rand::rngs::ThreadRng) -> Option<Expr> {
+    pub fn choose_expr(&self, rng: &mut impl rand::Rng) -> Option<Expr> {
         // Choose from all visible scopes (innermost to outermost)
         let pool: Vec<Expr> = self
             .scopes
@@ -162,6 +172,14 @@ pub struct ScopeState {
     pub scopes: ScopeStack,
     pending_function_names: Vec<Option<Ident>>,
     in_for_stmt: Option<&'static str>,
+    function_kinds: Vec<FunctionKind>,
+}
+
+/// Whether the innermost function allows `await` and `yield`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FunctionKind {
+    pub is_async: bool,
+    pub is_generator: bool,
 }
 
 impl Default for ScopeState {
@@ -170,6 +188,7 @@ impl Default for ScopeState {
             scopes: ScopeStack::new(),
             pending_function_names: Vec::new(),
             in_for_stmt: None,
+            function_kinds: Vec::new(),
         }
     }
 }
@@ -178,6 +197,21 @@ impl ScopeState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Kind of the innermost function, None at the top level
+    pub fn function_kind(&self) -> Option<FunctionKind> {
+        self.function_kinds.last().copied()
+    }
+
+    /// Enter code that runs as its own function, like class field initializers and
+    /// static blocks
+    pub fn enter_function(&mut self, kind: FunctionKind) {
+        self.function_kinds.push(kind);
+    }
+
+    pub fn exit_function(&mut self) {
+        self.function_kinds.pop();
+    }
 }
 
 /// Recursively walk patterns and collect all binding identifiers.
@@ -232,6 +266,27 @@ pub fn extend_params_from_fn_params(scopes: &mut ScopeStack, params: &[Param]) {
     scopes.extend_idents_on_current(collected);
 }
 
+/// Visit every statement of `stmts` with `visitor`, inserting the statements returned by
+/// `insert_after` right after it. The inserted statements aren't visited.
+pub fn visit_mut_stmts_inserting_after<V, I>(
+    visitor: &mut V,
+    stmts: &mut Vec<Stmt>,
+    mut insert_after: impl FnMut(&mut V) -> I,
+) where
+    V: VisitMut,
+    I: IntoIterator<Item = Stmt>,
+{
+    let mut i = 0;
+    while i < stmts.len() {
+        // visit first so declarations made by this statement are in scope
+        stmts[i].visit_mut_with(visitor);
+        let new_stmts: Vec<Stmt> = insert_after(visitor).into_iter().collect();
+        let count = new_stmts.len();
+        stmts.splice(i + 1..i + 1, new_stmts);
+        i += count + 1;
+    }
+}
+
 pub trait ScopedAstVisitor: VisitMut {
     fn scope_state(&mut self) -> &mut ScopeState;
 
@@ -269,6 +324,10 @@ pub trait ScopedAstVisitor: VisitMut {
         let fn_name = self.pending_function_names().pop().flatten();
 
         self.scope_stack().push_scope(ScopeKind::Function);
+        self.scope_state().enter_function(FunctionKind {
+            is_async: node.is_async,
+            is_generator: node.is_generator,
+        });
 
         if let Some(name) = fn_name.clone() {
             self.scope_stack().add_ident_to_current(name.clone());
@@ -285,6 +344,7 @@ pub trait ScopedAstVisitor: VisitMut {
             body.visit_mut_with(self);
         }
 
+        self.scope_state().exit_function();
         self.scope_stack().pop_scope();
     }
 
@@ -294,6 +354,10 @@ pub trait ScopedAstVisitor: VisitMut {
             scopes.push_scope(ScopeKind::Function);
             extend_params_from_pats(scopes, &node.params);
         }
+        self.scope_state().enter_function(FunctionKind {
+            is_async: node.is_async,
+            is_generator: false,
+        });
 
         for pat in &mut node.params {
             pat.visit_mut_with(self);
@@ -304,6 +368,7 @@ pub trait ScopedAstVisitor: VisitMut {
             BlockStmtOrExpr::Expr(expr) => expr.visit_mut_with(self),
         }
 
+        self.scope_state().exit_function();
         self.scope_stack().pop_scope();
     }
 
@@ -552,6 +617,34 @@ impl Visit for CountNumericLiterals {
     }
 }
 
+/// Rough AST size: counts statements, expressions and patterns
+#[derive(Default)]
+pub struct CountAstNodes {
+    pub count: usize,
+}
+impl Visit for CountAstNodes {
+    fn visit_stmt(&mut self, node: &Stmt) {
+        self.count += 1;
+        node.visit_children_with(self);
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        self.count += 1;
+        node.visit_children_with(self);
+    }
+
+    fn visit_pat(&mut self, node: &Pat) {
+        self.count += 1;
+        node.visit_children_with(self);
+    }
+}
+
+pub fn count_ast_nodes(ast: &Script) -> usize {
+    let mut counter = CountAstNodes::default();
+    ast.visit_with(&mut counter);
+    counter.count
+}
+
 impl Visit for NameCollector {
     fn visit_ident(&mut self, node: &Ident) {
         self.var_names.insert(node.sym.to_string());
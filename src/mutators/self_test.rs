@@ -0,0 +1,231 @@
+use std::any::Any;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Arc;
+
+use swc_ecma_visit::swc_ecma_ast::Script;
+
+use crate::mutators::ManagedMutator;
+use crate::parsing::parser::{generate_js, parse_js};
+use crate::utils::rand_utils::unseeded_rng;
+
+/// Seeds covering most of the syntax the mutators handle
+pub const SELF_TEST_SEEDS: &[&str] = &[
+    "let v0 = 1; let v1 = v0 + 2 * 3; print(v1);",
+    r#"function f0(v0, v1) { return v0 + v1 * 2; }
+let v2 = [1, 2.5, true, "foo"];
+const v3 = { a: 1, b: v2 };
+for (let v4 = 0; v4 < 10; v4++) { v2[v4] = f0(v4, v3.a); }
+if (v2.length > 3 && !false) { v3.b = null; }"#,
+    r#"class C0 extends Array {
+    #p0 = 1;
+    static s0 = "a";
+    constructor(v0) { super(); this.v0 = v0; }
+    get g0() { return this.#p0; }
+    m0(...v1) { return v1.map((v2) => v2 * 2); }
+}
+let v3 = new C0(2);
+v3.m0(1, 2, 3);"#,
+    r#"let { a: v0, b: [v1, ...v2] } = { a: 1, b: [2, 3, 4] };
+let v3 = `x${v0}y${v1 + 1}`;
+let v4 = /a+b/g.exec("aab");
+let v5 = v0 ? v1 : v2;
+v0 ??= 10;"#,
+    r#"function* f0() { yield 1; yield* [2, 3]; }
+async function f1(v0) { try { await v0; } catch (v1) { throw v1; } finally { v0 = null; } }
+for (const v2 of f0()) { switch (v2) { case 1: break; default: continue; } }
+let v3 = new Map([[1, "a"]]);
+label: while (true) { break label; }
+do { v3.delete(1); } while (v3.size > 0);"#,
+];
+
+/// Mutated output of a mutator that doesn't parse again, or a panic of the mutator, in
+/// which case there is no output
+#[derive(Debug, Clone)]
+pub struct SelfTestFailure {
+    pub seed: String,
+    pub output: String,
+    pub error: String,
+}
+
+/// Outcome of running a mutator repeatedly against the parser
+#[derive(Debug, Clone)]
+pub struct SelfTestResult {
+    pub name: String,
+    pub runs: usize,
+    /// Mutations that returned an error, which the fuzz loop skips
+    pub mutation_errors: usize,
+    /// Mutations that panicked, which are bugs in the mutator
+    pub panics: usize,
+    /// Mutations whose generated code doesn't parse
+    pub invalid_outputs: usize,
+    pub first_failure: Option<SelfTestFailure>,
+}
+
+impl SelfTestResult {
+    pub fn invalid_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.invalid_outputs as f64 / self.runs as f64
+        }
+    }
+}
+
+/// Apply `mutator` (or splice the next seed into each seed for splicers) to every seed
+/// `iterations` times and check that the generated code re-parses
+pub fn self_test_mutator(
+    mutator: &ManagedMutator,
+    seeds: &[(String, Script)],
+    iterations: usize,
+) -> SelfTestResult {
+    let mut result = SelfTestResult {
+        name: mutator.name().to_string(),
+        runs: 0,
+        mutation_errors: 0,
+        panics: 0,
+        invalid_outputs: 0,
+        first_failure: None,
+    };
+    let mut rng = unseeded_rng();
+    for _ in 0..iterations {
+        for (i, (source, seed)) in seeds.iter().enumerate() {
+            result.runs += 1;
+            let mutated = catch_unwind(AssertUnwindSafe(|| {
+                if mutator.is_splicer() {
+                    let donor = &seeds[(i + 1) % seeds.len()].1;
+                    mutator.splice(seed, donor, &mut rng)
+                } else {
+                    mutator.mutate(seed.clone(), &mut rng)
+                }
+            }));
+            let ast = match mutated {
+                Ok(Ok(ast)) => ast,
+                Ok(Err(_)) => {
+                    result.mutation_errors += 1;
+                    continue;
+                }
+                Err(payload) => {
+                    result.panics += 1;
+                    result.first_failure.get_or_insert_with(|| SelfTestFailure {
+                        seed: source.clone(),
+                        output: String::new(),
+                        error: format!("mutator panicked: {}", panic_message(&*payload)),
+                    });
+                    continue;
+                }
+            };
+
+            let (output, error) = match generate_js(ast) {
+                Ok(code) => {
+                    let output = String::from_utf8_lossy(&code).into_owned();
+                    match parse_js(output.clone()) {
+                        Ok(_) => continue,
+                        Err(err) => (output, format!("{:?}", err)),
+                    }
+                }
+                Err(err) => (String::new(), format!("code generation failed: {:?}", err)),
+            };
+            result.invalid_outputs += 1;
+            if result.first_failure.is_none() {
+                result.first_failure = Some(SelfTestFailure {
+                    seed: source.clone(),
+                    output,
+                    error,
+                });
+            }
+        }
+    }
+    result
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "<non-string panic payload>"
+    }
+}
+
+/// Self-test every mutator over the seeds
+pub fn self_test_mutators(
+    mutators: &[Arc<ManagedMutator>],
+    seeds: &[(String, Script)],
+    iterations: usize,
+) -> Vec<SelfTestResult> {
+    mutators
+        .iter()
+        .map(|mutator| self_test_mutator(mutator, seeds, iterations))
+        .collect()
+}
+
+/// Parse `SELF_TEST_SEEDS`
+pub fn parse_self_test_seeds() -> anyhow::Result<Vec<(String, Script)>> {
+    SELF_TEST_SEEDS
+        .iter()
+        .map(|source| Ok((source.to_string(), parse_js(source.to_string())?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::{AstMutator, get_ast_mutators};
+    use rand::rngs::StdRng;
+    use swc_common::DUMMY_SP;
+    use swc_ecma_visit::swc_ecma_ast::{Expr, ExprStmt, Invalid, Stmt};
+
+    /// Emits an invalid expression statement, which has no valid source form
+    struct BrokenMutator;
+
+    impl AstMutator for BrokenMutator {
+        fn mutate(&self, mut ast: Script, _rng: &mut StdRng) -> anyhow::Result<Script> {
+            ast.body.push(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Invalid(Invalid { span: DUMMY_SP })),
+            }));
+            Ok(ast)
+        }
+    }
+
+    #[test]
+    fn self_test_flags_broken_mutators() {
+        let seeds = parse_self_test_seeds().expect("failed to parse self-test seeds");
+        let mut mutators = get_ast_mutators();
+        mutators.push(Arc::new(ManagedMutator::new("BrokenMutator", Box::new(BrokenMutator), false)));
+
+        let results = self_test_mutators(&mutators, &seeds, 2);
+        assert_eq!(results.len(), mutators.len());
+        for result in &results {
+            assert_eq!(result.runs, 2 * seeds.len());
+        }
+
+        let broken = results.last().unwrap();
+        assert_eq!(broken.name, "BrokenMutator");
+        assert_eq!(broken.invalid_outputs, broken.runs);
+        assert_eq!(broken.invalid_rate(), 1.0);
+        let failure = broken.first_failure.as_ref().expect("no failure recorded");
+        assert_eq!(failure.seed, SELF_TEST_SEEDS[0]);
+    }
+
+    struct PanickingMutator;
+
+    impl AstMutator for PanickingMutator {
+        fn mutate(&self, _ast: Script, _rng: &mut StdRng) -> anyhow::Result<Script> {
+            panic!("no mutation for you");
+        }
+    }
+
+    #[test]
+    fn self_test_fails_panicking_mutators() {
+        let seeds = parse_self_test_seeds().expect("failed to parse self-test seeds");
+        let mutator = ManagedMutator::new("PanickingMutator", Box::new(PanickingMutator), false);
+        let result = self_test_mutator(&mutator, &seeds, 1);
+        assert_eq!(result.panics, seeds.len());
+        assert_eq!(result.mutation_errors, 0);
+        let failure = result.first_failure.expect("panic was not recorded as a failure");
+        assert_eq!(failure.seed, SELF_TEST_SEEDS[0]);
+        assert!(failure.error.contains("no mutation for you"), "unexpected error: {}", failure.error);
+    }
+}
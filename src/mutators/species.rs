@@ -0,0 +1,354 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_block, build_ctor_expr, build_ident, build_ident_expr_from_str, build_num, build_property_call, build_return,
+    build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::{JsMethodKind, get_global_object};
+use crate::mutators::scope::{NameCollector, VarRenamer};
+use crate::mutators::symbols::CountStmtSlots;
+
+/// SpeciesMutator
+/// Subclasses `Array` or a typed array, overrides `Symbol.species` and calls methods that
+/// construct their result through it, e.g.
+/// `class v1 extends Array { static get [Symbol.species]() { return Uint8Array; } }`
+/// followed by `let v2 = new v1(1, 2, 3); try { v2.map((v3) => v3); } catch {}`.
+///
+/// The species getter returns the base, the subclass, another array constructor, a
+/// non-constructor or `undefined`/`null`.
+pub struct SpeciesMutator;
+
+/// Typed arrays subclassed in place of the abstract `TypedArray` from the catalog
+const TYPED_ARRAYS: &[&str] = &[
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "BigInt64Array",
+    "BigUint64Array",
+];
+
+/// Methods creating their result with `ArraySpeciesCreate` or `TypedArraySpeciesCreate`
+const SPECIES_METHODS: &[&str] = &["map", "filter", "slice", "splice", "concat", "flat", "flatMap", "subarray"];
+
+/// A catalog object with a `Symbol.species` accessor, the constructors that can be
+/// subclassed for it and its methods consulting species
+struct SpeciesBase {
+    constructors: Vec<&'static str>,
+    methods: Vec<String>,
+    typed: bool,
+}
+
+/// Look up `sym` in the catalog. None unless it has `Symbol.species` and species methods.
+fn lookup_species_base(sym: &str, constructors: Vec<&'static str>, typed: bool) -> Option<SpeciesBase> {
+    let object = get_global_object(sym)?;
+    if !object
+        .static_properties()
+        .iter()
+        .any(|property| property.sym() == "Symbol.species")
+    {
+        return None;
+    }
+    let methods: Vec<String> = object
+        .methods()
+        .iter()
+        .filter(|method| method.kind() == JsMethodKind::Instance && SPECIES_METHODS.contains(&method.sym()))
+        .map(|method| method.sym().to_string())
+        .collect();
+    let constructors: Vec<&'static str> = constructors
+        .into_iter()
+        .filter(|ctor| get_global_object(ctor).is_some())
+        .collect();
+    if methods.is_empty() || constructors.is_empty() {
+        return None;
+    }
+    Some(SpeciesBase {
+        constructors,
+        methods,
+        typed,
+    })
+}
+
+fn species_bases() -> Vec<SpeciesBase> {
+    [
+        lookup_species_base("Array", vec!["Array"], false),
+        lookup_species_base("TypedArray", TYPED_ARRAYS.to_vec(), true),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// `static get [Symbol.species]() { return <species>; }`
+fn build_species_getter(species: Expr) -> ClassMember {
+    ClassMember::Method(ClassMethod {
+        span: DUMMY_SP,
+        key: PropName::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(build_ident_expr_from_str("Symbol")),
+                prop: MemberProp::Ident(IdentName::new(Atom::from("species"), DUMMY_SP)),
+            })),
+        }),
+        function: Box::new(Function {
+            params: Vec::new(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(build_block(vec![build_return(species)])),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+        kind: MethodKind::Getter,
+        is_static: true,
+        accessibility: None,
+        is_abstract: false,
+        is_optional: false,
+        is_override: false,
+    })
+}
+
+/// `class <name> extends <base> { <getter> }`
+fn build_subclass(name: &str, base: &str, getter: ClassMember) -> Stmt {
+    Stmt::Decl(Decl::Class(ClassDecl {
+        ident: build_ident(name),
+        declare: false,
+        class: Box::new(Class {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            decorators: Vec::new(),
+            body: vec![getter],
+            super_class: Some(Box::new(build_ident_expr_from_str(base))),
+            is_abstract: false,
+            type_params: None,
+            super_type_params: None,
+            implements: Vec::new(),
+        }),
+    }))
+}
+
+/// `(<param>) => <param>`
+fn build_identity_arrow(param: &str) -> Expr {
+    Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        params: vec![Pat::Ident(BindingIdent {
+            id: build_ident(param),
+            type_ann: None,
+        })],
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(build_ident_expr_from_str(param)))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
+}
+
+struct SpeciesMutatorVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    renamer: VarRenamer,
+    bases: Vec<SpeciesBase>,
+}
+
+impl SpeciesMutatorVisitor {
+    /// What the species getter of `class_name` extending `base` returns
+    fn build_species(&mut self, class_name: &str, base: usize) -> Expr {
+        match self.rng.random_range(0..6) {
+            0 => build_ident_expr_from_str(class_name),
+            1 => Expr::This(ThisExpr { span: DUMMY_SP }),
+            2 => {
+                // a constructor of the other kind makes the result type mismatch
+                let other = self.rng.random_range(0..self.bases.len());
+                let ctor = *self.bases[other]
+                    .constructors
+                    .choose(&mut self.rng)
+                    .expect("species bases have constructors");
+                build_ident_expr_from_str(ctor)
+            }
+            3 => build_ident_expr_from_str(if self.rng.random_bool(0.5) { "undefined" } else { "null" }),
+            4 => {
+                // not a constructor
+                let param = self.renamer.next_var_name();
+                build_identity_arrow(&param)
+            }
+            _ => {
+                let ctor = *self.bases[base]
+                    .constructors
+                    .choose(&mut self.rng)
+                    .expect("species bases have constructors");
+                build_ident_expr_from_str(ctor)
+            }
+        }
+    }
+
+    /// `<name>.<method>(...)` with arguments fitting the method
+    fn build_species_call(&mut self, name: &str, method: &str) -> Expr {
+        let args = match method {
+            "map" | "filter" | "flatMap" => {
+                let param = self.renamer.next_var_name();
+                vec![build_identity_arrow(&param)]
+            }
+            "slice" | "subarray" => (0..self.rng.random_range(0..=2))
+                .map(|_| build_num(self.rng.random_range(0..4) as f64))
+                .collect(),
+            "splice" => vec![build_num(0.0), build_num(self.rng.random_range(0..3) as f64)],
+            "concat" => vec![Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: vec![Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(build_num(1.0)),
+                })],
+            })],
+            _ => Vec::new(),
+        };
+        build_property_call(name, method, args)
+    }
+
+    fn build_species_stmts(&mut self) -> Vec<Stmt> {
+        let base = self.rng.random_range(0..self.bases.len());
+        let ctor = *self.bases[base]
+            .constructors
+            .choose(&mut self.rng)
+            .expect("species bases have constructors");
+        let class_name = self.renamer.next_var_name();
+        let instance = self.renamer.next_var_name();
+
+        let species = self.build_species(&class_name, base);
+        let mut stmts = vec![build_subclass(&class_name, ctor, build_species_getter(species))];
+
+        // typed arrays get a length so that BigInt arrays need no BigInt elements
+        let ctor_args = if self.bases[base].typed {
+            vec![build_num(self.rng.random_range(1..=16) as f64)]
+        } else {
+            (0..self.rng.random_range(2..=4))
+                .map(|_| build_num(self.rng.random_range(0..100) as f64))
+                .collect()
+        };
+        stmts.push(Stmt::Decl(Decl::Var(Box::new(build_var_decl(
+            &instance,
+            build_ctor_expr(&class_name, ctor_args),
+        )))));
+
+        let mut calls = Vec::new();
+        for _ in 0..self.rng.random_range(1..=3) {
+            let method = self.bases[base]
+                .methods
+                .choose(&mut self.rng)
+                .cloned()
+                .expect("species bases have methods");
+            let call = self.build_species_call(&instance, &method);
+            calls.push(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(call),
+            }));
+        }
+        // species constructors that aren't constructors or produce the wrong type throw
+        stmts.push(Stmt::Try(Box::new(TryStmt {
+            span: DUMMY_SP,
+            block: build_block(calls),
+            handler: Some(CatchClause {
+                span: DUMMY_SP,
+                param: None,
+                body: build_block(Vec::new()),
+            }),
+            finalizer: None,
+        })));
+        stmts
+    }
+}
+
+impl VisitMut for SpeciesMutatorVisitor {
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut i = 0;
+        while i < stmts.len() {
+            stmts[i].visit_mut_with(self);
+            let is_target = self.crt_idx == self.idx_to_mutate;
+            self.crt_idx += 1;
+            if is_target {
+                let new_stmts = self.build_species_stmts();
+                let count = new_stmts.len();
+                stmts.splice(i + 1..i + 1, new_stmts);
+                i += count;
+            }
+            i += 1;
+        }
+    }
+}
+
+impl AstMutator for SpeciesMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let bases = species_bases();
+        if bases.is_empty() {
+            return Ok(ast);
+        }
+
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = SpeciesMutatorVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            renamer: VarRenamer::new(names.var_names),
+            bases,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    #[test]
+    fn species_getter_round_trips() {
+        for (code, reparsed) in assert_mutations_reparse(&SpeciesMutator, "let v0 = 1;", 16) {
+            assert!(code.contains("static get [Symbol.species]()"), "no species getter in {}", code);
+
+            assert_eq!(reparsed.body.len(), 4, "unexpected statements in {}", code);
+            let Stmt::Decl(Decl::Class(decl)) = &reparsed.body[1] else {
+                panic!("expected a class in {}", code);
+            };
+            assert!(decl.class.super_class.is_some(), "class extends nothing in {}", code);
+            let getter = decl.class.body.iter().any(|member| {
+                matches!(
+                    member,
+                    ClassMember::Method(ClassMethod {
+                        kind: MethodKind::Getter,
+                        is_static: true,
+                        key: PropName::Computed(_),
+                        ..
+                    })
+                )
+            });
+            assert!(getter, "species is not a static getter in {}", code);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use rand::Rng;
+use rand::rngs::StdRng;
 use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
 use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
 
@@ -20,11 +21,10 @@ impl Visit for StmtCollector {
 }
 
 impl StmtCollector {
-    fn get_random_stmt_range(&self) -> Option<Vec<Stmt>> {
+    fn get_random_stmt_range(&self, rng: &mut StdRng) -> Option<Vec<Stmt>> {
         if self.stmts.is_empty() {
             return None;
         }
-        let mut rng = rand::rng();
         let start = rng.random_range(0..self.stmts.len());
         let end = rng.random_range(start + 1..=self.stmts.len());
         Some(self.stmts[start..end].to_vec())
@@ -105,11 +105,11 @@ impl VisitMut for InsertStmtMutator {
 }
 
 impl AstMutator for SpliceMutator {
-    fn mutate(&self, _ast: Script) -> anyhow::Result<Script> {
+    fn mutate(&self, _ast: Script, _rng: &mut StdRng) -> anyhow::Result<Script> {
         unreachable!("SpliceMutator does not support mutate; use splice instead");
     }
 
-    fn splice(&self, ast: &Script, donor: &Script) -> Result<Script> {
+    fn splice(&self, ast: &Script, donor: &Script, rng: &mut StdRng) -> Result<Script> {
         // first rename all variables and functions in both ASTs to avoid name collisions
         // for now we rename everything in donor AST, but renaming only what we splice could be better
         let mut donor = donor.clone();
@@ -124,15 +124,12 @@ impl AstMutator for SpliceMutator {
         ast.visit_with(&mut collector);
         donor.visit_with(&mut donor_collector);
 
-        let donor_stmts = match donor_collector.get_random_stmt_range() {
+        let donor_stmts = match donor_collector.get_random_stmt_range(rng) {
             Some(stmts) => stmts,
             None => return Ok(ast.clone()),
         };
 
-        let insert_pos = {
-            let mut rng = rand::rng();
-            rng.gen_range(0..=collector.stmts.len())
-        };
+        let insert_pos = rng.random_range(0..=collector.stmts.len());
 
         let mut new_ast = ast.clone();
         let mut inserter = InsertStmtMutator::new(donor_stmts, insert_pos);
@@ -144,6 +141,7 @@ impl AstMutator for SpliceMutator {
 #[cfg(test)]
 mod tests {
     use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
 
     use super::*;
 
@@ -171,7 +169,7 @@ mod tests {
         let ast2 = parse_js(source2).expect("failed to parse test script 2");
 
         let splice_mutator = SpliceMutator {};
-        let spliced_ast = splice_mutator.splice(&ast1, &ast2).expect("splicing failed");
+        let spliced_ast = splice_mutator.splice(&ast1, &ast2, &mut unseeded_rng()).expect("splicing failed");
         let script = generate_js(spliced_ast).expect("failed to generate JS from spliced AST");
         println!("Spliced script: {}", String::from_utf8_lossy(&script));
     }
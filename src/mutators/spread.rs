@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::DUMMY_SP;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::str_lit_value;
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods};
+
+/// SpreadMutator
+/// Moves an array element, a call argument or an object property into a spread of a
+/// one element literal, e.g. `[a, b]` becomes `[...[a], b]`, `f(x, y)` becomes
+/// `f(...[x], y)` and `{a, b}` becomes `{...{a}, b}`, which goes through the iterator
+/// protocol or the property copy instead of the plain element.
+///
+/// Those rewrites keep the semantics. Occasionally an array from the scope is also
+/// spread next to the element, which does change the values.
+pub struct SpreadMutator;
+
+/// Chance of also spreading an array from the scope
+const SCOPE_SPREAD_PROBABILITY: f64 = 0.1;
+
+/// Names declared with an array literal as initializer
+struct ArrayNames {
+    names: HashSet<Atom>,
+}
+
+impl Visit for ArrayNames {
+    fn visit_var_declarator(&mut self, decl: &VarDeclarator) {
+        decl.visit_children_with(self);
+        if let (Pat::Ident(binding), Some(Expr::Array(_))) = (&decl.name, decl.init.as_deref()) {
+            self.names.insert(binding.id.sym.clone());
+        }
+    }
+}
+
+/// Properties that keep their meaning when copied from another object. Accessors and
+/// methods would become data properties or lose their home object, and `__proto__` would
+/// become an own property instead of the prototype.
+fn is_spreadable_prop(prop: &PropOrSpread) -> bool {
+    let PropOrSpread::Prop(prop) = prop else {
+        return false;
+    };
+    match &**prop {
+        Prop::Shorthand(ident) => &*ident.sym != "__proto__",
+        Prop::KeyValue(KeyValueProp { key, .. }) => match key {
+            PropName::Ident(ident) => &*ident.sym != "__proto__",
+            PropName::Str(s) => str_lit_value(s) != "__proto__",
+            _ => true,
+        },
+        _ => false,
+    }
+}
+
+struct SpreadVisitor {
+    rng: StdRng,
+    /// None when only counting the elements that can be spread
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    arrays: HashSet<Atom>,
+}
+
+impl ScopedAstVisitor for SpreadVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl SpreadVisitor {
+    fn next_is_target(&mut self) -> bool {
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        is_target
+    }
+
+    /// `...<array>` for an array from the current scope, sometimes
+    fn scope_spread(&mut self) -> Option<ExprOrSpread> {
+        if !self.rng.random_bool(SCOPE_SPREAD_PROBABILITY) {
+            return None;
+        }
+        let arrays: Vec<Ident> = self
+            .scope_state
+            .scopes
+            .collect_idents()
+            .into_iter()
+            .filter(|ident| self.arrays.contains(&ident.sym))
+            .collect();
+        let array = arrays.choose(&mut self.rng)?.clone();
+        Some(ExprOrSpread {
+            spread: Some(DUMMY_SP),
+            expr: Box::new(Expr::Ident(array)),
+        })
+    }
+
+    /// Spread the target among `args` into `...[arg]`
+    fn spread_args(&mut self, args: &mut Vec<ExprOrSpread>) {
+        let mut i = 0;
+        while i < args.len() {
+            if args[i].spread.is_none() && self.next_is_target() {
+                args[i] = spread_of(args[i].clone());
+                if let Some(extra) = self.scope_spread() {
+                    args.insert(i + 1, extra);
+                    i += 1;
+                }
+            }
+            i += 1;
+        }
+    }
+}
+
+/// `...[<elem>]`
+fn spread_of(elem: ExprOrSpread) -> ExprOrSpread {
+    ExprOrSpread {
+        spread: Some(DUMMY_SP),
+        expr: Box::new(Expr::Array(ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![Some(elem)],
+        })),
+    }
+}
+
+impl VisitMut for SpreadVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_array_lit(&mut self, node: &mut ArrayLit) {
+        node.visit_mut_children_with(self);
+        let mut i = 0;
+        while i < node.elems.len() {
+            // holes can't be spread
+            let Some(elem) = &node.elems[i] else {
+                i += 1;
+                continue;
+            };
+            if elem.spread.is_none() && self.next_is_target() {
+                node.elems[i] = Some(spread_of(elem.clone()));
+                if let Some(extra) = self.scope_spread() {
+                    node.elems.insert(i + 1, Some(extra));
+                    i += 1;
+                }
+            }
+            i += 1;
+        }
+    }
+
+    fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
+        node.visit_mut_children_with(self);
+        self.spread_args(&mut node.args);
+    }
+
+    fn visit_mut_new_expr(&mut self, node: &mut NewExpr) {
+        node.visit_mut_children_with(self);
+        if let Some(args) = &mut node.args {
+            self.spread_args(args);
+        }
+    }
+
+    fn visit_mut_object_lit(&mut self, node: &mut ObjectLit) {
+        node.visit_mut_children_with(self);
+        for prop in node.props.iter_mut() {
+            if !is_spreadable_prop(prop) || !self.next_is_target() {
+                continue;
+            }
+            let inner = prop.clone();
+            *prop = PropOrSpread::Spread(SpreadElement {
+                dot3_token: DUMMY_SP,
+                expr: Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![inner],
+                })),
+            });
+        }
+    }
+}
+
+impl AstMutator for SpreadMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut names = ArrayNames { names: HashSet::new() };
+        ast.visit_with(&mut names);
+        let mut visitor = SpreadVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            arrays: names.names,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = SpreadVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+
+    /// Counts spreads of one element array and object literals
+    #[derive(Default)]
+    struct SpreadCounter {
+        array_spreads: usize,
+        object_spreads: usize,
+    }
+
+    impl Visit for SpreadCounter {
+        fn visit_expr_or_spread(&mut self, node: &ExprOrSpread) {
+            if node.spread.is_some() && matches!(&*node.expr, Expr::Array(array) if array.elems.len() == 1) {
+                self.array_spreads += 1;
+            }
+            node.visit_children_with(self);
+        }
+
+        fn visit_spread_element(&mut self, node: &SpreadElement) {
+            if matches!(&*node.expr, Expr::Object(object) if object.props.len() == 1) {
+                self.object_spreads += 1;
+            }
+            node.visit_children_with(self);
+        }
+    }
+
+    fn mutate_and_count(source: &str) -> (SpreadCounter, Script, String) {
+        let (code, reparsed) = assert_mutations_reparse(&SpreadMutator, source, 1).remove(0);
+        let mut counter = SpreadCounter::default();
+        reparsed.visit_with(&mut counter);
+        (counter, reparsed, code)
+    }
+
+    #[test]
+    fn array_elements_are_spread() {
+        for _ in 0..16 {
+            let (counter, reparsed, code) = mutate_and_count("let v0 = [1, , \"a\", ...v1];");
+            assert_eq!(counter.array_spreads, 1, "expected one spread element in {}", code);
+            let Stmt::Decl(Decl::Var(var)) = &reparsed.body[0] else {
+                panic!("expected a declaration in {}", code);
+            };
+            let Some(Expr::Array(array)) = var.decls[0].init.as_deref() else {
+                panic!("expected an array in {}", code);
+            };
+            assert_eq!(array.elems.len(), 4, "elements changed in {}", code);
+            assert!(array.elems[1].is_none(), "the hole was lost in {}", code);
+        }
+    }
+
+    #[test]
+    fn call_arguments_are_spread() {
+        for _ in 0..16 {
+            let (counter, _, code) = mutate_and_count("f0(v0, 3); new Map(v0);");
+            assert_eq!(counter.array_spreads, 1, "expected one spread argument in {}", code);
+        }
+    }
+
+    #[test]
+    fn object_properties_are_spread() {
+        for _ in 0..16 {
+            let (counter, _, code) =
+                mutate_and_count("let v0 = { a: 1, b, __proto__: null, get c() { return 1; } };");
+            assert_eq!(counter.object_spreads, 1, "expected one spread property in {}", code);
+            assert!(code.contains("__proto__: null"), "__proto__ was moved in {}", code);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_bin, build_block, build_ident_expr_from_str, build_num, build_property_call, build_return, build_var_decl,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{NameCollector, VarRenamer};
+
+/// SpreadHoleMutator
+/// Adds spreads that stress the argument evaluation of a call or `new` to its arguments:
+/// ```js
+/// f0(v1, ...[1, , 3]);                                   // holes read as undefined
+/// f0(...Object.setPrototypeOf([1, , 3], [7, 8, 9]), v1); // holes read through the prototype
+/// f0(v1, ...new Array(3));                               // only holes
+/// f0(v1, ...{ [Symbol.iterator]() { let v2 = 0; return { next() { ... } }; } });
+/// ```
+///
+/// The iterables end after at most `MAX_STEPS` values and only throw partway with
+/// `THROW_PROBABILITY`, so the program usually keeps running past the call.
+/// `SpreadMutator` only wraps existing arguments in one element arrays, which never have
+/// holes and never throw.
+pub struct SpreadHoleMutator;
+
+const MAX_STEPS: u32 = 6;
+
+/// Chance of the iterable throwing from `next` before it is done
+const THROW_PROBABILITY: f64 = 0.2;
+
+/// Chance of an element of the sparse arrays being an existing argument instead of a
+/// small integer
+const ARG_ELEMENT_PROBABILITY: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpreadKind {
+    Holes,      // ...[1, , 3]
+    ProtoHoles, // ...Object.setPrototypeOf([1, , 3], [7, 8, 9])
+    NewArray,   // ...new Array(3)
+    Iterable,   // ...{ [Symbol.iterator]() { ... } }
+}
+
+const SPREAD_KINDS: [SpreadKind; 4] = [
+    SpreadKind::Holes,
+    SpreadKind::ProtoHoles,
+    SpreadKind::NewArray,
+    SpreadKind::Iterable,
+];
+
+fn build_bool(value: bool) -> Expr {
+    Expr::Lit(Lit::Bool(Bool { span: DUMMY_SP, value }))
+}
+
+fn build_if(test: Expr, cons: Stmt) -> Stmt {
+    Stmt::If(IfStmt {
+        span: DUMMY_SP,
+        test: Box::new(test),
+        cons: Box::new(cons),
+        alt: None,
+    })
+}
+
+fn build_array(elems: Vec<Option<Expr>>) -> Expr {
+    Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: elems
+            .into_iter()
+            .map(|elem| {
+                elem.map(|expr| ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect(),
+    })
+}
+
+/// `<key>() { <body> }`
+fn build_method(key: PropName, body: Vec<Stmt>) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
+        key,
+        function: Box::new(Function {
+            params: Vec::new(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(build_block(body)),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    })))
+}
+
+fn build_object(props: Vec<PropOrSpread>) -> Expr {
+    Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props,
+    })
+}
+
+/// `{ value: <value>, done: <done> }`
+fn build_iter_result(value: Expr, done: bool) -> Expr {
+    let prop = |key: &str, value: Expr| {
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(IdentName::new(Atom::from(key), DUMMY_SP)),
+            value: Box::new(value),
+        })))
+    };
+    build_object(vec![prop("value", value), prop("done", build_bool(done))])
+}
+
+struct SpreadHoleVisitor {
+    rng: StdRng,
+    /// None when only counting the calls
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    renamer: VarRenamer,
+    kinds: Vec<SpreadKind>,
+}
+
+impl SpreadHoleVisitor {
+    fn next_is_target(&mut self) -> bool {
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        is_target
+    }
+
+    /// A small integer, or a copy of one of the plain arguments of the call
+    fn random_element(&mut self, args: &[ExprOrSpread]) -> Expr {
+        let plain: Vec<&ExprOrSpread> = args.iter().filter(|arg| arg.spread.is_none()).collect();
+        if self.rng.random_bool(ARG_ELEMENT_PROBABILITY) {
+            if let Some(arg) = plain.choose(&mut self.rng) {
+                return (*arg.expr).clone();
+            }
+        }
+        build_num(self.rng.random_range(0..10) as f64)
+    }
+
+    /// `[<elem>, , <elem>]` with at least one hole, never at the end where the codegen
+    /// would have to emit a trailing comma for it
+    fn build_holey_array(&mut self, args: &[ExprOrSpread]) -> Expr {
+        let len = self.rng.random_range(2..=5);
+        let mut elems: Vec<Option<Expr>> = Vec::with_capacity(len);
+        for _ in 0..len {
+            let elem = if self.rng.random_bool(0.5) { Some(self.random_element(args)) } else { None };
+            elems.push(elem);
+        }
+        let hole = self.rng.random_range(0..len - 1);
+        elems[hole] = None;
+        if elems[len - 1].is_none() {
+            elems[len - 1] = Some(self.random_element(args));
+        }
+        build_array(elems)
+    }
+
+    /// `{ [Symbol.iterator]() { let <step> = 0; return { next() { ... } }; } }` yielding
+    /// the steps and sometimes throwing before it is done
+    fn build_iterable(&mut self) -> Expr {
+        let step = self.renamer.next_var_name();
+        let limit = self.rng.random_range(1..=MAX_STEPS);
+        let mut next_body = vec![
+            Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Update(UpdateExpr {
+                    span: DUMMY_SP,
+                    op: UpdateOp::PlusPlus,
+                    prefix: false,
+                    arg: Box::new(build_ident_expr_from_str(&step)),
+                })),
+            }),
+            build_if(
+                build_bin(BinaryOp::Gt, build_ident_expr_from_str(&step), build_num(limit as f64)),
+                build_return(build_iter_result(build_ident_expr_from_str("undefined"), true)),
+            ),
+        ];
+        if self.rng.random_bool(THROW_PROBABILITY) {
+            let throw_at = self.rng.random_range(1..=limit);
+            next_body.push(build_if(
+                build_bin(BinaryOp::EqEqEq, build_ident_expr_from_str(&step), build_num(throw_at as f64)),
+                Stmt::Throw(ThrowStmt {
+                    span: DUMMY_SP,
+                    arg: Box::new(build_ident_expr_from_str(&step)),
+                }),
+            ));
+        }
+        next_body.push(build_return(build_iter_result(build_ident_expr_from_str(&step), false)));
+
+        let next = build_method(PropName::Ident(IdentName::new(Atom::from("next"), DUMMY_SP)), next_body);
+        let iterator_method = build_method(
+            PropName::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Member(MemberExpr {
+                    span: DUMMY_SP,
+                    obj: Box::new(build_ident_expr_from_str("Symbol")),
+                    prop: MemberProp::Ident(IdentName::new(Atom::from("iterator"), DUMMY_SP)),
+                })),
+            }),
+            vec![
+                Stmt::Decl(Decl::Var(Box::new(build_var_decl(&step, build_num(0.0))))),
+                build_return(build_object(vec![next])),
+            ],
+        );
+        build_object(vec![iterator_method])
+    }
+
+    fn build_spread(&mut self, kind: SpreadKind, args: &[ExprOrSpread]) -> ExprOrSpread {
+        let expr = match kind {
+            SpreadKind::Holes => self.build_holey_array(args),
+            SpreadKind::ProtoHoles => {
+                let holey = self.build_holey_array(args);
+                let proto = (0..MAX_STEPS)
+                    .map(|_| Some(build_num(self.rng.random_range(0..10) as f64)))
+                    .collect();
+                build_property_call("Object", "setPrototypeOf", vec![holey, build_array(proto)])
+            }
+            SpreadKind::NewArray => Expr::New(NewExpr {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                callee: Box::new(build_ident_expr_from_str("Array")),
+                args: Some(vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(build_num(self.rng.random_range(1..=4) as f64)),
+                }]),
+                type_args: None,
+            }),
+            SpreadKind::Iterable => self.build_iterable(),
+        };
+        ExprOrSpread {
+            spread: Some(DUMMY_SP),
+            expr: Box::new(expr),
+        }
+    }
+
+    /// Insert a spread of every kind at random positions of `args`
+    fn add_spreads(&mut self, args: &mut Vec<ExprOrSpread>) {
+        for kind in self.kinds.clone() {
+            let spread = self.build_spread(kind, args);
+            let at = self.rng.random_range(0..=args.len());
+            args.insert(at, spread);
+        }
+    }
+}
+
+impl VisitMut for SpreadHoleVisitor {
+    fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
+        node.visit_mut_children_with(self);
+        // `import()` takes a specifier, not an argument list
+        if matches!(node.callee, Callee::Import(_)) {
+            return;
+        }
+        if self.next_is_target() {
+            self.add_spreads(&mut node.args);
+        }
+    }
+
+    fn visit_mut_new_expr(&mut self, node: &mut NewExpr) {
+        node.visit_mut_children_with(self);
+        if self.next_is_target() {
+            self.add_spreads(node.args.get_or_insert_with(Vec::new));
+        }
+    }
+}
+
+impl SpreadHoleMutator {
+    fn mutate_with_kinds(&self, mut ast: Script, kinds: Vec<SpreadKind>, rng: &mut StdRng) -> Result<Script> {
+        let mut names = NameCollector::new();
+        ast.visit_with(&mut names);
+        let mut visitor = SpreadHoleVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            renamer: VarRenamer::new(names.var_names),
+            kinds,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = SpreadHoleVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for SpreadHoleMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        // mostly a single spread, sometimes a sparse array next to an iterable
+        let count = if rng.random_bool(0.3) { 2 } else { 1 };
+        let kinds = (0..count)
+            .map(|_| *SPREAD_KINDS.choose(rng).expect("there are spread kinds"))
+            .collect();
+        self.mutate_with_kinds(ast, kinds, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::rand_utils::unseeded_rng;
+    use swc_ecma_visit::Visit;
+
+    /// Positions of the holes of every spread array literal
+    #[derive(Default)]
+    struct SpreadHoles(Vec<Vec<bool>>);
+
+    impl Visit for SpreadHoles {
+        fn visit_expr_or_spread(&mut self, node: &ExprOrSpread) {
+            if let (Some(_), Expr::Array(array)) = (node.spread, &*node.expr) {
+                self.0.push(array.elems.iter().map(Option::is_none).collect());
+            }
+            node.visit_children_with(self);
+        }
+    }
+
+    fn spread_holes(ast: &Script) -> Vec<Vec<bool>> {
+        let mut holes = SpreadHoles::default();
+        ast.visit_with(&mut holes);
+        holes.0
+    }
+
+    #[test]
+    fn sparse_spreads_round_trip() {
+        let source = "function f0(a0, a1) { return a0; } f0(1, 2); new Set([3]);";
+        for _ in 0..32 {
+            let ast = parse_js(source.to_string()).expect("failed to parse test script");
+            let mutated = SpreadHoleMutator
+                .mutate_with_kinds(ast, vec![SpreadKind::Holes, SpreadKind::ProtoHoles], &mut unseeded_rng())
+                .expect("mutation failed");
+            let expected = spread_holes(&mutated);
+            assert_eq!(expected.len(), 1, "expected one spread array");
+            assert!(expected[0].contains(&true), "the spread array has no holes");
+
+            let code = String::from_utf8(generate_js(mutated).expect("code generation failed")).unwrap();
+            let reparsed = parse_js(code.clone()).expect("mutated code does not parse");
+            assert_eq!(spread_holes(&reparsed), expected, "holes changed in {}", code);
+            assert!(code.contains("Object.setPrototypeOf"), "no prototype spread in {}", code);
+        }
+    }
+
+    #[test]
+    fn every_spread_kind_round_trips() {
+        let source = "let v0 = [1]; function f0() { return arguments.length; } f0(v0);";
+        for kind in SPREAD_KINDS {
+            let mutants = assert_mutations_reparse_with(source, 8, |ast, rng| {
+                SpreadHoleMutator.mutate_with_kinds(ast, vec![kind], rng)
+            });
+            for (code, _) in mutants {
+                parse_js(code.clone()).expect("mutated code does not parse");
+                assert!(code.contains("..."), "no spread in {}", code);
+            }
+        }
+    }
+}
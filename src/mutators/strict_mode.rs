@@ -0,0 +1,241 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::rngs::StdRng;
+use swc_common::DUMMY_SP;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{build_str_lit, str_lit_value};
+use crate::mutators::AstMutator;
+
+/// StrictModeMutator
+/// Adds a `"use strict";` directive to the top of the program or of a function body, or
+/// removes an existing one, flipping the semantics of `this`, `arguments` and assignments
+/// to undeclared names in that code.
+///
+/// Strict mode isn't added to code using `with` or legacy octal literals and escapes, nor
+/// to functions with non-simple parameters, where the directive is a syntax error.
+pub struct StrictModeMutator;
+
+const USE_STRICT: &str = "use strict";
+
+/// Finds syntax that is an early error in strict mode
+#[derive(Default)]
+struct SloppyOnlySyntax {
+    found: bool,
+}
+
+/// `010` and `08`
+fn is_legacy_octal_number(num: &Number) -> bool {
+    let Some(raw) = &num.raw else {
+        return false;
+    };
+    let mut chars = raw.chars();
+    chars.next() == Some('0') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// `"\1"`, `"\01"` and `"\8"`
+fn has_octal_escape(lit: &Str) -> bool {
+    let Some(raw) = &lit.raw else {
+        return false;
+    };
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        match chars.next() {
+            Some('1'..='9') => return true,
+            Some('0') if chars.peek().is_some_and(|c| c.is_ascii_digit()) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+impl Visit for SloppyOnlySyntax {
+    fn visit_with_stmt(&mut self, _: &WithStmt) {
+        self.found = true;
+    }
+
+    fn visit_number(&mut self, num: &Number) {
+        self.found |= is_legacy_octal_number(num);
+    }
+
+    fn visit_str(&mut self, lit: &Str) {
+        self.found |= has_octal_escape(lit);
+    }
+}
+
+fn is_use_strict(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Expr(ExprStmt { expr, .. }) => {
+            matches!(&**expr, Expr::Lit(Lit::Str(lit)) if str_lit_value(lit) == USE_STRICT)
+        }
+        _ => false,
+    }
+}
+
+/// Number of leading string statements of `stmts`, the directive prologue of a script or function body
+pub(crate) fn directive_prologue_len(stmts: &[Stmt]) -> usize {
+    stmts
+        .iter()
+        .take_while(|stmt| matches!(stmt, Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(_)))))
+        .count()
+}
+
+/// Position of the `"use strict"` directive in the directive prologue of `stmts`
+pub(crate) fn use_strict_position(stmts: &[Stmt]) -> Option<usize> {
+    stmts[..directive_prologue_len(stmts)].iter().position(is_use_strict)
+}
+
+fn has_sloppy_only_syntax<N: VisitWith<SloppyOnlySyntax>>(node: &N) -> bool {
+    let mut sloppy = SloppyOnlySyntax::default();
+    node.visit_with(&mut sloppy);
+    sloppy.found
+}
+
+/// Remove the directive of `stmts` if there is one, otherwise add it if `can_be_strict`
+fn toggle_strict(stmts: &mut Vec<Stmt>, can_be_strict: bool) {
+    if let Some(pos) = use_strict_position(stmts) {
+        stmts.remove(pos);
+        return;
+    }
+    if !can_be_strict {
+        return;
+    }
+    stmts.insert(
+        0,
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Lit(Lit::Str(build_str_lit(USE_STRICT)))),
+        }),
+    );
+}
+
+/// Counts the function bodies that can hold a directive
+struct CountBodies {
+    count: usize,
+}
+
+impl Visit for CountBodies {
+    fn visit_function(&mut self, node: &Function) {
+        if node.body.is_some() {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        if matches!(&*node.body, BlockStmtOrExpr::BlockStmt(_)) {
+            self.count += 1;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+struct StrictModeVisitor {
+    idx_to_mutate: usize,
+    crt_idx: usize,
+}
+
+impl StrictModeVisitor {
+    fn next_is_target(&mut self) -> bool {
+        let is_target = self.crt_idx == self.idx_to_mutate;
+        self.crt_idx += 1;
+        is_target
+    }
+}
+
+impl VisitMut for StrictModeVisitor {
+    fn visit_mut_function(&mut self, node: &mut Function) {
+        if node.body.is_some() && self.next_is_target() {
+            let simple_params = node.params.iter().all(|param| matches!(param.pat, Pat::Ident(_)));
+            let can_be_strict = simple_params && !has_sloppy_only_syntax(&*node);
+            if let Some(body) = &mut node.body {
+                toggle_strict(&mut body.stmts, can_be_strict);
+            }
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_arrow_expr(&mut self, node: &mut ArrowExpr) {
+        if matches!(&*node.body, BlockStmtOrExpr::BlockStmt(_)) && self.next_is_target() {
+            let simple_params = node.params.iter().all(|param| matches!(param, Pat::Ident(_)));
+            let can_be_strict = simple_params && !has_sloppy_only_syntax(&*node);
+            if let BlockStmtOrExpr::BlockStmt(body) = &mut *node.body {
+                toggle_strict(&mut body.stmts, can_be_strict);
+            }
+        }
+        node.visit_mut_children_with(self);
+    }
+}
+
+impl AstMutator for StrictModeMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut counter = CountBodies { count: 0 };
+        ast.visit_with(&mut counter);
+
+        // the program itself is target 0
+        let idx_to_mutate = rng.random_range(0..=counter.count);
+        if idx_to_mutate == 0 {
+            let can_be_strict = !has_sloppy_only_syntax(&ast);
+            toggle_strict(&mut ast.body, can_be_strict);
+            return Ok(ast);
+        }
+        let mut visitor = StrictModeVisitor {
+            idx_to_mutate: idx_to_mutate - 1,
+            crt_idx: 0,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use crate::parsing::parser::parse_js;
+
+    fn mutate(source: &str) -> String {
+        let (code, _) = assert_mutations_reparse(&StrictModeMutator, source, 1).remove(0);
+        code
+    }
+
+    #[test]
+    fn clean_program_becomes_strict() {
+        let code = mutate("let v0 = 1;\nv0 += 2;");
+        let ast = parse_js(code.clone()).unwrap();
+        assert!(is_use_strict(&ast.body[0]), "no directive added in {}", code);
+
+        // and back
+        let code = mutate(&code);
+        assert!(!code.contains(USE_STRICT), "directive not removed in {}", code);
+    }
+
+    #[test]
+    fn sloppy_only_code_stays_sloppy() {
+        for source in [
+            "let v0 = {}; with (v0) { v1; }",
+            "let v0 = 010;",
+            "let v0 = \"\\01\";",
+        ] {
+            for _ in 0..16 {
+                let code = mutate(source);
+                assert!(!code.contains(USE_STRICT), "strict mode added to {}", code);
+            }
+        }
+
+        // the program may become strict, but not a function with a default parameter
+        for _ in 0..16 {
+            let code = mutate("function f0(v0 = 1) { return v0; }");
+            let ast = parse_js(code.clone()).unwrap();
+            let Some(Stmt::Decl(Decl::Fn(decl))) = ast.body.last() else {
+                panic!("expected a function in {}", code);
+            };
+            let body = decl.function.body.as_ref().expect("function without body");
+            assert!(!body.stmts.iter().any(is_use_strict), "strict mode added to {}", code);
+        }
+    }
+}
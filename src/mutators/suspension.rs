@@ -0,0 +1,298 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::swc_ecma_ast::*;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+
+use crate::code_generators::ast_helpers::{build_property_call, build_random_primitive_literal};
+use crate::mutators::AstMutator;
+use crate::mutators::scope::{FunctionKind, ScopeState, ScopedAstVisitor, scoped_visit_mut_methods};
+
+/// SuspensionMutator
+/// Inserts `await` or `yield` at the start or end of the body of a loop inside an async
+/// function or a generator, e.g.
+/// `for (let v1 = 0; v1 < 3; v1++) { await v1; v0 += v1; yield; }`, so every iteration
+/// suspends and resumes the function.
+///
+/// Loops outside async functions and generators are left alone, including loops in
+/// arrows, constructors, class field initializers and static blocks nested in one. The
+/// loop conditions aren't touched, so bounded loops stay bounded.
+pub struct SuspensionMutator;
+
+struct SuspensionVisitor {
+    rng: StdRng,
+    /// None when only counting the loops that can suspend
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+}
+
+impl ScopedAstVisitor for SuspensionVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+fn loop_body(stmt: &mut Stmt) -> Option<&mut Box<Stmt>> {
+    match stmt {
+        Stmt::For(ForStmt { body, .. })
+        | Stmt::ForIn(ForInStmt { body, .. })
+        | Stmt::ForOf(ForOfStmt { body, .. })
+        | Stmt::While(WhileStmt { body, .. })
+        | Stmt::DoWhile(DoWhileStmt { body, .. }) => Some(body),
+        _ => None,
+    }
+}
+
+impl SuspensionVisitor {
+    /// `await <value>;`, `yield <value>;`, `yield;` or `yield* [...];`
+    fn build_suspension(&mut self, kind: FunctionKind) -> Stmt {
+        let use_await = match (kind.is_async, kind.is_generator) {
+            (true, true) => self.rng.random_bool(0.5),
+            (is_async, _) => is_async,
+        };
+        let expr = if use_await {
+            let value = match self.rng.random_range(0..3) {
+                0 => build_property_call("Promise", "resolve", vec![build_random_primitive_literal(&mut self.rng)]),
+                _ => build_random_primitive_literal(&mut self.rng),
+            };
+            Expr::Await(AwaitExpr {
+                span: DUMMY_SP,
+                arg: Box::new(value),
+            })
+        } else {
+            let (arg, delegate) = match self.rng.random_range(0..4) {
+                0 => (None, false),
+                1 => {
+                    let elems = (0..self.rng.random_range(0..=2))
+                        .map(|_| {
+                            Some(ExprOrSpread {
+                                spread: None,
+                                expr: Box::new(build_random_primitive_literal(&mut self.rng)),
+                            })
+                        })
+                        .collect();
+                    (Some(Box::new(Expr::Array(ArrayLit { span: DUMMY_SP, elems }))), true)
+                }
+                _ => (Some(Box::new(build_random_primitive_literal(&mut self.rng))), false),
+            };
+            Expr::Yield(YieldExpr {
+                span: DUMMY_SP,
+                arg,
+                delegate,
+            })
+        };
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+        })
+    }
+
+    /// Add suspension points at the start and/or end of the loop body
+    fn suspend_loop_body(&mut self, body: &mut Box<Stmt>, kind: FunctionKind) {
+        if !matches!(**body, Stmt::Block(_)) {
+            let stmt = std::mem::replace(&mut **body, Stmt::Empty(EmptyStmt { span: DUMMY_SP }));
+            **body = Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts: vec![stmt],
+            });
+        }
+        let Stmt::Block(block) = &mut **body else {
+            unreachable!("loop body was wrapped in a block");
+        };
+        let (at_start, at_end) = match self.rng.random_range(0..3) {
+            0 => (true, false),
+            1 => (false, true),
+            _ => (true, true),
+        };
+        if at_start {
+            let suspension = self.build_suspension(kind);
+            block.stmts.insert(0, suspension);
+        }
+        if at_end {
+            let suspension = self.build_suspension(kind);
+            block.stmts.push(suspension);
+        }
+    }
+
+    /// Run code that can't suspend, whatever function it is nested in
+    fn visit_without_suspension<N: VisitMutWith<Self>>(&mut self, node: &mut N) {
+        self.scope_state.enter_function(FunctionKind::default());
+        node.visit_mut_children_with(self);
+        self.scope_state.exit_function();
+    }
+}
+
+impl VisitMut for SuspensionVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.visit_mut_children_with(self);
+        let Some(kind) = self.scope_state.function_kind() else {
+            return;
+        };
+        if !kind.is_async && !kind.is_generator {
+            return;
+        }
+        let Some(body) = loop_body(stmt) else {
+            return;
+        };
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        if is_target {
+            self.suspend_loop_body(body, kind);
+        }
+    }
+
+    fn visit_mut_constructor(&mut self, node: &mut Constructor) {
+        self.visit_without_suspension(node);
+    }
+
+    fn visit_mut_class_prop(&mut self, node: &mut ClassProp) {
+        self.visit_without_suspension(node);
+    }
+
+    fn visit_mut_private_prop(&mut self, node: &mut PrivateProp) {
+        self.visit_without_suspension(node);
+    }
+
+    fn visit_mut_static_block(&mut self, node: &mut StaticBlock) {
+        self.visit_without_suspension(node);
+    }
+
+    // object literal accessors are plain functions, not `Function` nodes
+    fn visit_mut_getter_prop(&mut self, node: &mut GetterProp) {
+        self.visit_without_suspension(node);
+    }
+
+    fn visit_mut_setter_prop(&mut self, node: &mut SetterProp) {
+        self.visit_without_suspension(node);
+    }
+}
+
+impl AstMutator for SuspensionMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let mut visitor = SuspensionVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = SuspensionVisitor {
+            rng: visitor.rng,
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    /// Counts `await` and `yield` expressions, and the ones outside a function allowing them
+    #[derive(Default)]
+    struct SuspensionChecker {
+        kinds: Vec<FunctionKind>,
+        suspensions: usize,
+        misplaced: usize,
+    }
+
+    impl SuspensionChecker {
+        fn check(&mut self, allowed: impl Fn(FunctionKind) -> bool) {
+            self.suspensions += 1;
+            if !self.kinds.last().copied().is_some_and(allowed) {
+                self.misplaced += 1;
+            }
+        }
+    }
+
+    impl Visit for SuspensionChecker {
+        fn visit_function(&mut self, node: &Function) {
+            self.kinds.push(FunctionKind {
+                is_async: node.is_async,
+                is_generator: node.is_generator,
+            });
+            node.visit_children_with(self);
+            self.kinds.pop();
+        }
+
+        fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+            self.kinds.push(FunctionKind {
+                is_async: node.is_async,
+                is_generator: false,
+            });
+            node.visit_children_with(self);
+            self.kinds.pop();
+        }
+
+        fn visit_constructor(&mut self, node: &Constructor) {
+            self.kinds.push(FunctionKind::default());
+            node.visit_children_with(self);
+            self.kinds.pop();
+        }
+
+        fn visit_static_block(&mut self, node: &StaticBlock) {
+            self.kinds.push(FunctionKind::default());
+            node.visit_children_with(self);
+            self.kinds.pop();
+        }
+
+        fn visit_getter_prop(&mut self, node: &GetterProp) {
+            self.kinds.push(FunctionKind::default());
+            node.visit_children_with(self);
+            self.kinds.pop();
+        }
+
+        fn visit_setter_prop(&mut self, node: &SetterProp) {
+            self.kinds.push(FunctionKind::default());
+            node.visit_children_with(self);
+            self.kinds.pop();
+        }
+
+        fn visit_await_expr(&mut self, node: &AwaitExpr) {
+            node.visit_children_with(self);
+            self.check(|kind| kind.is_async);
+        }
+
+        fn visit_yield_expr(&mut self, node: &YieldExpr) {
+            node.visit_children_with(self);
+            self.check(|kind| kind.is_generator);
+        }
+    }
+
+    #[test]
+    fn suspensions_are_only_inserted_in_async_functions_and_generators() {
+        let source = r#"
+            async function f0(v0) { for (let v1 = 0; v1 < 3; v1++) { v0 += v1; } }
+            function* f1() { let v2 = 0; while (v2 < 3) v2++; }
+            async function* f2() { for (const v3 of [1, 2]) {} }
+            function f3() { for (const v4 of [1, 2]) {} }
+            async function f4() { [1].forEach((v5) => { while (false) {} }); }
+            async function f5() { class C0 { constructor() { while (false) {} } static { do {} while (false); } } }
+            function* f6() { let v7 = { get a() { while (false) {} }, set a(v8) { do {} while (false); } }; }
+            for (let v6 = 0; v6 < 2; v6++) {}
+        "#;
+        for (code, reparsed) in assert_mutations_reparse(&SuspensionMutator, source, 32) {
+
+            let mut checker = SuspensionChecker::default();
+            reparsed.visit_with(&mut checker);
+            assert!(checker.suspensions > 0, "no suspension point inserted in {}", code);
+            assert_eq!(checker.misplaced, 0, "suspension point in the wrong context in {}", code);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::code_generators::ast_helpers::{
+    build_ident_expr_from_str, build_random_literal, build_random_primitive_literal,
+};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::get_global_object;
+use crate::mutators::js_objects::js_types::JsObjectType;
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_visit_mut_methods, visit_mut_stmts_inserting_after};
+
+/// SymbolMutator
+/// Defines properties keyed by well-known symbols on objects from the current scope, e.g.
+/// `v0[Symbol.iterator] = function* () { yield 1; }` or `v0[Symbol.toPrimitive] = () => 42`,
+/// to exercise the engine's symbol-protocol dispatch.
+pub struct SymbolMutator;
+
+/// Counts the positions after which a statement can be inserted
+pub(crate) struct CountStmtSlots {
+    pub count: usize,
+}
+
+impl Visit for CountStmtSlots {
+    fn visit_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            stmt.visit_with(self);
+            self.count += 1;
+        }
+    }
+}
+
+struct SymbolMutatorVisitor {
+    rng: StdRng,
+    idx_to_mutate: usize,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    symbols: Vec<String>,
+}
+
+impl ScopedAstVisitor for SymbolMutatorVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+/// `Symbol.<name>`
+fn build_well_known_symbol(name: &str) -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(build_ident_expr_from_str("Symbol")),
+        prop: MemberProp::Ident(IdentName::new(Atom::from(name), DUMMY_SP)),
+    })
+}
+
+/// `() => <body>`
+fn build_arrow_returning(body: Expr) -> Expr {
+    Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        params: Vec::new(),
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(body))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
+}
+
+/// `(async) function* () { yield <values>; ... }`
+fn build_generator(values: Vec<Expr>, is_async: bool) -> Expr {
+    let stmts = values
+        .into_iter()
+        .map(|value| {
+            Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Yield(YieldExpr {
+                    span: DUMMY_SP,
+                    arg: Some(Box::new(value)),
+                    delegate: false,
+                })),
+            })
+        })
+        .collect();
+    Expr::Fn(FnExpr {
+        ident: None,
+        function: Box::new(Function {
+            params: Vec::new(),
+            decorators: Vec::new(),
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts,
+            }),
+            is_generator: true,
+            is_async,
+            type_params: None,
+            return_type: None,
+        }),
+    })
+}
+
+impl SymbolMutatorVisitor {
+    /// Build a value that makes sense for the protocol behind the well-known symbol
+    fn build_symbol_value(&mut self, symbol: &str, objects: &[Ident]) -> Expr {
+        match symbol {
+            "iterator" | "asyncIterator" => {
+                let count = self.rng.random_range(0..=3);
+                let values = (0..count).map(|_| build_random_primitive_literal(&mut self.rng)).collect();
+                build_generator(values, symbol == "asyncIterator")
+            }
+            "toPrimitive" => {
+                let ty = if self.rng.random_bool(0.5) {
+                    JsObjectType::Number
+                } else {
+                    JsObjectType::JsString
+                };
+                build_arrow_returning(build_random_literal(&mut self.rng, ty))
+            }
+            "hasInstance" => build_arrow_returning(build_random_literal(&mut self.rng, JsObjectType::Boolean)),
+            "isConcatSpreadable" => build_random_literal(&mut self.rng, JsObjectType::Boolean),
+            "toStringTag" => build_random_literal(&mut self.rng, JsObjectType::JsString),
+            "unscopables" => build_random_literal(&mut self.rng, JsObjectType::Object),
+            "species" => match objects.choose(&mut self.rng) {
+                Some(ident) => Expr::Ident(ident.clone()),
+                None => build_ident_expr_from_str("Array"),
+            },
+            _ => {
+                let value = build_random_primitive_literal(&mut self.rng);
+                build_arrow_returning(value)
+            }
+        }
+    }
+
+    /// `<obj>[Symbol.<name>] = <value>;` for a random object from the current scope
+    fn build_symbol_assignment(&mut self) -> Option<Stmt> {
+        let objects = self.scope_state.scopes.collect_idents_and_functions();
+        let target = objects.choose(&mut self.rng)?.clone();
+        let symbol = self.symbols.choose(&mut self.rng)?.clone();
+        let value = self.build_symbol_value(&symbol, &objects);
+
+        Some(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+                    span: DUMMY_SP,
+                    obj: Box::new(Expr::Ident(target)),
+                    prop: MemberProp::Computed(ComputedPropName {
+                        span: DUMMY_SP,
+                        expr: Box::new(build_well_known_symbol(&symbol)),
+                    }),
+                })),
+                right: Box::new(value),
+            })),
+        }))
+    }
+}
+
+impl VisitMut for SymbolMutatorVisitor {
+    scoped_visit_mut_methods!();
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        visit_mut_stmts_inserting_after(self, stmts, |visitor| {
+            let is_target = visitor.crt_idx == visitor.idx_to_mutate;
+            visitor.crt_idx += 1;
+            if is_target { visitor.build_symbol_assignment() } else { None }
+        });
+    }
+}
+
+impl AstMutator for SymbolMutator {
+    fn mutate(&self, mut ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let symbols: Vec<String> = get_global_object("Symbol")
+            .map(|symbol| {
+                symbol
+                    .static_properties()
+                    .iter()
+                    .map(|property| property.sym().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if symbols.is_empty() {
+            return Ok(ast);
+        }
+
+        let mut counter = CountStmtSlots { count: 0 };
+        ast.visit_with(&mut counter);
+        if counter.count == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = rng.random_range(0..counter.count);
+        let mut visitor = SymbolMutatorVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            symbols,
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse;
+    use crate::parsing::parser::parse_js;
+    use crate::utils::rand_utils::unseeded_rng;
+
+    #[test]
+    fn symbol_key_round_trips() {
+        let (code, reparsed) = assert_mutations_reparse(&SymbolMutator, "let v0 = {};", 1).remove(0);
+        assert!(code.contains("v0[Symbol."), "missing symbol keyed property in {}", code);
+
+        let Stmt::Expr(ExprStmt { expr, .. }) = &reparsed.body[1] else {
+            panic!("expected an inserted assignment in {}", code);
+        };
+        let Expr::Assign(AssignExpr {
+            left: AssignTarget::Simple(SimpleAssignTarget::Member(member)),
+            ..
+        }) = &**expr
+        else {
+            panic!("expected a member assignment in {}", code);
+        };
+        let MemberProp::Computed(ComputedPropName { expr: key, .. }) = &member.prop else {
+            panic!("expected a computed key in {}", code);
+        };
+        let Expr::Member(MemberExpr { obj, prop: MemberProp::Ident(name), .. }) = &**key else {
+            panic!("expected a `Symbol.<name>` key in {}", code);
+        };
+        assert!(matches!(&**obj, Expr::Ident(ident) if &*ident.sym == "Symbol"));
+        let well_known = get_global_object("Symbol").unwrap();
+        assert!(well_known.static_properties().iter().any(|p| p.sym() == &*name.sym));
+    }
+
+    #[test]
+    fn empty_scope_is_left_untouched() {
+        let ast = parse_js("1 + 1;".to_string()).expect("failed to parse test script");
+        let mutated = SymbolMutator.mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+        assert_eq!(mutated.body.len(), 1);
+    }
+}
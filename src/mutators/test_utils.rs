@@ -0,0 +1,30 @@
+use rand::rngs::StdRng;
+use swc_ecma_visit::swc_ecma_ast::Script;
+
+use crate::mutators::AstMutator;
+use crate::parsing::parser::{generate_js, parse_js};
+use crate::utils::rand_utils::unseeded_rng;
+
+/// Mutate `source` with `mutator` `iterations` times and check that every mutant parses again.
+/// Returns the generated code and its reparsed AST for each mutant
+pub fn assert_mutations_reparse(mutator: &dyn AstMutator, source: &str, iterations: usize) -> Vec<(String, Script)> {
+    assert_mutations_reparse_with(source, iterations, |ast, rng| mutator.mutate(ast, rng))
+}
+
+/// Like `assert_mutations_reparse`, for mutators driven through something other than `AstMutator::mutate`
+pub fn assert_mutations_reparse_with(
+    source: &str,
+    iterations: usize,
+    mut mutate: impl FnMut(Script, &mut StdRng) -> anyhow::Result<Script>,
+) -> Vec<(String, Script)> {
+    (0..iterations)
+        .map(|_| {
+            let ast = parse_js(source.to_string()).expect("failed to parse test script");
+            let mutated = mutate(ast, &mut unseeded_rng()).expect("mutation failed");
+            let code = String::from_utf8(generate_js(mutated).expect("code generation failed")).unwrap();
+            let reparsed =
+                parse_js(code.clone()).unwrap_or_else(|err| panic!("mutated code does not parse: {}\n{}", err, code));
+            (code, reparsed)
+        })
+        .collect()
+}
@@ -0,0 +1,273 @@
+use anyhow::Result;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::swc_ecma_ast::*;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+
+use crate::code_generators::ast_helpers::{build_bin, build_block, build_ident_expr_from_str, build_str_lit};
+use crate::mutators::AstMutator;
+use crate::mutators::js_objects::js_objects::{get_global_object, get_global_object_names};
+use crate::mutators::scope::{ScopeState, ScopedAstVisitor, scoped_for_stmt_visitor, scoped_visit_mut_methods};
+
+/// TypeGuardMutator
+/// Puts a type check in front of a statement or a variable initializer, e.g.
+/// `if (typeof v0 === "number") { v1.push(v0); }`,
+/// `if (v0 instanceof Array) { f(); } else { f(); }` or
+/// `let v2 = "length" in Object(v0) ? v0.length : v0.length;`.
+///
+/// Both branches of the dispatch forms run the same code, so the program does the same
+/// thing whichever way the check goes, but the engine gets to specialize every branch on
+/// the checked type. Constructors come from the functions in scope and the catalog, `in`
+/// checks go through `Object()` so they don't throw on primitives.
+pub struct TypeGuardMutator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GuardKind {
+    Typeof,     // typeof v0 === "number"
+    Instanceof, // v0 instanceof Array
+    In,         // "length" in Object(v0)
+}
+
+const GUARD_KINDS: [GuardKind; 3] = [GuardKind::Typeof, GuardKind::Instanceof, GuardKind::In];
+
+const TYPEOF_RESULTS: &[&str] = &[
+    "number",
+    "string",
+    "boolean",
+    "object",
+    "function",
+    "undefined",
+    "symbol",
+    "bigint",
+];
+
+/// Property names checked with `in` besides the methods of the catalog
+const COMMON_PROPERTIES: &[&str] = &["length", "constructor", "prototype", "0", "__proto__"];
+
+fn build_str(value: &str) -> Expr {
+    Expr::Lit(Lit::Str(build_str_lit(value)))
+}
+
+/// Parenthesize the branches of a conditional that can't stand there bare
+fn wrap_branch(expr: Expr) -> Expr {
+    match expr {
+        Expr::Seq(_) => Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+        }),
+        expr => expr,
+    }
+}
+
+/// Directives such as `"use strict"` stop being directives once wrapped
+fn is_directive(stmt: &ExprStmt) -> bool {
+    matches!(&*stmt.expr, Expr::Lit(Lit::Str(_)))
+}
+
+struct TypeGuardVisitor {
+    rng: StdRng,
+    /// None when only counting the statements and initializers
+    idx_to_mutate: Option<usize>,
+    crt_idx: usize,
+    scope_state: ScopeState,
+    kind: GuardKind,
+}
+
+impl ScopedAstVisitor for TypeGuardVisitor {
+    fn scope_state(&mut self) -> &mut ScopeState {
+        &mut self.scope_state
+    }
+}
+
+impl TypeGuardVisitor {
+    fn is_target(&mut self) -> bool {
+        let is_target = self.idx_to_mutate == Some(self.crt_idx);
+        self.crt_idx += 1;
+        is_target
+    }
+
+    /// The value checked by the guard, a variable in scope if there is one
+    fn subject(&mut self) -> Expr {
+        let idents = self.scope_state.scopes.collect_idents();
+        match idents.choose(&mut self.rng) {
+            Some(ident) => Expr::Ident(ident.clone()),
+            None => build_ident_expr_from_str("globalThis"),
+        }
+    }
+
+    /// A function in scope, or a constructor of the catalog
+    fn constructor(&mut self) -> Expr {
+        let functions = self.scope_state.scopes.collect_functions();
+        if let Some(function) = functions.choose(&mut self.rng) {
+            if self.rng.random_bool(0.3) {
+                return Expr::Ident(function.clone());
+            }
+        }
+        let constructors: Vec<&str> = get_global_object_names()
+            .into_iter()
+            .filter(|name| get_global_object(name).is_some_and(|obj| !obj.get_constructor_signatures().is_empty()))
+            .collect();
+        let name = constructors.choose(&mut self.rng).copied().unwrap_or("Object");
+        build_ident_expr_from_str(name)
+    }
+
+    /// A method name of the catalog or a common property
+    fn property(&mut self) -> String {
+        if self.rng.random_bool(0.5) {
+            let names = get_global_object_names();
+            let method = names
+                .choose(&mut self.rng)
+                .and_then(|name| get_global_object(name))
+                .and_then(|obj| obj.methods().choose(&mut self.rng).map(|method| method.sym().to_string()));
+            if let Some(method) = method {
+                return method;
+            }
+        }
+        COMMON_PROPERTIES.choose(&mut self.rng).expect("there are common properties").to_string()
+    }
+
+    fn build_guard(&mut self) -> Expr {
+        let subject = self.subject();
+        match self.kind {
+            GuardKind::Typeof => {
+                let result = *TYPEOF_RESULTS.choose(&mut self.rng).expect("there are typeof results");
+                let typeof_expr = Expr::Unary(UnaryExpr {
+                    span: DUMMY_SP,
+                    op: op!("typeof"),
+                    arg: Box::new(subject),
+                });
+                build_bin(op!("==="), typeof_expr, build_str(result))
+            }
+            GuardKind::Instanceof => {
+                let constructor = self.constructor();
+                build_bin(op!("instanceof"), subject, constructor)
+            }
+            GuardKind::In => {
+                let property = self.property();
+                let object = Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    callee: Callee::Expr(Box::new(build_ident_expr_from_str("Object"))),
+                    args: vec![ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(subject),
+                    }],
+                    type_args: None,
+                });
+                build_bin(op!("in"), build_str(&property), object)
+            }
+        }
+    }
+}
+
+impl VisitMut for TypeGuardVisitor {
+    scoped_visit_mut_methods!();
+    scoped_for_stmt_visitor!(mut);
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        // only statements of a list are wrapped, an `if` without `else` as the body of
+        // another `if` would take over its `else`
+        for stmt in stmts.iter_mut() {
+            stmt.visit_mut_with(self);
+            let Stmt::Expr(expr_stmt) = stmt else {
+                continue;
+            };
+            if is_directive(expr_stmt) || !self.is_target() {
+                continue;
+            }
+            let test = Box::new(self.build_guard());
+            let original = stmt.clone();
+            // dispatch on the type, or only run the statement for one type
+            let alt = self.rng.random_bool(0.5).then(|| Box::new(Stmt::Block(build_block(vec![original.clone()]))));
+            *stmt = Stmt::If(IfStmt {
+                span: DUMMY_SP,
+                test,
+                cons: Box::new(Stmt::Block(build_block(vec![original]))),
+                alt,
+            });
+        }
+    }
+
+    fn visit_mut_var_declarator(&mut self, node: &mut VarDeclarator) {
+        node.visit_mut_children_with(self);
+        // `in` would be ambiguous in the head of a for loop
+        if self.in_for_stmt().is_some() {
+            return;
+        }
+        let Some(init) = &mut node.init else {
+            return;
+        };
+        if !self.is_target() {
+            return;
+        }
+        let test = Box::new(self.build_guard());
+        let value = wrap_branch(*init.clone());
+        *init = Box::new(Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test,
+            cons: Box::new(value.clone()),
+            alt: Box::new(value),
+        }));
+    }
+}
+
+impl TypeGuardMutator {
+    fn mutate_with_kind(&self, mut ast: Script, kind: GuardKind, rng: &mut StdRng) -> Result<Script> {
+        let mut visitor = TypeGuardVisitor {
+            rng: StdRng::from_rng(rng),
+            idx_to_mutate: None,
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            kind,
+        };
+        ast.visit_mut_with(&mut visitor);
+        if visitor.crt_idx == 0 {
+            return Ok(ast);
+        }
+
+        let idx_to_mutate = visitor.rng.random_range(0..visitor.crt_idx);
+        let mut visitor = TypeGuardVisitor {
+            idx_to_mutate: Some(idx_to_mutate),
+            crt_idx: 0,
+            scope_state: ScopeState::new(),
+            ..visitor
+        };
+        ast.visit_mut_with(&mut visitor);
+        Ok(ast)
+    }
+}
+
+impl AstMutator for TypeGuardMutator {
+    fn mutate(&self, ast: Script, rng: &mut StdRng) -> Result<Script> {
+        let kind = *GUARD_KINDS.choose(rng).expect("there are guard kinds");
+        self.mutate_with_kind(ast, kind, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::test_utils::assert_mutations_reparse_with;
+
+    #[test]
+    fn guards_round_trip() {
+        let source = "\"use strict\"; let v0 = [1]; function f0() {} let v1 = v0.length; v0.push(2);";
+        for (kind, marker) in [
+            (GuardKind::Typeof, "typeof "),
+            (GuardKind::Instanceof, " instanceof "),
+            (GuardKind::In, " in Object("),
+        ] {
+            let mutants =
+                assert_mutations_reparse_with(source, 16, |ast, rng| TypeGuardMutator.mutate_with_kind(ast, kind, rng));
+            for (code, reparsed) in mutants {
+                assert_eq!(reparsed.body.len(), 5, "statements lost in {}", code);
+                assert!(code.starts_with("\"use strict\";"), "directive wrapped in {}", code);
+                assert!(code.contains(marker), "no {:?} guard in {}", kind, code);
+                assert!(code.contains("if (") || code.contains(" ? "), "nothing guarded in {}", code);
+            }
+        }
+    }
+}
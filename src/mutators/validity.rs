@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use swc_atoms::Atom;
+use swc_common::{DUMMY_SP, SyntaxContext};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith};
+use swc_ecma_visit::{VisitWith, swc_ecma_ast::*};
+
+use crate::mutators::js_objects::js_objects::get_global_object_names;
+use crate::utils::state::UndefinedRefPolicy;
+
+/// Globals that aren't objects in the catalog but are always defined
+const EXTRA_GLOBALS: &[&str] = &[
+    "undefined",
+    "NaN",
+    "Infinity",
+    "globalThis",
+    "arguments",
+    "eval",
+    "isNaN",
+    "isFinite",
+    "parseInt",
+    "parseFloat",
+    "encodeURI",
+    "encodeURIComponent",
+    "decodeURI",
+    "decodeURIComponent",
+    "escape",
+    "unescape",
+    // shell helpers
+    "print",
+    "console",
+    "gc",
+];
+
+lazy_static::lazy_static! {
+    static ref KNOWN_GLOBALS: HashSet<&'static str> = get_global_object_names()
+        .into_iter()
+        .chain(EXTRA_GLOBALS.iter().copied())
+        .collect();
+}
+
+pub fn is_known_global(name: &str) -> bool {
+    KNOWN_GLOBALS.contains(name)
+}
+
+/// Every name bound anywhere in the program. Plain assignments (`a = 1`) count as
+/// declarations since they create a global in sloppy mode.
+#[derive(Default)]
+pub(crate) struct DeclaredNames {
+    pub names: HashSet<Atom>,
+}
+
+impl Visit for DeclaredNames {
+    fn visit_binding_ident(&mut self, node: &BindingIdent) {
+        self.names.insert(node.id.sym.clone());
+        node.visit_children_with(self);
+    }
+
+    fn visit_fn_decl(&mut self, node: &FnDecl) {
+        self.names.insert(node.ident.sym.clone());
+        node.visit_children_with(self);
+    }
+
+    fn visit_fn_expr(&mut self, node: &FnExpr) {
+        if let Some(ident) = &node.ident {
+            self.names.insert(ident.sym.clone());
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_class_decl(&mut self, node: &ClassDecl) {
+        self.names.insert(node.ident.sym.clone());
+        node.visit_children_with(self);
+    }
+
+    fn visit_class_expr(&mut self, node: &ClassExpr) {
+        if let Some(ident) = &node.ident {
+            self.names.insert(ident.sym.clone());
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_import_specifier(&mut self, node: &ImportSpecifier) {
+        let local = match node {
+            ImportSpecifier::Named(named) => &named.local,
+            ImportSpecifier::Default(default) => &default.local,
+            ImportSpecifier::Namespace(ns) => &ns.local,
+        };
+        self.names.insert(local.sym.clone());
+    }
+}
+
+fn declared_names(ast: &Script) -> HashSet<Atom> {
+    let mut collector = DeclaredNames::default();
+    ast.visit_with(&mut collector);
+    collector.names
+}
+
+struct UndefinedRefCollector<'a> {
+    declared: &'a HashSet<Atom>,
+    undefined: Vec<Ident>,
+}
+
+impl UndefinedRefCollector<'_> {
+    fn check(&mut self, ident: &Ident) {
+        if !self.declared.contains(&ident.sym) && !is_known_global(&ident.sym) {
+            self.undefined.push(ident.clone());
+        }
+    }
+}
+
+impl Visit for UndefinedRefCollector<'_> {
+    fn visit_expr(&mut self, node: &Expr) {
+        if let Expr::Ident(ident) = node {
+            self.check(ident);
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_prop(&mut self, node: &Prop) {
+        if let Prop::Shorthand(ident) = node {
+            self.check(ident);
+        }
+        node.visit_children_with(self);
+    }
+}
+
+/// Identifier references that are neither declared anywhere in the program nor a known
+/// global. This is a program-wide check, so a name used outside the scope it's declared
+/// in isn't reported.
+pub fn find_undefined_references(ast: &Script) -> Vec<Ident> {
+    let declared = declared_names(ast);
+    let mut collector = UndefinedRefCollector {
+        declared: &declared,
+        undefined: Vec::new(),
+    };
+    ast.visit_with(&mut collector);
+    collector.undefined
+}
+
+struct UndefinedRefRepairer<'a> {
+    declared: &'a HashSet<Atom>,
+    replacements: &'a [Atom],
+    rng: &'a mut StdRng,
+}
+
+impl UndefinedRefRepairer<'_> {
+    fn replacement(&mut self, ident: &Ident) -> Option<Ident> {
+        if self.declared.contains(&ident.sym) || is_known_global(&ident.sym) {
+            return None;
+        }
+        let name = self.replacements.choose(self.rng)?;
+        Some(Ident {
+            span: DUMMY_SP,
+            sym: name.clone(),
+            optional: false,
+            ctxt: SyntaxContext::empty(),
+        })
+    }
+}
+
+impl VisitMut for UndefinedRefRepairer<'_> {
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        if let Expr::Ident(ident) = node {
+            if let Some(replacement) = self.replacement(ident) {
+                *ident = replacement;
+            }
+            return;
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop(&mut self, node: &mut Prop) {
+        if let Prop::Shorthand(ident) = node {
+            // `{a}` becomes `{a: <declared>}` so the property name doesn't change
+            if let Some(replacement) = self.replacement(ident) {
+                let key = PropName::Ident(IdentName::new(ident.sym.clone(), DUMMY_SP));
+                *node = Prop::KeyValue(KeyValueProp {
+                    key,
+                    value: Box::new(Expr::Ident(replacement)),
+                });
+            }
+            return;
+        }
+        node.visit_mut_children_with(self);
+    }
+}
+
+/// Replace undefined references with names declared in the program, picked with `rng`.
+/// Returns false if there are undefined references but nothing to replace them with.
+pub fn repair_undefined_references(ast: &mut Script, rng: &mut StdRng) -> bool {
+    if find_undefined_references(ast).is_empty() {
+        return true;
+    }
+    let declared = declared_names(ast);
+    // sorted so the same rng picks the same names, whatever the set's iteration order
+    let mut replacements: Vec<Atom> = declared.iter().cloned().collect();
+    replacements.sort_by(|a, b| (**a).cmp(&**b));
+    if replacements.is_empty() {
+        return false;
+    }
+    let mut repairer = UndefinedRefRepairer {
+        declared: &declared,
+        replacements: &replacements,
+        rng,
+    };
+    ast.visit_mut_with(&mut repairer);
+    true
+}
+
+/// Apply `policy` to a mutated program, repairing with `rng`. Returns false if the program
+/// should be dropped.
+pub fn apply_undefined_ref_policy(ast: &mut Script, policy: UndefinedRefPolicy, rng: &mut StdRng) -> bool {
+    match policy {
+        UndefinedRefPolicy::Off => true,
+        UndefinedRefPolicy::Repair => repair_undefined_references(ast, rng),
+        UndefinedRefPolicy::Discard => find_undefined_references(ast).is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parser::parse_js;
+    use crate::utils::rand_utils::unseeded_rng;
+
+    fn undefined_names(source: &str) -> Vec<String> {
+        let ast = parse_js(source.to_string()).expect("failed to parse test script");
+        find_undefined_references(&ast)
+            .iter()
+            .map(|ident| ident.sym.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn reports_genuinely_undefined_reference() {
+        assert_eq!(undefined_names("let v0 = 1; v0 + v1;"), vec!["v1"]);
+        assert_eq!(undefined_names("let v0 = {v2};"), vec!["v2"]);
+    }
+
+    #[test]
+    fn known_globals_and_declarations_are_fine() {
+        assert!(undefined_names("let v0 = new Array(3); Math.max(v0.length, NaN);").is_empty());
+        assert!(undefined_names("function f0(v1) { return f0(v1) + arguments.length; }").is_empty());
+        assert!(undefined_names("try {} catch (e) { print(e); } class C0 {} new C0();").is_empty());
+        // property names aren't references
+        assert!(undefined_names("let v0 = {}; v0.notDeclared = v0.alsoNot;").is_empty());
+    }
+
+    #[test]
+    fn repair_replaces_undefined_references() {
+        let mut ast = parse_js("let v0 = 1; let v1 = {v3}; v2 + v0;".to_string()).unwrap();
+        assert!(repair_undefined_references(&mut ast, &mut unseeded_rng()));
+        assert!(find_undefined_references(&ast).is_empty());
+
+        let mut ast = parse_js("v2 + 1;".to_string()).unwrap();
+        assert!(!repair_undefined_references(&mut ast, &mut unseeded_rng()));
+    }
+}
@@ -1,41 +1,462 @@
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use swc_atoms::Atom;
 use swc_common;
 use swc_common::sync::Lrc;
-use swc_common::{FileName, SourceMap};
+use swc_common::util::take::Take;
+use swc_common::{DUMMY_SP, FileName, SourceMap, SyntaxContext};
 use swc_ecma_codegen::Emitter;
 use swc_ecma_codegen::text_writer::JsWriter;
-use swc_ecma_parser::{self, parse_file_as_script};
+use swc_ecma_parser::{self, parse_file_as_module, parse_file_as_script};
 use swc_ecma_parser::{EsSyntax, Syntax};
-use swc_ecma_visit::swc_ecma_ast::{EsVersion, Script};
+use swc_ecma_visit::swc_ecma_ast::{
+    BinExpr, BinaryOp, Callee, ClassDecl, ClassExpr, Decl, DefaultDecl, EsVersion, ExportDecl, ExportDefaultDecl,
+    ExportNamedSpecifier, ExportSpecifier, Expr, FnDecl, FnExpr, Ident, Invalid, Module, ModuleDecl,
+    ModuleExportName, ModuleItem, NamedExport, Program, Script, Stmt,
+};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
 
-pub fn parse_js(src: String) -> anyhow::Result<Script> {
-    let cm: Lrc<SourceMap> = Default::default();
-    let fm = cm.new_source_file(FileName::Custom("simz.js".into()).into(), src);
-    let syntax = Syntax::Es(EsSyntax {
+use crate::mutators::scope::collect_binding_idents_from_pat;
+
+/// How sources are parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SourceType {
+    #[default]
+    Script,
+    Module,
+    /// Script, falling back to module for sources that look like they use `import`/`export`
+    Auto,
+}
+
+/// Inputs bigger or more deeply nested than this are rejected before parsing. The parser
+/// recurses on every nested bracket, so a hostile seed like `[[[[...` can overflow the
+/// stack or take forever to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    pub max_bytes: usize,
+    /// Deepest nesting of `(`, `[` and `{` outside of strings and comments
+    pub max_depth: usize,
+}
+
+pub const DEFAULT_MAX_PARSE_BYTES: usize = 4 * 1024 * 1024;
+pub const DEFAULT_MAX_PARSE_DEPTH: usize = 512;
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_PARSE_BYTES,
+            max_depth: DEFAULT_MAX_PARSE_DEPTH,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SOURCE_TYPE: RwLock<SourceType> = RwLock::new(SourceType::Script);
+    static ref PARSE_LIMITS: RwLock<ParseLimits> = RwLock::new(ParseLimits::default());
+}
+
+/// Set how `parse_js`/`generate_js` treat sources from now on
+pub fn set_source_type(source_type: SourceType) {
+    *SOURCE_TYPE.write().expect("source type lock poisoned") = source_type;
+}
+
+pub fn source_type() -> SourceType {
+    *SOURCE_TYPE.read().expect("source type lock poisoned")
+}
+
+/// Set the limits checked by `parse_program` from now on
+pub fn set_parse_limits(limits: ParseLimits) {
+    *PARSE_LIMITS.write().expect("parse limits lock poisoned") = limits;
+}
+
+pub fn parse_limits() -> ParseLimits {
+    *PARSE_LIMITS.read().expect("parse limits lock poisoned")
+}
+
+/// Deepest bracket nesting of `src`, computed without recursion. Brackets in string
+/// literals and comments don't count; template literals and regexes aren't tokenized, so
+/// the result is only an estimate for them.
+pub fn bracket_depth(src: &str) -> usize {
+    let bytes = src.as_bytes();
+    let (mut depth, mut max_depth) = (0usize, 0usize);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'{' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    max_depth
+}
+
+/// Reject `src` if it exceeds `limits`, before handing it to the parser
+pub fn check_parse_limits(src: &str, limits: ParseLimits) -> anyhow::Result<()> {
+    if src.len() > limits.max_bytes {
+        anyhow::bail!("input of {} bytes exceeds the {} bytes parse limit", src.len(), limits.max_bytes);
+    }
+    let depth = bracket_depth(src);
+    if depth > limits.max_depth {
+        anyhow::bail!("input nested {} brackets deep exceeds the parse depth limit of {}", depth, limits.max_depth);
+    }
+    Ok(())
+}
+
+/// Natives syntax calls such as `%OptimizeFunctionOnNextCall(f0)` are parsed as calls of
+/// `<NATIVES_PLACEHOLDER>OptimizeFunctionOnNextCall`, then turned back into the
+/// `<invalid> % OptimizeFunctionOnNextCall(f0)` that `generate_program` emits as natives syntax
+const NATIVES_PLACEHOLDER: &str = "__jellyfuzz_natives_";
+
+/// Rewrite the `%<intrinsic>(` of natives syntax calls into calls of placeholder identifiers.
+/// A `%` only starts a natives call where an expression is expected, which is guessed from
+/// the character before it; strings and comments are skipped like in `bracket_depth`.
+fn hide_natives_syntax(src: &str) -> String {
+    let bytes = src.as_bytes();
+    let mut out = String::with_capacity(src.len());
+    let mut prev: Option<u8> = None;
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            b'%' if prev.is_none_or(|prev| b";{}(,=[!&|?:".contains(&prev)) => {
+                let name_len = bytes[i + 1..]
+                    .iter()
+                    .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'_')
+                    .count();
+                let starts_name = bytes.get(i + 1).is_some_and(|c| c.is_ascii_alphabetic() || *c == b'_');
+                if starts_name && bytes.get(i + 1 + name_len) == Some(&b'(') {
+                    out.push_str(&src[copied..i]);
+                    out.push_str(NATIVES_PLACEHOLDER);
+                    copied = i + 1;
+                }
+            }
+            _ => {}
+        }
+        if let Some(&c) = bytes.get(i) {
+            if !c.is_ascii_whitespace() {
+                prev = Some(c);
+            }
+        }
+        i += 1;
+    }
+    out.push_str(&src[copied..]);
+    out
+}
+
+/// Turns the placeholder calls of `hide_natives_syntax` back into natives syntax
+struct RestoreNativesSyntax;
+
+impl VisitMut for RestoreNativesSyntax {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+        let Expr::Call(call) = expr else {
+            return;
+        };
+        let Callee::Expr(callee) = &mut call.callee else {
+            return;
+        };
+        let Expr::Ident(ident) = &mut **callee else {
+            return;
+        };
+        let Some(intrinsic) = ident.sym.strip_prefix(NATIVES_PLACEHOLDER) else {
+            return;
+        };
+        ident.sym = Atom::from(intrinsic);
+        *expr = Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: BinaryOp::Mod,
+            left: Box::new(Expr::Invalid(Invalid { span: DUMMY_SP })),
+            right: Box::new(expr.take()),
+        });
+    }
+}
+
+fn syntax() -> Syntax {
+    Syntax::Es(EsSyntax {
         jsx: false, // set true if you want JSX
         decorators: false,
         decorators_before_export: false,
         export_default_from: true,
         import_attributes: true,
         ..Default::default()
-    });
+    })
+}
 
+/// Heuristic for `SourceType::Auto`: a line starting with a static `import` or an `export`
+pub fn looks_like_module(src: &str) -> bool {
+    src.lines().any(|line| {
+        let line = line.trim_start();
+        let rest = if let Some(rest) = line.strip_prefix("import") {
+            rest
+        } else if let Some(rest) = line.strip_prefix("export") {
+            rest
+        } else {
+            return false;
+        };
+        // `import(...)` and `import.meta` are expressions, `imported = 1` is an identifier
+        matches!(rest.chars().next(), Some(c) if c.is_whitespace() || matches!(c, '{' | '*' | '"' | '\''))
+    })
+}
+
+fn parse_as_script(src: String) -> anyhow::Result<Script> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Custom("simz.js".into()).into(), src);
     let mut errs = vec![];
-    let aux = parse_file_as_script(&fm, syntax, EsVersion::Es2024, None, &mut errs)
-        .map_err(|e| anyhow::anyhow!("error parsing script: {:?}", e));
-    aux
+    parse_file_as_script(&fm, syntax(), EsVersion::Es2024, None, &mut errs)
+        .map_err(|e| anyhow::anyhow!("error parsing script: {:?}", e))
 }
 
-pub fn generate_js(script: Script) -> anyhow::Result<Vec<u8>> {
+fn parse_as_module(src: String) -> anyhow::Result<Module> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Custom("simz.mjs".into()).into(), src);
+    let mut errs = vec![];
+    parse_file_as_module(&fm, syntax(), EsVersion::Es2024, None, &mut errs)
+        .map_err(|e| anyhow::anyhow!("error parsing module: {:?}", e))
+}
+
+/// Parse `src` as a script or a module depending on `source_type`. Inputs exceeding the
+/// limits set by `set_parse_limits` are rejected without being parsed.
+pub fn parse_program(src: String, source_type: SourceType) -> anyhow::Result<Program> {
+    check_parse_limits(&src, parse_limits())?;
+    let has_natives = src.contains('%');
+    let src = if has_natives { hide_natives_syntax(&src) } else { src };
+    let mut program = match source_type {
+        SourceType::Script => parse_as_script(src).map(Program::Script),
+        SourceType::Module => parse_as_module(src).map(Program::Module),
+        SourceType::Auto => match parse_as_script(src.clone()) {
+            Ok(script) => Ok(Program::Script(script)),
+            Err(err) if !looks_like_module(&src) => Err(err),
+            Err(_) => parse_as_module(src).map(Program::Module),
+        },
+    }?;
+    if has_natives {
+        program.visit_mut_with(&mut RestoreNativesSyntax);
+    }
+    Ok(program)
+}
+
+/// The `import`/`export` declarations of a module whose statements are mutated as a script.
+/// Imports are hoisted and export lists only bind names, so they are set aside and put
+/// back around the mutated statements.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleDecls {
+    shebang: Option<Atom>,
+    /// Go in front of the statements
+    imports: Vec<ModuleDecl>,
+    /// Go after the statements
+    exports: Vec<ModuleDecl>,
+    /// `(local, exported)` names of the lowered `export <decl>`s
+    exported_decls: Vec<(Atom, Atom)>,
+}
+
+impl ModuleDecls {
+    /// Split `module` into its statements and its declarations. `export <decl>` is lowered
+    /// into `<decl>` and an `export { ... }` list, so exported declarations stay where they
+    /// are and are mutated like any other. Default exports of expressions are evaluated
+    /// after the statements instead.
+    pub fn split(module: Module) -> (Script, Self) {
+        let mut decls = Self {
+            shebang: module.shebang,
+            ..Self::default()
+        };
+        let mut body = Vec::with_capacity(module.body.len());
+        for item in module.body {
+            match item {
+                ModuleItem::Stmt(stmt) => body.push(stmt),
+                ModuleItem::ModuleDecl(decl @ ModuleDecl::Import(_)) => decls.imports.push(decl),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+                    for name in declared_names(&decl) {
+                        decls.exported_decls.push((name.clone(), name));
+                    }
+                    body.push(Stmt::Decl(decl));
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                    decl: DefaultDecl::Fn(FnExpr { ident: Some(ident), function }),
+                    ..
+                })) => {
+                    decls.exported_decls.push((ident.sym.clone(), Atom::from("default")));
+                    body.push(Stmt::Decl(Decl::Fn(FnDecl {
+                        ident,
+                        declare: false,
+                        function,
+                    })));
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                    decl: DefaultDecl::Class(ClassExpr { ident: Some(ident), class }),
+                    ..
+                })) => {
+                    decls.exported_decls.push((ident.sym.clone(), Atom::from("default")));
+                    body.push(Stmt::Decl(Decl::Class(ClassDecl {
+                        ident,
+                        declare: false,
+                        class,
+                    })));
+                }
+                ModuleItem::ModuleDecl(decl) => decls.exports.push(decl),
+            }
+        }
+        let script = Script {
+            span: module.span,
+            body,
+            shebang: None,
+        };
+        (script, decls)
+    }
+
+    /// Put the declarations back around the mutated `script`. Lowered exports of
+    /// declarations the mutators removed are dropped, exporting them would be an error.
+    pub fn join(self, script: Script) -> Module {
+        let declared: HashSet<Atom> = script
+            .body
+            .iter()
+            .filter_map(|stmt| stmt.as_decl())
+            .flat_map(declared_names)
+            .collect();
+        let specifiers: Vec<ExportSpecifier> = self
+            .exported_decls
+            .into_iter()
+            .filter(|(local, _)| declared.contains(local))
+            .map(|(local, exported)| {
+                ExportSpecifier::Named(ExportNamedSpecifier {
+                    span: DUMMY_SP,
+                    exported: (local != exported).then(|| module_export_name(exported)),
+                    orig: module_export_name(local),
+                    is_type_only: false,
+                })
+            })
+            .collect();
+        let lowered = (!specifiers.is_empty()).then(|| {
+            ModuleDecl::ExportNamed(NamedExport {
+                span: DUMMY_SP,
+                specifiers,
+                src: None,
+                type_only: false,
+                with: None,
+            })
+        });
+        Module {
+            span: script.span,
+            body: self
+                .imports
+                .into_iter()
+                .map(ModuleItem::ModuleDecl)
+                .chain(script.body.into_iter().map(ModuleItem::Stmt))
+                .chain(lowered.into_iter().chain(self.exports).map(ModuleItem::ModuleDecl))
+                .collect(),
+            shebang: self.shebang,
+        }
+    }
+}
+
+/// Names bound by a top-level declaration
+fn declared_names(decl: &Decl) -> Vec<Atom> {
+    match decl {
+        Decl::Fn(fn_decl) => vec![fn_decl.ident.sym.clone()],
+        Decl::Class(class_decl) => vec![class_decl.ident.sym.clone()],
+        Decl::Var(var_decl) => {
+            let mut idents = Vec::new();
+            for declarator in &var_decl.decls {
+                collect_binding_idents_from_pat(&declarator.name, &mut idents);
+            }
+            idents.into_iter().map(|ident| ident.sym).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn module_export_name(sym: Atom) -> ModuleExportName {
+    ModuleExportName::Ident(Ident {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        sym,
+        optional: false,
+    })
+}
+
+/// Parse `src` for the mutators, keeping the declarations of a module so they can be put
+/// back with `generate_js_with_decls`
+pub fn parse_js_with_decls(src: String, source_type: SourceType) -> anyhow::Result<(Script, Option<ModuleDecls>)> {
+    match parse_program(src, source_type)? {
+        Program::Script(script) => Ok((script, None)),
+        Program::Module(module) => {
+            let (script, decls) = ModuleDecls::split(module);
+            Ok((script, Some(decls)))
+        }
+    }
+}
+
+/// Parse `src` for the mutators. Only the statements of a module are kept, including its
+/// exported declarations.
+pub fn parse_js_as(src: String, source_type: SourceType) -> anyhow::Result<Script> {
+    parse_js_with_decls(src, source_type).map(|(script, _)| script)
+}
+
+/// Parse `src` with the source type set by `set_source_type`
+pub fn parse_js(src: String) -> anyhow::Result<Script> {
+    parse_js_as(src, source_type())
+}
+
+pub fn generate_program(program: &Program) -> anyhow::Result<Vec<u8>> {
     let cm = Lrc::new(SourceMap::default());
     let mut out = Vec::new();
     let wr = JsWriter::new(cm.clone(), "\n", &mut out, None);
     let mut emitter = Emitter {
-        cfg: Default::default(),
+        // emit ES2024 so raw literals like `0b101` or `1_000` aren't lowered
+        cfg: swc_ecma_codegen::Config::default().with_target(EsVersion::Es2024),
         comments: None, // remove comments so we dont have to build another mutator
         cm,
         wr,
     };
-    emitter.emit_script(&script)?;
+    emitter.emit_program(program)?;
 
     // support natives syntax
     // TODO: this is wacky for now
@@ -44,3 +465,196 @@ pub fn generate_js(script: Script) -> anyhow::Result<Vec<u8>> {
     out = result.into_bytes();
     Ok(out)
 }
+
+/// Emit a script built by the mutators. When parsing as a module, it is emitted as a
+/// module so module-only syntax such as top-level `await` stays valid.
+pub fn generate_js(script: Script) -> anyhow::Result<Vec<u8>> {
+    generate_js_with_decls(script, None)
+}
+
+/// Emit a script built by the mutators within the declarations of the module it was
+/// parsed from, if any
+pub fn generate_js_with_decls(script: Script, decls: Option<ModuleDecls>) -> anyhow::Result<Vec<u8>> {
+    let program = match (decls, source_type()) {
+        (Some(decls), _) => Program::Module(decls.join(script)),
+        (None, SourceType::Module) => Program::Module(ModuleDecls::default().join(script)),
+        (None, SourceType::Script | SourceType::Auto) => Program::Script(script),
+    };
+    generate_program(&program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swc_ecma_visit::swc_ecma_ast::ExprStmt;
+
+    #[test]
+    fn deeply_nested_input_is_rejected_early() {
+        let limits = ParseLimits::default();
+        let deep = format!("{}1{};", "[".repeat(1_000_000), "]".repeat(1_000_000));
+        let start = std::time::Instant::now();
+        let err = check_parse_limits(&deep, limits).expect_err("deep input accepted");
+        assert!(err.to_string().contains("depth limit"), "unexpected error: {}", err);
+        assert!(parse_program(deep, SourceType::Script).is_err());
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "depth check is too slow");
+
+        // brackets in strings and comments aren't nesting
+        let quoted = format!("let v0 = \"{}\"; // {}\n", "(".repeat(1000), "{".repeat(1000));
+        assert_eq!(bracket_depth(&quoted), 0);
+        assert!(check_parse_limits(&quoted, limits).is_ok());
+        assert_eq!(bracket_depth("f0([{ a: 1 }], '}]');"), 3);
+
+        let small = ParseLimits {
+            max_bytes: 8,
+            max_depth: 2,
+        };
+        assert!(check_parse_limits("let v0 = 1;", small).is_err());
+        assert!(check_parse_limits("f0([1]);", small).is_ok());
+        assert!(check_parse_limits("f([[1]])", small).is_err());
+    }
+
+    #[test]
+    fn script_mode_rejects_module_syntax() {
+        assert!(parse_program("let v0 = 1;".to_string(), SourceType::Script).is_ok());
+        assert!(parse_program("export const v0 = 1;".to_string(), SourceType::Script).is_err());
+    }
+
+    #[test]
+    fn module_with_export_round_trips() {
+        let source = "import { v1 } from \"./v1.js\";\nexport const v0 = v1 + 1;\n";
+        let program = parse_program(source.to_string(), SourceType::Module).expect("failed to parse module");
+        assert!(matches!(program, Program::Module(_)));
+        let code = String::from_utf8(generate_program(&program).expect("code generation failed")).unwrap();
+        assert!(code.contains("export const v0"), "export lost in {}", code);
+
+        let reparsed = parse_program(code.clone(), SourceType::Module).expect("generated module does not parse");
+        let Program::Module(module) = reparsed else {
+            panic!("expected a module");
+        };
+        assert_eq!(module.body.len(), 2, "unexpected items in {}", code);
+
+        let script = parse_js_as("await 1;".to_string(), SourceType::Module).expect("failed to parse module");
+        assert_eq!(script.body.len(), 1);
+    }
+
+    fn module_items(code: &[u8]) -> Vec<ModuleItem> {
+        let code = String::from_utf8(code.to_vec()).unwrap();
+        match parse_program(code.clone(), SourceType::Module).expect("generated module does not parse") {
+            Program::Module(module) => module.body,
+            Program::Script(_) => panic!("expected a module from {}", code),
+        }
+    }
+
+    fn split_module(source: &str) -> (Script, ModuleDecls) {
+        let (script, decls) = parse_js_with_decls(source.to_string(), SourceType::Module).expect("failed to parse");
+        (script, decls.expect("module declarations were not kept"))
+    }
+
+    fn exported_names(item: &ModuleItem) -> Vec<String> {
+        let ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) = item else {
+            panic!("expected an export list, got {:?}", item);
+        };
+        export
+            .specifiers
+            .iter()
+            .map(|specifier| match specifier {
+                ExportSpecifier::Named(named) => match named.exported.as_ref().unwrap_or(&named.orig) {
+                    ModuleExportName::Ident(ident) => ident.sym.to_string(),
+                    name => panic!("unexpected export name {:?}", name),
+                },
+                _ => panic!("unexpected specifier {:?}", specifier),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn module_declarations_survive_mutation() {
+        // the exported declaration is mutated like any other statement
+        let (script, decls) = split_module("export const v0 = 1;");
+        assert_eq!(script.body.len(), 1);
+        let items = decls.clone().join(script.clone());
+        assert_eq!(items.body.len(), 2);
+        assert_eq!(exported_names(&items.body[1]), vec!["v0"]);
+        let code = generate_js_with_decls(script, Some(decls)).expect("code generation failed");
+        assert_eq!(module_items(&code).len(), 2);
+
+        // imports go before the mutated statements, export lists after them
+        let source = "let v2 = 1;\nimport { v1 } from \"./v1.js\";\nexport { v2 };\nv2 += v1;\n\
+            export function f0() {}\nexport default class C0 {}\n";
+        let (mut script, decls) = split_module(source);
+        assert_eq!(script.body.len(), 4);
+        let print = parse_js_as("print(v2);".to_string(), SourceType::Script).unwrap();
+        script.body.extend(print.body);
+        let items = decls.join(script).body;
+        assert_eq!(items.len(), 8);
+        assert!(matches!(items[0], ModuleItem::ModuleDecl(ModuleDecl::Import(_))));
+        assert!(items[1..6].iter().all(|item| matches!(item, ModuleItem::Stmt(_))));
+        assert_eq!(exported_names(&items[6]), vec!["f0", "default"]);
+        assert_eq!(exported_names(&items[7]), vec!["v2"]);
+
+        // exported declarations between statements stay in place, and are no longer
+        // exported once a mutator removed them
+        let (mut script, decls) = split_module("let v0 = 1;\nexport const v1 = v0, v2 = v1;\nprint(v1);\n");
+        assert_eq!(script.body.len(), 3);
+        assert!(matches!(decls.clone().join(script.clone()).body[1], ModuleItem::Stmt(_)));
+        script.body.remove(1);
+        let items = decls.join(script).body;
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| matches!(item, ModuleItem::Stmt(_))));
+    }
+
+    /// The intrinsic called by `stmt` if it is natives syntax
+    fn intrinsic(stmt: &Stmt) -> Option<String> {
+        let Stmt::Expr(ExprStmt { expr, .. }) = stmt else {
+            return None;
+        };
+        let Expr::Bin(BinExpr { op: BinaryOp::Mod, left, right, .. }) = &**expr else {
+            return None;
+        };
+        let (Expr::Invalid(_), Expr::Call(call)) = (&**left, &**right) else {
+            return None;
+        };
+        call.callee.as_expr()?.as_ident().map(|ident| ident.sym.to_string())
+    }
+
+    #[test]
+    fn natives_syntax_is_parsed() {
+        let source = "function f0() {}\n%PrepareFunctionForOptimization(f0);\nf0(); %OptimizeFunctionOnNextCall(f0);\n\
+            let v0 = 5 %f0(1);\nlet v1 = \"a;%f0(\";\n";
+        let script = parse_js_as(source.to_string(), SourceType::Script).expect("failed to parse natives syntax");
+        let intrinsics: Vec<Option<String>> = script.body.iter().map(intrinsic).collect();
+        assert_eq!(
+            intrinsics,
+            vec![
+                None,
+                Some("PrepareFunctionForOptimization".to_string()),
+                None,
+                Some("OptimizeFunctionOnNextCall".to_string()),
+                None,
+                None
+            ]
+        );
+        // a modulo and a string containing `%` are left alone
+        assert!(!hide_natives_syntax("let v0 = 5 %f0(1);").contains(NATIVES_PLACEHOLDER));
+        assert!(!hide_natives_syntax("let v1 = \"a;%f0(\";").contains(NATIVES_PLACEHOLDER));
+
+        let code = String::from_utf8(generate_js(script).expect("code generation failed")).unwrap();
+        assert!(code.contains("%OptimizeFunctionOnNextCall(f0)"), "natives syntax lost in {}", code);
+        assert!(!code.contains(NATIVES_PLACEHOLDER), "placeholder left in {}", code);
+    }
+
+    #[test]
+    fn auto_mode_detects_modules() {
+        let program = parse_program("let v0 = import(\"x\");".to_string(), SourceType::Auto).unwrap();
+        assert!(matches!(program, Program::Script(_)));
+        let program = parse_program("export default 1;".to_string(), SourceType::Auto).unwrap();
+        assert!(matches!(program, Program::Module(_)));
+        // not module syntax, so the script error is kept
+        assert!(parse_program("let v0 = (1 + ;".to_string(), SourceType::Auto).is_err());
+
+        assert!(looks_like_module("  import * as v0 from \"x\";"));
+        assert!(looks_like_module("export{ v0 };"));
+        assert!(!looks_like_module("import(\"x\");\nimport.meta;"));
+        assert!(!looks_like_module("let exported = 1;\nimported = 2;"));
+    }
+}
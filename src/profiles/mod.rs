@@ -1,6 +1,9 @@
 pub mod profile;
 pub mod v8;
 
+/// Names accepted by `get_profile`
+pub const PROFILE_NAMES: &[&str] = &["v8"];
+
 pub fn get_profile(name: &str) -> Option<impl profile::JsEngineProfile + Clone> {
     match name {
         "v8" => Some(v8::V8Profile),
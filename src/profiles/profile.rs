@@ -1,12 +1,56 @@
+use crate::runner::backend::ExecBackendKind;
+use crate::utils::reward::RewardConfig;
+
+/// Milliseconds an engine gets to send its HELO after being spawned
+pub const DEFAULT_HANDSHAKE_TIMEOUT_MS: u64 = 10_000;
+
+/// Largest program handed to an engine by default
+pub const DEFAULT_MAX_PROGRAM_BYTES: usize = 1 << 20;
+
 // enum JsEngineProfile {
 //     V8(V8Profile)
 // }
 
 pub trait JsEngineProfile: Send + Sync + 'static{
+    fn get_name(&self) -> String;
     fn get_path(&self) -> String;
     fn get_args(&self) -> Vec<String>;
     fn fuzz_worker_job_queue_size(&self) -> usize;
     fn get_timeout(&self) -> u64;
     fn get_jobs_per_process(&self) -> usize;
     fn get_min_new_edges_to_add_corpus(&self) -> usize;
+    /// Milliseconds to wait for the HELO of a freshly spawned engine, 0 waits forever
+    fn get_handshake_timeout(&self) -> u64 {
+        DEFAULT_HANDSHAKE_TIMEOUT_MS
+    }
+    /// Whether the engine accepts V8 natives syntax (`%OptimizeFunctionOnNextCall(f)`)
+    fn supports_natives(&self) -> bool {
+        false
+    }
+    /// Rewards credited to mutators, unless `--reward-config` is given
+    fn reward_config(&self) -> RewardConfig {
+        RewardConfig::default()
+    }
+    /// How programs are handed to the engine. Engines without REPRL support can use
+    /// `ExecBackendKind::TempFile`, at the cost of throughput and coverage.
+    fn exec_backend(&self) -> ExecBackendKind {
+        ExecBackendKind::Reprl
+    }
+    /// Address space limit of engine processes (RLIMIT_AS), None for no limit. Engines
+    /// reserving large virtual ranges up front, like V8 with its pointer cage, need a
+    /// generous value or none at all.
+    fn mem_limit_bytes(&self) -> Option<u64> {
+        None
+    }
+    /// CPU time limit of engine processes (RLIMIT_CPU), None for no limit. Persistent
+    /// engines accumulate CPU time over all their executions, so this is only a backstop
+    /// against runaway processes, the per-execution timeout still applies.
+    fn cpu_limit_secs(&self) -> Option<u64> {
+        None
+    }
+    /// Largest program the workers send to the engine, None for no limit. Larger ones
+    /// are rejected instead of being pushed through the data pipe.
+    fn max_program_bytes(&self) -> Option<usize> {
+        Some(DEFAULT_MAX_PROGRAM_BYTES)
+    }
 }
@@ -4,6 +4,11 @@ use crate::profiles::profile::JsEngineProfile;
 pub struct V8Profile;
 
 impl JsEngineProfile for V8Profile {
+    /// engine tag used for per-engine coverage and crash file names
+    fn get_name(&self) -> String {
+        "v8".to_string()
+    }
+
     fn get_path(&self) -> String {
         "/home/mcsky/Desktop/CTF/v8_research2/v8/out/fuzzbuild/d8".to_string()
     }
@@ -34,4 +39,9 @@ impl JsEngineProfile for V8Profile {
     fn get_min_new_edges_to_add_corpus(&self) -> usize {
         10
     }
+
+    /// d8 runs with `--allow-natives-syntax`
+    fn supports_natives(&self) -> bool {
+        true
+    }
 }
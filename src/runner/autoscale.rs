@@ -0,0 +1,129 @@
+/// Bounds of the per-engine worker count in `--auto-scale-workers` mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerBounds {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Pool load over the last scaling interval
+#[derive(Debug, Clone, Copy)]
+pub struct LoadMetrics {
+    /// Workers per engine
+    pub workers: usize,
+    /// Jobs scheduled but not completed
+    pub queued_jobs: usize,
+    /// Jobs the worker queues can hold
+    pub queue_capacity: usize,
+    pub execs_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleDecision {
+    Grow,
+    Shrink,
+    Hold,
+}
+
+/// Queues fuller than this mean the workers can't keep up with the fuzz loop
+const HIGH_PRESSURE: f64 = 0.75;
+/// Queues emptier than this mean workers sit idle waiting for jobs
+const LOW_PRESSURE: f64 = 0.25;
+/// Throughput gain a new worker has to bring to be kept
+const MIN_GAIN: f64 = 0.05;
+
+/// Decides when to add or retire workers. Workers are added while the queues are full,
+/// but a worker that doesn't raise execs/sec (the host is saturated) is retired again and
+/// the count isn't grown past that point anymore.
+#[derive(Debug, Clone)]
+pub struct AutoScaler {
+    bounds: WorkerBounds,
+    ceiling: usize,
+    rate_before_growth: Option<f64>,
+}
+
+impl AutoScaler {
+    pub fn new(bounds: WorkerBounds) -> Self {
+        Self {
+            bounds,
+            ceiling: bounds.max,
+            rate_before_growth: None,
+        }
+    }
+
+    pub fn decide(&mut self, metrics: &LoadMetrics) -> ScaleDecision {
+        if metrics.workers < self.bounds.min {
+            return ScaleDecision::Grow;
+        }
+        if metrics.workers > self.bounds.max {
+            return ScaleDecision::Shrink;
+        }
+        // judge the last growth now that its effect was measured
+        if let Some(rate_before) = self.rate_before_growth.take() {
+            if metrics.execs_per_sec < rate_before * (1.0 + MIN_GAIN) {
+                self.ceiling = metrics.workers.saturating_sub(1).max(self.bounds.min);
+                return if metrics.workers > self.ceiling {
+                    ScaleDecision::Shrink
+                } else {
+                    ScaleDecision::Hold
+                };
+            }
+        }
+
+        let pressure = if metrics.queue_capacity == 0 {
+            0.0
+        } else {
+            metrics.queued_jobs as f64 / metrics.queue_capacity as f64
+        };
+        if pressure >= HIGH_PRESSURE && metrics.workers < self.ceiling {
+            self.rate_before_growth = Some(metrics.execs_per_sec);
+            ScaleDecision::Grow
+        } else if pressure <= LOW_PRESSURE && metrics.workers > self.bounds.min {
+            ScaleDecision::Shrink
+        } else {
+            ScaleDecision::Hold
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(workers: usize, queued_jobs: usize, execs_per_sec: f64) -> LoadMetrics {
+        LoadMetrics {
+            workers,
+            queued_jobs,
+            queue_capacity: workers * 10,
+            execs_per_sec,
+        }
+    }
+
+    #[test]
+    fn scales_with_queue_pressure_within_bounds() {
+        let mut scaler = AutoScaler::new(WorkerBounds { min: 2, max: 4 });
+        assert_eq!(scaler.decide(&metrics(1, 0, 0.0)), ScaleDecision::Grow);
+        assert_eq!(scaler.decide(&metrics(5, 50, 100.0)), ScaleDecision::Shrink);
+
+        // saturated queues grow the pool as long as throughput follows
+        assert_eq!(scaler.decide(&metrics(2, 19, 100.0)), ScaleDecision::Grow);
+        assert_eq!(scaler.decide(&metrics(3, 28, 150.0)), ScaleDecision::Grow);
+        // at the maximum
+        assert_eq!(scaler.decide(&metrics(4, 40, 200.0)), ScaleDecision::Hold);
+        assert_eq!(scaler.decide(&metrics(4, 40, 200.0)), ScaleDecision::Hold);
+
+        // idle workers are retired down to the minimum
+        assert_eq!(scaler.decide(&metrics(4, 2, 200.0)), ScaleDecision::Shrink);
+        assert_eq!(scaler.decide(&metrics(2, 0, 100.0)), ScaleDecision::Hold);
+        assert_eq!(scaler.decide(&metrics(3, 15, 100.0)), ScaleDecision::Hold);
+    }
+
+    #[test]
+    fn growth_that_does_not_pay_off_is_undone() {
+        let mut scaler = AutoScaler::new(WorkerBounds { min: 1, max: 8 });
+        assert_eq!(scaler.decide(&metrics(2, 20, 100.0)), ScaleDecision::Grow);
+        // the third worker brought nothing, the host is saturated
+        assert_eq!(scaler.decide(&metrics(3, 30, 101.0)), ScaleDecision::Shrink);
+        // and the pool isn't grown past two workers again
+        assert_eq!(scaler.decide(&metrics(2, 20, 100.0)), ScaleDecision::Hold);
+    }
+}
@@ -0,0 +1,261 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::profiles::profile::JsEngineProfile;
+use crate::runner::process::{EngineStdio, ExecutionStatus, FuzzProcess, ResourceLimits};
+
+/// Log output kept per execution, the rest is dropped
+const MAX_LOG_BYTES: u64 = 64 * 1024;
+
+static NEXT_TEMP_FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// How a profile's engine gets the programs to run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecBackendKind {
+    /// A persistent engine speaking the Fuzzilli REPRL protocol, with coverage
+    #[default]
+    Reprl,
+    /// A fresh engine per execution, given the program as a temp file path after its args.
+    /// Works with any engine but is slower and reports no coverage.
+    TempFile,
+}
+
+impl ExecBackendKind {
+    pub const ALL: [ExecBackendKind; 2] = [ExecBackendKind::Reprl, ExecBackendKind::TempFile];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExecBackendKind::Reprl => "reprl",
+            ExecBackendKind::TempFile => "tempfile",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ExecBackendKind::Reprl => "persistent engine over the Fuzzilli REPRL protocol, with coverage",
+            ExecBackendKind::TempFile => "fresh engine per program read from a temp file, no coverage",
+        }
+    }
+}
+
+/// Something that runs programs in a JS engine
+pub trait ExecBackend: Send {
+    /// Make sure the engine is ready to take programs
+    fn handshake(&mut self) -> io::Result<()>;
+    /// Run a program and wait for it to finish. Fails with `TimedOut` if it runs over the
+    /// profile's timeout.
+    fn execute(&mut self, script: &[u8]) -> io::Result<ExecutionStatus>;
+    /// What the engine printed since the last call
+    fn read_log(&mut self) -> io::Result<Vec<u8>>;
+    /// Whether the engine ran enough programs to be replaced by a fresh one
+    fn needs_restart(&self) -> bool;
+    /// PID of the engine currently alive, if any
+    fn pid(&self) -> Option<u32>;
+    fn kill(&mut self);
+    /// Whether executions fill the shared coverage bitmap
+    fn has_coverage(&self) -> bool;
+}
+
+impl ExecBackend for FuzzProcess {
+    fn handshake(&mut self) -> io::Result<()> {
+        FuzzProcess::handshake(self)
+    }
+
+    fn execute(&mut self, script: &[u8]) -> io::Result<ExecutionStatus> {
+        FuzzProcess::execute(self, script)
+    }
+
+    fn read_log(&mut self) -> io::Result<Vec<u8>> {
+        FuzzProcess::read_log(self)
+    }
+
+    fn needs_restart(&self) -> bool {
+        self.crt_executions >= self.max_executions
+    }
+
+    fn pid(&self) -> Option<u32> {
+        Some(self.child.id())
+    }
+
+    fn kill(&mut self) {
+        let _ = self.child.kill();
+    }
+
+    fn has_coverage(&self) -> bool {
+        true
+    }
+}
+
+/// Spawn the backend chosen by the profile. `shm_id` names the coverage bitmap of
+/// backends that have one, `stdio` is where persistent engines send their output.
+pub fn spawn_backend<T: JsEngineProfile>(
+    profile: &T,
+    shm_id: &str,
+    stdio: &EngineStdio,
+) -> anyhow::Result<Box<dyn ExecBackend>> {
+    Ok(match profile.exec_backend() {
+        ExecBackendKind::Reprl => Box::new(FuzzProcess::spawn(profile, shm_id, stdio.clone())?),
+        ExecBackendKind::TempFile => Box::new(TempFileProcess::new(
+            profile.get_path(),
+            profile.get_args(),
+            profile.get_timeout(),
+        )
+        .with_limits(ResourceLimits::from_profile(profile))),
+    })
+}
+
+/// Runs every program in a new engine process as `<path> <args...> <temp file>`.
+/// The engine's stdout and stderr make up the log of the execution.
+#[derive(Debug)]
+pub struct TempFileProcess {
+    path: String,
+    args: Vec<String>,
+    timeout: u64,
+    script_path: PathBuf,
+    log_path: PathBuf,
+    limits: ResourceLimits,
+    child: Option<Child>,
+}
+
+impl TempFileProcess {
+    pub fn new(path: String, args: Vec<String>, timeout: u64) -> Self {
+        let id = NEXT_TEMP_FILE_ID.fetch_add(1, Ordering::Relaxed);
+        let base = std::env::temp_dir().join(format!("jellyfuzz_exec_{}_{}", std::process::id(), id));
+        Self {
+            path,
+            args,
+            timeout,
+            script_path: base.with_extension("js"),
+            log_path: base.with_extension("log"),
+            limits: ResourceLimits::default(),
+            child: None,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Wait for the child, killing it if it takes over the timeout. A timeout of 0 waits
+    /// forever.
+    fn wait_with_timeout(&mut self) -> io::Result<std::process::ExitStatus> {
+        let child = self.child.as_mut().expect("no engine process to wait for");
+        if self.timeout == 0 {
+            return child.wait();
+        }
+        let deadline = Instant::now() + Duration::from_millis(self.timeout);
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "engine process timed out"));
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+impl ExecBackend for TempFileProcess {
+    /// Engines are only spawned per execution, there is nothing to greet
+    fn handshake(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn execute(&mut self, script: &[u8]) -> io::Result<ExecutionStatus> {
+        std::fs::write(&self.script_path, script)?;
+        let log = File::create(&self.log_path)?;
+
+        let mut cmd = Command::new(&self.path);
+        cmd.args(&self.args)
+            .arg(&self.script_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::from(log.try_clone()?))
+            .stderr(Stdio::from(log));
+        let limits = self.limits;
+        unsafe {
+            cmd.pre_exec(move || limits.apply());
+        }
+
+        let start_time = Instant::now();
+        self.child = Some(cmd.spawn()?);
+        let status = self.wait_with_timeout()?;
+        Ok(ExecutionStatus {
+            exit_code: status.code().unwrap_or(0),
+            signal: status.signal().unwrap_or(0),
+            exec_time_ms: start_time.elapsed(),
+        })
+    }
+
+    fn read_log(&mut self) -> io::Result<Vec<u8>> {
+        let mut log = Vec::new();
+        match OpenOptions::new().read(true).open(&self.log_path) {
+            Ok(file) => {
+                file.take(MAX_LOG_BYTES).read_to_end(&mut log)?;
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        // the next call only reports what the next execution printed
+        let _ = std::fs::remove_file(&self.log_path);
+        Ok(log)
+    }
+
+    fn needs_restart(&self) -> bool {
+        false
+    }
+
+    fn pid(&self) -> Option<u32> {
+        self.child.as_ref().map(|child| child.id())
+    }
+
+    fn kill(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    fn has_coverage(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for TempFileProcess {
+    fn drop(&mut self) {
+        self.kill();
+        let _ = std::fs::remove_file(&self.script_path);
+        let _ = std::fs::remove_file(&self.log_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_file_backend_runs_a_script() {
+        let mut process = TempFileProcess::new("sh".to_string(), Vec::new(), 5000);
+        process.handshake().expect("handshake failed");
+
+        let status = process.execute(b"echo hello; exit 3").expect("execution failed");
+        assert_eq!(status.exit_code, 3);
+        assert_eq!(status.signal, 0);
+        let log = String::from_utf8(process.read_log().expect("failed to read log")).unwrap();
+        assert!(log.contains("hello"));
+        assert!(!process.has_coverage());
+
+        let status = process.execute(b"kill -SEGV $$").expect("execution failed");
+        assert_eq!(status.signal, libc::SIGSEGV);
+        assert!(process.read_log().expect("failed to read log").is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use libc::{c_int, c_void};
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::fs::File;
 use std::io::{self, Read, Write};
 use std::mem::{self, MaybeUninit};
@@ -26,8 +27,9 @@ pub struct CovContext {
     pub edge_count: *mut u32,
 }
 
+/// The `edge_set` filled by `cov_evaluate`, the indices are malloc'ed by libcoverage
 #[repr(C)]
-pub struct EdgeSet {
+pub struct CovEdgeSet {
     pub count: u32,
     pub edge_indices: *mut u32,
 }
@@ -41,7 +43,114 @@ unsafe extern "C" {
     pub fn cov_finish_initialization(ctx: *mut CovContext, track_edges: c_int);
     pub fn cov_shutdown(ctx: *mut CovContext);
     pub fn cov_clear_bitmap(ctx: *mut CovContext);
-    pub fn cov_evaluate(ctx: *mut CovContext, new_edges: *mut EdgeSet) -> c_int;
+    pub fn cov_evaluate(ctx: *mut CovContext, new_edges: *mut CovEdgeSet) -> c_int;
     pub fn cov_clear_edge_data(ctx: *mut CovContext, index: u32);
     pub fn cov_get_edge_counts(ctx: *mut CovContext) -> c_int;
 }
+
+static NEXT_COV_CONTEXT_ID: AtomicI32 = AtomicI32::new(0);
+
+/// What a coverage evaluation found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EdgeSet {
+    /// Whether edges never hit before in this backend were hit
+    pub new_coverage: bool,
+    pub edges: Vec<u32>,
+}
+
+/// Where a worker reads the edges its executions hit from
+pub trait CoverageBackend: Send {
+    /// Forget the edges hit so far, before an execution
+    fn clear(&mut self);
+    /// The new edges hit since the last `clear`
+    fn evaluate(&mut self) -> EdgeSet;
+    /// Make `edge` count as new again
+    fn clear_edge(&mut self, edge: u32);
+    /// Edges instrumented in the engine, 0 if unknown
+    fn num_edges(&self) -> u32;
+}
+
+/// Coverage through the shared memory bitmap of Fuzzilli's libcoverage
+pub struct FuzzilliCoverage {
+    ctx: CovContext,
+}
+
+impl FuzzilliCoverage {
+    pub fn new() -> anyhow::Result<Self> {
+        let mut ctx = unsafe { MaybeUninit::<CovContext>::zeroed().assume_init() };
+        ctx.id = NEXT_COV_CONTEXT_ID.fetch_add(1, Ordering::Relaxed);
+        if unsafe { cov_initialize(&mut ctx) } != 0 {
+            anyhow::bail!("cov_initialize failed");
+        }
+        Ok(Self { ctx })
+    }
+
+    /// Name of the shared memory the engine maps the bitmap from
+    pub fn shm_id(&self) -> String {
+        format!("shm_id_{}_{}", std::process::id(), self.ctx.id)
+    }
+
+    /// Attach to the bitmap, once the engine created it. Engines that never map it
+    /// can't use this backend, libcoverage treats that as fatal.
+    pub fn finish_initialization(&mut self) {
+        unsafe { cov_finish_initialization(&mut self.ctx, 0) };
+    }
+}
+
+impl CoverageBackend for FuzzilliCoverage {
+    fn clear(&mut self) {
+        unsafe { cov_clear_bitmap(&mut self.ctx) };
+    }
+
+    fn evaluate(&mut self) -> EdgeSet {
+        let mut edges = CovEdgeSet {
+            count: 0,
+            edge_indices: std::ptr::null_mut(),
+        };
+        let new_coverage = unsafe { cov_evaluate(&mut self.ctx, &mut edges) } == 1;
+        if edges.edge_indices.is_null() {
+            return EdgeSet {
+                new_coverage,
+                edges: Vec::new(),
+            };
+        }
+        let slice = unsafe { std::slice::from_raw_parts(edges.edge_indices, edges.count as usize) };
+        let set = EdgeSet {
+            new_coverage,
+            edges: slice.to_vec(),
+        };
+        unsafe { libc::free(edges.edge_indices as *mut c_void) };
+        set
+    }
+
+    fn clear_edge(&mut self, edge: u32) {
+        unsafe { cov_clear_edge_data(&mut self.ctx, edge) };
+    }
+
+    fn num_edges(&self) -> u32 {
+        self.ctx.num_edges
+    }
+}
+
+impl Drop for FuzzilliCoverage {
+    fn drop(&mut self) {
+        unsafe { cov_shutdown(&mut self.ctx) };
+    }
+}
+
+/// For engines without coverage: nothing is ever hit, so nothing is ever new
+pub struct NoCoverage;
+
+impl CoverageBackend for NoCoverage {
+    fn clear(&mut self) {}
+
+    fn evaluate(&mut self) -> EdgeSet {
+        EdgeSet::default()
+    }
+
+    fn clear_edge(&mut self, _edge: u32) {}
+
+    fn num_edges(&self) -> u32 {
+        0
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::runner::pool::JobResult;
+use crate::runner::sanitizer::SanitizerReport;
+
+/// What the cache remembers of one engine's execution of a program
+#[derive(Debug, Clone)]
+pub struct CachedResult {
+    engine: String,
+    status_code: i32,
+    signal: i32,
+    is_crash: bool,
+    is_timeout: bool,
+    is_stack_overflow: bool,
+    exec_time_ms: Duration,
+    sanitizer: Option<SanitizerReport>,
+}
+
+impl CachedResult {
+    pub fn from_job_result(job_result: &JobResult) -> Self {
+        Self {
+            engine: job_result.engine.clone(),
+            status_code: job_result.status_code,
+            signal: job_result.signal,
+            is_crash: job_result.is_crash,
+            is_timeout: job_result.is_timeout,
+            is_stack_overflow: job_result.is_stack_overflow,
+            exec_time_ms: job_result.exec_time_ms,
+            sanitizer: job_result.sanitizer.clone(),
+        }
+    }
+
+    /// The edges of the program were recorded when it first ran, so a replayed result
+    /// never has new coverage. It is marked as cached so it isn't accounted twice.
+    pub fn to_job_result(&self) -> JobResult {
+        JobResult {
+            engine: self.engine.clone(),
+            status_code: self.status_code,
+            signal: self.signal,
+            new_coverage: false,
+            edge_hits: Vec::new(),
+            is_crash: self.is_crash,
+            is_timeout: self.is_timeout,
+            is_stack_overflow: self.is_stack_overflow,
+            exec_time_ms: self.exec_time_ms,
+            sanitizer: self.sanitizer.clone(),
+            cached: true,
+        }
+    }
+}
+
+/// Bounded LRU cache of the results of recently executed programs, keyed by the md5 of
+/// the program, so exact duplicates don't run again
+pub struct ExecCache {
+    capacity: usize,
+    entries: HashMap<[u8; 16], (u64, Vec<CachedResult>)>,
+    /// Last use of every key, least recently used first
+    recency: BTreeMap<u64, [u8; 16]>,
+    tick: u64,
+    hits: u64,
+}
+
+impl ExecCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+        }
+    }
+
+    pub fn key(js_code: &[u8]) -> [u8; 16] {
+        md5::compute(js_code).0
+    }
+
+    /// The results of every engine for the program, if it ran recently
+    pub fn get(&mut self, key: &[u8; 16]) -> Option<Vec<CachedResult>> {
+        self.tick += 1;
+        let (last_use, results) = self.entries.get_mut(key)?;
+        self.recency.remove(last_use);
+        *last_use = self.tick;
+        self.recency.insert(self.tick, *key);
+        self.hits += 1;
+        Some(results.clone())
+    }
+
+    pub fn insert(&mut self, key: [u8; 16], results: Vec<CachedResult>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((last_use, _)) = self.entries.remove(&key) {
+            self.recency.remove(&last_use);
+        }
+        while self.entries.len() >= self.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        self.entries.insert(key, (self.tick, results));
+        self.recency.insert(self.tick, key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(status_code: i32) -> Vec<CachedResult> {
+        vec![CachedResult {
+            engine: "v8".to_string(),
+            status_code,
+            signal: 0,
+            is_crash: false,
+            is_timeout: false,
+            is_stack_overflow: false,
+            exec_time_ms: Duration::from_millis(1),
+            sanitizer: None,
+        }]
+    }
+
+    #[test]
+    fn least_recently_used_programs_are_evicted() {
+        let mut cache = ExecCache::new(2);
+        let (a, b, c) = (ExecCache::key(b"a"), ExecCache::key(b"b"), ExecCache::key(b"c"));
+        cache.insert(a, result(0));
+        cache.insert(b, result(1));
+        // `a` is now more recent than `b`
+        assert_eq!(cache.get(&a).unwrap()[0].status_code, 0);
+        cache.insert(c, result(2));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&b).is_none());
+        assert!(cache.get(&a).is_some());
+        assert!(cache.get(&c).is_some());
+        assert_eq!(cache.hits(), 3);
+    }
+}
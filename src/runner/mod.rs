@@ -1,3 +1,9 @@
+pub mod autoscale;
+pub mod backend;
 pub mod coverage;
+pub mod exec_cache;
 pub mod pool;
 pub mod process;
+pub mod replay;
+pub mod sanitizer;
+pub mod watchdog;
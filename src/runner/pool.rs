@@ -1,23 +1,28 @@
-use libc::c_void;
 use std::collections::{HashMap, HashSet};
 use std::io;
-use std::mem::MaybeUninit;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicI32, AtomicU32, AtomicUsize, Ordering};
 use std::time::Duration;
 use tokio::sync::mpsc::error::TrySendError;
+use tokio::time::Instant;
 use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore, mpsc};
 use tokio::task::yield_now;
 use tokio::sync::Mutex;
 
-static NEXT_COV_CONTEXT_ID: AtomicI32 = AtomicI32::new(0);
-
+use crate::corpus::manager::EntryEdges;
 use crate::profiles::profile::JsEngineProfile;
-use crate::runner::{coverage::*, process::FuzzProcess};
+use crate::runner::exec_cache::{CachedResult, ExecCache};
+use crate::runner::sanitizer::{SanitizerReport, parse_sanitizer_report};
+use crate::runner::backend::{ExecBackend, spawn_backend};
+use crate::runner::coverage::{CoverageBackend, FuzzilliCoverage, NoCoverage};
+use crate::runner::process::EngineStdio;
+use crate::runner::watchdog::StallWatchdog;
 
-lazy_static::lazy_static! {
-    pub static ref TOTAL_EDGE_COUNT: AtomicU32 = AtomicU32::new(0);
-}
+/// Rounds over full worker queues after which `dispatch` sleeps instead of yielding
+const DISPATCH_BACKOFF_SPINS: u32 = 100;
+/// Longest sleep between two rounds over full worker queues
+const DISPATCH_MAX_BACKOFF: Duration = Duration::from_millis(50);
+/// Rounds over full worker queues before warning that the engine is saturated
+const DISPATCH_WARN_SPINS: u32 = 200;
 
 /// A job to be executed by a FuzzWorker
 pub struct Job {
@@ -48,37 +53,274 @@ impl Job {
 
 /// The result of a job executed by a FuzzWorker
 pub struct JobResult {
+    /// Name of the engine profile that produced this result
+    pub engine: String,
     pub status_code: i32,
     pub signal: i32,
     pub new_coverage: bool,
     pub edge_hits: Vec<u32>,
     pub is_crash: bool,
     pub is_timeout: bool,
+    /// The program exited with an uncaught stack overflow RangeError, according to the
+    /// engine's log channel
+    pub is_stack_overflow: bool,
     pub exec_time_ms: Duration,
+    /// Sanitizer error the engine reported on its log channel before crashing
+    pub sanitizer: Option<SanitizerReport>,
+    /// Replayed from the exec cache, the program did not run again
+    pub cached: bool,
     // pub edge_hash: Option<Vec
 }
 
+/// What an execution amounted to, derived once from the raw `JobResult` fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Clean exit that hit new, stable edges
+    NewCoverage,
+    /// Clean exit without new coverage
+    NoCoverage,
+    Timeout,
+    Crash { signal: i32 },
+    /// The program died of unbounded recursion hitting the engine's stack limit
+    StackOverflow,
+    /// The program threw or otherwise exited with a nonzero status
+    RuntimeError,
+}
+
+impl JobResult {
+    /// Crashes take precedence over timeouts, and both over the exit status.
+    /// Coverage only counts for programs that exited cleanly.
+    pub fn outcome(&self) -> Outcome {
+        if self.is_crash {
+            Outcome::Crash { signal: self.signal }
+        } else if self.is_timeout {
+            Outcome::Timeout
+        } else if self.is_stack_overflow {
+            Outcome::StackOverflow
+        } else if self.status_code != 0 {
+            Outcome::RuntimeError
+        } else if self.new_coverage {
+            Outcome::NewCoverage
+        } else {
+            Outcome::NoCoverage
+        }
+    }
+}
+
+/// The results of one job on every engine it was dispatched to, sorted by engine
+#[derive(Default)]
+pub struct JobResults {
+    pub results: Vec<JobResult>,
+}
+
+impl JobResults {
+    /// Wait for the result of every engine. Engines that failed to execute the program
+    /// are returned apart, the results of the others are still usable.
+    pub async fn collect(
+        result_rx: &mut mpsc::Receiver<anyhow::Result<JobResult>>,
+    ) -> (Self, Vec<anyhow::Error>) {
+        let mut results = Vec::new();
+        let mut errors = Vec::new();
+        while let Some(result) = result_rx.recv().await {
+            match result {
+                Ok(job_result) => results.push(job_result),
+                Err(err) => errors.push(err),
+            }
+        }
+        // the engines finish in any order
+        results.sort_by(|a, b| a.engine.cmp(&b.engine));
+        (Self { results }, errors)
+    }
+
+    /// The outcome of the job as a whole. A crash, timeout or stack overflow in any
+    /// engine is one of the job, new coverage in any engine counts even if another one
+    /// threw.
+    pub fn outcome(&self) -> Outcome {
+        let rank = |outcome: &Outcome| match outcome {
+            Outcome::Crash { .. } => 5,
+            Outcome::Timeout => 4,
+            Outcome::StackOverflow => 3,
+            Outcome::NewCoverage => 2,
+            Outcome::RuntimeError => 1,
+            Outcome::NoCoverage => 0,
+        };
+        self.results
+            .iter()
+            .map(JobResult::outcome)
+            .max_by_key(rank)
+            .unwrap_or(Outcome::NoCoverage)
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.results.iter().any(|result| result.is_timeout)
+    }
+
+    /// Every engine's result came from the exec cache
+    pub fn is_cached(&self) -> bool {
+        !self.results.is_empty() && self.results.iter().all(|result| result.cached)
+    }
+
+    /// Execution time of the slowest engine
+    pub fn exec_time_ms(&self) -> Duration {
+        self.results.iter().map(|result| result.exec_time_ms).max().unwrap_or_default()
+    }
+
+    /// The new edges of the engines the job found new coverage in, kept per engine
+    /// when it ran on several
+    pub fn new_edges(&self) -> EntryEdges {
+        let mut covering = self.results.iter().filter(|result| result.outcome() == Outcome::NewCoverage);
+        if self.results.len() == 1 {
+            return covering.next().map(|result| result.edge_hits.clone()).unwrap_or_default().into();
+        }
+        EntryEdges {
+            edge_hits: Vec::new(),
+            engine_edges: covering.map(|result| (result.engine.clone(), result.edge_hits.clone())).collect(),
+        }
+    }
+
+    /// Results of the engines the program crashed
+    pub fn crashes(&self) -> impl Iterator<Item = &JobResult> {
+        self.results.iter().filter(|result| result.is_crash)
+    }
+}
+
+/// A program a worker refused to run because it is over the profile's
+/// `max_program_bytes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramTooLarge {
+    pub size: usize,
+    pub max: usize,
+}
+
+impl std::fmt::Display for ProgramTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "program of {} bytes is over the limit of {} bytes, not executed", self.size, self.max)
+    }
+}
+
+impl std::error::Error for ProgramTooLarge {}
+
+/// Refuse programs over `max_bytes` before they reach the engine
+pub fn check_program_size(js_code: &[u8], max_bytes: Option<usize>) -> Result<(), ProgramTooLarge> {
+    match max_bytes {
+        Some(max) if js_code.len() > max => Err(ProgramTooLarge {
+            size: js_code.len(),
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Messages engines report uncaught stack overflows with
+const STACK_OVERFLOW_MESSAGES: &[&str] = &[
+    // V8, JavaScriptCore
+    "Maximum call stack size exceeded",
+    // SpiderMonkey
+    "too much recursion",
+];
+
+/// Whether engine log output reports a stack overflow RangeError
+pub fn is_stack_overflow_log(log: &[u8]) -> bool {
+    let log = String::from_utf8_lossy(log);
+    STACK_OVERFLOW_MESSAGES.iter().any(|message| log.contains(message))
+}
+
+/// Histogram of the signals of every crash seen by the pool
+#[derive(Default)]
+pub struct CrashStats {
+    signals: std::sync::Mutex<HashMap<i32, usize>>,
+}
+
+impl CrashStats {
+    pub fn record(&self, job_result: &JobResult) {
+        if let Outcome::Crash { signal } = job_result.outcome() {
+            *self
+                .signals
+                .lock()
+                .expect("crash stats poisoned")
+                .entry(signal)
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// (signal, count) pairs, most frequent first
+    pub fn histogram(&self) -> Vec<(i32, usize)> {
+        let mut histogram: Vec<(i32, usize)> = self
+            .signals
+            .lock()
+            .expect("crash stats poisoned")
+            .iter()
+            .map(|(&signal, &count)| (signal, count))
+            .collect();
+        histogram.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        histogram
+    }
+}
+
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGILL => "SIGILL",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGKILL => "SIGKILL",
+        _ => "unknown",
+    }
+}
+
 pub struct FuzzWorkerInternal {
-    process: FuzzProcess,
-    cov_ctx: CovContext,
+    process: Box<dyn ExecBackend>,
+    coverage: Box<dyn CoverageBackend>,
 }
 
 /// Stand-alone FuzzProcess wrapper that holds a queue for Js code to be executed in said process
 pub struct FuzzWorker<T: JsEngineProfile + Clone + Send + Sync + 'static> {
     internal: FuzzWorkerInternal,
     job_queue: mpsc::Receiver<Job>,
-    job_tx: mpsc::Sender<Job>, // interface to send jobs to this worker
+    job_tx: Option<mpsc::Sender<Job>>, // interface to send jobs to this worker
     edge_tracker: Arc<RwLock<EdgeTracker>>,
+    watchdog: Arc<StallWatchdog>,
+    crash_stats: Arc<CrashStats>,
     cache: FuzzWorkerCache,
     profile: Arc<T>,
+    /// Re-run inputs with new coverage and keep only the edges hit both times
+    confirm_edges: bool,
+    /// Where the engine processes of this worker send their output
+    stdio: EngineStdio,
 }
 
-/// The fuzzer pool contains multiple fuzz processes
-pub struct FuzzPool {
+/// Spawns a worker for an engine and returns the sender of its job queue
+type WorkerSpawner = Box<dyn Fn() -> anyhow::Result<mpsc::Sender<Job>> + Send + Sync>;
+
+/// The workers of a single engine, sharing one coverage tracker
+struct EngineWorkers {
+    name: String,
     job_senders: Vec<mpsc::Sender<Job>>,
     next_worker: usize,
+    edge_tracker: Arc<RwLock<EdgeTracker>>,
+    queue_size: usize,
+    /// None for engines whose worker set can't change
+    spawner: Option<WorkerSpawner>,
+}
+
+/// The fuzzer pool contains multiple fuzz processes, possibly for several engines.
+/// Every scheduled job is dispatched once to each engine.
+pub struct FuzzPool {
+    engines: Vec<EngineWorkers>,
     job_capacity: Arc<Semaphore>,
-    edge_tracker: Arc<RwLock<EdgeTracker>>
+    /// Permits handed to `job_capacity` for the current workers
+    total_permits: usize,
+    watchdog: Arc<StallWatchdog>,
+    crash_stats: Arc<CrashStats>,
+    confirm_edges: bool,
+    /// Where the engine processes of workers added from now on send their output
+    engine_stdio: EngineStdio,
+    /// Results of recently executed programs, None if disabled
+    exec_cache: Option<Arc<std::sync::Mutex<ExecCache>>>,
+    /// How long `schedule_job` waits for room in the worker queues, None waits forever
+    schedule_timeout: Option<Duration>,
 }
 
 // pub struct FuzzProcessCache {
@@ -94,6 +336,8 @@ pub struct EdgeTracker {
     seen_edges: HashSet<u32>,
     blacklist: HashMap<u32, usize>, // edge -> reset count
     max_resets: usize,
+    /// Number of instrumented edges of the engine, once a worker reported it
+    total_edges: Option<u32>,
 }
 
 impl EdgeTracker {
@@ -102,31 +346,57 @@ impl EdgeTracker {
             seen_edges: HashSet::new(),
             blacklist: HashMap::new(),
             max_resets,
+            total_edges: None,
+        }
+    }
+
+    /// Record the edge count a worker of the engine reported. The first count sticks, a
+    /// different one is rejected and false is returned.
+    pub fn record_total_edges(&mut self, total_edges: u32) -> bool {
+        *self.total_edges.get_or_insert(total_edges) == total_edges
+    }
+
+    pub fn total_edges(&self) -> Option<u32> {
+        self.total_edges
+    }
+
+    /// Drop blacklisted and already seen edges from `edges` and mark the rest as seen
+    pub fn record_new_edges(&mut self, edges: &[u32]) -> Vec<u32> {
+        let mut new_edges = vec![];
+        for &edge in edges {
+            if self.blacklist.get(&edge).unwrap_or(&0) >= &self.max_resets ||
+            self.seen_edges.contains(&edge) {
+                continue;
+            }
+            new_edges.push(edge);
+            self.seen_edges.insert(edge);
         }
+        new_edges
+    }
+
+    pub fn seen_count(&self) -> usize {
+        self.seen_edges.len()
     }
 }
 
 impl FuzzWorkerInternal {
-    pub fn new(profile: &impl JsEngineProfile) -> anyhow::Result<Self> {
-        let mut cov_ctx = unsafe {
-            let mut ctx = MaybeUninit::<CovContext>::zeroed().assume_init();
-            ctx.id = NEXT_COV_CONTEXT_ID.fetch_add(1, Ordering::Relaxed);
-            if cov_initialize(&mut ctx) != 0 {
-                panic!("cov_initialize failed");
-            }
-            ctx
-        };
-        let shm_id = format!("shm_id_{}_{}", std::process::id(), cov_ctx.id);
+    pub fn new(profile: &impl JsEngineProfile, stdio: &EngineStdio) -> anyhow::Result<Self> {
+        let mut fuzzilli_coverage = FuzzilliCoverage::new()?;
+        let shm_id = fuzzilli_coverage.shm_id();
         
-        let mut process = FuzzProcess::spawn(profile, &shm_id)?;
+        let mut process = spawn_backend(profile, &shm_id, stdio)?;
         // println!("Spawned FuzzProcess with PID {}; shm_id: {}", process.child.id(), shm_id);
         process.handshake()?;
         
-        unsafe {
-            cov_finish_initialization(&mut cov_ctx, 0);
-        }
+        // backends without coverage never map the bitmap, which libcoverage treats as fatal
+        let coverage: Box<dyn CoverageBackend> = if process.has_coverage() {
+            fuzzilli_coverage.finish_initialization();
+            Box::new(fuzzilli_coverage)
+        } else {
+            Box::new(NoCoverage)
+        };
         
-        Ok(Self { process, cov_ctx })
+        Ok(Self { process, coverage })
     }
 }
 
@@ -134,41 +404,46 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
     pub fn new(
         profile: &T,
         edge_tracker: Arc<RwLock<EdgeTracker>>,
+        watchdog: Arc<StallWatchdog>,
+        crash_stats: Arc<CrashStats>,
+        confirm_edges: bool,
+        stdio: EngineStdio,
     ) -> anyhow::Result<Self> {
-        let mut internal = FuzzWorkerInternal::new(profile)?;
+        let internal = FuzzWorkerInternal::new(profile, &stdio)?;
         
         let (job_queue_tx, job_queue_rx) = mpsc::channel(profile.fuzz_worker_job_queue_size());
         
-        println!(
-            "Created FuzzWorker for process PID {}",
-            internal.process.child.id()
-        );
+        match internal.process.pid() {
+            Some(pid) => println!("Created FuzzWorker for process PID {}", pid),
+            None => println!("Created FuzzWorker for {}", profile.get_name()),
+        }
         
-        Self::set_edge_count(&mut internal.cov_ctx);
         Ok(Self {
             internal,
             job_queue: job_queue_rx,
-            job_tx: job_queue_tx,
+            job_tx: Some(job_queue_tx),
             edge_tracker,
-            cache: FuzzWorkerCache::new(1, profile),
+            watchdog,
+            crash_stats,
+            cache: FuzzWorkerCache::new(1, profile, &stdio),
             profile: Arc::new(profile.clone()),
+            confirm_edges,
+            stdio,
         })
     }
     
-    pub fn set_edge_count(cov_ctx: &mut CovContext) {
-        let total_edge_count = cov_ctx.num_edges;
-        if TOTAL_EDGE_COUNT.load(Ordering::SeqCst) == 0 {
-            TOTAL_EDGE_COUNT.store(total_edge_count, Ordering::SeqCst);
-        }
+    /// Number of instrumented edges of this worker's engine, if it has coverage
+    fn total_edges(&self) -> Option<u32> {
+        self.internal.process.has_coverage().then(|| self.internal.coverage.num_edges())
     }
     
     pub fn get_job_sender(&self) -> mpsc::Sender<Job> {
-        self.job_tx.clone()
+        self.job_tx.clone().expect("job senders must be taken before the worker runs")
     }
     
     fn restart(&mut self) -> anyhow::Result<()> {
         let new_worker = self.cache.get_worker().unwrap_or_else(|| {
-            FuzzWorkerInternal::new(self.profile.as_ref())
+            FuzzWorkerInternal::new(self.profile.as_ref(), &self.stdio)
             .expect("Failed to create new FuzzWorkerInternal on restart")
         });
         self.internal = new_worker;
@@ -178,16 +453,20 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
     
     
     fn start_internal(&mut self, js_code: &[u8]) -> anyhow::Result<JobResult> {
-        if self.internal.process.crt_executions >= self.internal.process.max_executions {
+        if self.internal.process.needs_restart() {
             self.restart()?;
         }
 
-        unsafe {
-            cov_clear_bitmap(&mut self.internal.cov_ctx);
-        }
+        self.internal.coverage.clear();
         let exec_status = self.internal.process.execute(js_code);
         let timed_out =
         matches!(exec_status, Err(ref err) if err.kind() == io::ErrorKind::TimedOut);
+        // a crashed child may have left a sanitizer report behind
+        let log = if timed_out {
+            Vec::new()
+        } else {
+            self.internal.process.read_log().unwrap_or_default()
+        };
         
         if timed_out {
             let start = std::time::Instant::now();
@@ -197,34 +476,35 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
                 start.elapsed()
             );
             return Ok(JobResult {
+                engine: self.profile.get_name(),
                 status_code: -1,
                 signal: 0,
                 new_coverage: false,
                 edge_hits: Vec::new(),
                 is_crash: false,
                 is_timeout: true,
+                is_stack_overflow: false,
                 exec_time_ms: Duration::from_millis(100000),
+                sanitizer: None,
+                cached: false,
             });
         }
         
         let mut edge_hits = Vec::new();
         let mut new_cov_flag = false;
         if exec_status.is_ok() {
-            let mut edges = EdgeSet {
-                count: 0,
-                edge_indices: std::ptr::null_mut(),
-            };
-            let new_cov = unsafe { cov_evaluate(&mut self.internal.cov_ctx, &mut edges) };
-            if new_cov == 1 && !edges.edge_indices.is_null() {
-                let slice =
-                unsafe { std::slice::from_raw_parts(edges.edge_indices, edges.count as usize) };
-                edge_hits.extend_from_slice(slice);
-                unsafe { libc::free(edges.edge_indices as *mut c_void) };
+            let edges = self.internal.coverage.evaluate();
+            if edges.new_coverage {
+                edge_hits = edges.edges;
             }
-            new_cov_flag = new_cov == 1;
+            new_cov_flag = edges.new_coverage;
         }
         
-        if new_cov_flag && !edge_hits.is_empty() {
+        if new_cov_flag && !edge_hits.is_empty() && !self.confirm_edges {
+            // trust the first run; the tracker still dedups and honours the blacklist
+            edge_hits = self.edge_tracker.blocking_write().record_new_edges(&edge_hits);
+            new_cov_flag = !edge_hits.is_empty();
+        } else if new_cov_flag && !edge_hits.is_empty() {
             match self.confirm_new_edges(js_code, &edge_hits) {
                 Ok(stable_edges) => {
                     if stable_edges.is_empty() {
@@ -242,7 +522,9 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
             }
         }
         let (status_code, signal, is_crash, exec_time_ms) = match exec_status {
-            Ok(status) => (status.exit_code, status.signal, false, status.exec_time_ms),
+            // REPRL engines report crashes by dying mid-execution, engines spawned per
+            // execution by their exit signal
+            Ok(status) => (status.exit_code, status.signal, status.signal != 0, status.exec_time_ms),
             Err(err) => {
                 if err.kind() == io::ErrorKind::TimedOut {
                     (-1, 0, false, Duration::from_millis(0))
@@ -256,14 +538,20 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
         if is_crash {
             self.restart()?;
         }
+        // signal crashes near the stack limit stay crashes, only clean error exits count
+        let is_stack_overflow = !is_crash && status_code != 0 && is_stack_overflow_log(&log);
         let mut job_result = JobResult {
+            engine: self.profile.get_name(),
             status_code,
             signal,
             new_coverage: if timed_out { false } else { new_cov_flag },
             edge_hits: if timed_out { Vec::new() } else { edge_hits },
             is_crash,
             is_timeout: timed_out,
+            is_stack_overflow,
             exec_time_ms,
+            sanitizer: if is_crash { parse_sanitizer_report(&log) } else { None },
+            cached: false,
         };
         if job_result.edge_hits.len() < self.profile.get_min_new_edges_to_add_corpus() {
             job_result.new_coverage = false;
@@ -283,12 +571,15 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
         }
         
         for &edge in candidate_edges {
-            unsafe { cov_clear_edge_data(&mut self.internal.cov_ctx, edge) };
+            self.internal.coverage.clear_edge(edge);
         }
         
-        unsafe { cov_clear_bitmap(&mut self.internal.cov_ctx) };
+        self.internal.coverage.clear();
         
         let exec_status = self.internal.process.execute(js_code);
+        if exec_status.is_ok() {
+            let _ = self.internal.process.read_log();
+        }
         let timed_out =
         matches!(exec_status, Err(ref err) if err.kind() == io::ErrorKind::TimedOut);
         if timed_out {
@@ -302,18 +593,13 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
             return Ok(Vec::new());
         }
         
-        let mut edges = EdgeSet {
-            count: 0,
-            edge_indices: std::ptr::null_mut(),
-        };
-        let new_cov = unsafe { cov_evaluate(&mut self.internal.cov_ctx, &mut edges) };
+        let edges = self.internal.coverage.evaluate();
         
-        if edges.edge_indices.is_null() {
+        if edges.edges.is_empty() {
             return Ok(Vec::new());
         }
         
-        let second_slice =
-        unsafe { std::slice::from_raw_parts(edges.edge_indices, edges.count as usize) };
+        let second_slice = edges.edges.as_slice();
         let mut first_set: HashSet<u32> = HashSet::with_capacity(candidate_edges.len());
         let mut second_set: HashSet<u32> = HashSet::with_capacity(second_slice.len());
         
@@ -338,12 +624,11 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
             } else {
                 let reset_count = aux_tracker.entry(edge).or_insert(0);
                 *reset_count += 1;
-                unsafe { cov_clear_edge_data(&mut self.internal.cov_ctx, edge) };
+                self.internal.coverage.clear_edge(edge);
             }
         }
-        unsafe { libc::free(edges.edge_indices as *mut c_void) };
         
-        if new_cov != 1 {
+        if !edges.new_coverage {
             return Ok(Vec::new());
         }
         
@@ -357,26 +642,41 @@ impl<T: JsEngineProfile + Clone + Send + Sync + 'static> FuzzWorker<T> {
                     // eprintln!("Blacklisting edge {}", edge);
                 }
             }
-            let mut stable_edges_curated = vec![];
-            for &edge in &stable_edges {
-                if tracker.blacklist.get(&edge).unwrap_or(&0) >= &tracker.max_resets || 
-                tracker.seen_edges.contains(&edge) {
-                    continue;
-                }
-                stable_edges_curated.push(edge);
-                tracker.seen_edges.insert(edge);
-            }
-            stable_edges_curated
+            tracker.record_new_edges(&stable_edges)
         };
         
         Ok(stable_edges)
     }
     
-    /// Start the fuzz worker's main loop
+    /// Start the fuzz worker's main loop. The loop ends, and the worker with its engine
+    /// processes is dropped, once the pool drops its senders for this worker.
     pub async fn run(mut self) -> anyhow::Result<()> {
+        // only the pool's senders keep the queue open
+        self.job_tx = None;
+        if let Some(total_edges) = self.total_edges() {
+            let mut tracker = self.edge_tracker.write().await;
+            if !tracker.record_total_edges(total_edges) {
+                eprintln!(
+                    "[{}] worker reports {} edges, unlike the other workers ({}); keeping the first count",
+                    self.profile.get_name(),
+                    total_edges,
+                    tracker.total_edges().unwrap_or(0)
+                );
+            }
+        }
         while let Some(job) = self.job_queue.recv().await {
             let (js_code, result_tx) = job.into_parts();
+            if let Err(err) = check_program_size(&js_code, self.profile.max_program_bytes()) {
+                self.watchdog.record_completion();
+                result_tx
+                .send(Err(anyhow::Error::new(err)))
+                .await
+                .map_err(|_| anyhow::anyhow!("failed to deliver job result"))?;
+                continue;
+            }
             let job_result = tokio::task::block_in_place(|| self.start_internal(&js_code))?;
+            self.watchdog.record_completion();
+            self.crash_stats.record(&job_result);
             result_tx
             .send(Ok(job_result))
             .await
@@ -393,10 +693,11 @@ struct FuzzWorkerCache {
 }
 
 impl FuzzWorkerCache {
-    pub fn new(cache_size: usize, profile: &impl JsEngineProfile) -> Self {
+    pub fn new(cache_size: usize, profile: &impl JsEngineProfile, stdio: &EngineStdio) -> Self {
         let mut cache = Vec::with_capacity(cache_size);
         for _ in 0..cache_size {
-            let worker = FuzzWorkerInternal::new(profile).expect("Failed to create FuzzWorkerInternal for cache");
+            let worker =
+                FuzzWorkerInternal::new(profile, stdio).expect("Failed to create FuzzWorkerInternal for cache");
             cache.push(worker);
         }
         
@@ -417,7 +718,7 @@ impl FuzzWorkerCache {
     }
     
     /// Start a background task that keeps the cache filled.
-    pub fn start_refiller<T>(&mut self, profile: T)
+    pub fn start_refiller<T>(&mut self, profile: T, stdio: EngineStdio)
     where
         T: JsEngineProfile + Clone + Send + Sync + 'static,
     {
@@ -440,7 +741,7 @@ impl FuzzWorkerCache {
                     break;
                 }
                 while self_cache.lock().await.len() < cache_size {
-                    match FuzzWorkerInternal::new(&profile_clone) {
+                    match FuzzWorkerInternal::new(&profile_clone, &stdio) {
                         Ok(worker) => {
                             self_cache.lock().await.push(worker);
                         }
@@ -455,101 +756,680 @@ impl FuzzWorkerCache {
     }
 }
 
+impl EngineWorkers {
+    /// Hand the job to the first worker of this engine with room in its queue. Backs off
+    /// while every queue is full and gives up once `deadline` passes.
+    async fn dispatch(&mut self, mut job: Job, deadline: Option<Instant>) -> anyhow::Result<()> {
+        let worker_count = self.job_senders.len();
+        let start = Instant::now();
+        let mut spins: u32 = 0;
+        let mut backoff = Duration::from_millis(1);
+        loop {
+            for offset in 0..worker_count {
+                let idx = (self.next_worker + offset) % worker_count;
+                match self.job_senders[idx].try_send(job) {
+                    Ok(()) => {
+                        self.next_worker = (idx + 1) % worker_count;
+                        return Ok(());
+                    }
+                    Err(TrySendError::Full(returned_job)) => {
+                        job = returned_job;
+                    }
+                    Err(TrySendError::Closed(_)) => {
+                        return Err(anyhow::anyhow!("Fuzz worker channel closed"));
+                    }
+                }
+            }
+            spins += 1;
+            if spins == DISPATCH_WARN_SPINS {
+                eprintln!(
+                    "[pool] all {} workers of {} have had full queues for {:?}",
+                    worker_count,
+                    self.name,
+                    start.elapsed()
+                );
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Err(anyhow::anyhow!(
+                    "Fuzz workers of {} stayed saturated for {:?}",
+                    self.name,
+                    start.elapsed()
+                ));
+            }
+            if spins < DISPATCH_BACKOFF_SPINS {
+                yield_now().await;
+            } else {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(DISPATCH_MAX_BACKOFF);
+            }
+        }
+    }
+}
+
 impl FuzzPool {
+    /// Create a pool with `num_workers` workers for a single engine profile
     pub fn new<T: JsEngineProfile + Clone + Send + Sync + 'static>(
         num_workers: usize,
         profile: &T,
     ) -> anyhow::Result<Self> {
-        let mut job_senders = Vec::new();
+        let mut pool = Self::empty();
+        pool.add_engine(num_workers, profile)?;
+        Ok(pool)
+    }
+
+    /// Create a pool without any workers. Engines are added with `add_engine`.
+    pub fn empty() -> Self {
+        Self {
+            engines: Vec::new(),
+            job_capacity: Arc::new(Semaphore::new(0)),
+            total_permits: 0,
+            watchdog: Arc::new(StallWatchdog::new()),
+            crash_stats: Arc::new(CrashStats::default()),
+            confirm_edges: true,
+            engine_stdio: EngineStdio::Null,
+            exec_cache: None,
+            schedule_timeout: None,
+        }
+    }
+
+    /// Where the engine processes of workers added from now on send their output
+    pub fn set_engine_stdio(&mut self, stdio: EngineStdio) {
+        self.engine_stdio = stdio;
+    }
+
+    /// Remember the results of the last `size` distinct programs and answer exact
+    /// duplicates from memory instead of executing them again. 0 disables the cache.
+    pub fn set_exec_cache_size(&mut self, size: usize) {
+        self.exec_cache = (size > 0).then(|| Arc::new(std::sync::Mutex::new(ExecCache::new(size))));
+    }
+
+    /// Make `schedule_job` fail instead of waiting longer than `timeout` for room in the
+    /// worker queues. None waits forever.
+    pub fn set_schedule_timeout(&mut self, timeout: Option<Duration>) {
+        self.schedule_timeout = timeout;
+    }
+
+    /// Whether workers added from now on re-run new-coverage inputs to filter out flaky
+    /// edges. Disabling it saves the second execution of every productive input, at the
+    /// cost of letting nondeterministic edges into the corpus.
+    pub fn set_confirm_edges(&mut self, confirm_edges: bool) {
+        self.confirm_edges = confirm_edges;
+    }
+
+    /// Spawn `num_workers` workers for `profile`. The engine gets its own edge tracker so
+    /// coverage is tracked separately for every engine in the pool.
+    pub fn add_engine<T: JsEngineProfile + Clone + Send + Sync + 'static>(
+        &mut self,
+        num_workers: usize,
+        profile: &T,
+    ) -> anyhow::Result<()> {
         let edge_tracker = Arc::new(RwLock::new(EdgeTracker::new(1000)));
+        let spawner: WorkerSpawner = {
+            let profile = profile.clone();
+            let edge_tracker = edge_tracker.clone();
+            let watchdog = self.watchdog.clone();
+            let crash_stats = self.crash_stats.clone();
+            let confirm_edges = self.confirm_edges;
+            let stdio = self.engine_stdio.clone();
+            Box::new(move || {
+                let mut worker = FuzzWorker::new(
+                    &profile,
+                    edge_tracker.clone(),
+                    watchdog.clone(),
+                    crash_stats.clone(),
+                    confirm_edges,
+                    stdio.clone(),
+                )?;
+                worker.cache.start_refiller(profile.clone(), stdio.clone());
+
+                let job_tx = worker.get_job_sender();
+                tokio::spawn(async move {
+                    if let Err(err) = worker.run().await {
+                        eprintln!("FuzzWorker exited with error: {:?}", err);
+                    }
+                });
+                Ok(job_tx)
+            })
+        };
 
+        let mut job_senders = Vec::new();
         for _ in 0..num_workers {
-            let mut worker = FuzzWorker::new(profile, edge_tracker.clone())?;
-            worker.cache.start_refiller(profile.clone());
+            job_senders.push(spawner()?);
+        }
 
-            let job_tx = worker.get_job_sender();
-            tokio::spawn(async move {
-                if let Err(err) = worker.run().await {
-                    eprintln!("FuzzWorker exited with error: {:?}", err);
-                }
-            });
-            job_senders.push(job_tx);
+        self.add_engine_senders(
+            profile.get_name(),
+            job_senders,
+            edge_tracker,
+            profile.fuzz_worker_job_queue_size().max(1),
+        );
+        if let Some(engine) = self.engines.last_mut() {
+            engine.spawner = Some(spawner);
         }
+        Ok(())
+    }
 
-        let queue_capacity = num_workers * profile.fuzz_worker_job_queue_size().max(1);
-        Ok(Self {
+    /// Number of workers of the largest engine
+    pub fn worker_count(&self) -> usize {
+        self.engines.iter().map(|engine| engine.job_senders.len()).max().unwrap_or(0)
+    }
+
+    /// Jobs scheduled but not yet completed
+    pub fn queued_jobs(&self) -> usize {
+        self.total_permits.saturating_sub(self.job_capacity.available_permits())
+    }
+
+    /// Number of jobs the worker queues can hold
+    pub fn queue_capacity(&self) -> usize {
+        self.total_permits
+    }
+
+    /// Spawn one more worker for every engine
+    pub fn add_worker(&mut self) -> anyhow::Result<()> {
+        for engine in self.engines.iter_mut() {
+            let Some(spawner) = engine.spawner.as_ref() else {
+                continue;
+            };
+            engine.job_senders.push(spawner()?);
+            self.job_capacity.add_permits(engine.queue_size);
+            self.total_permits += engine.queue_size;
+        }
+        Ok(())
+    }
+
+    /// Retire one worker of every engine with more than one. The worker finishes the jobs
+    /// already in its queue, then its engine process is killed.
+    pub async fn retire_worker(&mut self) -> anyhow::Result<()> {
+        for engine in self.engines.iter_mut() {
+            if engine.spawner.is_none() || engine.job_senders.len() <= 1 {
+                continue;
+            }
+            // take the retired queue's share of the capacity out of circulation
+            self.job_capacity
+                .acquire_many(engine.queue_size as u32)
+                .await
+                .map_err(|_| anyhow::anyhow!("Fuzz pool capacity semaphore closed"))?
+                .forget();
+            self.total_permits -= engine.queue_size;
+            engine.job_senders.pop();
+            engine.next_worker %= engine.job_senders.len();
+        }
+        Ok(())
+    }
+
+    fn add_engine_senders(
+        &mut self,
+        name: String,
+        job_senders: Vec<mpsc::Sender<Job>>,
+        edge_tracker: Arc<RwLock<EdgeTracker>>,
+        queue_size: usize,
+    ) {
+        self.job_capacity.add_permits(job_senders.len() * queue_size);
+        self.total_permits += job_senders.len() * queue_size;
+        self.engines.push(EngineWorkers {
+            name,
             job_senders,
             next_worker: 0,
-            job_capacity: Arc::new(Semaphore::new(queue_capacity)),
             edge_tracker,
-        })
+            queue_size,
+            spawner: None,
+        });
     }
-    
-    /// Schedule a job to be executed by one of the FuzzWorkers
+
+    /// Schedule a job to be executed by one FuzzWorker of every engine in the pool.
+    /// The returned receiver yields one result per engine. Fails if the workers stay
+    /// saturated past the schedule timeout.
     pub async fn schedule_job(
         &mut self,
         js_code: Vec<u8>,
     ) -> anyhow::Result<mpsc::Receiver<anyhow::Result<JobResult>>> {
-        if self.job_senders.is_empty() {
+        if self.engines.iter().all(|engine| engine.job_senders.is_empty()) {
             return Err(anyhow::anyhow!("No fuzz workers available"));
         }
-        
-        let (result_tx, result_rx) = mpsc::channel(1);
-        let worker_count = self.job_senders.len();
-        let permit = self
-            .job_capacity
-            .clone()
-            .acquire_owned()
-            .await
+
+        let cache_key = self.exec_cache.as_ref().map(|_| ExecCache::key(&js_code));
+        if let (Some(cache), Some(key)) = (&self.exec_cache, &cache_key) {
+            let cached = cache.lock().expect("exec cache poisoned").get(key);
+            if let Some(results) = cached {
+                let (result_tx, result_rx) = mpsc::channel(results.len().max(1));
+                for result in results {
+                    let _ = result_tx.try_send(Ok(result.to_job_result()));
+                }
+                return Ok(result_rx);
+            }
+        }
+
+        let (result_tx, mut result_rx) = mpsc::channel(self.engines.len());
+        let deadline = self.schedule_timeout.map(|timeout| Instant::now() + timeout);
+        let mut dispatched = 0;
+        for engine in self.engines.iter_mut() {
+            if engine.job_senders.is_empty() {
+                continue;
+            }
+            dispatched += 1;
+            let acquire = self.job_capacity.clone().acquire_owned();
+            let permit = match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline, acquire)
+                    .await
+                    .map_err(|_| {
+                        let timeout = self.schedule_timeout.unwrap_or_default();
+                        anyhow::anyhow!("Fuzz pool queues stayed saturated for {:?}", timeout)
+                    })?,
+                None => acquire.await,
+            }
             .map_err(|_| anyhow::anyhow!("Fuzz pool capacity semaphore closed"))?;
-        let mut job = Job::new(js_code, result_tx, permit);
-        
-        loop {
-            for offset in 0..worker_count {
-                let idx = (self.next_worker + offset) % worker_count;
-                match self.job_senders[idx].try_send(job) {
-                    Ok(()) => {
-                        self.next_worker = (idx + 1) % worker_count;
-                        return Ok(result_rx);
-                    }
-                    Err(TrySendError::Full(returned_job)) => {
-                        job = returned_job;
-                    }
-                    Err(TrySendError::Closed(_)) => {
-                        return Err(anyhow::anyhow!("Fuzz worker channel closed"));
-                    }
+            let job = Job::new(js_code.clone(), result_tx.clone(), permit);
+            engine.dispatch(job, deadline).await?;
+        }
+
+        let (Some(cache), Some(key)) = (self.exec_cache.clone(), cache_key) else {
+            return Ok(result_rx);
+        };
+        // pass the results through, remembering them once every engine answered
+        let (caller_tx, caller_rx) = mpsc::channel(self.engines.len());
+        tokio::spawn(async move {
+            let mut results = Vec::new();
+            let mut failed = false;
+            while let Some(result) = result_rx.recv().await {
+                match &result {
+                    Ok(job_result) => results.push(CachedResult::from_job_result(job_result)),
+                    Err(_) => failed = true,
                 }
+                let _ = caller_tx.send(result).await;
+            }
+            if !failed && results.len() == dispatched {
+                cache.lock().expect("exec cache poisoned").insert(key, results);
             }
-            yield_now().await;
+        });
+        Ok(caller_rx)
+    }
+    
+    /// Execute a job and wait for the results of every engine. Fails if any engine
+    /// failed to execute it.
+    pub async fn execute_job(&mut self, js_code: Vec<u8>) -> anyhow::Result<JobResults> {
+        let mut result_rx = self.schedule_job(js_code).await?;
+        let (job_results, errors) = JobResults::collect(&mut result_rx).await;
+        if let Some(err) = errors.into_iter().next() {
+            return Err(err);
+        }
+        if job_results.results.is_empty() {
+            return Err(anyhow::anyhow!("Failed to receive job result"));
         }
+        Ok(job_results)
+    }
+    
+    /// Watchdog tracking job completions of every worker in the pool
+    pub fn watchdog(&self) -> Arc<StallWatchdog> {
+        self.watchdog.clone()
     }
     
-    /// Execute a job and wait for the result
-    pub async fn execute_job(&mut self, js_code: Vec<u8>) -> anyhow::Result<JobResult> {
-        self.schedule_job(js_code)
-        .await?
-        .recv()
-        .await
-        .ok_or_else(|| anyhow::anyhow!("Failed to receive job result"))?
+    /// Name and edge tracker of every engine, to read the coverage without the pool
+    pub fn edge_trackers(&self) -> Vec<(String, Arc<RwLock<EdgeTracker>>)> {
+        self.engines
+            .iter()
+            .map(|engine| (engine.name.clone(), engine.edge_tracker.clone()))
+            .collect()
     }
     
     pub async fn print_pool_stats(&self) {
-        let tracker = self.edge_tracker.read().await;
-        println!(
-            "Edge tracker: seen edges: {}, blacklisted edges: {}, total edges: {}, coverage: {:.2}%",
-            tracker.seen_edges.len(),
-            tracker.blacklist.iter().filter(|&(_, &count)| count >= tracker.max_resets).count(),
-            TOTAL_EDGE_COUNT.load(Ordering::SeqCst),
-            (tracker.seen_edges.len() as f64 / TOTAL_EDGE_COUNT.load(Ordering::SeqCst) as f64) * 100.0
+        for engine in &self.engines {
+            let tracker = engine.edge_tracker.read().await;
+            let total_edges = tracker.total_edges.unwrap_or(0);
+            println!(
+                "[{}] Edge tracker: seen edges: {}, blacklisted edges: {}, total edges: {}, coverage: {:.2}%",
+                engine.name,
+                tracker.seen_edges.len(),
+                tracker.blacklist.iter().filter(|&(_, &count)| count >= tracker.max_resets).count(),
+                total_edges,
+                (tracker.seen_edges.len() as f64 / total_edges.max(1) as f64) * 100.0
+            );
+        }
+        if let Some(cache) = &self.exec_cache {
+            let cache = cache.lock().expect("exec cache poisoned");
+            println!("[exec cache] programs: {}, duplicates skipped: {}", cache.len(), cache.hits());
+        }
+        let histogram = self.crash_stats.histogram();
+        if !histogram.is_empty() {
+            let signals: Vec<String> = histogram
+                .iter()
+                .map(|(signal, count)| format!("{} ({}): {}", signal_name(*signal), signal, count))
+                .collect();
+            println!("[crashes] {}", signals.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+impl FuzzPool {
+    /// Pool with a single fake engine that answers every job with `respond`
+    pub(crate) fn mock<F>(name: &str, respond: F) -> Self
+    where
+        F: Fn(&[u8]) -> JobResult + Send + 'static,
+    {
+        let mut pool = Self::empty();
+        let (job_tx, mut job_rx) = mpsc::channel::<Job>(4);
+        tokio::spawn(async move {
+            while let Some(job) = job_rx.recv().await {
+                let (js_code, result_tx) = job.into_parts();
+                let _ = result_tx.send(Ok(respond(&js_code))).await;
+            }
+        });
+        pool.add_engine_senders(
+            name.to_string(),
+            vec![job_tx],
+            Arc::new(RwLock::new(EdgeTracker::new(1000))),
+            4,
         );
+        pool
     }
 }
 
 impl Drop for FuzzWorkerInternal {
+    // the coverage backend is dropped after the engine is killed
     fn drop(&mut self) {
-        let _ = self.process.child.kill();
-        unsafe {
-            cov_shutdown(&mut self.cov_ctx);
+        self.process.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_programs_are_rejected() {
+        let program = vec![b' '; 64];
+        assert_eq!(check_program_size(&program, Some(64)), Ok(()));
+        assert_eq!(check_program_size(&program, None), Ok(()));
+        let err = check_program_size(&program, Some(63)).expect_err("oversized program accepted");
+        assert_eq!(err, ProgramTooLarge { size: 64, max: 63 });
+        let err = anyhow::Error::new(err);
+        assert!(err.downcast_ref::<ProgramTooLarge>().is_some());
+        assert!(err.to_string().contains("64 bytes"), "unclear rejection: {}", err);
+    }
+
+    fn job_result(status_code: i32, signal: i32, new_coverage: bool, is_crash: bool, is_timeout: bool) -> JobResult {
+        JobResult {
+            engine: "v8".to_string(),
+            status_code,
+            signal,
+            new_coverage,
+            edge_hits: if new_coverage { vec![1, 2, 3] } else { Vec::new() },
+            is_crash,
+            is_timeout,
+            is_stack_overflow: false,
+            exec_time_ms: Duration::from_millis(1),
+            sanitizer: None,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn clean_exits_map_to_coverage_outcomes() {
+        assert_eq!(job_result(0, 0, true, false, false).outcome(), Outcome::NewCoverage);
+        assert_eq!(job_result(0, 0, false, false, false).outcome(), Outcome::NoCoverage);
+    }
+
+    #[test]
+    fn nonzero_status_is_a_runtime_error() {
+        assert_eq!(job_result(1, 0, false, false, false).outcome(), Outcome::RuntimeError);
+        // coverage of a program that threw does not count
+        assert_eq!(job_result(1, 0, true, false, false).outcome(), Outcome::RuntimeError);
+    }
+
+    #[test]
+    fn job_results_are_judged_across_engines() {
+        let on = |engine: &str, result: JobResult| JobResult {
+            engine: engine.to_string(),
+            ..result
+        };
+        let covering = JobResults {
+            results: vec![on("jsc", job_result(1, 0, false, false, false)), on("v8", job_result(0, 0, true, false, false))],
+        };
+        // new coverage in one engine counts even though the other threw
+        assert_eq!(covering.outcome(), Outcome::NewCoverage);
+        let edges = covering.new_edges();
+        assert!(edges.edge_hits.is_empty());
+        assert_eq!(edges.engine_edges.get("v8"), Some(&vec![1, 2, 3]));
+        assert!(!edges.engine_edges.contains_key("jsc"));
+
+        let crashing = JobResults {
+            results: vec![on("jsc", job_result(0, 0, true, false, false)), on("v8", job_result(-1, 11, false, true, false))],
+        };
+        assert_eq!(crashing.outcome(), Outcome::Crash { signal: 11 });
+        assert_eq!(crashing.crashes().map(|result| result.engine.as_str()).collect::<Vec<_>>(), vec!["v8"]);
+
+        let single = JobResults {
+            results: vec![job_result(0, 0, true, false, false)],
+        };
+        assert_eq!(single.new_edges(), EntryEdges::from(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn timeouts_map_to_timeout() {
+        assert_eq!(job_result(-1, 0, false, false, true).outcome(), Outcome::Timeout);
+        assert_eq!(job_result(0, 0, true, false, true).outcome(), Outcome::Timeout);
+    }
+
+    #[test]
+    fn crashes_take_precedence() {
+        assert_eq!(job_result(-1, 11, false, true, false).outcome(), Outcome::Crash { signal: 11 });
+        assert_eq!(job_result(-1, -1, true, true, true).outcome(), Outcome::Crash { signal: -1 });
+        assert_eq!(job_result(0, 6, false, true, false).outcome(), Outcome::Crash { signal: 6 });
+    }
+
+    #[test]
+    fn stack_overflows_are_classified_from_engine_output() {
+        let v8 = b"test.js:1: RangeError: Maximum call stack size exceeded\nfunction f0() { f0(); }\n";
+        let spidermonkey = b"test.js:1:17 InternalError: too much recursion\n";
+        assert!(is_stack_overflow_log(v8));
+        assert!(is_stack_overflow_log(spidermonkey));
+        assert!(!is_stack_overflow_log(b"test.js:1: RangeError: Invalid array length\n"));
+        assert!(!is_stack_overflow_log(b""));
+
+        let mut overflow = job_result(1, 0, false, false, false);
+        overflow.is_stack_overflow = true;
+        assert_eq!(overflow.outcome(), Outcome::StackOverflow);
+        // a signal crash is never downgraded
+        let mut crash = job_result(-1, libc::SIGSEGV, false, true, false);
+        crash.is_stack_overflow = true;
+        assert_eq!(crash.outcome(), Outcome::Crash { signal: libc::SIGSEGV });
+    }
+
+    #[test]
+    fn crash_signals_are_counted() {
+        let stats = CrashStats::default();
+        stats.record(&job_result(-1, libc::SIGSEGV, false, true, false));
+        stats.record(&job_result(-1, libc::SIGABRT, false, true, false));
+        stats.record(&job_result(-1, libc::SIGSEGV, false, true, false));
+        // not crashes
+        stats.record(&job_result(-1, 0, false, false, true));
+        stats.record(&job_result(1, 0, false, false, false));
+
+        assert_eq!(stats.histogram(), vec![(libc::SIGSEGV, 2), (libc::SIGABRT, 1)]);
+        assert_eq!(signal_name(libc::SIGFPE), "SIGFPE");
+    }
+
+    #[test]
+    fn unconfirmed_edges_are_recorded_directly() {
+        let mut tracker = EdgeTracker::new(2);
+        tracker.blacklist.insert(7, 2);
+
+        assert_eq!(tracker.record_new_edges(&[1, 2, 3, 7]), vec![1, 2, 3]);
+        assert_eq!(tracker.record_new_edges(&[3, 4]), vec![4]);
+        assert_eq!(tracker.seen_edges.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn jobs_are_dispatched_to_every_engine() {
+        let mut pool = FuzzPool::empty();
+        let (v8_tx, mut v8_rx) = mpsc::channel(4);
+        let (jsc_tx, mut jsc_rx) = mpsc::channel(4);
+        pool.add_engine_senders(
+            "v8".to_string(),
+            vec![v8_tx],
+            Arc::new(RwLock::new(EdgeTracker::new(1000))),
+            4,
+        );
+        pool.add_engine_senders(
+            "jsc".to_string(),
+            vec![jsc_tx],
+            Arc::new(RwLock::new(EdgeTracker::new(1000))),
+            4,
+        );
+
+        let _result_rx = pool.schedule_job(b"1 + 1".to_vec()).await.expect("failed to schedule job");
+
+        let v8_job = v8_rx.try_recv().expect("v8 worker did not receive the job");
+        let jsc_job = jsc_rx.try_recv().expect("jsc worker did not receive the job");
+        assert_eq!(v8_job.js_code, b"1 + 1");
+        assert_eq!(jsc_job.js_code, b"1 + 1");
+
+        // execute_job returns the results of both
+        for (engine, mut job_rx) in [("v8", v8_rx), ("jsc", jsc_rx)] {
+            tokio::spawn(async move {
+                while let Some(job) = job_rx.recv().await {
+                    let (_, result_tx) = job.into_parts();
+                    let result = JobResult {
+                        engine: engine.to_string(),
+                        ..job_result(0, 0, false, false, false)
+                    };
+                    let _ = result_tx.send(Ok(result)).await;
+                }
+            });
+        }
+        let job_results = pool.execute_job(b"2 + 2".to_vec()).await.expect("failed to execute job");
+        let engines: Vec<&str> = job_results.results.iter().map(|result| result.engine.as_str()).collect();
+        assert_eq!(engines, vec!["jsc", "v8"]);
+    }
+
+    #[tokio::test]
+    async fn scheduling_on_saturated_workers_gives_up() {
+        let mut pool = FuzzPool::empty();
+        let (full_tx, _full_rx) = mpsc::channel(1);
+        let (closed_tx, closed_rx) = mpsc::channel(1);
+        drop(closed_rx);
+        pool.add_engine_senders(
+            "full".to_string(),
+            vec![full_tx],
+            Arc::new(RwLock::new(EdgeTracker::new(1000))),
+            4,
+        );
+        pool.set_schedule_timeout(Some(Duration::from_millis(100)));
+
+        // the first job fills the only queue slot, nobody ever takes it out
+        let _result_rx = pool.schedule_job(b"1".to_vec()).await.expect("failed to schedule job");
+        let start = std::time::Instant::now();
+        let err = pool.schedule_job(b"2".to_vec()).await.expect_err("job scheduled on a full queue");
+        assert!(err.to_string().contains("saturated"), "unexpected error: {}", err);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let mut pool = FuzzPool::empty();
+        pool.add_engine_senders(
+            "closed".to_string(),
+            vec![closed_tx],
+            Arc::new(RwLock::new(EdgeTracker::new(1000))),
+            4,
+        );
+        let err = pool.schedule_job(b"1".to_vec()).await.expect_err("job scheduled on a closed queue");
+        assert!(err.to_string().contains("closed"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn edge_counts_are_tracked_per_engine() {
+        let (mut v8, mut jsc) = (EdgeTracker::new(1000), EdgeTracker::new(1000));
+        assert_eq!(v8.total_edges(), None);
+        assert!(v8.record_total_edges(1000));
+        assert!(jsc.record_total_edges(2500));
+        assert!(v8.record_total_edges(1000));
+        // a differing count doesn't replace the first one
+        assert!(!v8.record_total_edges(1200));
+        assert_eq!(v8.total_edges(), Some(1000));
+        assert_eq!(jsc.total_edges(), Some(2500));
+    }
+
+    #[tokio::test]
+    async fn cached_programs_are_not_executed_again() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let executions = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&executions);
+        let mut pool = FuzzPool::mock("mock", move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            job_result(1, 0, true, false, false)
+        });
+        pool.set_exec_cache_size(4);
+
+        let mut first = pool.schedule_job(b"1 + 1".to_vec()).await.expect("failed to schedule job");
+        let first = first.recv().await.expect("no result").expect("job failed");
+        assert!(first.new_coverage);
+        // give the forwarding task time to remember the result
+        while pool.exec_cache.as_ref().unwrap().lock().unwrap().len() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let mut second = pool.schedule_job(b"1 + 1".to_vec()).await.expect("failed to schedule job");
+        let second = second.recv().await.expect("no result").expect("job failed");
+        assert_eq!(second.status_code, first.status_code);
+        assert!(!second.new_coverage);
+        assert!(!first.cached && second.cached);
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+    }
+
+    /// `sh` run on a temp file per program
+    #[derive(Clone)]
+    struct ShellProfile;
+
+    impl JsEngineProfile for ShellProfile {
+        fn get_name(&self) -> String {
+            "sh".to_string()
+        }
+        fn get_path(&self) -> String {
+            "sh".to_string()
+        }
+        fn get_args(&self) -> Vec<String> {
+            Vec::new()
+        }
+        fn fuzz_worker_job_queue_size(&self) -> usize {
+            4
+        }
+        fn get_timeout(&self) -> u64 {
+            5000
+        }
+        fn get_jobs_per_process(&self) -> usize {
+            1000
+        }
+        fn get_min_new_edges_to_add_corpus(&self) -> usize {
+            1
+        }
+        fn exec_backend(&self) -> crate::runner::backend::ExecBackendKind {
+            crate::runner::backend::ExecBackendKind::TempFile
+        }
+    }
+
+    #[test]
+    fn executions_complete_without_coverage() {
+        let mut worker = FuzzWorker::new(
+            &ShellProfile,
+            Arc::new(RwLock::new(EdgeTracker::new(1000))),
+            Arc::new(StallWatchdog::new()),
+            Arc::new(CrashStats::default()),
+            true,
+            EngineStdio::Null,
+        )
+        .expect("failed to create worker");
+        let mut process = crate::runner::backend::TempFileProcess::new("sh".to_string(), Vec::new(), 5000);
+        process.handshake().expect("handshake failed");
+        worker.internal = FuzzWorkerInternal {
+            process: Box::new(process),
+            coverage: Box::new(NoCoverage),
+        };
+
+        for code in [&b"exit 0"[..], b"echo hello; exit 2"] {
+            let result = worker.start_internal(code).expect("execution failed");
+            assert!(result.edge_hits.is_empty());
+            assert!(!result.new_coverage);
+            assert!(!result.is_crash && !result.is_timeout);
         }
+        assert_eq!(worker.start_internal(b"exit 2").unwrap().outcome(), Outcome::RuntimeError);
     }
 }
@@ -1,8 +1,9 @@
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
 use std::os::unix::process::CommandExt;
-use std::process::{Child, Command};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -12,6 +13,76 @@ const REPRL_CRFD: RawFd = 100; // child reads control
 const REPRL_CWFD: RawFd = 101; // child writes status
 const REPRL_DRFD: RawFd = 102; // child reads program bytes
 const REPRL_DWFD: RawFd = 103; // child writes fuzzer prints / logs
+/// Log output kept per execution, the rest is drained and dropped
+const MAX_LOG_BYTES: usize = 64 * 1024;
+
+/// Where the stdout/stderr of engine processes go
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EngineStdio {
+    /// Discarded, the default since engines can be very chatty
+    #[default]
+    Null,
+    /// Shared with the fuzzer's terminal
+    Inherit,
+    /// Appended to a log file
+    File(PathBuf),
+}
+
+impl EngineStdio {
+    /// Stdio handles for the child's (stdout, stderr)
+    fn handles(&self) -> io::Result<(Stdio, Stdio)> {
+        match self {
+            EngineStdio::Null => Ok((Stdio::null(), Stdio::null())),
+            EngineStdio::Inherit => Ok((Stdio::inherit(), Stdio::inherit())),
+            EngineStdio::File(path) => {
+                let log = OpenOptions::new().create(true).append(true).open(path)?;
+                Ok((Stdio::from(log.try_clone()?), Stdio::from(log)))
+            }
+        }
+    }
+}
+
+/// Resource limits set on engine processes before they exec, so a program exhausting
+/// memory fails inside its engine instead of taking the host down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub mem_limit_bytes: Option<u64>,
+    pub cpu_limit_secs: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn from_profile<T: JsEngineProfile>(profile: &T) -> Self {
+        Self {
+            mem_limit_bytes: profile.mem_limit_bytes(),
+            cpu_limit_secs: profile.cpu_limit_secs(),
+        }
+    }
+
+    /// Apply the limits to the calling process. Meant for `pre_exec`, so it only makes
+    /// async-signal-safe calls.
+    pub(crate) fn apply(&self) -> io::Result<()> {
+        if let Some(bytes) = self.mem_limit_bytes {
+            let limit = libc::rlimit {
+                rlim_cur: bytes as libc::rlim_t,
+                rlim_max: bytes as libc::rlim_t,
+            };
+            if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        if let Some(secs) = self.cpu_limit_secs {
+            // SIGXCPU at the soft limit, SIGKILL a second later if it is ignored
+            let limit = libc::rlimit {
+                rlim_cur: secs as libc::rlim_t,
+                rlim_max: secs.saturating_add(1) as libc::rlim_t,
+            };
+            if unsafe { libc::setrlimit(libc::RLIMIT_CPU, &limit) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug)]
 pub struct FuzzProcess {
@@ -19,9 +90,12 @@ pub struct FuzzProcess {
     pub crt_executions: usize,
     pub max_executions: usize,
     timeout: u64,
+    handshake_timeout: u64,
     path: String,
     args: Vec<String>,
     shm_id: String,
+    stdio: EngineStdio,
+    limits: ResourceLimits,
     data_tx: File,
     data_rx: File,
     ctrl_tx: File,
@@ -49,7 +123,8 @@ fn make_inheritable(fd: RawFd) -> io::Result<()> {
 }
 
 impl FuzzProcess {
-    pub fn spawn<T: JsEngineProfile>(profile: &T, shm_id: &str) -> anyhow::Result<FuzzProcess> {
+    /// Spawn the engine of `profile`, sending its output to `stdio`
+    pub fn spawn<T: JsEngineProfile>(profile: &T, shm_id: &str, stdio: EngineStdio) -> anyhow::Result<FuzzProcess> {
         let path = profile.get_path();
         let args = profile.get_args();
         let timeout = profile.get_timeout();
@@ -58,7 +133,10 @@ impl FuzzProcess {
             args,
             shm_id.to_string(),
             timeout,
+            profile.get_handshake_timeout(),
             profile.get_jobs_per_process(),
+            stdio,
+            ResourceLimits::from_profile(profile),
         )
     }
 
@@ -67,19 +145,25 @@ impl FuzzProcess {
         args: Vec<String>,
         shm_id: String,
         timeout: u64,
+        handshake_timeout: u64,
         max_executions: usize,
+        stdio: EngineStdio,
+        limits: ResourceLimits,
     ) -> anyhow::Result<FuzzProcess> {
         let (child, ctrl_tx, ctrl_rx, data_tx, data_rx) =
-            Self::launch_process(&path, &args, &shm_id)?;
+            Self::launch_process(&path, &args, &shm_id, &stdio, limits)?;
 
         Ok(Self {
             child,
             crt_executions: 0,
             max_executions: max_executions,
             timeout,
+            handshake_timeout,
             path,
             args,
             shm_id,
+            stdio,
+            limits,
             ctrl_tx,
             ctrl_rx,
             data_tx,
@@ -91,7 +175,10 @@ impl FuzzProcess {
         path: &str,
         args: &[String],
         shm_id: &str,
+        stdio: &EngineStdio,
+        limits: ResourceLimits,
     ) -> io::Result<(Child, File, File, File, File)> {
+        let (stdout, stderr) = stdio.handles()?;
         let (cr_read, cr_write) = pipe()?;
         let (cw_read, cw_write) = pipe()?;
         let (dr_read, dr_write) = pipe()?;
@@ -102,8 +189,8 @@ impl FuzzProcess {
             .env("REPRL_MODE", "1")
             .env("SHM_ID", shm_id)
             .stdin(std::process::Stdio::null())
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null());
+            .stdout(stdout)
+            .stderr(stderr);
 
         unsafe {
             cmd.pre_exec(move || {
@@ -119,6 +206,7 @@ impl FuzzProcess {
                 dup(cw_write, REPRL_CWFD)?;
                 dup(dr_read, REPRL_DRFD)?;
                 dup(dw_write, REPRL_DWFD)?;
+                limits.apply()?;
                 Ok(())
             });
         }
@@ -146,7 +234,7 @@ impl FuzzProcess {
         let _ = self.child.wait();
 
         let (child, ctrl_tx, ctrl_rx, data_tx, data_rx) =
-            Self::launch_process(&self.path, &self.args, &self.shm_id)?;
+            Self::launch_process(&self.path, &self.args, &self.shm_id, &self.stdio, self.limits)?;
 
         self.child = child;
         self.ctrl_tx = ctrl_tx;
@@ -157,10 +245,19 @@ impl FuzzProcess {
         Ok(())
     }
 
+    /// Exchange HELOs with the child. Fails if it doesn't send one within the handshake
+    /// timeout, e.g. because it isn't a REPRL engine or crashed at startup.
     pub fn handshake(&mut self) -> io::Result<()> {
         let mut buf = [0u8; 4];
-        self.ctrl_rx.read_exact(&mut buf)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("failed to read HELO from child: {}", e)))?;
+        let timeout = self.handshake_timeout;
+        self.read_ctrl_with_timeout(&mut buf, timeout)
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::TimedOut => io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{} did not send HELO within {}ms, is it a REPRL engine?", self.path, timeout),
+                ),
+                _ => io::Error::new(io::ErrorKind::Other, format!("failed to read HELO from child: {}", e)),
+            })?;
         if &buf != b"HELO" {
             return Err(io::Error::new(io::ErrorKind::Other, "bad HELO from child"));
         }
@@ -201,8 +298,47 @@ impl FuzzProcess {
 }
 
 impl FuzzProcess {
+    /// Drain what the child wrote to the log channel since the last call without blocking.
+    /// Reading it also keeps a chatty child from blocking on a full pipe.
+    pub fn read_log(&mut self) -> io::Result<Vec<u8>> {
+        let fd = self.data_rx.as_raw_fd();
+        let original_flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if original_flags == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let _restore = FdFlagRestore {
+            fd,
+            flags: original_flags,
+        };
+        if unsafe { libc::fcntl(fd, libc::F_SETFL, original_flags | libc::O_NONBLOCK) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut log = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            match self.data_rx.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let keep = n.min(MAX_LOG_BYTES.saturating_sub(log.len()));
+                    log.extend_from_slice(&buf[..keep]);
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(log)
+    }
+
     fn read_status_with_timeout(&mut self, buf: &mut [u8]) -> io::Result<()> {
-        if self.timeout == 0 {
+        self.read_ctrl_with_timeout(buf, self.timeout)
+    }
+
+    /// Fill `buf` from the control pipe, killing the child if it takes over `timeout`
+    /// milliseconds. A timeout of 0 waits forever.
+    fn read_ctrl_with_timeout(&mut self, buf: &mut [u8], timeout: u64) -> io::Result<()> {
+        if timeout == 0 {
             self.ctrl_rx.read_exact(buf)?;
             return Ok(());
         }
@@ -220,7 +356,7 @@ impl FuzzProcess {
             return Err(io::Error::last_os_error());
         }
 
-        let deadline = Instant::now() + Duration::from_millis(self.timeout);
+        let deadline = Instant::now() + Duration::from_millis(timeout);
         let mut offset = 0;
 
         while offset < buf.len() {
@@ -240,7 +376,7 @@ impl FuzzProcess {
                         let _ = self.child.wait();
                         return Err(io::Error::new(
                             io::ErrorKind::TimedOut,
-                            "execution timed out",
+                            "read from child timed out",
                         ));
                     }
                     thread::sleep(Duration::from_millis(1));
@@ -272,3 +408,70 @@ impl Drop for FdFlagRestore {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch_shell(script: &str, stdio: &EngineStdio) -> Child {
+        let args = vec!["-c".to_string(), script.to_string()];
+        let (child, ..) = FuzzProcess::launch_process("sh", &args, "shm_id_test", stdio, ResourceLimits::default())
+            .expect("failed to launch shell");
+        child
+    }
+
+    #[test]
+    fn engine_output_goes_to_the_chosen_stdio() {
+        let log = std::env::temp_dir().join(format!("jellyfuzz_engine_log_{}", std::process::id()));
+        let _ = std::fs::remove_file(&log);
+
+        let mut child = launch_shell("echo out; echo err >&2", &EngineStdio::File(log.clone()));
+        child.wait().expect("shell did not exit");
+        let contents = std::fs::read_to_string(&log).expect("engine log was not written");
+        assert!(contents.contains("out"));
+        assert!(contents.contains("err"));
+
+        let mut child = launch_shell("echo discarded", &EngineStdio::Null);
+        child.wait().expect("shell did not exit");
+        assert!(!std::fs::read_to_string(&log).unwrap().contains("discarded"));
+
+        let _ = std::fs::remove_file(&log);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn memory_limit_fails_the_child() {
+        let limits = ResourceLimits {
+            mem_limit_bytes: Some(64 * 1024 * 1024),
+            cpu_limit_secs: Some(10),
+        };
+        // the shell keeps the whole 256MB output of the substitution in memory
+        let args = vec!["-c".to_string(), "x=$(head -c 268435456 /dev/zero | tr '\\0' a); exit 0".to_string()];
+        let (mut child, ..) = FuzzProcess::launch_process("sh", &args, "shm_id_test", &EngineStdio::Null, limits)
+            .expect("failed to launch shell");
+        let status = child.wait().expect("shell did not exit");
+        assert!(!status.success(), "shell stayed under the memory limit");
+    }
+
+    #[test]
+    fn silent_child_fails_the_handshake() {
+        let args = vec!["-c".to_string(), "sleep 10".to_string()];
+        let mut process = FuzzProcess::spawn_with_details(
+            "sh".to_string(),
+            args,
+            "shm_id_test".to_string(),
+            1000,
+            200,
+            1,
+            EngineStdio::Null,
+            ResourceLimits::default(),
+        )
+        .expect("failed to launch shell");
+
+        let start = Instant::now();
+        let err = process.handshake().expect_err("handshake with a silent child succeeded");
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(err.to_string().contains("HELO"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,394 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::corpus::CorpusManager;
+use crate::mutators::ManagedMutator;
+use crate::parsing::parser::{generate_js_with_decls, parse_js_with_decls, source_type};
+use crate::runner::pool::{EdgeTracker, FuzzPool, Outcome};
+use crate::utils::iteration_log::IterationRecord;
+use crate::utils::state::UndefinedRefPolicy;
+
+/// What happened to a single replayed file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayStatus {
+    Executed(Outcome),
+    /// The file couldn't be executed, with the reason
+    Skipped(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayEntry {
+    pub path: PathBuf,
+    pub status: ReplayStatus,
+}
+
+impl ReplayEntry {
+    pub fn is_crash(&self) -> bool {
+        matches!(self.status, ReplayStatus::Executed(Outcome::Crash { .. }))
+    }
+}
+
+/// Every `.js` file under `dir`, including subdirectories, in a stable order.
+/// Symlinked directories aren't followed, they could loop back into `dir`
+pub fn collect_js_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to read replay directory {:?}", dir))?;
+        for entry in entries {
+            let entry = entry.with_context(|| format!("failed to iterate replay directory {:?}", dir))?;
+            let path = entry.path();
+            let file_type = entry
+            .file_type()
+            .with_context(|| format!("failed to read the file type of {:?}", path))?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "js") && path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Execute every `.js` file under `dir` once and collect the outcomes
+pub async fn replay_dir(pool: &mut FuzzPool, dir: &Path) -> Result<Vec<ReplayEntry>> {
+    let mut entries = Vec::new();
+    for path in collect_js_files(dir)? {
+        let status = match std::fs::read(&path) {
+            Err(err) => ReplayStatus::Skipped(format!("unreadable: {}", err)),
+            Ok(source) if std::str::from_utf8(&source).is_err() => {
+                ReplayStatus::Skipped("not valid UTF-8".to_string())
+            }
+            Ok(source) => match pool.execute_job(source).await {
+                Ok(job_results) => ReplayStatus::Executed(job_results.outcome()),
+                Err(err) => ReplayStatus::Skipped(format!("execution failed: {}", err)),
+            },
+        };
+        entries.push(ReplayEntry { path, status });
+    }
+    Ok(entries)
+}
+
+/// Render the replay results as a (file, outcome, signal) table
+pub fn format_replay_table(entries: &[ReplayEntry]) -> String {
+    let rows: Vec<(String, String, String)> = entries
+        .iter()
+        .map(|entry| {
+            let (outcome, signal) = match &entry.status {
+                ReplayStatus::Executed(Outcome::Crash { signal }) => ("crash".to_string(), signal.to_string()),
+                ReplayStatus::Executed(Outcome::Timeout) => ("timeout".to_string(), "-".to_string()),
+                ReplayStatus::Executed(Outcome::RuntimeError) => ("error".to_string(), "-".to_string()),
+                ReplayStatus::Executed(Outcome::StackOverflow) => ("stack overflow".to_string(), "-".to_string()),
+                ReplayStatus::Executed(Outcome::NewCoverage | Outcome::NoCoverage) => {
+                    ("ok".to_string(), "-".to_string())
+                }
+                ReplayStatus::Skipped(reason) => (format!("skipped ({})", reason), "-".to_string()),
+            };
+            (entry.path.display().to_string(), outcome, signal)
+        })
+        .collect();
+
+    let file_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0).max("file".len());
+    let outcome_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0).max("outcome".len());
+    let mut table = format!("{:<file_width$}  {:<outcome_width$}  signal\n", "file", "outcome");
+    for (file, outcome, signal) in rows {
+        table.push_str(&format!("{:<file_width$}  {:<outcome_width$}  {}\n", file, outcome, signal));
+    }
+    table
+}
+
+/// Outcomes of executing a crash repro several times
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrashVerification {
+    pub runs: usize,
+    pub crashes: usize,
+    /// Crashes per signal, sorted by signal
+    pub signals: Vec<(i32, usize)>,
+}
+
+impl CrashVerification {
+    /// Share of the runs that crashed
+    pub fn reproduction_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.crashes as f64 / self.runs as f64
+        }
+    }
+
+    /// Whether every crash died of the same signal
+    pub fn is_signal_stable(&self) -> bool {
+        self.signals.len() == 1
+    }
+}
+
+/// Execute `path` `times` times and count the runs that crash, to tell solid repros
+/// from flaky ones
+pub async fn verify_crash(pool: &mut FuzzPool, path: &Path, times: usize) -> Result<CrashVerification> {
+    let source = std::fs::read(path).with_context(|| format!("failed to read {:?}", path))?;
+    let mut verification = CrashVerification::default();
+    for _ in 0..times {
+        let job_results = pool
+        .execute_job(source.clone())
+        .await
+        .with_context(|| format!("failed to execute {:?}", path))?;
+        verification.runs += 1;
+        if let Outcome::Crash { signal } = job_results.outcome() {
+            verification.crashes += 1;
+            match verification.signals.iter_mut().find(|(s, _)| *s == signal) {
+                Some((_, count)) => *count += 1,
+                None => verification.signals.push((signal, 1)),
+            }
+        }
+    }
+    verification.signals.sort_unstable();
+    Ok(verification)
+}
+
+pub fn format_crash_verification(verification: &CrashVerification) -> String {
+    let signals = verification
+    .signals
+    .iter()
+    .map(|(signal, count)| format!("{} ({}x)", signal, count))
+    .collect::<Vec<_>>()
+    .join(", ");
+    format!(
+        "crashed in {} of {} runs ({:.0}%)
+signals: {}
+signal stable: {}
+",
+        verification.crashes,
+        verification.runs,
+        verification.reproduction_rate() * 100.0,
+        if signals.is_empty() { "-".to_string() } else { signals },
+        if verification.is_signal_stable() { "yes" } else { "no" }
+    )
+}
+
+/// Edges hit by a single execution of a file
+#[derive(Debug, Clone)]
+pub struct CoverageReport {
+    pub outcome: Outcome,
+    /// Every edge hit, sorted
+    pub edges: Vec<u32>,
+    /// The edges `tracker` hadn't seen yet
+    pub new_edges: Vec<u32>,
+}
+
+/// Execute `path` once and report the edges it hit. A fresh pool reports every edge
+/// of its first execution, which are then checked against `tracker`. `pool` runs a
+/// single engine, edges of different engines can't be compared.
+pub async fn show_coverage(pool: &mut FuzzPool, path: &Path, tracker: &mut EdgeTracker) -> Result<CoverageReport> {
+    let source = std::fs::read(path).with_context(|| format!("failed to read {:?}", path))?;
+    let job_results = pool
+    .execute_job(source)
+    .await
+    .with_context(|| format!("failed to execute {:?}", path))?;
+    let job_result = &job_results.results[0];
+    let mut edges = job_result.edge_hits.clone();
+    edges.sort_unstable();
+    edges.dedup();
+    let new_edges = tracker.record_new_edges(&edges);
+    Ok(CoverageReport {
+        outcome: job_result.outcome(),
+        edges,
+        new_edges,
+    })
+}
+
+fn format_edge_list(edges: &[u32]) -> String {
+    edges.iter().map(u32::to_string).collect::<Vec<_>>().join(" ")
+}
+
+pub fn format_coverage_report(report: &CoverageReport) -> String {
+    format!(
+        "outcome: {:?}\nedges hit: {}\n{}\nnew edges: {}\n{}\n",
+        report.outcome,
+        report.edges.len(),
+        format_edge_list(&report.edges),
+        report.new_edges.len(),
+        format_edge_list(&report.new_edges)
+    )
+}
+
+/// Rebuild the programs a logged iteration scheduled from its corpus seed and donors and
+/// execute them in order. Returns every program with its outcome.
+pub async fn replay_iteration(
+    pool: &mut FuzzPool,
+    corpus_manager: &mut CorpusManager,
+    record: &IterationRecord,
+    mutators: &[Arc<ManagedMutator>],
+    undefined_refs: UndefinedRefPolicy,
+) -> Result<Vec<(Vec<u8>, Outcome)>> {
+    let entry = corpus_manager
+    .entries()
+    .iter()
+    .find(|entry| entry.id == record.seed_id)
+    .with_context(|| format!("seed {} of iteration {} is not in the corpus", record.seed_id, record.iteration))?;
+    let source = corpus_manager.read_file(&corpus_manager.root().join(&entry.path)).await?;
+    let (seed, module_decls) =
+        parse_js_with_decls(String::from_utf8(source).context("seed is not valid UTF-8")?, source_type())?;
+    let mut donors = HashMap::new();
+    for donor_id in record.donor_ids() {
+        let donor = corpus_manager
+        .get_script(donor_id)
+        .await?
+        .with_context(|| format!("donor {} of iteration {} is not in the corpus", donor_id, record.iteration))?;
+        donors.insert(donor_id, donor);
+    }
+    let mut results = Vec::with_capacity(record.steps.len());
+    for program in record.reconstruct(seed, mutators, &donors, undefined_refs)? {
+        let program = generate_js_with_decls(program, module_decls.clone())?;
+        let job_results = pool
+        .execute_job(program.clone())
+        .await
+        .with_context(|| format!("failed to execute iteration {}", record.iteration))?;
+        results.push((program, job_results.outcome()));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::pool::JobResult;
+    use std::time::Duration;
+
+    fn temp_replay_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("jellyfuzz_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // mock engine: exits with 1 on `throw`, cleanly otherwise
+    fn mock_result(js_code: &[u8]) -> JobResult {
+        let throws = String::from_utf8_lossy(js_code).contains("throw");
+        JobResult {
+            engine: "mock".to_string(),
+            status_code: if throws { 1 } else { 0 },
+            signal: 0,
+            new_coverage: false,
+            edge_hits: Vec::new(),
+            is_crash: false,
+            is_timeout: false,
+            is_stack_overflow: false,
+            exec_time_ms: Duration::from_millis(1),
+            sanitizer: None,
+            cached: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_reports_benign_and_error_files() {
+        let dir = temp_replay_dir("replay");
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("benign.js"), "let v0 = 1 + 2;").unwrap();
+        std::fs::write(dir.join("nested").join("error.js"), "throw new Error();").unwrap();
+        std::fs::write(dir.join("nested").join("binary.js"), [0xff, 0xfe, 0x00]).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a repro").unwrap();
+
+        let mut pool = FuzzPool::mock("mock", mock_result);
+        let entries = replay_dir(&mut pool, &dir).await.expect("replay failed");
+        let status = |name: &str| {
+            entries
+            .iter()
+            .find(|entry| entry.path.ends_with(name))
+            .map(|entry| entry.status.clone())
+        };
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(status("benign.js"), Some(ReplayStatus::Executed(Outcome::NoCoverage)));
+        assert_eq!(status("nested/error.js"), Some(ReplayStatus::Executed(Outcome::RuntimeError)));
+        assert!(matches!(status("nested/binary.js"), Some(ReplayStatus::Skipped(_))));
+        assert!(!entries.iter().any(ReplayEntry::is_crash));
+
+        let table = format_replay_table(&entries);
+        assert!(table.lines().any(|line| line.contains("benign.js") && line.contains("ok")));
+        assert!(table.lines().any(|line| line.contains("error.js") && line.contains("error")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn symlinked_directories_are_not_followed() {
+        let dir = temp_replay_dir("symlinks");
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("nested").join("repro.js"), "let v0 = 1;").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("nested").join("loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("nested").join("repro.js"), dir.join("linked.js")).unwrap();
+
+        let files = collect_js_files(&dir).expect("failed to collect the replay files");
+        assert_eq!(files, vec![dir.join("linked.js"), dir.join("nested").join("repro.js")]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn flaky_crashes_are_counted() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let dir = temp_replay_dir("verify_crash");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("crash.js");
+        std::fs::write(&path, "let v0 = [1, 2];").unwrap();
+
+        // crashes on every other run, with SIGSEGV and then SIGTRAP
+        let runs = AtomicUsize::new(0);
+        let mut pool = FuzzPool::mock("mock", move |js_code| {
+            let run = runs.fetch_add(1, Ordering::Relaxed);
+            let signal = match run % 4 {
+                0 => libc::SIGSEGV,
+                2 => libc::SIGTRAP,
+                _ => 0,
+            };
+            JobResult {
+                signal,
+                is_crash: signal != 0,
+                ..mock_result(js_code)
+            }
+        });
+        let verification = verify_crash(&mut pool, &path, 8).await.expect("execution failed");
+
+        assert_eq!(verification.runs, 8);
+        assert_eq!(verification.crashes, 4);
+        assert_eq!(verification.reproduction_rate(), 0.5);
+        let mut expected = vec![(libc::SIGSEGV, 2), (libc::SIGTRAP, 2)];
+        expected.sort_unstable();
+        assert_eq!(verification.signals, expected);
+        assert!(!verification.is_signal_stable());
+        assert!(format_crash_verification(&verification).contains("crashed in 4 of 8 runs (50%)"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn coverage_of_a_single_execution_is_reported() {
+        let dir = temp_replay_dir("show_coverage");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("seed.js");
+        std::fs::write(&path, "let v0 = [1, 2];").unwrap();
+
+        let mut pool = FuzzPool::mock("mock", |js_code| JobResult {
+            new_coverage: true,
+            edge_hits: vec![9, 3, 5, 3],
+            ..mock_result(js_code)
+        });
+        let mut tracker = EdgeTracker::new(1000);
+        tracker.record_new_edges(&[3]);
+        let report = show_coverage(&mut pool, &path, &mut tracker).await.expect("execution failed");
+
+        assert_eq!(report.outcome, Outcome::NewCoverage);
+        assert_eq!(report.edges, vec![3, 5, 9]);
+        assert_eq!(report.new_edges, vec![5, 9]);
+        let output = format_coverage_report(&report);
+        assert!(output.contains("edges hit: 3\n3 5 9\n"));
+        assert!(output.contains("new edges: 2\n5 9\n"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// The first error a sanitizer-instrumented engine reported on its log channel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizerReport {
+    /// e.g. `AddressSanitizer` or `UndefinedBehaviorSanitizer`
+    pub sanitizer: String,
+    /// e.g. `heap-buffer-overflow` or `signed integer overflow`
+    pub error_type: String,
+    /// Function and location of the first stack frame outside the sanitizer runtime
+    pub top_frame: Option<String>,
+}
+
+impl SanitizerReport {
+    /// Identifies the bug rather than the input, so repros of the same bug collapse
+    pub fn fingerprint(&self) -> String {
+        let key = format!(
+            "{}|{}|{}",
+            self.sanitizer,
+            self.error_type,
+            self.top_frame.as_deref().unwrap_or("")
+        );
+        format!("{:x}", md5::compute(key))
+    }
+}
+
+/// Frames of the sanitizer runtime itself, skipped when looking for the top frame
+const RUNTIME_FRAME_PREFIXES: &[&str] = &["__asan", "__ubsan", "__msan", "__tsan", "__sanitizer", "__interceptor"];
+
+/// `==1234==ERROR: AddressSanitizer: heap-buffer-overflow on address ...`
+fn parse_error_line(line: &str) -> Option<(String, String)> {
+    let rest = &line[line.find("ERROR: ")? + "ERROR: ".len()..];
+    let (sanitizer, description) = rest.split_once(": ")?;
+    if !sanitizer.ends_with("Sanitizer") {
+        return None;
+    }
+    let error_type = description.split_whitespace().next()?.trim_end_matches(':');
+    Some((sanitizer.to_string(), error_type.to_string()))
+}
+
+/// `src/foo.cc:12:5: runtime error: signed integer overflow: 2147483647 + 1 ...`
+fn parse_runtime_error_line(line: &str) -> Option<(String, String, String)> {
+    let (location, description) = line.split_once(": runtime error: ")?;
+    let error_type = description.split(':').next()?.trim();
+    Some((
+        "UndefinedBehaviorSanitizer".to_string(),
+        error_type.to_string(),
+        location.trim().to_string(),
+    ))
+}
+
+/// `    #0 0x55d0c1 in v8::internal::Foo(int) /src/foo.cc:12:5`
+fn parse_frame_line(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    if !line.starts_with('#') {
+        return None;
+    }
+    let (_, frame) = line.split_once(" in ")?;
+    let frame = frame.trim();
+    if RUNTIME_FRAME_PREFIXES.iter().any(|prefix| frame.starts_with(prefix)) {
+        return None;
+    }
+    Some(frame.to_string())
+}
+
+/// Extract the first sanitizer error from engine log output, if there is one
+pub fn parse_sanitizer_report(log: &[u8]) -> Option<SanitizerReport> {
+    let log = String::from_utf8_lossy(log);
+    let mut lines = log.lines();
+    while let Some(line) = lines.next() {
+        if let Some((sanitizer, error_type)) = parse_error_line(line) {
+            let top_frame = lines
+                .by_ref()
+                .take_while(|line| !line.contains("SUMMARY:"))
+                .find_map(parse_frame_line);
+            return Some(SanitizerReport {
+                sanitizer,
+                error_type,
+                top_frame,
+            });
+        }
+        if let Some((sanitizer, error_type, location)) = parse_runtime_error_line(line) {
+            return Some(SanitizerReport {
+                sanitizer,
+                error_type,
+                top_frame: Some(location),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASAN_REPORT: &str = "\
+=================================================================
+==4242==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000014 at pc 0x55d0c1 bp 0x7ffd sp 0x7ffd
+READ of size 4 at 0x602000000014 thread T0
+    #0 0x55d0c1 in __asan_memcpy (/out/d8+0x55d0c1)
+    #1 0x55d0c2 in v8::internal::FastElementsAccessor::CopyElements(int) /src/v8/src/objects/elements.cc:1234:7
+    #2 0x55d0c3 in v8::internal::Builtins::ArrayConcat /src/v8/src/builtins/builtins-array.cc:88:3
+
+SUMMARY: AddressSanitizer: heap-buffer-overflow /src/v8/src/objects/elements.cc:1234:7
+";
+
+    #[test]
+    fn asan_reports_are_parsed() {
+        let report = parse_sanitizer_report(ASAN_REPORT.as_bytes()).expect("no report parsed");
+        assert_eq!(report.sanitizer, "AddressSanitizer");
+        assert_eq!(report.error_type, "heap-buffer-overflow");
+        assert_eq!(
+            report.top_frame.as_deref(),
+            Some("v8::internal::FastElementsAccessor::CopyElements(int) /src/v8/src/objects/elements.cc:1234:7")
+        );
+
+        let ubsan = b"src/foo.cc:12:5: runtime error: signed integer overflow: 2147483647 + 1 cannot be represented\n";
+        let report = parse_sanitizer_report(ubsan).expect("no report parsed");
+        assert_eq!(report.error_type, "signed integer overflow");
+        assert_eq!(report.top_frame.as_deref(), Some("src/foo.cc:12:5"));
+        assert_ne!(report.fingerprint(), parse_sanitizer_report(ASAN_REPORT.as_bytes()).unwrap().fingerprint());
+
+        assert_eq!(parse_sanitizer_report(b"test.js:1: TypeError: x is not a function\n"), None);
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::utils::pause::FUZZ_PAUSE;
+
+/// Tracks the time since a job last completed anywhere in the pool.
+///
+/// If every worker gets wedged (e.g. all children hung and restarting fails), the fuzz loop
+/// keeps scheduling jobs that never complete without any visible error. The watchdog makes
+/// this situation loud.
+pub struct StallWatchdog {
+    start: Instant,
+    // milliseconds since `start` at which the last job completed
+    last_completion_ms: AtomicU64,
+    completions: AtomicU64,
+}
+
+impl Default for StallWatchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StallWatchdog {
+    pub fn new() -> Self {
+        Self::new_at(Instant::now())
+    }
+
+    fn new_at(start: Instant) -> Self {
+        Self {
+            start,
+            last_completion_ms: AtomicU64::new(0),
+            completions: AtomicU64::new(0),
+        }
+    }
+
+    /// Called by workers every time they finish a job
+    pub fn record_completion(&self) {
+        self.record_completion_at(Instant::now());
+    }
+
+    fn record_completion_at(&self, now: Instant) {
+        let elapsed_ms = now.saturating_duration_since(self.start).as_millis() as u64;
+        self.last_completion_ms.fetch_max(elapsed_ms, Ordering::Relaxed);
+        self.completions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Restart the stall clock without counting a completion
+    fn touch(&self) {
+        let elapsed_ms = Instant::now().saturating_duration_since(self.start).as_millis() as u64;
+        self.last_completion_ms.fetch_max(elapsed_ms, Ordering::Relaxed);
+    }
+
+    /// Number of jobs completed so far
+    pub fn completions(&self) -> u64 {
+        self.completions.load(Ordering::Relaxed)
+    }
+
+    /// Returns how long the pool has been stalled if no job completed in the last `threshold`
+    pub fn stalled_for(&self, now: Instant, threshold: Duration) -> Option<Duration> {
+        let last_completion =
+            self.start + Duration::from_millis(self.last_completion_ms.load(Ordering::Relaxed));
+        let idle = now.saturating_duration_since(last_completion);
+        if idle >= threshold { Some(idle) } else { None }
+    }
+
+    /// Periodically check for stalls. Warns once per stall. With `exit_on_stall` set, returns
+    /// once the pool is stalled so the caller can shut down; otherwise it never returns.
+    pub async fn monitor(self: Arc<Self>, threshold: Duration, exit_on_stall: bool) {
+        let check_interval = (threshold / 4).max(Duration::from_secs(1));
+        let mut warned = false;
+        loop {
+            tokio::time::sleep(check_interval).await;
+            if FUZZ_PAUSE.is_paused() {
+                // nothing is scheduled on purpose, the stall clock restarts on resume
+                self.touch();
+                warned = false;
+                continue;
+            }
+            match self.stalled_for(Instant::now(), threshold) {
+                Some(idle) => {
+                    if !warned {
+                        eprintln!(
+                            "[watchdog] !!! No job has completed in {:?} (threshold {:?}); all workers may be wedged !!!",
+                            idle, threshold
+                        );
+                        warned = true;
+                    }
+                    if exit_on_stall {
+                        return;
+                    }
+                }
+                None => warned = false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn fires_after_threshold_without_completions() {
+        let start = Instant::now();
+        let watchdog = StallWatchdog::new_at(start);
+        assert!(watchdog.stalled_for(start + Duration::from_secs(9), THRESHOLD).is_none());
+        assert_eq!(
+            watchdog.stalled_for(start + Duration::from_secs(12), THRESHOLD),
+            Some(Duration::from_secs(12))
+        );
+    }
+
+    #[test]
+    fn resets_on_completion() {
+        let start = Instant::now();
+        let watchdog = StallWatchdog::new_at(start);
+        watchdog.record_completion_at(start + Duration::from_secs(8));
+        assert!(watchdog.stalled_for(start + Duration::from_secs(12), THRESHOLD).is_none());
+        assert!(watchdog.stalled_for(start + Duration::from_secs(18), THRESHOLD).is_some());
+    }
+
+    #[test]
+    fn older_completions_do_not_rewind_the_timer() {
+        let start = Instant::now();
+        let watchdog = StallWatchdog::new_at(start);
+        watchdog.record_completion_at(start + Duration::from_secs(8));
+        watchdog.record_completion_at(start + Duration::from_secs(2));
+        assert!(watchdog.stalled_for(start + Duration::from_secs(15), THRESHOLD).is_none());
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use rand::Rng;
+
+/// Tokens known to be meaningful to the target, loaded from an AFL-style dictionary.
+///
+/// Every non-empty, non-comment line holds one quoted token, optionally preceded by a
+/// name (and AFL's `@level` suffix), e.g. `kw_proto="__proto__"` or `"0x7fffffff"`.
+/// Inside the quotes `\\`, `\"` and `\xNN` escapes are supported.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    tokens: Vec<String>,
+    // tokens that also parse as numbers
+    numbers: Vec<f64>,
+}
+
+impl Dictionary {
+    pub fn new(tokens: Vec<String>) -> Self {
+        let numbers = tokens
+            .iter()
+            .filter_map(|token| token.trim().parse::<f64>().ok())
+            .collect();
+        Self { tokens, numbers }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read dictionary {:?}", path))?;
+        Self::parse(&contents).with_context(|| format!("failed to parse dictionary {:?}", path))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut tokens = Vec::new();
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some(quote_pos) = line.find('"') else {
+                bail!("line {}: missing quoted value", line_no + 1);
+            };
+            let name = line[..quote_pos].trim();
+            if !name.is_empty() && !name.ends_with('=') {
+                bail!("line {}: expected name=\"value\"", line_no + 1);
+            }
+            let value = &line[quote_pos..];
+            if value.len() < 2 || !value.ends_with('"') {
+                bail!("line {}: unterminated value", line_no + 1);
+            }
+            let token = unescape(&value[1..value.len() - 1])
+                .with_context(|| format!("line {}: invalid value", line_no + 1))?;
+            tokens.push(token);
+        }
+        Ok(Self::new(tokens))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn has_numbers(&self) -> bool {
+        !self.numbers.is_empty()
+    }
+
+    pub fn random_token(&self, rng: &mut impl Rng) -> Option<&str> {
+        if self.tokens.is_empty() {
+            return None;
+        }
+        Some(&self.tokens[rng.random_range(0..self.tokens.len())])
+    }
+
+    pub fn random_number(&self, rng: &mut impl Rng) -> Option<f64> {
+        if self.numbers.is_empty() {
+            return None;
+        }
+        Some(self.numbers[rng.random_range(0..self.numbers.len())])
+    }
+}
+
+fn unescape(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        match bytes.get(i + 1) {
+            Some(b'\\') => out.push(b'\\'),
+            Some(b'"') => out.push(b'"'),
+            Some(b'x') => {
+                let hex = value
+                    .get(i + 2..i + 4)
+                    .with_context(|| "truncated \\x escape".to_string())?;
+                out.push(u8::from_str_radix(hex, 16).with_context(|| format!("invalid \\x escape {:?}", hex))?);
+                i += 2;
+            }
+            _ => bail!("unsupported escape at offset {}", i),
+        }
+        i += 2;
+    }
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+# JavaScript keywords
+kw_proto="__proto__"
+kw_length@2="length"
+"0x7fffffff"
+max_smi="1073741823"
+quote="say \"hi\""
+nul="\x00a"
+"#;
+
+    #[test]
+    fn parses_afl_dictionary() {
+        let dict = Dictionary::parse(SAMPLE).expect("failed to parse dictionary");
+        assert_eq!(dict.len(), 6);
+        assert_eq!(
+            dict.tokens,
+            vec!["__proto__", "length", "0x7fffffff", "1073741823", "say \"hi\"", "\0a"]
+        );
+        assert_eq!(dict.numbers, vec![1073741823.0]);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(Dictionary::parse("kw=__proto__").is_err());
+        assert!(Dictionary::parse("kw=\"open").is_err());
+        assert!(Dictionary::parse("kw=\"\\q\"").is_err());
+    }
+}
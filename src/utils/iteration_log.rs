@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use swc_ecma_visit::swc_ecma_ast::Script;
+
+use crate::mutators::ManagedMutator;
+use crate::mutators::validity::apply_undefined_ref_policy;
+use crate::utils::state::UndefinedRefPolicy;
+
+/// Seed of the RNG of iteration `index` of a campaign seeded with `seed`, mixed with
+/// splitmix64 so neighbouring iterations get unrelated streams
+pub fn iteration_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed ^ index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The RNG the fuzz loop draws the decisions of iteration `index` from with `--rng-seed`
+pub fn iteration_rng(seed: u64, index: u64) -> StdRng {
+    StdRng::seed_from_u64(iteration_seed(seed, index))
+}
+
+/// A program scheduled by a fuzz loop iteration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IterationStep {
+    pub mutator: String,
+    /// Seed of the RNG the mutation and the undefined reference policy drew from
+    pub rng_seed: u64,
+    /// Corpus entry spliced into the program, for splices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub donor_id: Option<u64>,
+}
+
+impl IterationStep {
+    pub fn is_splice(&self) -> bool {
+        self.donor_id.is_some()
+    }
+}
+
+/// The decisions of one fuzz loop iteration, a line of the `--iteration-log` file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IterationRecord {
+    pub iteration: u64,
+    /// Corpus entry the programs were mutated from
+    pub seed_id: u64,
+    /// Scheduled programs, in order. Mutations are applied on top of each other to the
+    /// seed, splices branch off the chain and aren't part of it.
+    pub steps: Vec<IterationStep>,
+}
+
+impl IterationRecord {
+    pub fn to_line(&self) -> Result<String> {
+        serde_json::to_string(self).context("failed to serialize iteration record")
+    }
+
+    pub fn from_line(line: &str) -> Result<Self> {
+        serde_json::from_str(line).context("failed to parse iteration record")
+    }
+
+    /// Corpus entries the splices of the iteration used
+    pub fn donor_ids(&self) -> Vec<u64> {
+        self.steps.iter().filter_map(|step| step.donor_id).collect()
+    }
+
+    /// Apply the recorded steps to `seed` again and return the scheduled programs in
+    /// order. Every step draws from an RNG seeded with its recorded seed, so with the
+    /// campaign's undefined reference policy the programs are the ones the iteration
+    /// scheduled. `donors` holds the scripts of `donor_ids`.
+    pub fn reconstruct(
+        &self,
+        seed: Script,
+        mutators: &[Arc<ManagedMutator>],
+        donors: &HashMap<u64, Script>,
+        policy: UndefinedRefPolicy,
+    ) -> Result<Vec<Script>> {
+        let mut chain = seed;
+        let mut programs = Vec::with_capacity(self.steps.len());
+        for (idx, step) in self.steps.iter().enumerate() {
+            let Some(mutator) = mutators.iter().find(|mutator| mutator.name() == step.mutator) else {
+                bail!("iteration {} used unknown mutator {}", self.iteration, step.mutator);
+            };
+            let mut rng = StdRng::seed_from_u64(step.rng_seed);
+            let mut program = match step.donor_id {
+                Some(donor_id) => {
+                    let Some(donor) = donors.get(&donor_id) else {
+                        bail!("donor {} of iteration {} is missing", donor_id, self.iteration);
+                    };
+                    mutator.splice(&chain, donor, &mut rng)
+                }
+                None => mutator.mutate(chain.clone(), &mut rng),
+            }
+            .with_context(|| format!("{} failed while replaying iteration {}", step.mutator, self.iteration))?;
+            if !apply_undefined_ref_policy(&mut program, policy, &mut rng) {
+                bail!("step {} of iteration {} was dropped on replay", idx, self.iteration);
+            }
+            if !step.is_splice() {
+                chain = program.clone();
+            }
+            programs.push(program);
+        }
+        Ok(programs)
+    }
+}
+
+/// The record of iteration `index` in the iteration log at `path`
+pub fn find_iteration(path: &Path, index: u64) -> Result<IterationRecord> {
+    let file = File::open(path).with_context(|| format!("failed to open iteration log {:?}", path))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("failed to read iteration log {:?}", path))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = IterationRecord::from_line(&line)?;
+        if record.iteration == index {
+            return Ok(record);
+        }
+    }
+    bail!("iteration {} is not in {:?}", index, path)
+}
+
+/// Appends a JSONL record per fuzz loop iteration. Writes are buffered, `flush` pushes
+/// them to the file.
+#[derive(Debug)]
+pub struct IterationLog {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl IterationLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open iteration log {:?}", path))?;
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub fn append(&self, record: &IterationRecord) -> Result<()> {
+        let line = record.to_line()?;
+        let mut writer = self.writer.lock().expect("iteration log lock poisoned");
+        writeln!(writer, "{}", line).context("failed to write iteration log")
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        let mut writer = self.writer.lock().expect("iteration log lock poisoned");
+        writer.flush().context("failed to flush iteration log")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::get_ast_mutators_with_dictionary;
+    use crate::parsing::parser::{generate_js, parse_js};
+    use crate::utils::dictionary::Dictionary;
+    use rand::Rng;
+
+    fn step(mutator: &str, rng_seed: u64, donor_id: Option<u64>) -> IterationStep {
+        IterationStep {
+            mutator: mutator.to_string(),
+            rng_seed,
+            donor_id,
+        }
+    }
+
+    fn reconstruct_code(
+        record: &IterationRecord,
+        seed: &Script,
+        mutators: &[Arc<ManagedMutator>],
+        donors: &HashMap<u64, Script>,
+    ) -> Result<Vec<Vec<u8>>> {
+        record
+            .reconstruct(seed.clone(), mutators, donors, UndefinedRefPolicy::Repair)?
+            .into_iter()
+            .map(generate_js)
+            .collect()
+    }
+
+    #[test]
+    fn iteration_rng_is_deterministic() {
+        let (mut a, mut b) = (iteration_rng(1234, 17), iteration_rng(1234, 17));
+        for _ in 0..8 {
+            assert_eq!(a.random::<u64>(), b.random::<u64>());
+        }
+        assert_eq!(iteration_seed(1234, 17), iteration_seed(1234, 17));
+        assert_ne!(iteration_seed(1234, 17), iteration_seed(1234, 18));
+        assert_ne!(iteration_seed(1234, 17), iteration_seed(1235, 17));
+    }
+
+    #[test]
+    fn reconstructions_are_byte_identical() {
+        let mutators = get_ast_mutators_with_dictionary(Arc::new(Dictionary::default()));
+        let seed = parse_js(
+            "let v0 = [1, 2.5, 3]; let v1 = \"abc\"; let v2 = {a: true, b: v0};
+             function f0(a0, a1) { return a0 + v0.length * a1; }
+             for (let v3 = 0; v3 < 10; v3++) { v1 = v1 + f0(v3, 2); }
+             print(v1, v0[1] > 2 ? v2.a : false, f0(1, 2) === 7);"
+                .to_string(),
+        )
+        .unwrap();
+        let donors = HashMap::from([(
+            7,
+            parse_js("let v0 = {a: 1, b: [2, 3]}; v0.a += 4; print(v0, undefinedName);".to_string()).unwrap(),
+        )]);
+
+        // every mutator on its own
+        for (idx, mutator) in mutators.iter().enumerate() {
+            let donor_id = mutator.is_splicer().then_some(7);
+            let record = IterationRecord {
+                iteration: idx as u64,
+                seed_id: 0,
+                steps: vec![step(mutator.name(), iteration_seed(99, idx as u64), donor_id)],
+            };
+            let first = reconstruct_code(&record, &seed, &mutators, &donors);
+            let second = reconstruct_code(&record, &seed, &mutators, &donors);
+            match (first, second) {
+                (Ok(first), Ok(second)) => assert_eq!(first, second, "{} is not reproducible", mutator.name()),
+                (Err(_), Err(_)) => {}
+                _ => panic!("{} failed in only one reconstruction", mutator.name()),
+            }
+        }
+
+        // a chain with a splice branching off it
+        let splicer = mutators.iter().find(|mutator| mutator.is_splicer()).expect("no splicer");
+        let record = IterationRecord {
+            iteration: 1,
+            seed_id: 0,
+            steps: vec![
+                step("NumericTweaker", 11, None),
+                step("StringMutator", 12, None),
+                step(splicer.name(), 13, Some(7)),
+                step("BooleanFlipper", 14, None),
+                step("ArrayMutator", 15, None),
+            ],
+        };
+        assert_eq!(record.donor_ids(), vec![7]);
+        let first = reconstruct_code(&record, &seed, &mutators, &donors).unwrap();
+        assert_eq!(first.len(), record.steps.len());
+        assert_eq!(first, reconstruct_code(&record, &seed, &mutators, &donors).unwrap());
+        // the splice isn't part of the chain the later mutations build on
+        assert!(!String::from_utf8_lossy(&first[3]).contains("+= 4"));
+
+        let missing_donor = HashMap::new();
+        assert!(record.reconstruct(seed, &mutators, &missing_donor, UndefinedRefPolicy::Off).is_err());
+    }
+
+    #[test]
+    fn records_are_found_by_iteration() {
+        let path = std::env::temp_dir().join(format!("jellyfuzz_iteration_log_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = IterationLog::open(&path).unwrap();
+        let records: Vec<IterationRecord> = (1..=3)
+            .map(|iteration| IterationRecord {
+                iteration,
+                seed_id: iteration * 10,
+                steps: vec![
+                    step("BooleanFlipper", iteration, None),
+                    step("SpliceMutator", iteration + 1, Some(iteration * 10 + 1)),
+                    step("ArrayMutator", iteration + 2, None),
+                ],
+            })
+            .collect();
+        for record in &records {
+            log.append(record).unwrap();
+        }
+        log.flush().unwrap();
+
+        assert_eq!(find_iteration(&path, 2).unwrap(), records[1]);
+        assert!(find_iteration(&path, 4).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
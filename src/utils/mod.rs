@@ -1,2 +1,10 @@
+pub mod dictionary;
+pub mod iteration_log;
+pub mod mutation_log;
+pub mod pause;
 pub mod rand_utils;
+pub mod reward;
+pub mod run_report;
+pub mod stage_times;
 pub mod state;
+pub mod throttle;
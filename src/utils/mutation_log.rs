@@ -0,0 +1,113 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::runner::pool::Outcome;
+
+/// One mutated program and what executing it did, a line of the `--mutation-log` file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MutationRecord {
+    /// Corpus entry the program was mutated from
+    pub seed_id: u64,
+    pub mutator: String,
+    /// Hash of the generated source, tells apart programs without storing them
+    pub program_hash: u64,
+    /// e.g. "new_coverage" or "crash:11"
+    pub outcome: String,
+}
+
+impl MutationRecord {
+    pub fn new(seed_id: u64, mutator: &str, program: &[u8], outcome: &Outcome) -> Self {
+        let mut hasher = DefaultHasher::new();
+        program.hash(&mut hasher);
+        Self {
+            seed_id,
+            mutator: mutator.to_string(),
+            program_hash: hasher.finish(),
+            outcome: outcome_label(outcome),
+        }
+    }
+
+    pub fn to_line(&self) -> Result<String> {
+        serde_json::to_string(self).context("failed to serialize mutation record")
+    }
+
+    pub fn from_line(line: &str) -> Result<Self> {
+        serde_json::from_str(line).context("failed to parse mutation record")
+    }
+}
+
+fn outcome_label(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::NewCoverage => "new_coverage".to_string(),
+        Outcome::NoCoverage => "no_coverage".to_string(),
+        Outcome::Timeout => "timeout".to_string(),
+        Outcome::Crash { signal } => format!("crash:{}", signal),
+        Outcome::StackOverflow => "stack_overflow".to_string(),
+        Outcome::RuntimeError => "runtime_error".to_string(),
+    }
+}
+
+/// Appends a JSONL record per executed mutation. Writes are buffered, `flush` pushes them
+/// to the file.
+#[derive(Debug)]
+pub struct MutationLog {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl MutationLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open mutation log {:?}", path))?;
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub fn append(&self, record: &MutationRecord) -> Result<()> {
+        let line = record.to_line()?;
+        let mut writer = self.writer.lock().expect("mutation log lock poisoned");
+        writeln!(writer, "{}", line).context("failed to write mutation log")
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        let mut writer = self.writer.lock().expect("mutation log lock poisoned");
+        writer.flush().context("failed to flush mutation log")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let record = MutationRecord::new(7, "BooleanFlipper", b"let v0 = false;", &Outcome::Crash { signal: 11 });
+        assert_eq!(record.outcome, "crash:11");
+        let line = record.to_line().unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(MutationRecord::from_line(&line).unwrap(), record);
+
+        let path = std::env::temp_dir().join(format!("jellyfuzz_mutation_log_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = MutationLog::open(&path).unwrap();
+        let other = MutationRecord::new(8, "SpliceMutator", b"1;", &Outcome::NoCoverage);
+        log.append(&record).unwrap();
+        log.append(&other).unwrap();
+        log.flush().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<_> = contents.lines().map(|line| MutationRecord::from_line(line).unwrap()).collect();
+        assert_eq!(records, vec![record, other]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// What the fuzz loop should do next, given the pause flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseCheck {
+    /// Not paused, schedule as usual
+    Run,
+    /// Paused since the last check: drain the in-flight jobs and dump the status
+    Paused,
+    /// Still paused, schedule nothing
+    StillPaused,
+    /// Resumed since the last check, schedule again
+    Resumed,
+}
+
+/// Pause switch of the fuzz loop, toggled by SIGUSR1
+#[derive(Debug, Default)]
+pub struct PauseFlag {
+    paused: AtomicBool,
+    /// State seen by the last `check`
+    was_paused: AtomicBool,
+}
+
+pub static FUZZ_PAUSE: PauseFlag = PauseFlag::new();
+
+impl PauseFlag {
+    pub const fn new() -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            was_paused: AtomicBool::new(false),
+        }
+    }
+
+    /// Flip the flag, returns whether it is now paused
+    pub fn toggle(&self) -> bool {
+        !self.paused.fetch_xor(true, Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Called by the loop before every iteration
+    pub fn check(&self) -> PauseCheck {
+        let paused = self.is_paused();
+        let was_paused = self.was_paused.swap(paused, Ordering::SeqCst);
+        match (was_paused, paused) {
+            (false, false) => PauseCheck::Run,
+            (false, true) => PauseCheck::Paused,
+            (true, true) => PauseCheck::StillPaused,
+            (true, false) => PauseCheck::Resumed,
+        }
+    }
+}
+
+/// Toggle `FUZZ_PAUSE` on every SIGUSR1 for the rest of the process
+pub fn spawn_pause_handler() -> std::io::Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut usr1 = signal(SignalKind::user_defined1())?;
+    tokio::spawn(async move {
+        while usr1.recv().await.is_some() {
+            if FUZZ_PAUSE.toggle() {
+                println!("[pause] SIGUSR1 received, pausing after the jobs in flight");
+            } else {
+                println!("[pause] SIGUSR1 received, resuming");
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_flag_gates_scheduling() {
+        let flag = PauseFlag::new();
+        assert_eq!(flag.check(), PauseCheck::Run);
+        assert_eq!(flag.check(), PauseCheck::Run);
+
+        assert!(flag.toggle());
+        assert_eq!(flag.check(), PauseCheck::Paused);
+        assert_eq!(flag.check(), PauseCheck::StillPaused);
+        assert_eq!(flag.check(), PauseCheck::StillPaused);
+
+        assert!(!flag.toggle());
+        assert_eq!(flag.check(), PauseCheck::Resumed);
+        assert_eq!(flag.check(), PauseCheck::Run);
+
+        // a pause and resume between two checks goes unnoticed
+        flag.toggle();
+        flag.toggle();
+        assert_eq!(flag.check(), PauseCheck::Run);
+    }
+}
@@ -1,7 +1,8 @@
-use rand::{Rng, rngs::ThreadRng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 pub fn random_weighted_choice<T: Clone, R: Copy + Into<f64>>(
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
     choices: &[(T, R)],
 ) -> T {
     let total_weight: f64 = choices.iter().map(|&(_, weight)| weight.into()).sum();
@@ -16,13 +17,46 @@ pub fn random_weighted_choice<T: Clone, R: Copy + Into<f64>>(
     choices.last().unwrap().0.clone()
 }
 
+/// Integers at the edges of the engine number representations: small integers (31 bit
+/// SMIs), int32, uint32 and the exactly representable doubles
+pub const INTERESTING_INTEGERS: &[f64] = &[
+    1073741823.0,         // 2^30 - 1, largest 31 bit SMI
+    1073741824.0,         // 2^30
+    -1073741824.0,        // -2^30, smallest 31 bit SMI
+    -1073741825.0,        // -2^30 - 1
+    2147483647.0,         // 2^31 - 1, largest int32
+    2147483648.0,         // 2^31
+    -2147483648.0,        // -2^31, smallest int32
+    -2147483649.0,        // -2^31 - 1
+    4294967295.0,         // 2^32 - 1, largest uint32
+    4294967296.0,         // 2^32
+    9007199254740991.0,   // 2^53 - 1, largest safe integer
+    9007199254740992.0,   // 2^53
+    -9007199254740991.0,  // -(2^53 - 1)
+];
+
+/// An interesting boundary integer, sometimes nudged by one
+pub fn interesting_integer(rng: &mut impl Rng) -> f64 {
+    let value = INTERESTING_INTEGERS[rng.random_range(0..INTERESTING_INTEGERS.len())];
+    match rng.random_range(0..4) {
+        0 => value + 1.0,
+        1 => value - 1.0,
+        _ => value,
+    }
+}
+
+/// A fresh RNG seeded from the thread RNG, for mutations that aren't replayed
+pub fn unseeded_rng() -> StdRng {
+    StdRng::from_rng(&mut rand::rng())
+}
+
 pub fn boolean_with_probability(probability: f64) -> bool {
     let mut rng = rand::rng();
     let roll: f64 = rng.random();
     roll < probability
 }
 
-pub fn gaussian_sample(rng: &mut ThreadRng, mean: f64, std_dev: f64) -> f64 {
+pub fn gaussian_sample(rng: &mut impl Rng, mean: f64, std_dev: f64) -> f64 {
     let u1: f64 = rng.random();
     let u2: f64 = rng.random();
 
@@ -30,13 +64,13 @@ pub fn gaussian_sample(rng: &mut ThreadRng, mean: f64, std_dev: f64) -> f64 {
     z0 * std_dev + mean
 }
 
-pub fn small_delta(rng: &mut ThreadRng, base: f64) -> f64 {
+pub fn small_delta(rng: &mut impl Rng, base: f64) -> f64 {
     let base_scale = (base.abs().max(1.0) * 0.05).max(1.0);
     let raw = gaussian_sample(rng, 0.0, base_scale);
     raw.clamp(-base_scale * 100.0, base_scale * 100.0)
 }
 
-pub fn poisson_sample(rng: &mut ThreadRng, lambda: f64) -> u32 {
+pub fn poisson_sample(rng: &mut impl Rng, lambda: f64) -> u32 {
     let l = (-lambda).exp();
     let mut k = 0;
     let mut p = 1.0;
@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// Rewards credited to a mutator for each outcome of its programs. Hunting hangs, for
+/// example, calls for a positive `timeout`.
+///
+/// Loaded from JSON files like `{"crash": 10.0, "timeout": 0.5}`; missing fields keep
+/// their default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RewardConfig {
+    pub crash: f64,
+    pub new_coverage: f64,
+    pub timeout: f64,
+    pub stack_overflow: f64,
+    /// Runs without new coverage, including runtime errors
+    pub neutral: f64,
+    /// Factor earlier rewards of a mutator are scaled by whenever it gets a new one, so
+    /// mutator selection follows what is productive now. 1.0 never forgets.
+    pub decay: f64,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            crash: 5.0,
+            new_coverage: 1.0,
+            timeout: -1.0,
+            stack_overflow: -0.5,
+            neutral: 0.0,
+            decay: 0.99,
+        }
+    }
+}
+
+impl RewardConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read reward config {:?}", path))?;
+        let config: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse reward config {:?}", path))?;
+        if !(0.0..=1.0).contains(&config.decay) {
+            bail!("reward config {:?}: decay must be between 0.0 and 1.0, got {}", path, config.decay);
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_keep_their_default() {
+        let path = std::env::temp_dir().join(format!("jellyfuzz_reward_config_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"crash": 10.0, "timeout": 0.5}"#).unwrap();
+        let config = RewardConfig::load(&path).expect("failed to load reward config");
+        assert_eq!(config.crash, 10.0);
+        assert_eq!(config.timeout, 0.5);
+        assert_eq!(config.new_coverage, RewardConfig::default().new_coverage);
+
+        std::fs::write(&path, r#"{"crsh": 10.0}"#).unwrap();
+        assert!(RewardConfig::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn decay_outside_the_unit_interval_is_rejected() {
+        let path = std::env::temp_dir().join(format!("jellyfuzz_reward_decay_{}.json", std::process::id()));
+        for (decay, valid) in [("0.0", true), ("1.0", true), ("1.5", false), ("-0.1", false)] {
+            std::fs::write(&path, format!(r#"{{"decay": {}}}"#, decay)).unwrap();
+            assert_eq!(RewardConfig::load(&path).is_ok(), valid, "decay {}", decay);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
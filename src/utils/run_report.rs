@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::corpus::CorpusManager;
+use crate::mutators::{ManagedMutator, MutatorStats};
+use crate::runner::pool::{EdgeTracker, FuzzPool};
+use crate::runner::watchdog::StallWatchdog;
+
+/// Written to the output directory when the campaign ends
+pub const RUN_REPORT_FILE: &str = "run_report.json";
+
+const CRASHES_DIR: &str = "crashes";
+
+/// Summary of a campaign, to compare runs after the fact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub wall_clock_secs: f64,
+    /// Jobs completed by the workers of every engine
+    pub total_execs: u64,
+    pub corpus_size: usize,
+    pub edges: Vec<EngineEdges>,
+    /// Names of the saved crash repros, which carry the md5 or sanitizer fingerprint
+    pub crashes: Vec<String>,
+    pub timeouts_seen: u64,
+    pub timeouts_stored: usize,
+    pub mutators: Vec<MutatorReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineEdges {
+    pub engine: String,
+    pub seen: usize,
+    /// Edges instrumented in the engine, None if no worker reported it
+    pub total: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MutatorReport {
+    pub name: String,
+    pub uses: u64,
+    pub total_reward: f64,
+    pub mean_reward: f64,
+    pub recent_mean_reward: f64,
+    pub invalid_count: u64,
+    pub timeout_count: u64,
+    pub noop_count: u64,
+}
+
+impl MutatorReport {
+    fn new(name: &str, stats: &MutatorStats) -> Self {
+        Self {
+            name: name.to_string(),
+            uses: stats.uses,
+            total_reward: stats.total_reward,
+            mean_reward: stats.mean_reward,
+            recent_mean_reward: stats.recent_mean_reward,
+            invalid_count: stats.invalid_count,
+            timeout_count: stats.timeout_count,
+            noop_count: stats.noop_count,
+        }
+    }
+}
+
+/// What the report is built from. It doesn't borrow the pool, so a copy can go to the
+/// Ctrl-C handler.
+#[derive(Clone)]
+pub struct RunReporter {
+    started: Instant,
+    corpus_manager: Arc<Mutex<CorpusManager>>,
+    mutators: Vec<Arc<ManagedMutator>>,
+    watchdog: Arc<StallWatchdog>,
+    edge_trackers: Vec<(String, Arc<RwLock<EdgeTracker>>)>,
+}
+
+impl RunReporter {
+    pub fn new(
+        pool: &FuzzPool,
+        corpus_manager: Arc<Mutex<CorpusManager>>,
+        mutators: &[Arc<ManagedMutator>],
+        started: Instant,
+    ) -> Self {
+        Self {
+            started,
+            corpus_manager,
+            mutators: mutators.to_vec(),
+            watchdog: pool.watchdog(),
+            edge_trackers: pool.edge_trackers(),
+        }
+    }
+
+    pub async fn report(&self) -> RunReport {
+        let mut edges = Vec::with_capacity(self.edge_trackers.len());
+        for (engine, tracker) in &self.edge_trackers {
+            let tracker = tracker.read().await;
+            edges.push(EngineEdges {
+                engine: engine.clone(),
+                seen: tracker.seen_count(),
+                total: tracker.total_edges(),
+            });
+        }
+        let (corpus_size, timeouts_seen, timeouts_stored, root) = {
+            let mgr = self.corpus_manager.lock().await;
+            (mgr.len(), mgr.timeouts_seen(), mgr.stored_timeouts(), mgr.root().to_path_buf())
+        };
+        RunReport {
+            wall_clock_secs: self.started.elapsed().as_secs_f64(),
+            total_execs: self.watchdog.completions(),
+            corpus_size,
+            edges,
+            crashes: crash_names(&root.join(CRASHES_DIR)),
+            timeouts_seen,
+            timeouts_stored,
+            mutators: self
+                .mutators
+                .iter()
+                .map(|mutator| MutatorReport::new(mutator.name(), &mutator.stats_snapshot()))
+                .collect(),
+        }
+    }
+}
+
+/// File stems of the crash repros in `dir`, sorted
+fn crash_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "js"))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Write the report of the campaign to `run_report.json` in the corpus directory
+pub async fn write_run_report(reporter: &RunReporter) -> Result<PathBuf> {
+    let report = reporter.report().await;
+    let path = reporter.corpus_manager.lock().await.root().join(RUN_REPORT_FILE);
+    let blob = serde_json::to_vec_pretty(&report).context("failed to serialize run report")?;
+    tokio::fs::write(&path, blob)
+        .await
+        .with_context(|| format!("failed to write run report {:?}", path))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_serializes_expected_fields() {
+        let report = RunReport {
+            wall_clock_secs: 12.5,
+            total_execs: 1000,
+            corpus_size: 42,
+            edges: vec![EngineEdges {
+                engine: "v8".to_string(),
+                seen: 300,
+                total: Some(1200),
+            }],
+            crashes: vec!["crash_v8_0123abcd".to_string()],
+            timeouts_seen: 3,
+            timeouts_stored: 2,
+            mutators: vec![MutatorReport {
+                name: "ArrayMutator".to_string(),
+                uses: 10,
+                total_reward: 2.0,
+                mean_reward: 0.2,
+                recent_mean_reward: 0.25,
+                invalid_count: 1,
+                timeout_count: 0,
+                noop_count: 4,
+            }],
+        };
+        let value = serde_json::to_value(&report).unwrap();
+        for field in [
+            "wall_clock_secs",
+            "total_execs",
+            "corpus_size",
+            "edges",
+            "crashes",
+            "timeouts_seen",
+            "timeouts_stored",
+            "mutators",
+        ] {
+            assert!(value.get(field).is_some(), "missing {}", field);
+        }
+        assert_eq!(value["edges"][0]["seen"], 300);
+        assert_eq!(value["crashes"][0], "crash_v8_0123abcd");
+        assert_eq!(value["mutators"][0]["name"], "ArrayMutator");
+        assert_eq!(value["mutators"][0]["noop_count"], 4);
+
+        let parsed: RunReport = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn crash_names_skip_sidecars() {
+        let dir = std::env::temp_dir().join(format!("jellyfuzz_run_report_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["crash_v8_b.js", "crash_v8_b.origin.json", "crash_v8_a.js", "crash_v8_a.json"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        assert_eq!(crash_names(&dir), vec!["crash_v8_a", "crash_v8_b"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
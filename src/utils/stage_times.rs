@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Steps of the fuzz loop whose wall-clock time is tracked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    SeedRead,
+    Parse,
+    Mutate,
+    Generate,
+    Schedule,
+    CorpusUpdate,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 6] = [
+        Stage::SeedRead,
+        Stage::Parse,
+        Stage::Mutate,
+        Stage::Generate,
+        Stage::Schedule,
+        Stage::CorpusUpdate,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::SeedRead => "seed read",
+            Stage::Parse => "parse",
+            Stage::Mutate => "mutate",
+            Stage::Generate => "generate",
+            Stage::Schedule => "schedule",
+            Stage::CorpusUpdate => "corpus update",
+        }
+    }
+}
+
+/// Time spent per stage, summed over every task of the fuzz loop. Result handlers run
+/// concurrently, so the total can exceed the wall-clock time of the loop.
+pub struct StageTimes {
+    nanos: [AtomicU64; Stage::ALL.len()],
+}
+
+pub static STAGE_TIMES: StageTimes = StageTimes::new();
+
+impl StageTimes {
+    pub const fn new() -> Self {
+        Self {
+            nanos: [const { AtomicU64::new(0) }; Stage::ALL.len()],
+        }
+    }
+
+    pub fn record(&self, stage: Stage, elapsed: Duration) {
+        self.nanos[stage as usize].fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Run `f` and charge the time it took to `stage`
+    pub fn time<T>(&self, stage: Stage, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(stage, start.elapsed());
+        result
+    }
+
+    pub fn get(&self, stage: Stage) -> Duration {
+        Duration::from_nanos(self.nanos[stage as usize].load(Ordering::Relaxed))
+    }
+
+    /// Share of the tracked time spent in every stage, in percent
+    pub fn percentages(&self) -> Vec<(Stage, f64)> {
+        let total: u128 = Stage::ALL.iter().map(|&stage| self.get(stage).as_nanos()).sum();
+        Stage::ALL
+            .iter()
+            .map(|&stage| {
+                let share = if total == 0 {
+                    0.0
+                } else {
+                    self.get(stage).as_nanos() as f64 / total as f64 * 100.0
+                };
+                (stage, share)
+            })
+            .collect()
+    }
+
+    /// Start over, e.g. after every stats tick
+    pub fn reset(&self) {
+        for nanos in &self.nanos {
+            nanos.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// One line like "seed read: 1.2%, parse: 30.5%, ..."
+    pub fn format(&self) -> String {
+        self.percentages()
+            .iter()
+            .map(|(stage, share)| format!("{}: {:.1}%", stage.name(), share))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl Default for StageTimes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_durations_add_up() {
+        let times = StageTimes::new();
+        times.record(Stage::Parse, Duration::from_millis(30));
+        times.record(Stage::Parse, Duration::from_millis(30));
+        times.record(Stage::Schedule, Duration::from_millis(20));
+        times.record(Stage::CorpusUpdate, Duration::from_millis(20));
+        assert_eq!(times.time(Stage::Mutate, || 7), 7);
+
+        assert_eq!(times.get(Stage::Parse), Duration::from_millis(60));
+        let percentages = times.percentages();
+        let share = |stage: Stage| percentages.iter().find(|(s, _)| *s == stage).unwrap().1;
+        assert!(share(Stage::Parse) > 59.0 && share(Stage::Parse) <= 60.0);
+        assert!(share(Stage::Schedule) > 19.0 && share(Stage::Schedule) <= 20.0);
+        assert_eq!(share(Stage::SeedRead), 0.0);
+        let total: f64 = percentages.iter().map(|(_, share)| share).sum();
+        assert!((total - 100.0).abs() < 1e-9);
+        assert!(times.format().starts_with("seed read: 0.0%, parse: "));
+
+        times.reset();
+        assert!(times.percentages().iter().all(|(_, share)| *share == 0.0));
+    }
+}
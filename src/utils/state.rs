@@ -1,3 +1,10 @@
+use std::sync::Arc;
+
+use crate::runner::autoscale::WorkerBounds;
+use crate::utils::iteration_log::IterationLog;
+use crate::utils::mutation_log::MutationLog;
+use crate::utils::reward::RewardConfig;
+
 /// Represents the state of the fuzzer process
 pub struct FuzzerState {
     pub total_jobs_executed: usize,
@@ -6,8 +13,89 @@ pub struct FuzzerState {
     pub success_rate: f64,
 }
 
+/// What to do with mutated programs referencing identifiers that aren't declared anywhere
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum UndefinedRefPolicy {
+    /// Execute them anyway
+    #[default]
+    Off,
+    /// Replace undefined references with declared names
+    Repair,
+    /// Drop the mutation and charge an invalid to the mutator
+    Discard,
+}
+
 /// Configuration settings for the fuzzer
+#[derive(Debug, Clone, Default)]
 pub struct FuzzerSettings {
     // True if the initial corpus has been fully processed
     pub initial_corpus_processed: bool,
+    /// Reproduction mode: a single worker executes every job and each result is handled
+    /// before the next job is scheduled, so corpus growth no longer depends on job
+    /// completion order. The iterations draw from `rng_seed` and the periodic stats
+    /// no longer reset or pull in other shards' seeds. This serializes the whole
+    /// pipeline and costs roughly a factor of `--workers` in throughput.
+    pub deterministic: bool,
+    /// Re-parse every generated program before scheduling it, discarding the ones that
+    /// don't parse and charging an invalid to the mutator that produced them
+    pub verify_roundtrip: bool,
+    pub undefined_refs: UndefinedRefPolicy,
+    /// Engine output is shown instead of discarded, so only a single worker is spawned
+    pub debug_engine: bool,
+    /// Cap on the number of jobs scheduled per second
+    pub max_execs_per_sec: Option<f64>,
+    /// Grow and shrink the number of workers per profile within these bounds
+    pub auto_scale: Option<WorkerBounds>,
+    pub rewards: RewardConfig,
+    /// Record of every executed mutation, for replaying a session
+    pub mutation_log: Option<Arc<MutationLog>>,
+    /// Seed the per-iteration RNGs are derived from, picking the seed and the mutators of
+    /// an iteration no longer depends on the thread RNG
+    pub rng_seed: Option<u64>,
+    /// Record of the seed and mutator chain of every iteration, for `--replay-iteration`
+    pub iteration_log: Option<Arc<IterationLog>>,
+}
+
+/// Seed of the per-iteration RNGs of a deterministic run without `--rng-seed`
+pub const DETERMINISTIC_RNG_SEED: u64 = 0;
+
+impl FuzzerSettings {
+    /// Number of workers to spawn for every profile
+    pub fn workers_per_profile(&self, requested: usize) -> usize {
+        if self.deterministic || self.debug_engine {
+            1
+        } else if let Some(bounds) = self.auto_scale {
+            requested.clamp(bounds.min, bounds.max)
+        } else {
+            requested
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic_mode_forces_a_single_worker() {
+        let settings = FuzzerSettings {
+            deterministic: true,
+            ..Default::default()
+        };
+        assert_eq!(settings.workers_per_profile(16), 1);
+        assert_eq!(FuzzerSettings::default().workers_per_profile(16), 16);
+
+        let settings = FuzzerSettings {
+            debug_engine: true,
+            ..Default::default()
+        };
+        assert_eq!(settings.workers_per_profile(16), 1);
+
+        let settings = FuzzerSettings {
+            auto_scale: Some(WorkerBounds { min: 2, max: 8 }),
+            ..Default::default()
+        };
+        assert_eq!(settings.workers_per_profile(1), 2);
+        assert_eq!(settings.workers_per_profile(16), 8);
+    }
 }
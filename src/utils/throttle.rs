@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+/// Token bucket capping the rate at which jobs are scheduled. Tokens refill at `rate` per
+/// second up to one second's worth, so short bursts are smoothed out but the long-run rate
+/// never exceeds the cap.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Throttle {
+    pub fn new(rate: f64, now: Instant) -> Self {
+        assert!(rate > 0.0, "throttle rate must be positive");
+        let capacity = rate.max(1.0);
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Take `count` tokens and return how long to wait before scheduling more. The bucket
+    /// goes into debt instead of refusing, so the jobs already scheduled are paid for by
+    /// the wait.
+    pub fn consume(&mut self, count: usize, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= count as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pacing_keeps_the_rate_under_the_cap() {
+        let start = Instant::now();
+        let mut now = start;
+        let mut throttle = Throttle::new(100.0, start);
+        let mut scheduled = 0;
+        // batches of 10 jobs, each batch taking 1ms plus whatever the throttle asks for
+        while now.duration_since(start) < Duration::from_secs(10) {
+            scheduled += 10;
+            now += Duration::from_millis(1);
+            now += throttle.consume(10, now);
+        }
+        let elapsed = now.duration_since(start).as_secs_f64();
+        // at most one second's worth of burst above the cap
+        let allowed = 100.0 * (elapsed + 1.0);
+        assert!(scheduled as f64 <= allowed + 1e-6, "{} jobs in {}s", scheduled, elapsed);
+        assert!(scheduled as f64 / elapsed >= 95.0, "{} jobs in {}s", scheduled, elapsed);
+    }
+
+    #[test]
+    fn idle_time_only_refills_up_to_capacity() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(10.0, start);
+        assert_eq!(throttle.consume(10, start), Duration::ZERO);
+        assert_eq!(throttle.consume(10, start), Duration::from_secs(1));
+
+        // an hour idle only buys one second's worth of tokens
+        let later = start + Duration::from_secs(3600);
+        assert_eq!(throttle.consume(10, later), Duration::ZERO);
+        assert!(throttle.consume(1, later) > Duration::ZERO);
+    }
+}